@@ -1,5 +1,5 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut env = unity_rs::Env::new();
+    let env = unity_rs::Env::new();
     let data = std::fs::read("char_1016_agoat2.ab")?;
     env.load_from_slice(&data)?;
     for obj in env.objects() {
@@ -1,11 +1,12 @@
 use crate::common::common_string;
-use crate::error::UnityResult;
+use crate::error::{UnityError, UnityResult};
 use crate::object::ObjectInfo;
-use crate::reader::{ByteOrder, Reader};
+use crate::reader::{ByteOrder, ReadLimits, Reader};
 use crate::typetree::{TypeTree, TypeTreeNode};
+use crate::version::UnityVersion;
 use std::sync::Arc;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct SerializedFileHeader {
     pub metadata_size: usize,
     pub file_size: usize,
@@ -29,45 +30,23 @@ pub struct SerializedType {
     pub asm_name: String,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct LocalSerializedObjectIdentifier {
     pub local_serialized_file_index: i32,
     pub local_identifier_in_file: i64,
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct FileIdentifier {
     pub guid: [u8; 16],
     pub type_: i32,
     pub path_name: String,
 }
 
-#[derive(Clone, PartialEq, Eq)]
-pub enum BuildType {
-    Unknown,
-    Alpha,
-    Patch,
-    Other(String),
-}
-
-impl BuildType {
-    pub fn new(type_: String) -> Self {
-        match type_.as_str() {
-            "a" => Self::Alpha,
-            "p" => Self::Patch,
-            _ => Self::Other(type_),
-        }
-    }
-
-    pub fn is_patch(&self) -> bool {
-        &Self::Patch == self
-    }
-}
-
+#[derive(Clone)]
 pub struct Asset {
     pub path: String,
-    pub version: [i32; 4],
-    pub build_type: BuildType,
+    pub version: UnityVersion,
     pub header: SerializedFileHeader,
     pub file_endian: u8,
     pub unity_version: String,
@@ -83,12 +62,11 @@ pub struct Asset {
 }
 
 impl Asset {
-    pub(crate) fn new(src: Arc<Vec<u8>>, path: &str) -> UnityResult<Self> {
-        let mut r = Reader::new(src.as_slice(), ByteOrder::Big);
+    pub(crate) fn new(src: Arc<Vec<u8>>, path: &str, limits: ReadLimits) -> UnityResult<Self> {
+        let mut r = Reader::new(src.as_slice(), ByteOrder::Big).with_limits(limits);
         let mut ret = Self {
             path: path.to_string(),
-            version: [0; 4],
-            build_type: BuildType::Unknown,
+            version: UnityVersion::default(),
             header: SerializedFileHeader::default(),
             file_endian: 0,
             unity_version: String::default(),
@@ -111,7 +89,7 @@ impl Asset {
             ret.header.reserved = r.read_u8_array()?;
             ret.file_endian = ret.header.endian;
         } else {
-            r.set_offset(ret.header.file_size - ret.header.metadata_size)?;
+            r.set_offset(ret.header.file_size.checked_sub(ret.header.metadata_size).ok_or(UnityError::Eof)?)?;
             ret.file_endian = r.read_u8()?;
         }
         if ret.header.version >= 22 {
@@ -125,22 +103,7 @@ impl Asset {
         }
         if ret.header.version >= 7 {
             ret.unity_version = r.read_string_util_null()?;
-            let mut s = String::new();
-            for i in ret.unity_version.chars() {
-                if i.is_ascii_alphabetic() {
-                    ret.build_type = BuildType::new(i.to_string());
-                    s.push('.');
-                } else {
-                    s.push(i)
-                }
-            }
-            let s = s.split('.');
-            for (i, j) in s.into_iter().enumerate() {
-                if i >= 4 {
-                    break;
-                }
-                ret.version[i] = j.parse().unwrap()
-            }
+            ret.version = UnityVersion::parse(&ret.unity_version).unwrap_or_default();
         }
         if ret.header.version >= 8 {
             ret.target_platform = r.read_i32()?;
@@ -161,7 +124,6 @@ impl Asset {
         let object_count = r.read_i32()?;
         for _ in 0..object_count {
             let mut object_info = ObjectInfo {
-                build_type: ret.build_type.clone(),
                 data: src.clone(),
                 bytes_order: r.get_order(),
                 asset_version: ret.header.version,
@@ -173,7 +135,10 @@ impl Asset {
                 stripped: 0,
                 path_id: 0,
                 serialized_type: SerializedType::default(),
-                version: [0; 4],
+                ref_types: Arc::default(),
+                version: ret.version,
+                consumed: Default::default(),
+                limits,
             };
             if ret.big_id_enabled {
                 object_info.path_id = r.read_i64()?;
@@ -188,9 +153,12 @@ impl Asset {
             } else {
                 object_info.bytes_start = r.read_u32()? as usize;
             }
-            object_info.bytes_start += ret.header.data_offset;
-            object_info.version = ret.version;
+            object_info.bytes_start = object_info.bytes_start.checked_add(ret.header.data_offset).ok_or(UnityError::Eof)?;
             object_info.bytes_size = r.read_u32()? as usize;
+            match object_info.bytes_start.checked_add(object_info.bytes_size) {
+                Some(end) if end <= src.len() => {}
+                _ => return Err(UnityError::Eof),
+            }
             object_info.type_id = r.read_i32()?;
             if ret.header.version < 16 {
                 object_info.class_id = r.read_u16()? as i32;
@@ -201,7 +169,7 @@ impl Asset {
                     }
                 }
             } else {
-                let type_ = ret.types[object_info.type_id as usize].clone();
+                let type_ = usize::try_from(object_info.type_id).ok().and_then(|x| ret.types.get(x)).ok_or(UnityError::InvalidValue)?.clone();
                 object_info.class_id = type_.class_id;
                 object_info.serialized_type = type_;
             }
@@ -252,6 +220,10 @@ impl Asset {
                 let st = ret.read_serialized_type(&mut r, true)?;
                 ret.ref_types.push(st)
             }
+            let ref_types = Arc::new(ret.ref_types.clone());
+            for object_info in &mut ret.objects_info {
+                object_info.ref_types = ref_types.clone();
+            }
         }
         if ret.header.version >= 5 {
             ret.user_information = r.read_string_util_null()?;
@@ -259,6 +231,14 @@ impl Asset {
         Ok(ret)
     }
 
+    /// Replaces the Unity version of the asset and of all of its objects.
+    pub fn set_version(&mut self, version: UnityVersion) {
+        self.version = version;
+        for info in &mut self.objects_info {
+            info.version = version;
+        }
+    }
+
     pub fn read_serialized_type(&mut self, r: &mut Reader, is_ref_type: bool) -> UnityResult<SerializedType> {
         let mut result = SerializedType {
             class_id: r.read_i32()?,
@@ -290,7 +270,7 @@ impl Asset {
                     result.name_space = r.read_string_util_null()?;
                     result.asm_name = r.read_string_util_null()?;
                 } else {
-                    let length = r.read_i32()? as usize;
+                    let length = r.read_array_len()?;
                     result.type_dependencies = r.read_i32_list(length)?;
                 }
             }
@@ -313,7 +293,7 @@ impl Asset {
         }
 
         let node_number = r.read_i32()?;
-        let string_buffer_size = r.read_i32()? as usize;
+        let string_buffer_size = r.read_array_len()?;
         for _ in 0..node_number {
             let mut type_tree_node = TypeTreeNode {
                 version: r.read_u16()? as i32,
@@ -341,6 +321,7 @@ impl Asset {
     }
 
     pub fn read_type_tree(&mut self, r: &mut Reader, type_tree: &mut TypeTree, level: i32) -> UnityResult<()> {
+        r.check_depth(level as usize)?;
         let mut type_tree_node = TypeTreeNode {
             level,
             type_: r.read_string_util_null()?,
@@ -365,8 +346,4 @@ impl Asset {
         }
         Ok(())
     }
-
-    pub fn version_greater_or_equal(&self, other: &[i32]) -> bool {
-        !self.version.iter().zip(other.iter()).any(|(x, y)| *x < *y)
-    }
 }
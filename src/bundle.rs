@@ -1,6 +1,7 @@
 use crate::asset::Asset;
-use crate::error::{UnityError, UnityResult};
-use crate::reader::{ByteOrder, Reader};
+use crate::error::{ErrorContext, UnityError, UnityResult};
+use crate::reader::{ByteOrder, ReadLimits, Reader};
+use crate::version::UnityVersion;
 use std::sync::Arc;
 
 #[derive(PartialEq)]
@@ -69,7 +70,7 @@ impl CompressionType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BundleHead {
     pub signature: String,
     pub version: u32,
@@ -102,12 +103,14 @@ impl BundleHead {
     }
 }
 
+#[derive(Clone)]
 pub struct StorageBlock {
     compressed_size: u32,
     uncompressed_size: u32,
     flags: u16,
 }
 
+#[derive(Clone)]
 pub struct Node {
     pub offset: i64,
     pub size: i64,
@@ -115,17 +118,26 @@ pub struct Node {
     pub path: String,
 }
 
+#[derive(Clone)]
 pub struct AssetBundle {
     header: BundleHead,
     block_infos: Vec<StorageBlock>,
     pub nodes: Vec<Node>,
     pub files: Vec<Arc<Vec<u8>>>,
     pub assets: Vec<Asset>,
+    /// Where the bundle was loaded from, used in error context. Empty for bundles parsed from memory.
+    pub source: String,
+    limits: ReadLimits,
 }
 
 impl AssetBundle {
     pub fn from_slice(src: &[u8]) -> UnityResult<Self> {
-        let mut r = Reader::new(src, ByteOrder::Big);
+        Self::from_slice_with_limits(src, ReadLimits::default())
+    }
+
+    /// Parses a bundle, enforcing `limits` on it and on the objects of its assets.
+    pub fn from_slice_with_limits(src: &[u8], limits: ReadLimits) -> UnityResult<Self> {
+        let mut r = Reader::new(src, ByteOrder::Big).with_limits(limits);
         let signature = r.read_string_util_null()?;
         let version = r.read_u32()?;
         let unity_version = r.read_string_util_null()?;
@@ -145,6 +157,8 @@ impl AssetBundle {
             nodes: Vec::new(),
             files: Vec::new(),
             assets: Vec::new(),
+            source: String::new(),
+            limits,
         };
         match ret.header.signature.as_str() {
             "UnityFS" => {
@@ -158,9 +172,21 @@ impl AssetBundle {
             _ => return Err(UnityError::UnsupportFileType(ret.header.signature)),
         }
         ret.assets = ret.load_assets()?;
+        if let Some(revision) = UnityVersion::parse(&ret.header.unity_revision).filter(|x| !x.is_stripped()) {
+            for asset in ret.assets.iter_mut().filter(|x| x.version.is_stripped()) {
+                asset.set_version(revision);
+            }
+        }
         Ok(ret)
     }
 
+    /// Overrides the Unity version of every asset in the bundle.
+    pub fn set_unity_version(&mut self, version: UnityVersion) {
+        for asset in &mut self.assets {
+            asset.set_version(version);
+        }
+    }
+
     fn read_header(&mut self, r: &mut Reader) -> UnityResult<()> {
         self.header.size = r.read_i64()? as u64;
         self.header.compressed_blocks_info_size = r.read_u32()?;
@@ -179,13 +205,14 @@ impl AssetBundle {
         }
         let offset = r.get_offset();
         if self.header.flags & ArchiveFlags::BlocksInfoAtTheEnd as u32 != 0 {
-            r.set_offset(r.len() - self.header.compressed_blocks_info_size as usize)?;
+            r.set_offset(r.len().checked_sub(self.header.compressed_blocks_info_size as usize).ok_or(UnityError::Eof)?)?;
             block_info_bytes = r.read_u8_list(self.header.compressed_blocks_info_size as usize)?;
             r.set_offset(offset)?;
         } else {
             block_info_bytes = r.read_u8_list(self.header.compressed_blocks_info_size as usize)?;
         }
         let uncompressed_size = self.header.uncompressed_blocks_info_size;
+        r.check_alloc(uncompressed_size as usize)?;
         let compressed_type = CompressionType::from_magic_num(self.header.flags & ArchiveFlags::CompressionTypeMask as u32)?;
         let block_info_uncompressed_bytes = match compressed_type {
             CompressionType::None => block_info_bytes,
@@ -193,7 +220,7 @@ impl AssetBundle {
             CompressionType::Lz4 | CompressionType::Lz4HC => lz4_flex::decompress(&block_info_bytes, uncompressed_size as usize)?,
             CompressionType::LzInv => return Err(UnityError::Unimplemented),
         };
-        let mut block_info_reader = Reader::new(&block_info_uncompressed_bytes, ByteOrder::Big);
+        let mut block_info_reader = Reader::new(&block_info_uncompressed_bytes, ByteOrder::Big).with_limits(self.limits);
         let _uncompressed_data_hash = block_info_reader.read_u8_slice(16)?;
         let block_info_count = block_info_reader.read_i32()?;
         for _ in 0..block_info_count {
@@ -223,6 +250,8 @@ impl AssetBundle {
     fn read_blocks(&self, r: &mut Reader) -> UnityResult<Vec<u8>> {
         let mut result = Vec::new();
         for block_info in &self.block_infos {
+            // the blocks are decompressed into one buffer, which the limit bounds as a whole
+            r.check_alloc(result.len().saturating_add(block_info.uncompressed_size as usize))?;
             let compress_type = CompressionType::from_magic_num((block_info.flags & StorageBlockFlags::CompressionTypeMask as u16) as u32)?;
             match compress_type {
                 CompressionType::None => {
@@ -230,6 +259,9 @@ impl AssetBundle {
                 }
                 CompressionType::Lzma => {
                     let in_buf = r.read_u8_slice(block_info.compressed_size as usize)?;
+                    if in_buf.len() < 5 {
+                        return Err(UnityError::Eof);
+                    }
                     let mut in_buf_new = Vec::new();
                     in_buf_new.extend_from_slice(&in_buf[..5]);
                     in_buf_new.extend_from_slice(&(block_info.uncompressed_size as u64).to_le_bytes());
@@ -258,7 +290,7 @@ impl AssetBundle {
     }
 
     fn read_files(&mut self, data: &[u8]) -> UnityResult<()> {
-        let mut r = Reader::new(data, ByteOrder::Big);
+        let mut r = Reader::new(data, ByteOrder::Big).with_limits(self.limits);
         for node in &self.nodes {
             r.set_offset(node.offset as usize)?;
             let file = r.read_u8_list(node.size as usize)?;
@@ -334,10 +366,17 @@ impl AssetBundle {
     pub fn load_assets(&self) -> UnityResult<Vec<Asset>> {
         let mut ret = Vec::new();
         for (file, node) in self.files.iter().zip(self.nodes.iter()) {
-            if FileType::AssetsFile != AssetBundle::check_file_type(file).unwrap() {
+            // files too short or malformed to classify are left as resources
+            if AssetBundle::check_file_type(file).ok() != Some(FileType::AssetsFile) {
                 continue;
             }
-            ret.push(Asset::new(file.clone(), &node.path)?)
+            let asset = Asset::new(file.clone(), &node.path, self.limits).map_err(|e| {
+                e.with_context(ErrorContext {
+                    node: Some(node.path.clone()),
+                    ..ErrorContext::default()
+                })
+            })?;
+            ret.push(asset)
         }
         Ok(ret)
     }
@@ -1,7 +1,10 @@
-use crate::error::UnityResult;
+use crate::error::{UnityError, UnityResult};
 use crate::math::Vector3;
 use crate::object::ObjectInfo;
-use crate::reader::Reader;
+use crate::reader::{ReadLimits, Reader};
+use serde_json::Value;
+
+use super::{type_tree, Field};
 
 #[derive(Default, Debug)]
 pub struct AnimationClip {
@@ -15,6 +18,23 @@ impl AnimationClip {
         let extent = r.read_vector3()?;
         Ok(Self { center, extent })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            center: type_tree::vector3(type_tree::field(value, "m_Center")?)?,
+            extent: type_tree::vector3(type_tree::field(value, "m_Extent")?)?,
+        })
+    }
+}
+
+impl Field for AnimationClip {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Self::load(r)
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Self::load_type_tree(value)
+    }
 }
 
 #[derive(Debug)]
@@ -33,12 +53,25 @@ impl PackedFloatVector {
             range: r.read_f32()?,
             start: r.read_f32()?,
             data: {
-                let len = r.read_i32()? as usize;
+                let len = r.read_array_len()?;
                 r.read_u8_list(len)?
             },
             bit_size: r.read_u8()?,
         };
         r.align(4)?;
+        check_packed(ret.num_items, ret.bit_size, &ret.data, r.limits().max_array_len)?;
+        Ok(ret)
+    }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        let ret = Self {
+            num_items: type_tree::int(value, "m_NumItems")? as u32,
+            range: type_tree::float(value, "m_Range")?,
+            start: type_tree::float(value, "m_Start")?,
+            data: type_tree::bytes(value, "m_Data")?,
+            bit_size: type_tree::int(value, "m_BitSize")? as u8,
+        };
+        check_packed(ret.num_items, ret.bit_size, &ret.data, ReadLimits::default().max_array_len)?;
         Ok(ret)
     }
 
@@ -48,9 +81,10 @@ impl PackedFloatVector {
         let mut bit_pos = bit_pos % 8;
 
         let scale = 1.0 / self.range;
+        let mask = bit_mask(self.bit_size);
         let num_chunks = num_chunks.unwrap_or((self.num_items as usize).div_ceil(item_count_in_chunk));
         let end = chunk_stride * num_chunks / 4;
-        let mut data = Vec::with_capacity(end);
+        let mut data = Vec::with_capacity(end.min(self.num_items as usize));
 
         let mut index = 0;
         while index < end {
@@ -59,7 +93,9 @@ impl PackedFloatVector {
                 let mut bits = 0;
 
                 while bits < self.bit_size as usize {
-                    let byte = self.data[index_pos];
+                    let Some(&byte) = self.data.get(index_pos) else {
+                        return data;
+                    };
                     let num = std::cmp::min(self.bit_size as usize - bits, 8 - bit_pos);
                     x |= ((byte >> bit_pos) as u32) << bits;
                     bit_pos += num;
@@ -69,8 +105,8 @@ impl PackedFloatVector {
                         bit_pos = 0;
                     }
                 }
-                x &= (1 << self.bit_size) - 1;
-                let float_value = (x as f32) / (scale * ((1 << self.bit_size) as f32 - 1.0)) + self.start;
+                x &= mask;
+                let float_value = (x as f32) / (scale * mask as f32) + self.start;
                 data.push(float_value);
             }
             index += chunk_stride / 4;
@@ -91,7 +127,7 @@ impl PackedIntVector {
         let ret = Self {
             num_items: r.read_u32()?,
             data: {
-                let len = r.read_i32()? as usize;
+                let len = r.read_array_len()?;
                 r.read_u8_list(len)?
             },
             bit_size: {
@@ -100,10 +136,22 @@ impl PackedIntVector {
             },
         };
         r.align(4)?;
+        check_packed(ret.num_items, ret.bit_size, &ret.data, r.limits().max_array_len)?;
+        Ok(ret)
+    }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        let ret = Self {
+            num_items: type_tree::int(value, "m_NumItems")? as u32,
+            data: type_tree::bytes(value, "m_Data")?,
+            bit_size: type_tree::int(value, "m_BitSize")? as u8,
+        };
+        check_packed(ret.num_items, ret.bit_size, &ret.data, ReadLimits::default().max_array_len)?;
         Ok(ret)
     }
 
     pub fn unpack_ints(&self) -> Vec<i32> {
+        let mask = bit_mask(self.bit_size) as i32;
         let mut unpacked_data = vec![0; self.num_items as usize];
         let mut index_pos = 0;
         let mut bit_pos = 0;
@@ -113,7 +161,11 @@ impl PackedIntVector {
             unpacked_data[i as usize] = 0;
 
             while bits < self.bit_size as usize {
-                unpacked_data[i as usize] |= ((self.data[index_pos] >> bit_pos) as i32) << bits;
+                let Some(&byte) = self.data.get(index_pos) else {
+                    unpacked_data.truncate(i as usize);
+                    return unpacked_data;
+                };
+                unpacked_data[i as usize] |= ((byte >> bit_pos) as i32) << bits;
                 let num = std::cmp::min(self.bit_size as usize - bits, 8 - bit_pos);
                 bit_pos += num;
                 bits += num;
@@ -123,8 +175,23 @@ impl PackedIntVector {
                     bit_pos = 0;
                 }
             }
-            unpacked_data[i as usize] &= (1 << self.bit_size) - 1;
+            unpacked_data[i as usize] &= mask;
         }
         unpacked_data
     }
 }
+
+fn bit_mask(bit_size: u8) -> u32 {
+    ((1u64 << bit_size) - 1) as u32
+}
+
+/// Rejects packed vectors whose items do not fit in their data, which unpacking would otherwise read past.
+fn check_packed(num_items: u32, bit_size: u8, data: &[u8], max_items: usize) -> UnityResult<()> {
+    if bit_size > 32 || num_items as u64 * bit_size as u64 > data.len() as u64 * 8 {
+        return Err(UnityError::InvalidValue);
+    }
+    if num_items as usize > max_items {
+        return Err(UnityError::LimitExceeded("array length"));
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use crate::env::Object;
+use crate::error::UnityResult;
+use serde_json::Value;
+
+use super::{AudioClip, ClassID, Cubemap, GameObject, Material, Mesh, MeshFilter, MeshRenderer, MonoBehaviour, MonoScript, RectTransform, Shader, Sprite, SpriteAtlas, TextAsset, Texture2D, Texture2DArray, Texture3D, Transform};
+
+/// An object read without knowing its class in advance, see [`Object::read_any`].
+pub enum AnyObject {
+    AudioClip(AudioClip),
+    Cubemap(Cubemap),
+    GameObject(GameObject),
+    Material(Material),
+    Mesh(Box<Mesh>),
+    MeshFilter(MeshFilter),
+    MeshRenderer(MeshRenderer),
+    MonoBehaviour(MonoBehaviour),
+    MonoScript(MonoScript),
+    RectTransform(RectTransform),
+    Shader(Shader),
+    Sprite(Box<Sprite>),
+    SpriteAtlas(SpriteAtlas),
+    TextAsset(TextAsset),
+    Texture2D(Texture2D),
+    Texture2DArray(Texture2DArray),
+    Texture3D(Texture3D),
+    Transform(Transform),
+    /// A class without a typed loader, decoded through its TypeTree.
+    TypeTree(ClassID, Value),
+    /// A class without a typed loader or a TypeTree, as its serialized bytes.
+    Raw(ClassID, Vec<u8>),
+}
+
+impl AnyObject {
+    pub(crate) fn read(object: &Object) -> UnityResult<Self> {
+        let any = match object.class() {
+            ClassID::AudioClip => Self::AudioClip(object.read()?),
+            ClassID::Cubemap => Self::Cubemap(object.read()?),
+            ClassID::GameObject => Self::GameObject(object.read()?),
+            ClassID::Material => Self::Material(object.read()?),
+            ClassID::Mesh => Self::Mesh(Box::new(object.read()?)),
+            ClassID::MeshFilter => Self::MeshFilter(object.read()?),
+            ClassID::MeshRenderer => Self::MeshRenderer(object.read()?),
+            ClassID::MonoBehaviour => Self::MonoBehaviour(object.read()?),
+            ClassID::MonoScript => Self::MonoScript(object.read()?),
+            ClassID::RectTransform => Self::RectTransform(object.read()?),
+            ClassID::Shader => Self::Shader(object.read()?),
+            ClassID::Sprite => Self::Sprite(Box::new(object.read()?)),
+            ClassID::SpriteAtlas => Self::SpriteAtlas(object.read()?),
+            ClassID::TextAsset => Self::TextAsset(object.read()?),
+            ClassID::Texture2D => Self::Texture2D(object.read()?),
+            ClassID::Texture2DArray => Self::Texture2DArray(object.read()?),
+            ClassID::Texture3D => Self::Texture3D(object.read()?),
+            ClassID::Transform => Self::Transform(object.read()?),
+            class if object.info.serialized_type.type_tree.nodes.is_empty() => {
+                let info = &object.info;
+                Self::Raw(class, info.data[info.bytes_start..info.bytes_start + info.bytes_size].to_vec())
+            }
+            class => Self::TypeTree(class, Value::Object(object.read_type_tree()?.into_iter().collect())),
+        };
+        Ok(any)
+    }
+
+    pub fn class(&self) -> ClassID {
+        match self {
+            Self::AudioClip(_) => ClassID::AudioClip,
+            Self::Cubemap(_) => ClassID::Cubemap,
+            Self::GameObject(_) => ClassID::GameObject,
+            Self::Material(_) => ClassID::Material,
+            Self::Mesh(_) => ClassID::Mesh,
+            Self::MeshFilter(_) => ClassID::MeshFilter,
+            Self::MeshRenderer(_) => ClassID::MeshRenderer,
+            Self::MonoBehaviour(_) => ClassID::MonoBehaviour,
+            Self::MonoScript(_) => ClassID::MonoScript,
+            Self::RectTransform(_) => ClassID::RectTransform,
+            Self::Shader(_) => ClassID::Shader,
+            Self::Sprite(_) => ClassID::Sprite,
+            Self::SpriteAtlas(_) => ClassID::SpriteAtlas,
+            Self::TextAsset(_) => ClassID::TextAsset,
+            Self::Texture2D(_) => ClassID::Texture2D,
+            Self::Texture2DArray(_) => ClassID::Texture2DArray,
+            Self::Texture3D(_) => ClassID::Texture3D,
+            Self::Transform(_) => ClassID::Transform,
+            Self::TypeTree(class, _) | Self::Raw(class, _) => *class,
+        }
+    }
+
+    /// The name of the object, when its class has one and it could be read.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Self::AudioClip(x) => Some(&x.name),
+            Self::Cubemap(x) => Some(&x.texture.name),
+            Self::GameObject(x) => Some(&x.name),
+            Self::Material(x) => Some(&x.name),
+            Self::Mesh(x) => Some(&x.name),
+            Self::MonoBehaviour(x) => Some(&x.name),
+            Self::MonoScript(x) => Some(&x.name),
+            Self::Shader(x) => Some(&x.name),
+            Self::Sprite(x) => Some(&x.name),
+            Self::SpriteAtlas(x) => Some(&x.name),
+            Self::TextAsset(x) => Some(&x.name),
+            Self::Texture2D(x) => Some(&x.name),
+            Self::Texture2DArray(x) => Some(&x.name),
+            Self::Texture3D(x) => Some(&x.name),
+            Self::TypeTree(_, value) => value.get("m_Name").and_then(Value::as_str),
+            Self::MeshFilter(_) | Self::MeshRenderer(_) | Self::RectTransform(_) | Self::Transform(_) | Self::Raw(..) => None,
+        }
+    }
+}
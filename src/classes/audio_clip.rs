@@ -1,19 +1,22 @@
 use std::collections::HashMap;
 
 use num_enum::FromPrimitive;
+use serde_json::{json, Value};
 
-use crate::classes::FromObject;
+use crate::classes::field::bytes;
+use crate::classes::{type_tree, Field, FromObject};
 use crate::env::Object;
 use crate::error::UnityResult;
+use crate::object::ObjectInfo;
 use crate::reader::{ByteOrder, Reader};
 use crate::UnityError;
 
-#[derive(Debug, Eq, PartialEq, FromPrimitive, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, FromPrimitive, Clone, Copy, Default)]
 #[repr(i32)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum AudioCompressionFormat {
-    #[num_enum(default)]
+    #[default]
     UnknownType = -1,
     PCM = 0,
     Vorbis = 1,
@@ -27,12 +30,12 @@ pub enum AudioCompressionFormat {
     ATRAC9 = 9,
 }
 
-#[derive(Debug, Eq, PartialEq, FromPrimitive, Clone, Copy)]
+#[derive(Debug, Eq, PartialEq, FromPrimitive, Clone, Copy, Default)]
 #[repr(i32)]
 #[allow(non_camel_case_types)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum FMODSoundType {
-    #[num_enum(default)]
+    #[default]
     UNKNOWN = 0,
     ACC = 1,
     AIFF = 2,
@@ -65,121 +68,154 @@ pub enum FMODSoundType {
     MEDIA_FOUNDATION = 29,
 }
 
-pub enum AudioClipMeta {
-    Low {
-        format: i32,
-        typ: FMODSoundType,
-        is_3d: bool,
-        use_hardware: bool,
-    },
-    High {
-        load_type: i32,
-        channels: i32,
-        frequency: i32,
-        bits_per_sample: i32,
-        length: f32,
-        is_tracker_format: bool,
-        subsound_index: i32,
-        preload_audio_data: bool,
-        load_in_background: bool,
-        legacy_3d: bool,
-        compression_format: AudioCompressionFormat,
-    },
+/// Formats this crate has no name for are read as `UnknownType`, which leaves them out of strict mode's comparison.
+impl Field for AudioCompressionFormat {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Ok(Self::from(r.read_i32()?))
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self::from(i32::from_value(info, value)?))
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        (*self != Self::UnknownType).then(|| json!(*self as i32))
+    }
+}
+
+impl Field for FMODSoundType {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Ok(Self::from(r.read_i32()?))
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self::from(i32::from_value(info, value)?))
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        (*self != Self::UNKNOWN).then(|| json!(*self as i32))
+    }
+}
+
+/// Where the audio data of 5 and later is, in a resource file of the bundle.
+#[derive(Debug, Default, Clone)]
+pub struct StreamedResource {
+    pub source: String,
+    pub offset: i64,
+    pub size: i64,
+}
+
+impl Field for StreamedResource {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Ok(Self {
+            source: r.read_aligned_string()?,
+            offset: r.read_i64()?,
+            size: r.read_i64()?,
+        })
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            source: type_tree::string(value, "m_Source")?,
+            offset: type_tree::int(value, "m_Offset")?,
+            size: type_tree::int(value, "m_Size")?,
+        })
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(json!({"m_Source": self.source, "m_Offset": self.offset, "m_Size": self.size}))
+    }
 }
 
+#[derive(FromObject)]
+#[unity(finish = "AudioClip::read_streamed_data")]
 pub struct AudioClip {
     pub name: String,
-    pub meta: AudioClipMeta,
-    pub source: Option<String>,
-    pub offset: Option<i64>,
-    pub size: i64,
+    #[unity(until = "5")]
+    pub format: i32,
+    #[unity(until = "5", rename = "m_Type")]
+    pub sound_type: FMODSoundType,
+    #[unity(until = "5", rename = "m_3D")]
+    pub is_3d: bool,
+    #[unity(until = "5", align)]
+    pub use_hardware: bool,
+    #[unity(since = "3.2", until = "5")]
+    pub stream: i32,
+    #[unity(since = "5")]
+    pub load_type: i32,
+    #[unity(since = "5")]
+    pub channels: i32,
+    #[unity(since = "5")]
+    pub frequency: i32,
+    #[unity(since = "5")]
+    pub bits_per_sample: i32,
+    #[unity(since = "5")]
+    pub length: f32,
+    #[unity(since = "5", align)]
+    pub is_tracker_format: bool,
+    #[unity(since = "5")]
+    pub subsound_index: i32,
+    #[unity(since = "5")]
+    pub preload_audio_data: bool,
+    #[unity(since = "5")]
+    pub load_in_background: bool,
+    #[unity(since = "5", rename = "m_Legacy3D", align)]
+    pub legacy_3d: bool,
+    #[unity(since = "5")]
+    pub resource: StreamedResource,
+    #[unity(since = "5")]
+    pub compression_format: AudioCompressionFormat,
+    /// The audio data, stored in the object before 5 and read from [`Self::resource`] from 5 on.
+    #[unity(until = "5", with = "audio_data", rename = "m_AudioData")]
     pub data: Vec<u8>,
 }
 
-impl FromObject<'_> for AudioClip {
-    fn load(object: &Object) -> UnityResult<Self> {
-        let mut r = object.info.get_reader();
-        let name = r.read_aligned_string()?;
-        let meta: AudioClipMeta;
-        let size: i64;
-        let mut offset: Option<i64> = None;
-        let mut source: Option<String> = None;
-        if object.asset.version[0] < 5 {
-            meta = AudioClipMeta::Low {
-                format: r.read_i32()?,
-                typ: r.read_i32()?.into(),
-                is_3d: r.read_bool()?,
-                use_hardware: r.read_bool()?,
-            };
-            r.align(4)?;
-            if object.asset.version_greater_or_equal(&[3, 2]) {
-                let _stream = r.read_i32()?;
-                size = r.read_i32()? as i64;
-                let tsize = if size % 4 != 0 { size + 4 - size % 4 } else { size };
-                if r.len() - r.get_offset() != tsize as usize {
-                    offset = Some(r.read_u32()? as i64);
-                    source = Some(object.asset.path.clone()); // 可能与unitypy不同
-                }
-            } else {
-                size = r.read_i32()? as i64;
-            }
-        } else {
-            meta = AudioClipMeta::High {
-                load_type: r.read_i32()?,
-                channels: r.read_i32()?,
-                frequency: r.read_i32()?,
-                bits_per_sample: r.read_i32()?,
-                length: r.read_f32()?,
-                is_tracker_format: {
-                    let is_tracker_format = r.read_bool()?;
-                    r.align(4)?;
-                    is_tracker_format
-                },
-                subsound_index: r.read_i32()?,
-                preload_audio_data: r.read_bool()?,
-                load_in_background: r.read_bool()?,
-                legacy_3d: {
-                    let legacy_3d = r.read_bool()?;
-                    r.align(4)?;
-                    source = Some(r.read_aligned_string()?);
-                    offset = Some(r.read_i64()?);
-                    size = r.read_i64()?;
-                    legacy_3d
-                },
-                compression_format: { r.read_i32()?.into() },
-            }
+/// `m_AudioData`, which from 3.2 can be an offset into the file of the object when the clip is streamed.
+mod audio_data {
+    use super::AudioClip;
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<Vec<u8>> {
+        let size = r.read_i32()? as i64;
+        let aligned_size = (size + 3) & !3;
+        if object.version() >= (3, 2) && r.len() - r.get_offset() != aligned_size as usize {
+            let offset = r.read_u32()? as i64;
+            return AudioClip::read_resource(object, &object.asset().path, offset, size);
         }
-        let data = match (source.as_deref(), offset) {
-            (Some(source), Some(offset)) => {
-                let mut data = None;
-                let path = source.split('/').last().ok_or(UnityError::InvalidValue)?;
-                for i in 0..object.bundle.nodes.len() {
-                    let node = &object.bundle.nodes[i];
-                    if node.path != path {
-                        continue;
-                    }
-                    let file = &object.bundle.files[i];
-                    let mut r = Reader::new(file.as_slice(), ByteOrder::Big);
-                    r.set_offset(offset as usize)?;
-                    data = Some(r.read_u8_list(size as usize)?);
-                    break;
-                }
-                match data {
-                    Some(data) => data,
-                    None => return Err(UnityError::CustomError("can not find resource".to_string())),
-                }
-            }
-            _ => r.read_u8_list(size as usize)?,
-        };
-        Ok(Self { name, meta, source, offset, size, data })
+        r.read_u8_list(size as usize)
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::AudioClip
+    pub(super) fn from_value(object: &Object, value: &Value) -> UnityResult<Vec<u8>> {
+        super::bytes::from_value(object, value)
     }
 }
 
 impl AudioClip {
+    fn read_streamed_data(&mut self, object: &Object) -> UnityResult<()> {
+        if object.version() >= (5, 0) {
+            self.data = Self::read_resource(object, &self.resource.source, self.resource.offset, self.resource.size)?;
+        }
+        Ok(())
+    }
+
+    fn read_resource(object: &Object, source: &str, offset: i64, size: i64) -> UnityResult<Vec<u8>> {
+        let path = source.split('/').last().ok_or(UnityError::InvalidValue)?;
+        for i in 0..object.bundle.nodes.len() {
+            let node = &object.bundle.nodes[i];
+            if node.path != path {
+                continue;
+            }
+            let file = &object.bundle.files[i];
+            let mut r = Reader::new(file.as_slice(), ByteOrder::Big).with_limits(object.info.limits);
+            r.set_offset(offset as usize)?;
+            return r.read_u8_list(size as usize);
+        }
+        Err(UnityError::CustomError("can not find resource".to_string()))
+    }
+
     pub fn samples(&self) -> UnityResult<HashMap<String, Vec<u8>>> {
         let mut ret = HashMap::new();
         match self.data.as_slice() {
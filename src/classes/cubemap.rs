@@ -0,0 +1,46 @@
+use image::{imageops, RgbaImage};
+
+use crate::classes::pptr::PPtr;
+use crate::classes::{FromObject, Texture2D};
+use crate::error::UnityResult;
+
+/// A cube texture, stored as a Texture2D of six images.
+#[derive(FromObject)]
+pub struct Cubemap {
+    /// The faces as the images of the texture, in the order +X, -X, +Y, -Y, +Z, -Z.
+    #[unity(flatten)]
+    pub texture: Texture2D,
+    #[unity(pptr, default = "Vec::new()")]
+    pub source_textures: Vec<PPtr<Texture2D>>,
+}
+
+impl Cubemap {
+    /// Short names of the faces in storage order, for naming exported files.
+    pub const FACE_NAMES: [&'static str; 6] = ["px", "nx", "py", "ny", "pz", "nz"];
+
+    /// Decodes the largest level of the face `face`, counted in the order of [`Self::FACE_NAMES`]. Faces are stored top
+    /// row first, unlike 2D textures.
+    pub fn decode_face(&self, face: usize) -> UnityResult<RgbaImage> {
+        let mut img = self.texture.decode_mip(0, face)?;
+        imageops::flip_vertical_in_place(&mut img);
+        Ok(img)
+    }
+
+    /// Decodes the six faces in storage order.
+    pub fn decode_faces(&self) -> UnityResult<Vec<RgbaImage>> {
+        (0..6).map(|face| self.decode_face(face)).collect()
+    }
+
+    /// Lays the faces out as a horizontal cross, four faces wide and three high: -X, +Z, +X and -Z across the middle,
+    /// with +Y above and -Y below +Z. The corners are left transparent.
+    pub fn decode_cross(&self) -> UnityResult<RgbaImage> {
+        let faces = self.decode_faces()?;
+        let (width, height) = faces[0].dimensions();
+        let mut cross = RgbaImage::new(width * 4, height * 3);
+        let cells = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)];
+        for (face, (x, y)) in faces.iter().zip(cells) {
+            imageops::replace(&mut cross, face, (x * width) as i64, (y * height) as i64);
+        }
+        Ok(cross)
+    }
+}
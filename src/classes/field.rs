@@ -0,0 +1,281 @@
+use crate::env::Object;
+use crate::error::UnityResult;
+use crate::math::{Color, Matrix4x4, Quaternion, RectF32, Vector2, Vector3, Vector4};
+use crate::object::ObjectInfo;
+use crate::reader::Reader;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::pptr::PPtr;
+use super::type_tree::{self, mistyped};
+use super::FromObject;
+
+/// A value that `#[derive(FromObject)]` reads from the object data and from the decoded TypeTree.
+pub trait Field: Sized {
+    fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self>;
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self>;
+
+    /// The value as the TypeTree decodes it, which strict mode compares with the object's TypeTree. `None` leaves
+    /// the field out of the comparison.
+    fn to_value(&self) -> Option<Value> {
+        None
+    }
+}
+
+/// A field holding PPtrs, marked `#[unity(pptr)]`, which keep the object they were read from to be resolved later.
+pub trait PPtrField: Sized {
+    fn read(object: &Object, r: &mut Reader) -> UnityResult<Self>;
+    fn from_value(object: &Object, value: &Value) -> UnityResult<Self>;
+}
+
+macro_rules! int_field {
+    ($($ty:ty => $read:ident),* $(,)?) => {
+        $(
+            impl Field for $ty {
+                fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+                    r.$read()
+                }
+
+                fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+                    let int = match value {
+                        Value::Bool(b) => Some(*b as i64),
+                        v => v.as_i64().or_else(|| v.as_u64().map(|x| x as i64)),
+                    };
+                    int.map(|x| x as $ty).ok_or_else(|| mistyped("integer"))
+                }
+
+                fn to_value(&self) -> Option<Value> {
+                    Some(json!(self))
+                }
+            }
+        )*
+    };
+}
+
+int_field!(u8 => read_u8, i8 => read_i8, u16 => read_u16, i16 => read_i16, u32 => read_u32, i32 => read_i32, u64 => read_u64, i64 => read_i64);
+
+impl Field for bool {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        r.read_bool()
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(u8::from_value(info, value)? != 0)
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(json!(self))
+    }
+}
+
+impl Field for f32 {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        r.read_f32()
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        match value {
+            // serde_json stores NaN and infinities as null
+            Value::Null => Ok(f32::NAN),
+            v => v.as_f64().map(|x| x as f32).ok_or_else(|| mistyped("float")),
+        }
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(json!(self))
+    }
+}
+
+impl Field for String {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        r.read_aligned_string()
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        value.as_str().map(|x| x.to_string()).ok_or_else(|| mistyped("string"))
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(json!(self))
+    }
+}
+
+impl<T: Field> Field for Vec<T> {
+    fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        let count = r.read_array_len()?;
+        (0..count).map(|_| T::read(info, r)).collect()
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        value.as_array().ok_or_else(|| mistyped("array"))?.iter().map(|x| T::from_value(info, x)).collect()
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        self.iter().map(T::to_value).collect::<Option<_>>().map(Value::Array)
+    }
+}
+
+/// A `map`, stored in the TypeTree as an object by key, or as an array of `first` and `second` pairs.
+impl<K: Field + Eq + Hash, V: Field> Field for HashMap<K, V> {
+    fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        let count = r.read_array_len()?;
+        (0..count).map(|_| Ok((K::read(info, r)?, V::read(info, r)?))).collect()
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        match value {
+            Value::Array(items) => items.iter().map(|x| Ok((K::from_value(info, type_tree::field(x, "first")?)?, V::from_value(info, type_tree::field(x, "second")?)?))).collect(),
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| {
+                    // keys other than strings are stored as their JSON text
+                    let key = K::from_value(info, &Value::String(k.clone())).or_else(|_| K::from_value(info, &serde_json::from_str(k).map_err(|_| mistyped("map key"))?))?;
+                    Ok((key, V::from_value(info, v)?))
+                })
+                .collect(),
+            _ => Err(mistyped("map")),
+        }
+    }
+}
+
+/// A field that only exists in some versions, `None` outside of its `since` and `until` range.
+impl<T: Field> Field for Option<T> {
+    fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        T::read(info, r).map(Some)
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        T::from_value(info, value).map(Some)
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        self.as_ref().and_then(T::to_value)
+    }
+}
+
+/// Fixed size byte arrays such as `Hash128`, stored in the TypeTree as `bytes[0]` to `bytes[N - 1]`.
+impl<const N: usize> Field for [u8; N] {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        r.read_u8_array()
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        let bytes: Vec<u8> = match value {
+            Value::Array(items) => items.iter().map(|x| u8::from_value(info, x)).collect::<UnityResult<_>>()?,
+            // the keys sort as text, bytes[10] before bytes[2], so they are looked up by index
+            Value::Object(map) if map.len() == N => (0..N).map(|i| u8::from_value(info, map.get(&format!("bytes[{i}]")).ok_or_else(|| mistyped("byte array"))?)).collect::<UnityResult<_>>()?,
+            _ => return Err(mistyped("byte array")),
+        };
+        bytes.try_into().map_err(|_| mistyped("byte array"))
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(Value::Object(self.iter().enumerate().map(|(i, x)| (format!("bytes[{i}]"), json!(x))).collect()))
+    }
+}
+
+macro_rules! math_field {
+    ($($ty:ty => $read:expr, $from_value:path, $to_value:expr),* $(,)?) => {
+        $(
+            impl Field for $ty {
+                fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+                    $read(r)
+                }
+
+                fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+                    $from_value(value)
+                }
+
+                fn to_value(&self) -> Option<Value> {
+                    $to_value(self)
+                }
+            }
+        )*
+    };
+}
+
+math_field!(
+    Vector2 => Reader::read_vector2, type_tree::vector2, |v: &Vector2| Some(json!({"x": v.x, "y": v.y})),
+    Vector3 => Reader::read_vector3, type_tree::vector3, |v: &Vector3| Some(json!({"x": v.x, "y": v.y, "z": v.z})),
+    Vector4 => Reader::read_vector4, type_tree::vector4, |v: &Vector4| Some(json!({"x": v.x, "y": v.y, "z": v.z, "w": v.w})),
+    RectF32 => Reader::read_rect_f32, type_tree::rect, |v: &RectF32| Some(json!({"x": v.x, "y": v.y, "width": v.w, "height": v.h})),
+    Matrix4x4 => Reader::read_matrix4x4, type_tree::matrix4x4, |_: &Matrix4x4| None,
+    Quaternion => |r: &mut Reader| Ok(Quaternion::from_array(r.read_f32_array()?)), type_tree::quaternion, |_: &Quaternion| None,
+    Color => |r: &mut Reader| Ok(Color::from_array(r.read_f32_array()?)), type_tree::color, |_: &Color| None,
+);
+
+impl<T: FromObject> PPtrField for PPtr<T> {
+    fn read(object: &Object, r: &mut Reader) -> UnityResult<Self> {
+        PPtr::load(object, r)
+    }
+
+    fn from_value(object: &Object, value: &Value) -> UnityResult<Self> {
+        PPtr::load_type_tree(object, value)
+    }
+}
+
+impl<T: PPtrField> PPtrField for Vec<T> {
+    fn read(object: &Object, r: &mut Reader) -> UnityResult<Self> {
+        let count = r.read_array_len()?;
+        (0..count).map(|_| T::read(object, r)).collect()
+    }
+
+    fn from_value(object: &Object, value: &Value) -> UnityResult<Self> {
+        value.as_array().ok_or_else(|| mistyped("array"))?.iter().map(|x| T::from_value(object, x)).collect()
+    }
+}
+
+impl<T: PPtrField> PPtrField for Option<T> {
+    fn read(object: &Object, r: &mut Reader) -> UnityResult<Self> {
+        T::read(object, r).map(Some)
+    }
+
+    fn from_value(object: &Object, value: &Value) -> UnityResult<Self> {
+        T::from_value(object, value).map(Some)
+    }
+}
+
+/// Byte arrays such as `image data`, for `#[unity(with = "bytes")]`, which `Vec<u8>` would read one byte at a time.
+pub(super) mod bytes {
+    use super::*;
+
+    pub(in crate::classes) fn read(_object: &Object, r: &mut Reader) -> UnityResult<Vec<u8>> {
+        let length = r.read_i32()?;
+        r.read_u8_list(length as usize)
+    }
+
+    pub(in crate::classes) fn from_value(object: &Object, value: &Value) -> UnityResult<Vec<u8>> {
+        Vec::<u8>::from_value(&object.info, value)
+    }
+}
+
+/// Support code for the loaders generated by `#[derive(FromObject)]`.
+#[doc(hidden)]
+pub mod derive {
+    use super::*;
+
+    pub use crate::reader::Reader;
+    pub use serde_json::Value;
+
+    /// The fields of a derived struct, read where the reader is so that `#[unity(flatten)]` can embed them in the
+    /// fields of another.
+    pub trait Fields: Sized {
+        fn read_fields(object: &Object, r: &mut Reader) -> UnityResult<Self>;
+        fn fields_from_tree(object: &Object, tree: &Value) -> UnityResult<Self>;
+        fn push_checked_fields(&self, fields: &mut Vec<(&'static str, Value)>);
+    }
+
+    /// The TypeTree value of the field `name`.
+    pub fn tree_field<'v>(tree: &'v Value, name: &str) -> UnityResult<&'v Value> {
+        type_tree::field(tree, name)
+    }
+
+    pub fn field_value<T: Field>(info: &ObjectInfo, value: &Value, name: &str) -> UnityResult<T> {
+        T::from_value(info, value).map_err(|_| mistyped(name))
+    }
+
+    pub fn pptr_value<T: PPtrField>(object: &Object, value: &Value, name: &str) -> UnityResult<T> {
+        T::from_value(object, value).map_err(|_| mistyped(name))
+    }
+}
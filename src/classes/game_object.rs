@@ -1,33 +1,46 @@
-use crate::classes::FromObject;
-use crate::env::Object;
-use crate::error::UnityResult;
-
 use super::pptr::PPtr;
 use super::Component;
+use super::FromObject;
 
-pub struct GameObject<'a> {
-    pub components: Vec<PPtr<'a, Component<'a>>>,
+#[derive(FromObject)]
+pub struct GameObject {
+    #[unity(with = "components", rename = "m_Component")]
+    pub components: Vec<PPtr<Component>>,
+    pub layer: u32,
     pub name: String,
+    pub tag: u16,
+    pub is_active: bool,
 }
 
-impl<'a> FromObject<'a> for GameObject<'a> {
-    fn load(object: &'a Object) -> UnityResult<Self> {
-        let mut r = object.info.get_reader();
-        let version = object.info.version;
-        let count = r.read_i32()? as usize;
+mod components {
+    use crate::classes::pptr::PPtr;
+    use crate::classes::{type_tree, Component};
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<Vec<PPtr<Component>>> {
+        let count = r.read_array_len()?;
         let mut components = Vec::new();
         for _ in 0..count {
-            if (version[0] == 5 && version[1] < 5) || version[0] < 5 {
+            // before 5.5 each entry is a pair of class id and component
+            if object.info.version < (5, 5) {
                 r.read_i32()?;
             }
-            components.push(PPtr::load(object, &mut r)?);
+            components.push(PPtr::load(object, r)?);
         }
-        let _layer = r.read_i32()?;
-        let name = r.read_aligned_string()?;
-        Ok(Self { components, name })
+        Ok(components)
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::GameObject
+    pub(super) fn from_value(object: &Object, value: &Value) -> UnityResult<Vec<PPtr<Component>>> {
+        let pairs = value.as_array().ok_or_else(|| type_tree::mistyped("m_Component"))?;
+        pairs
+            .iter()
+            .map(|pair| {
+                let component = pair.get("component").map_or_else(|| type_tree::field(pair, "second"), Ok)?;
+                PPtr::load_type_tree(object, component)
+            })
+            .collect()
     }
 }
@@ -377,3 +377,40 @@ pub enum ClassID {
     LocalizationAsset = 2083778819,
     ScriptedImporter = 2089858483,
 }
+
+impl ClassID {
+    /// Whether objects of this class are named objects whose serialized data starts with `m_Name`.
+    pub fn has_leading_name(self) -> bool {
+        matches!(
+            self,
+            Self::Material
+                | Self::Texture2D
+                | Self::Mesh
+                | Self::Shader
+                | Self::TextAsset
+                | Self::ComputeShader
+                | Self::AnimationClip
+                | Self::AudioClip
+                | Self::RenderTexture
+                | Self::CustomRenderTexture
+                | Self::Cubemap
+                | Self::Avatar
+                | Self::AnimatorController
+                | Self::RuntimeAnimatorController
+                | Self::MonoScript
+                | Self::Texture3D
+                | Self::Font
+                | Self::PhysicMaterial
+                | Self::PhysicsMaterial2D
+                | Self::AssetBundle
+                | Self::Texture2DArray
+                | Self::CubemapArray
+                | Self::ShaderVariantCollection
+                | Self::Sprite
+                | Self::AnimatorOverrideController
+                | Self::AvatarMask
+                | Self::VideoClip
+                | Self::SpriteAtlas
+        )
+    }
+}
@@ -2,116 +2,122 @@ use std::collections::HashMap;
 
 use crate::classes::FromObject;
 use crate::env::Object;
-use crate::error::UnityResult;
+use crate::error::{UnityError, UnityResult};
 use crate::math::{Color, Vector2};
 use crate::reader::Reader;
+use serde_json::Value;
 
 use super::pptr::PPtr;
 use super::shader::Shader;
+use super::type_tree;
 use super::Texture2D;
 
-pub struct Material<'a> {
+#[derive(FromObject)]
+pub struct Material {
     pub name: String,
-    pub shader: PPtr<'a, Shader>,
-    pub saved_properties: UnityPropertySheet<'a>,
+    #[unity(pptr)]
+    pub shader: PPtr<Shader>,
+    #[unity(since = "4.1", until = "5", rename = "m_ShaderKeywords")]
+    pub shader_keyword_list: Vec<String>,
+    #[unity(since = "5", until = "2021.3")]
+    pub shader_keywords: String,
+    #[unity(since = "2021.3")]
+    pub valid_keywords: Vec<String>,
+    #[unity(since = "2021.3")]
+    pub invalid_keywords: Vec<String>,
+    #[unity(since = "5")]
+    pub lightmap_flags: u32,
+    #[unity(since = "5.6", align)]
+    pub enable_instancing_variants: bool,
+    #[unity(since = "4.3")]
+    pub custom_render_queue: i32,
+    #[unity(since = "5.1")]
+    pub string_tag_map: HashMap<String, String>,
+    #[unity(since = "5.6")]
+    pub disabled_shader_passes: Vec<String>,
+    #[unity(with = "UnityPropertySheet")]
+    pub saved_properties: UnityPropertySheet,
 }
 
-impl<'a> FromObject<'a> for Material<'a> {
-    fn load(object: &'a Object<'a>) -> UnityResult<Self> {
-        let version = object.info.version;
-        let r = &mut object.info.get_reader();
-        let name = r.read_aligned_string()?;
-        let shader = PPtr::load(object, r)?;
-        if version[0] == 4 && version[1] >= 1 {
-            let _shader_keywords = r.read_string_list()?;
-        }
-        if version[0] > 2021 || (version[0] == 2021 && version[1] >= 3) {
-            let _valid_keywords = r.read_string_list()?;
-            let _invalid_keywords = r.read_string_list()?;
-        } else if version[0] >= 5 {
-            let _shader_keywords = r.read_aligned_string()?;
-        }
-        if version[0] >= 5 {
-            let _lightmap_flags = r.read_u32()?;
-        }
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 6) {
-            let _enable_instancing_variants = r.read_bool()?;
-            r.align(4)?;
-        }
-        if version[0] > 4 || (version[0] == 4 && version[1] >= 3) {
-            let _custom_render_queue = r.read_i32()?;
-        }
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 1) {
-            let string_tag_map_size = r.read_i32()?;
-            for _ in 0..string_tag_map_size {
-                let _first = r.read_aligned_string()?;
-                let _second = r.read_aligned_string()?;
-            }
-        }
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 6) {
-            let _disabled_shader_passes = r.read_string_list()?;
-        }
-        Ok(Self {
-            name,
-            shader,
-            saved_properties: UnityPropertySheet::load(object, r)?,
-        })
-    }
-
-    fn class() -> super::ClassID {
-        super::ClassID::Material
-    }
-}
-
-pub struct UnityPropertySheet<'a> {
-    pub tex_envs: HashMap<String, UnityTexEnv<'a>>,
+pub struct UnityPropertySheet {
+    pub tex_envs: HashMap<String, UnityTexEnv>,
     pub ints: HashMap<String, i32>,
     pub floats: HashMap<String, f32>,
     pub colors: HashMap<String, Color>,
 }
 
-impl<'a> UnityPropertySheet<'a> {
-    pub(super) fn load(object: &'a Object, r: &mut Reader) -> UnityResult<Self> {
+impl UnityPropertySheet {
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<Self> {
         let version = object.info.version;
-        let tex_envs_size = r.read_i32()? as usize;
+        let tex_envs_size = r.read_array_len()?;
         let mut tex_envs = HashMap::with_capacity(tex_envs_size);
         for _ in 0..tex_envs_size {
             tex_envs.insert(r.read_aligned_string()?, UnityTexEnv::load(object, r)?);
         }
         let mut ints = HashMap::new();
-        if version[0] >= 2021 {
-            let ints_size = r.read_i32()? as usize;
+        if version.major >= 2021 {
+            let ints_size = r.read_array_len()?;
             ints = HashMap::with_capacity(ints_size);
             for _ in 0..ints_size {
                 ints.insert(r.read_aligned_string()?, r.read_i32()?);
             }
         }
-        let floats_size = r.read_i32()? as usize;
+        let floats_size = r.read_array_len()?;
         let mut floats = HashMap::with_capacity(tex_envs_size);
         for _ in 0..floats_size {
             floats.insert(r.read_aligned_string()?, r.read_f32()?);
         }
-        let colors_size = r.read_i32()? as usize;
+        let colors_size = r.read_array_len()?;
         let mut colors = HashMap::with_capacity(tex_envs_size);
         for _ in 0..colors_size {
             colors.insert(r.read_aligned_string()?, Color::from_array(r.read_f32_array::<4>()?));
         }
         Ok(Self { tex_envs, ints, floats, colors })
     }
+
+    pub(super) fn from_value(object: &Object, value: &Value) -> UnityResult<Self> {
+        let mut tex_envs = HashMap::new();
+        for (key, env) in type_tree::pairs(value, "m_TexEnvs")? {
+            tex_envs.insert(type_tree::property_name(&key)?, UnityTexEnv::load_type_tree(object, env)?);
+        }
+        let mut ints = HashMap::new();
+        if value.get("m_Ints").is_some() {
+            for (key, v) in type_tree::pairs(value, "m_Ints")? {
+                ints.insert(type_tree::property_name(&key)?, v.as_i64().ok_or(UnityError::InvalidValue)? as i32);
+            }
+        }
+        let mut floats = HashMap::new();
+        for (key, v) in type_tree::pairs(value, "m_Floats")? {
+            floats.insert(type_tree::property_name(&key)?, v.as_f64().unwrap_or(f64::NAN) as f32);
+        }
+        let mut colors = HashMap::new();
+        for (key, v) in type_tree::pairs(value, "m_Colors")? {
+            colors.insert(type_tree::property_name(&key)?, type_tree::color(v)?);
+        }
+        Ok(Self { tex_envs, ints, floats, colors })
+    }
 }
 
-pub struct UnityTexEnv<'a> {
-    pub texture: PPtr<'a, Texture2D>,
+pub struct UnityTexEnv {
+    pub texture: PPtr<Texture2D>,
     pub scale: Vector2,
     pub offset: Vector2,
 }
 
-impl<'a> UnityTexEnv<'a> {
-    pub(super) fn load(object: &'a Object, r: &mut Reader) -> UnityResult<Self> {
+impl UnityTexEnv {
+    pub(super) fn load(object: &Object, r: &mut Reader) -> UnityResult<Self> {
         Ok(Self {
             texture: PPtr::load(object, r)?,
             scale: r.read_vector2()?,
             offset: r.read_vector2()?,
         })
     }
+
+    pub(super) fn load_type_tree(object: &Object, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            texture: PPtr::load_type_tree(object, type_tree::field(value, "m_Texture")?)?,
+            scale: type_tree::vector2(type_tree::field(value, "m_Scale")?)?,
+            offset: type_tree::vector2(type_tree::field(value, "m_Offset")?)?,
+        })
+    }
 }
@@ -1,13 +1,16 @@
 #![allow(non_upper_case_globals)]
 use super::animation_clip::{AnimationClip, PackedFloatVector, PackedIntVector};
+use super::field::bytes;
 use super::texture2d::StreamingInfo;
-use super::FromObject;
+use super::{type_tree, Field, FromObject};
 use crate::error::{UnityError, UnityResult};
-use crate::math::{Matrix4x4, Vector3};
+use crate::math::{Matrix4x4, Vector2, Vector3, Vector4};
 use crate::object::ObjectInfo;
 use crate::reader::{ByteOrder, Reader};
+use crate::version::UnityVersion;
 use crate::Object;
 use num_enum::TryFromPrimitive;
+use serde_json::Value;
 
 #[derive(Debug, Eq, PartialEq, TryFromPrimitive, Clone, Copy, Default)]
 #[repr(i32)]
@@ -21,38 +24,249 @@ pub enum GfxPrimitiveType {
     Points = 5,
 }
 
-#[derive(Debug)]
+#[derive(Debug, FromObject)]
+#[unity(finish = "Mesh::process_data")]
 pub struct Mesh {
     pub name: String,
+    /// From 2017.4 whether `m_IndexFormat` is 16-bit.
+    #[unity(until = "3.5", with = "positive", rename = "m_Use16BitIndices", default = "true")]
     pub use_16_bit_indices: bool,
+    #[unity(until = "2.6", with = "bytes", rename = "m_IndexBuffer")]
+    legacy_index_bytes: Vec<u8>,
     pub sub_meshes: Vec<SubMesh>,
-    pub index_buffer: Vec<u32>,
+    #[unity(since = "4.1")]
     pub shapes: Option<BlendShapeData>,
+    /// Read after the skin before 4.3.
+    #[unity(since = "4.3")]
     pub bind_pose: Vec<Matrix4x4>,
+    #[unity(since = "4.3")]
     pub bone_name_hashes: Vec<u32>,
+    #[unity(since = "4.3")]
+    pub root_bone_name_hash: u32,
+    #[unity(since = "2019", rename = "m_BonesAABB")]
+    pub bones_aabb: Vec<MinMaxAABB>,
+    #[unity(since = "2019", with = "variable_bone_count_weights")]
+    pub variable_bone_count_weights: Vec<u32>,
+    #[unity(since = "2.6")]
+    pub mesh_compression: u8,
+    #[unity(since = "4", until = "5")]
+    pub stream_compression: u8,
+    #[unity(since = "4")]
+    pub is_readable: bool,
+    #[unity(since = "4")]
+    pub keep_vertices: bool,
+    #[unity(since = "4", align(since = "2.6"))]
+    pub keep_indices: bool,
+    #[unity(when = "version >= (2017, 4) || (version == (2017, 3, 1) && version.build_type.is_patch()) || (version == (2017, 3) && mesh_compression == 0)", default = "if use_16_bit_indices { 0 } else { 1 }")]
+    pub index_format: i32,
+    #[unity(since = "2.6", with = "bytes", rename = "m_IndexBuffer", align)]
+    index_bytes: Vec<u8>,
+    #[unity(until = "3.5", rename = "m_Vertices")]
+    legacy_vertices: Vec<Vector3>,
+    #[unity(until = "2018.2")]
+    pub skin: Option<Vec<BoneWeights4>>,
+    #[unity(until = "4.3", rename = "m_BindPose")]
+    legacy_bind_pose: Vec<Matrix4x4>,
+    #[unity(until = "3.5", rename = "m_UV")]
+    legacy_uv0: Vec<Vector2>,
+    #[unity(until = "3.5", rename = "m_UV1")]
+    legacy_uv1: Vec<Vector2>,
+    #[unity(until = "2.6", rename = "m_TangentSpace")]
+    legacy_tangent_space: Vec<TangentSpace>,
+    #[unity(since = "2.6", until = "3.5", rename = "m_Tangents")]
+    legacy_tangents: Vec<Vector4>,
+    #[unity(since = "2.6", until = "3.5", rename = "m_Normals")]
+    legacy_normals: Vec<Vector3>,
+    #[unity(since = "3.5")]
+    pub vertex_data: Option<VertexData>,
+    #[unity(since = "2.6")]
+    pub compressed_mesh: Option<CompressedMesh>,
+    #[unity(rename = "m_LocalAABB")]
+    pub local_aabb: AnimationClip,
+    #[unity(until = "3.5", with = "colors", rename = "m_Colors")]
+    legacy_colors: Vec<[u8; 4]>,
+    #[unity(until = "3.5")]
+    pub collision_triangles: Vec<u32>,
+    #[unity(until = "3.5")]
+    pub collision_vertex_count: i32,
+    pub mesh_usage_flags: i32,
+    #[unity(since = "2022.1")]
+    pub cooking_options: i32,
+    #[unity(since = "5", with = "bytes", align)]
+    pub baked_convex_collision_mesh: Vec<u8>,
+    #[unity(since = "5", with = "bytes", align)]
+    pub baked_triangle_collision_mesh: Vec<u8>,
+    #[unity(since = "2018.2", flatten, with = "mesh_metrics", align(since = "2018.3"))]
+    pub mesh_metrics: [f32; 2],
+    #[unity(since = "2018.3")]
+    pub stream_data: Option<StreamingInfo>,
+    /// The index buffer as `u32`s. It and the fields below are filled in from the fields above once they are read.
+    #[unity(skip)]
+    pub index_buffer: Vec<u32>,
+    #[unity(skip)]
     pub vertex_count: usize,
+    #[unity(skip)]
     pub vertices: Vec<f32>,
-    pub skin: Option<Vec<BoneWeights4>>,
+    #[unity(skip)]
     pub normals: Vec<f32>,
+    #[unity(skip)]
     pub colors: Vec<f32>,
+    #[unity(skip)]
     pub uv0: Vec<f32>,
+    #[unity(skip)]
     pub uv1: Vec<f32>,
+    #[unity(skip)]
     pub uv2: Vec<f32>,
+    #[unity(skip)]
     pub uv3: Vec<f32>,
+    #[unity(skip)]
     pub uv4: Vec<f32>,
+    #[unity(skip)]
     pub uv5: Vec<f32>,
+    #[unity(skip)]
     pub uv6: Vec<f32>,
+    #[unity(skip)]
     pub uv7: Vec<f32>,
+    #[unity(skip)]
     pub tangents: Vec<f32>,
-    pub vertex_data: Option<VertexData>,
-    pub compressed_mesh: Option<CompressedMesh>,
-    pub stream_data: Option<StreamingInfo>,
+    #[unity(skip)]
     pub indices: Vec<u32>,
 }
 
+/// The normal and tangent of a vertex before 2.6.
+#[derive(Debug, Default)]
+struct TangentSpace {
+    normal: Vector3,
+    tangent: Vector4,
+}
+
+impl Field for TangentSpace {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Ok(Self {
+            normal: r.read_vector3()?,
+            tangent: r.read_vector4()?,
+        })
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            normal: type_tree::vector3(type_tree::field(value, "normal")?)?,
+            tangent: type_tree::vector4(type_tree::field(value, "tangent")?)?,
+        })
+    }
+}
+
+/// `m_Use16BitIndices`, an `int` that is positive for 16-bit indices.
+mod positive {
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(_object: &Object, r: &mut Reader) -> UnityResult<bool> {
+        Ok(r.read_i32()? > 0)
+    }
+
+    pub(super) fn from_value(_object: &Object, value: &Value) -> UnityResult<bool> {
+        Ok(value.as_i64().is_some_and(|x| x > 0))
+    }
+}
+
+/// `m_VariableBoneCountWeights`, whose weights are in `m_Data`.
+mod variable_bone_count_weights {
+    use crate::classes::{type_tree, Field};
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<Vec<u32>> {
+        Vec::read(&object.info, r)
+    }
+
+    pub(super) fn from_value(object: &Object, value: &Value) -> UnityResult<Vec<u32>> {
+        Vec::from_value(&object.info, type_tree::field(value, "m_Data")?)
+    }
+}
+
+/// `m_Colors` before 3.5, as `ColorRGBA32`s stored in the TypeTree as one `rgba` integer each.
+mod colors {
+    use crate::classes::{type_tree, Field};
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<Vec<[u8; 4]>> {
+        Vec::read(&object.info, r)
+    }
+
+    pub(super) fn from_value(_object: &Object, value: &Value) -> UnityResult<Vec<[u8; 4]>> {
+        let colors = value.as_array().ok_or_else(|| type_tree::mistyped("m_Colors"))?;
+        colors.iter().map(|x| Ok((type_tree::int(x, "rgba")? as u32).to_le_bytes())).collect()
+    }
+}
+
+/// `m_MeshMetrics[0]` and `m_MeshMetrics[1]`, two fields of the TypeTree read as one array.
+mod mesh_metrics {
+    use crate::classes::type_tree;
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(_object: &Object, r: &mut Reader) -> UnityResult<[f32; 2]> {
+        r.read_f32_array()
+    }
+
+    pub(super) fn from_value(_object: &Object, tree: &Value) -> UnityResult<[f32; 2]> {
+        Ok([type_tree::float(tree, "m_MeshMetrics[0]")?, type_tree::float(tree, "m_MeshMetrics[1]")?])
+    }
+}
+
+/// The structs of a mesh, read by their own loaders.
+macro_rules! mesh_field {
+    ($($ty:ty => $load_type_tree:expr),* $(,)?) => {
+        $(
+            impl Field for $ty {
+                fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+                    <$ty>::load(info, r)
+                }
+
+                fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+                    $load_type_tree(info, value)
+                }
+            }
+        )*
+    };
+}
+
+mesh_field!(
+    SubMesh => SubMesh::load_type_tree,
+    BlendShapeData => BlendShapeData::load_type_tree,
+    VertexData => VertexData::load_type_tree,
+    CompressedMesh => CompressedMesh::load_type_tree,
+    BoneWeights4 => |_: &ObjectInfo, value| BoneWeights4::load_type_tree(value),
+    MinMaxAABB => |_: &ObjectInfo, value| MinMaxAABB::load_type_tree(value),
+);
+
 impl Mesh {
     fn process_data(&mut self, object: &Object) -> UnityResult<()> {
         let version = object.info.version;
+        self.use_16_bit_indices = self.index_format == 0;
+        let index_bytes = if version < (2, 6) { &self.legacy_index_bytes } else { &self.index_bytes };
+        self.index_buffer = match (self.use_16_bit_indices, object.info.bytes_order) {
+            (true, ByteOrder::Little) => index_bytes.chunks_exact(2).map(|x| u16::from_le_bytes([x[0], x[1]]) as u32).collect(),
+            (true, ByteOrder::Big) => index_bytes.chunks_exact(2).map(|x| u16::from_be_bytes([x[0], x[1]]) as u32).collect(),
+            (false, ByteOrder::Little) => index_bytes.chunks_exact(4).map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]])).collect(),
+            (false, ByteOrder::Big) => index_bytes.chunks_exact(4).map(|x| u32::from_be_bytes([x[0], x[1], x[2], x[3]])).collect(),
+        };
+        if version < (4, 3) {
+            self.bind_pose = std::mem::take(&mut self.legacy_bind_pose);
+        }
+        if version < (3, 5) {
+            self.read_legacy_vertices();
+        }
         if let (Some(stream), Some(vertex_data)) = (&self.stream_data, self.vertex_data.as_mut()) {
             if !stream.path.is_empty() && vertex_data.vertex_count > 0 {
                 let path = stream.path.split('/').last().ok_or(UnityError::InvalidValue)?;
@@ -62,22 +276,38 @@ impl Mesh {
                         continue;
                     }
                     let file = &object.bundle.files[i];
-                    let mut r = Reader::new(file.as_slice(), ByteOrder::Big);
+                    let mut r = Reader::new(file.as_slice(), ByteOrder::Big).with_limits(object.info.limits);
                     r.set_offset(stream.offset as usize)?;
                     vertex_data.data_size = r.read_u8_list(stream.size as usize)?;
                 }
             }
         }
-        if version[0] > 3 || (version[0] == 3 && version[1] >= 5) {
+        if version >= (3, 5) {
             self.read_vertex_data(object)?;
         }
-        if version[0] > 2 || (version[0] == 2 && version[1] >= 6) {
+        if version >= (2, 6) {
             self.decompress_compressed_mesh(object)?;
         }
         self.get_triangles(object)?;
         Ok(())
     }
 
+    /// Flattens the vertex arrays stored before 3.5 like the channels of [`VertexData`].
+    fn read_legacy_vertices(&mut self) {
+        self.vertex_count = self.legacy_vertices.len();
+        self.vertices = self.legacy_vertices.iter().flat_map(|v| [v.x, v.y, v.z]).collect();
+        self.uv0 = self.legacy_uv0.iter().flat_map(|v| [v.x, v.y]).collect();
+        self.uv1 = self.legacy_uv1.iter().flat_map(|v| [v.x, v.y]).collect();
+        let (normals, tangents): (Vec<_>, Vec<_>) = if self.legacy_tangent_space.is_empty() {
+            (self.legacy_normals.iter().collect(), self.legacy_tangents.iter().collect())
+        } else {
+            self.legacy_tangent_space.iter().map(|x| (&x.normal, &x.tangent)).unzip()
+        };
+        self.normals = normals.into_iter().flat_map(|v| [v.x, v.y, v.z]).collect();
+        self.tangents = tangents.into_iter().flat_map(|v| [v.x, v.y, v.z, v.w]).collect();
+        self.colors = self.legacy_colors.iter().flatten().map(|x| *x as f32 / 255.0).collect();
+    }
+
     fn read_vertex_data(&mut self, object: &Object) -> UnityResult<()> {
         let version = object.info.version;
         let Some(vertex_data) = self.vertex_data.as_mut() else {
@@ -95,12 +325,12 @@ impl Mesh {
             if (stream.channel_mask >> chn) & 0x1 == 0 {
                 continue;
             }
-            if version[0] < 2018 && chn == 2 && channel.format == 2 {
+            if version.major < 2018 && chn == 2 && channel.format == 2 {
                 channel.dimension = 4;
             }
             let vertex_format = VertexFormat::load(channel.format, version)?;
             let component_byte_size = vertex_format.get_format_size() as usize;
-            let mut component_bytes = Vec::with_capacity(vertex_count * channel.dimension as usize * component_byte_size);
+            let mut component_bytes = Vec::with_capacity((vertex_count * channel.dimension as usize * component_byte_size).min(vertex_data.data_size.len()));
             for v in 0..vertex_count {
                 let vertex_offset = stream.offset as usize + channel.offset as usize + stream.stride as usize * v;
                 for d in 0..channel.dimension {
@@ -120,7 +350,7 @@ impl Mesh {
             } else {
                 components_f32_array = bytes_to_f32_vec(&component_bytes, vertex_format);
             }
-            if version[0] >= 2018 {
+            if version.major >= 2018 {
                 match chn {
                     0 => self.vertices = components_f32_array,
                     1 => self.normals = components_f32_array,
@@ -178,7 +408,7 @@ impl Mesh {
                     3 => self.uv0 = components_f32_array,
                     4 => self.uv1 = components_f32_array,
                     5 => {
-                        if version[0] >= 5 {
+                        if version.major >= 5 {
                             self.uv2 = components_f32_array;
                         } else {
                             self.tangents = components_f32_array;
@@ -241,7 +471,7 @@ impl Mesh {
                 }
             }
         }
-        if version[0] < 5 {
+        if version.major < 5 {
             if let Some(bind_poses) = &compressed_mesh.bind_poses {
                 let size = bind_poses.num_items as usize / 16;
                 self.bind_pose = Vec::with_capacity(size);
@@ -305,7 +535,7 @@ impl Mesh {
                 self.tangents.extend([x, y, z, w])
             }
         }
-        if version[0] >= 5 {
+        if version.major >= 5 {
             if let Some(float_colors) = &compressed_mesh.float_colors {
                 if float_colors.num_items > 0 {
                     self.colors = float_colors.unpack_floats(1, 4, 0, None)
@@ -319,16 +549,16 @@ impl Mesh {
             let mut bone_pos = 0;
             let mut bone_index_pos = 0;
             let mut j = 0;
-            let mut sum = 0;
+            let mut sum = 0i32;
             for weight in weights {
                 let Some(bone) = skins.get_mut(bone_pos) else {
                     continue;
                 };
                 bone.weight[j] = weight as f32 / 31.0;
-                bone.bone_index[j] = bone_indices[bone_index_pos];
+                bone.bone_index[j] = *bone_indices.get(bone_index_pos).ok_or(UnityError::Eof)?;
                 bone_index_pos += 1;
                 j += 1;
-                sum += weight;
+                sum = sum.saturating_add(weight);
                 if sum >= 31 {
                     bone.bone_index = Default::default();
                     bone.weight = Default::default();
@@ -337,7 +567,7 @@ impl Mesh {
                     sum = 0;
                 } else if j == 3 {
                     bone.weight[j] = (31 - sum) as f32 / 31.0;
-                    bone.bone_index[j] = bone_indices[bone_index_pos];
+                    bone.bone_index[j] = *bone_indices.get(bone_index_pos).ok_or(UnityError::Eof)?;
                     bone_index_pos += 1;
                     bone_pos += 1;
                     j = 0;
@@ -372,7 +602,7 @@ impl Mesh {
             if topology == GfxPrimitiveType::Triangles {
                 let sub = self.index_buffer.get(first_index..(first_index + index_count - index_count % 3)).ok_or(UnityError::Eof)?;
                 self.indices.extend_from_slice(sub)
-            } else if version[0] < 4 || topology == GfxPrimitiveType::TriangleStrip {
+            } else if version.major < 4 || topology == GfxPrimitiveType::TriangleStrip {
                 let mut tri_index = 0;
                 let mut iter = self.index_buffer.get(first_index..).ok_or(UnityError::Eof)?.windows(3).enumerate();
                 while let Some((i, &[a, b, c])) = iter.next() {
@@ -404,197 +634,6 @@ impl Mesh {
     }
 }
 
-impl<'a> FromObject<'a> for Mesh {
-    fn load(object: &'a crate::Object<'a>) -> UnityResult<Self> {
-        let version = object.info.version;
-        let mut r = object.info.get_reader();
-        let name = r.read_aligned_string()?;
-        let mut ret = Mesh {
-            name,
-            use_16_bit_indices: false,
-            sub_meshes: Vec::new(),
-            index_buffer: Vec::new(),
-            shapes: None,
-            bind_pose: Vec::new(),
-            bone_name_hashes: Vec::new(),
-            vertex_count: 0,
-            vertices: Vec::new(),
-            skin: None,
-            normals: Vec::new(),
-            colors: Vec::new(),
-            uv0: Vec::new(),
-            uv1: Vec::new(),
-            uv2: Vec::new(),
-            uv3: Vec::new(),
-            uv4: Vec::new(),
-            uv5: Vec::new(),
-            uv6: Vec::new(),
-            uv7: Vec::new(),
-            tangents: Vec::new(),
-            vertex_data: None,
-            compressed_mesh: None,
-            stream_data: None,
-            indices: Vec::new(),
-        };
-        ret.use_16_bit_indices = if version[0] < 3 || (version[0] == 3 && version[1] < 5) { r.read_i32()? > 0 } else { false };
-        if version[0] == 2 && version[1] <= 5 {
-            let index_buffer_size = r.read_i32()?;
-            if ret.use_16_bit_indices {
-                let index_buffer_size = index_buffer_size as usize / 2;
-                ret.index_buffer = Vec::with_capacity(index_buffer_size);
-                for _ in 0..index_buffer_size {
-                    ret.index_buffer.push(r.read_u16()? as u32);
-                }
-            } else {
-                ret.index_buffer = r.read_u32_list(index_buffer_size as usize / 4)?;
-            }
-        }
-        let sub_meshes_size = r.read_i32()? as usize;
-        ret.sub_meshes = Vec::with_capacity(sub_meshes_size);
-        for _ in 0..sub_meshes_size {
-            ret.sub_meshes.push(SubMesh::load(&object.info, &mut r)?)
-        }
-        if version[0] > 4 || (version[0] == 4 && version[1] >= 1) {
-            ret.shapes = Some(BlendShapeData::load(&object.info, &mut r)?)
-        };
-        if version[0] > 4 || (version[0] == 4 && version[1] >= 3) {
-            let size = r.read_i32()?;
-            ret.bind_pose = r.read_matrix4x4_list(size as usize)?;
-            let size = r.read_i32()?;
-            ret.bone_name_hashes = r.read_u32_list(size as usize)?;
-            let _root_bone_name_hash = r.read_u32()?;
-        }
-
-        if version[0] > 2 || (version[0] == 2 && version[1] >= 6) {
-            if version[0] >= 2019 {
-                let _bones_aabb_size = r.read_i32()?;
-                let mut _bones_aabb = Vec::new();
-                for _ in 0.._bones_aabb_size {
-                    _bones_aabb.push(MinMaxAABB::load(&object.info, &mut r)?)
-                }
-                let _variable_bone_count_weights = r.read_u32()?;
-            }
-            let mesh_compression = r.read_u8()?;
-            if version[0] >= 4 {
-                if version[0] < 5 {
-                    let _stream_compression = r.read_u8()?;
-                }
-                let _is_readable = r.read_bool()?;
-                let _keep_vertices = r.read_bool()?;
-                let _keep_indices = r.read_bool()?;
-            }
-            r.align(4)?;
-            if (version[0] > 2017 || (version[0] == 2017 && version[1] >= 4)) || //2017.4
-            ((version[0] == 2017 && version[1] == 3 && version[2] == 1) && object.info.build_type.is_patch()) || //fixed after 2017.3.1px
-            ((version[0] == 2017 && version[1] == 3) && mesh_compression == 0)
-            {
-                let index_format = r.read_i32()?;
-                ret.use_16_bit_indices = index_format == 0;
-            }
-            let index_buffer_size = r.read_i32()?;
-            if ret.use_16_bit_indices {
-                let index_buffer_size = index_buffer_size as usize / 2;
-                ret.index_buffer = Vec::with_capacity(index_buffer_size);
-                for _ in 0..index_buffer_size {
-                    ret.index_buffer.push(r.read_u16()? as u32);
-                }
-                r.align(4)?;
-            } else {
-                ret.index_buffer = r.read_u32_list(index_buffer_size as usize / 4)?;
-            }
-        }
-        if version[0] < 3 || (version[0] == 3 && version[1] < 5) {
-            ret.vertex_count = r.read_i32()? as usize;
-            ret.vertices = r.read_f32_list(ret.vertex_count)?;
-            let size = r.read_i32()?;
-            let mut skin = Vec::with_capacity(size as usize);
-            for _ in 0..size {
-                skin.push(BoneWeights4::load(&object.info, &mut r)?)
-            }
-            ret.skin = Some(skin);
-            let size = r.read_i32()?;
-            ret.bind_pose = r.read_matrix4x4_list(size as usize)?;
-            let size = r.read_i32()? as usize;
-            ret.uv0 = r.read_f32_list(size * 2)?;
-            let size = r.read_i32()? as usize;
-            ret.uv1 = r.read_f32_list(size * 2)?;
-            if version[0] == 2 && version[1] <= 5 {
-                let tangent_space_size = r.read_i32()? as usize;
-                ret.normals = Vec::with_capacity(tangent_space_size * 3);
-                ret.tangents = Vec::with_capacity(tangent_space_size * 4);
-                for _ in 0..tangent_space_size {
-                    for _ in 0..3 {
-                        ret.normals.push(r.read_f32()?)
-                    }
-                    for _ in 0..4 {
-                        ret.tangents.push(r.read_f32()?)
-                    }
-                }
-            } else {
-                let size = r.read_i32()? as usize;
-                ret.tangents = r.read_f32_list(size * 4)?;
-                let size = r.read_i32()? as usize;
-                ret.normals = r.read_f32_list(size * 3)?;
-            }
-        } else {
-            if version[0] < 2018 || (version[0] == 2018 && version[1] < 2) {
-                let size = r.read_i32()?;
-                let mut skin = Vec::with_capacity(size as usize);
-                for _ in 0..size {
-                    skin.push(BoneWeights4::load(&object.info, &mut r)?)
-                }
-                ret.skin = Some(skin);
-            }
-            if version[0] == 3 || (version[0] == 4 && version[1] <= 2) {
-                let size = r.read_i32()?;
-                ret.bind_pose = r.read_matrix4x4_list(size as usize)?;
-            }
-            ret.vertex_data = Some(VertexData::load(&object.info, &mut r)?);
-        }
-        if version[0] > 2 || (version[0] == 2 && version[1] >= 6) {
-            ret.compressed_mesh = Some(CompressedMesh::load(&object.info, &mut r)?);
-        }
-        let offset = r.get_offset() + 24;
-        r.set_offset(offset)?;
-        if version[0] < 3 || (version[0] == 3 && version[1] <= 4) {
-            let color_size = r.read_i32()? as usize;
-            ret.colors = Vec::with_capacity(color_size * 4);
-            for _ in 0..(color_size * 4) {
-                ret.colors.push(r.read_u8()? as f32 / 255.0)
-            }
-            let collision_triangles_size = r.read_i32()? as usize;
-            let offset = r.get_offset() + collision_triangles_size * 4;
-            r.set_offset(offset)?;
-            let _collision_vertex_count = r.read_i32()?;
-        }
-        let _mesh_usage_flags = r.read_i32()?;
-        if version[0] > 2022 || (version[0] == 2022 && version[1] >= 1) {
-            let _cooking_options = r.read_i32()?;
-        }
-        if version[0] >= 5 {
-            let size = r.read_i32()? as usize;
-            let _baked_convex_collision_mesh = r.read_u8_list(size)?;
-            r.align(4)?;
-            let size = r.read_i32()? as usize;
-            let _baked_triangle_collision_mesh = r.read_u8_list(size)?;
-            r.align(4)?;
-        }
-        if version[0] > 2018 || (version[0] == 2018 && version[1] >= 2) {
-            let _mesh_metrics = r.read_f32_array::<2>()?;
-        }
-        if version[0] > 2018 || (version[0] == 2018 && version[1] >= 3) {
-            r.align(4)?;
-            ret.stream_data = Some(StreamingInfo::load(&object.info, &mut r)?);
-        }
-        ret.process_data(object)?;
-        Ok(ret)
-    }
-
-    fn class() -> super::ClassID {
-        super::ClassID::Mesh
-    }
-}
-
 #[derive(Debug)]
 pub struct BlendShapeData {
     pub vertices: Vec<BlendShapeVertex>,
@@ -605,34 +644,34 @@ pub struct BlendShapeData {
 
 impl BlendShapeData {
     pub(super) fn load(object: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
-        if object.version[0] > 4 || (object.version[0] == 4 && object.version[1] >= 3) {
-            let num_verts = r.read_i32()?;
-            let mut vertices = Vec::with_capacity(num_verts as usize);
+        if object.version >= (4, 3) {
+            let num_verts = r.read_array_len()?;
+            let mut vertices = Vec::with_capacity(num_verts);
             for _ in 0..num_verts {
                 vertices.push(BlendShapeVertex::load(object, r)?);
             }
-            let num_shapes = r.read_i32()?;
-            let mut shapes = Vec::with_capacity(num_shapes as usize);
+            let num_shapes = r.read_array_len()?;
+            let mut shapes = Vec::with_capacity(num_shapes);
             for _ in 0..num_shapes {
                 shapes.push(MeshBlendShape::load(object, r)?);
             }
-            let num_channels = r.read_i32()?;
-            let mut channels = Vec::with_capacity(num_channels as usize);
+            let num_channels = r.read_array_len()?;
+            let mut channels = Vec::with_capacity(num_channels);
             for _ in 0..num_channels {
                 channels.push(MeshBlendShapeChannel::load(object, r)?);
             }
-            let length = r.read_i32()? as usize;
+            let length = r.read_array_len()?;
             let full_weights = r.read_f32_list(length)?;
             Ok(Self { vertices, shapes, channels, full_weights })
         } else {
-            let num_shapes = r.read_i32()?;
-            let mut shapes = Vec::with_capacity(num_shapes as usize);
+            let num_shapes = r.read_array_len()?;
+            let mut shapes = Vec::with_capacity(num_shapes);
             for _ in 0..num_shapes {
                 shapes.push(MeshBlendShape::load(object, r)?);
             }
             r.align(4)?;
-            let num_verts = r.read_i32()?;
-            let mut vertices = Vec::with_capacity(num_verts as usize);
+            let num_verts = r.read_array_len()?;
+            let mut vertices = Vec::with_capacity(num_verts);
             for _ in 0..num_verts {
                 vertices.push(BlendShapeVertex::load(object, r)?);
             }
@@ -644,6 +683,15 @@ impl BlendShapeData {
             })
         }
     }
+
+    pub(super) fn load_type_tree(_object: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            vertices: type_tree::array(value, "vertices")?.iter().map(BlendShapeVertex::load_type_tree).collect::<UnityResult<_>>()?,
+            shapes: type_tree::array(value, "shapes")?.iter().map(MeshBlendShape::load_type_tree).collect::<UnityResult<_>>()?,
+            channels: type_tree::array(value, "channels").unwrap_or_default().iter().map(MeshBlendShapeChannel::load_type_tree).collect::<UnityResult<_>>()?,
+            full_weights: type_tree::floats(value, "fullWeights").unwrap_or_default(),
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -663,6 +711,15 @@ impl BlendShapeVertex {
             index: r.read_u32()?,
         })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            vertex: type_tree::vector3(type_tree::field(value, "vertex")?)?,
+            normal: type_tree::vector3(type_tree::field(value, "normal")?)?,
+            tangent: type_tree::vector3(type_tree::field(value, "tangent")?)?,
+            index: type_tree::int(value, "index")? as u32,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -675,18 +732,18 @@ pub struct MeshBlendShape {
 
 impl MeshBlendShape {
     pub(super) fn load(object: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
-        if object.version[0] == 4 && object.version[1] < 3 {
+        if object.version.major == 4 && object.version.minor < 3 {
             let _name = r.read_aligned_string()?;
         }
         let first_vertex = r.read_u32()?;
         let vertex_count = r.read_u32()?;
-        if object.version[0] == 4 && object.version[1] < 3 {
+        if object.version.major == 4 && object.version.minor < 3 {
             let _aabb_min_delta = r.read_vector3()?;
             let _aabb_max_delta = r.read_vector3()?;
         }
         let has_normals = r.read_bool()?;
         let has_tangent = r.read_bool()?;
-        if object.version[0] > 4 || (object.version[0] == 4 && object.version[1] >= 3) {
+        if object.version >= (4, 3) {
             r.align(4)?;
         }
         Ok(Self {
@@ -696,6 +753,15 @@ impl MeshBlendShape {
             has_tangent,
         })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            first_vertex: type_tree::int(value, "firstVertex")? as u32,
+            vertex_count: type_tree::int(value, "vertexCount")? as u32,
+            has_normals: type_tree::boolean(value, "hasNormals")?,
+            has_tangent: type_tree::boolean(value, "hasTangents")?,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -715,6 +781,15 @@ impl MeshBlendShapeChannel {
             frame_count: r.read_i32()?,
         })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            name: type_tree::string(value, "name")?,
+            name_hash: type_tree::int(value, "nameHash")? as u32,
+            frame_index: type_tree::int(value, "frameIndex")? as i32,
+            frame_count: type_tree::int(value, "frameCount")? as i32,
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -739,19 +814,19 @@ impl CompressedMesh {
         let version = object.version;
         let vertices = PackedFloatVector::load(object, r)?;
         let uv = PackedFloatVector::load(object, r)?;
-        let bind_poses = if version[0] < 5 { Some(PackedFloatVector::load(object, r)?) } else { None };
+        let bind_poses = if version.major < 5 { Some(PackedFloatVector::load(object, r)?) } else { None };
         let normals = PackedFloatVector::load(object, r)?;
         let tangents = PackedFloatVector::load(object, r)?;
         let weights = PackedIntVector::load(object, r)?;
         let normal_signs = PackedIntVector::load(object, r)?;
         let tangent_signs = PackedIntVector::load(object, r)?;
-        let float_colors = if version[0] < 5 { None } else { Some(PackedFloatVector::load(object, r)?) };
+        let float_colors = if version.major < 5 { None } else { Some(PackedFloatVector::load(object, r)?) };
         let bone_indices = PackedIntVector::load(object, r)?;
         let triangles = PackedIntVector::load(object, r)?;
         let mut colors = None;
         let mut uv_info = 0;
-        if version[0] > 3 || (version[0] == 3 && version[1] >= 5) {
-            if version[0] < 5 {
+        if version >= (3, 5) {
+            if version.major < 5 {
                 colors = Some(PackedIntVector::load(object, r)?);
             } else {
                 uv_info = r.read_u32()?;
@@ -773,6 +848,26 @@ impl CompressedMesh {
             uv_info,
         })
     }
+
+    pub(super) fn load_type_tree(_object: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        let float_vector = |name: &str| PackedFloatVector::load_type_tree(type_tree::field(value, name)?);
+        let int_vector = |name: &str| PackedIntVector::load_type_tree(type_tree::field(value, name)?);
+        Ok(Self {
+            vertices: float_vector("m_Vertices")?,
+            uv: float_vector("m_UV")?,
+            bind_poses: float_vector("m_BindPoses").ok(),
+            normals: float_vector("m_Normals")?,
+            tangents: float_vector("m_Tangents")?,
+            weights: int_vector("m_Weights")?,
+            normal_signs: int_vector("m_NormalSigns")?,
+            tangent_signs: int_vector("m_TangentSigns")?,
+            float_colors: float_vector("m_FloatColors").ok(),
+            bone_indices: int_vector("m_BoneIndices")?,
+            triangles: int_vector("m_Triangles")?,
+            colors: int_vector("m_Colors").ok(),
+            uv_info: type_tree::int(value, "m_UVInfo").unwrap_or_default() as u32,
+        })
+    }
 }
 
 #[derive(Default, Debug)]
@@ -794,21 +889,34 @@ impl SubMesh {
         result.first_bytes = r.read_u32()?;
         result.index_count = r.read_u32()?;
         result.topology = r.read_i32()?.try_into().or(Err(UnityError::InvalidValue))?;
-        if version[0] < 4 {
+        if version.major < 4 {
             result.triangle_count = r.read_u32()?;
         }
 
-        if version[0] > 2017 || (version[0] == 2017 && version[1] >= 3) {
+        if version >= (2017, 3) {
             result.base_vertex = r.read_u32()?;
         }
 
-        if version[0] >= 3 {
+        if version.major >= 3 {
             result.first_vertex = r.read_u32()?;
             result.vertex_count = r.read_u32()?;
             result.local_aabb = Some(AnimationClip::load(r)?);
         }
         Ok(result)
     }
+
+    pub(super) fn load_type_tree(_object: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            first_bytes: type_tree::int(value, "firstByte")? as u32,
+            index_count: type_tree::int(value, "indexCount")? as u32,
+            topology: (type_tree::int(value, "topology")? as i32).try_into().or(Err(UnityError::InvalidValue))?,
+            triangle_count: type_tree::int(value, "triangleCount").unwrap_or_default() as u32,
+            base_vertex: type_tree::int(value, "baseVertex").unwrap_or_default() as u32,
+            first_vertex: type_tree::int(value, "firstVertex").unwrap_or_default() as u32,
+            vertex_count: type_tree::int(value, "vertexCount").unwrap_or_default() as u32,
+            local_aabb: type_tree::field(value, "localAABB").ok().map(AnimationClip::load_type_tree).transpose()?,
+        })
+    }
 }
 
 #[derive(Default, Debug)]
@@ -828,6 +936,15 @@ impl ChannelInfo {
             dimension: r.read_u8()? & 0xF,
         })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            stream: type_tree::int(value, "stream")? as u8,
+            offset: type_tree::int(value, "offset")? as u8,
+            format: type_tree::int(value, "format")? as u8,
+            dimension: type_tree::int(value, "dimension")? as u8 & 0xF,
+        })
+    }
 }
 
 #[derive(Default, Debug)]
@@ -849,7 +966,7 @@ impl StreamInfo {
             ..Self::default()
         };
 
-        if version[0] < 4 {
+        if version.major < 4 {
             result.stride = r.read_u32()? as u8;
             result.align = r.read_u32()? as u8;
         } else {
@@ -859,6 +976,17 @@ impl StreamInfo {
         }
         Ok(result)
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            channel_mask: type_tree::int(value, "channelMask")? as u8,
+            offset: type_tree::int(value, "offset")? as u8,
+            stride: type_tree::int(value, "stride")? as u8,
+            align: type_tree::int(value, "align").unwrap_or_default() as u8,
+            divider_op: type_tree::int(value, "dividerOp").unwrap_or_default() as u8,
+            frequency: type_tree::int(value, "frequency").unwrap_or_default() as u16,
+        })
+    }
 }
 #[derive(Default, Debug)]
 pub struct VertexData {
@@ -873,39 +1001,63 @@ impl VertexData {
     pub(super) fn load(object: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
         let version = object.version;
         let mut result = Self::default();
-        if version[0] < 2018 {
+        if version.major < 2018 {
             result.current_channels = r.read_u32()? as u8;
         }
 
         result.vertex_count = r.read_u32()? as usize;
+        if result.vertex_count > r.limits().max_array_len {
+            return Err(UnityError::LimitExceeded("vertex count"));
+        }
 
-        if version[0] >= 4 {
-            let size = r.read_i32()?;
+        if version.major >= 4 {
+            let size = r.read_array_len()?;
             for _ in 0..size {
                 result.channels.push(ChannelInfo::load(object, r)?)
             }
         }
-        if version[0] < 5 {
-            if version[0] < 4 {
+        if version.major < 5 {
+            if version.major < 4 {
                 result.streams = Vec::with_capacity(4);
             } else {
-                result.streams = Vec::with_capacity(r.read_i32()? as usize);
+                result.streams = Vec::with_capacity(r.read_array_len()?);
             }
             for _ in 0..result.streams.capacity() {
                 result.streams.push(StreamInfo::load(object, r)?)
             }
-            if version[0] < 4 {
+            if version.major < 4 {
                 result.get_channels(version)?;
             }
         } else {
             result.get_streams(version)?;
         }
-        let size = r.read_i32()?;
-        result.data_size = r.read_u8_list(size as usize)?;
+        let size = r.read_array_len()?;
+        result.data_size = r.read_u8_list(size)?;
+        Ok(result)
+    }
+
+    pub(super) fn load_type_tree(object: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        let mut result = Self {
+            current_channels: type_tree::int(value, "m_CurrentChannels").unwrap_or_default() as u8,
+            vertex_count: type_tree::int(value, "m_VertexCount")? as usize,
+            channels: type_tree::array(value, "m_Channels").unwrap_or_default().iter().map(ChannelInfo::load_type_tree).collect::<UnityResult<_>>()?,
+            streams: Vec::new(),
+            data_size: type_tree::bytes(value, "m_DataSize")?,
+        };
+        if result.vertex_count > object.limits.max_array_len {
+            return Err(UnityError::LimitExceeded("vertex count"));
+        }
+        match type_tree::array(value, "m_Streams") {
+            Ok(streams) => result.streams = streams.iter().map(StreamInfo::load_type_tree).collect::<UnityResult<_>>()?,
+            Err(_) => result.get_streams(object.version)?,
+        }
+        if result.channels.is_empty() {
+            result.get_channels(object.version)?;
+        }
         Ok(result)
     }
 
-    fn get_channels(&mut self, _version: [i32; 4]) -> UnityResult<()> {
+    fn get_channels(&mut self, _version: UnityVersion) -> UnityResult<()> {
         self.channels = Vec::with_capacity(6);
         for _ in 0..6 {
             self.channels.push(ChannelInfo::default())
@@ -950,7 +1102,7 @@ impl VertexData {
         Ok(())
     }
 
-    fn get_streams(&mut self, version: [i32; 4]) -> UnityResult<()> {
+    fn get_streams(&mut self, version: UnityVersion) -> UnityResult<()> {
         let stream_count = {
             let mut max = 0;
             for i in &self.channels {
@@ -1032,8 +1184,8 @@ pub enum VertexFormat2017 {
     SInt32,
 }
 impl VertexFormat {
-    fn load(format: u8, version: [i32; 4]) -> UnityResult<Self> {
-        if version[0] < 2017 {
+    fn load(format: u8, version: UnityVersion) -> UnityResult<Self> {
+        if version.major < 2017 {
             let result = match VertexChannelFormat::try_from(format).or(Err(UnityError::InvalidValue))? {
                 VertexChannelFormat::Float => VertexFormat::Float,
                 VertexChannelFormat::Float16 => VertexFormat::Float16,
@@ -1043,7 +1195,7 @@ impl VertexFormat {
             };
             return Ok(result);
         }
-        if version[0] < 2019 {
+        if version.major < 2019 {
             let result = match VertexFormat2017::try_from(format).or(Err(UnityError::InvalidValue))? {
                 VertexFormat2017::Float => VertexFormat::Float,
                 VertexFormat2017::Float16 => VertexFormat::Float16,
@@ -1104,6 +1256,15 @@ impl BoneWeights4 {
         let bone_index = r.read_i32_array::<4>()?;
         Ok(Self { weight, bone_index })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        let mut result = Self::default();
+        for i in 0..4 {
+            result.weight[i] = type_tree::float(value, &format!("weight[{i}]"))?;
+            result.bone_index[i] = type_tree::int(value, &format!("boneIndex[{i}]"))? as i32;
+        }
+        Ok(result)
+    }
 }
 
 #[allow(dead_code)]
@@ -1119,6 +1280,13 @@ impl MinMaxAABB {
         let max = r.read_vector3()?;
         Ok(Self { min, max })
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            min: type_tree::vector3(type_tree::field(value, "m_Min")?)?,
+            max: type_tree::vector3(type_tree::field(value, "m_Max")?)?,
+        })
+    }
 }
 
 fn bytes_to_f32_vec(data: &[u8], format: VertexFormat) -> Vec<f32> {
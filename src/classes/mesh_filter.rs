@@ -0,0 +1,11 @@
+use super::game_object::GameObject;
+use super::pptr::PPtr;
+use super::{FromObject, Mesh};
+
+#[derive(FromObject)]
+pub struct MeshFilter {
+    #[unity(pptr)]
+    pub game_object: PPtr<GameObject>,
+    #[unity(pptr)]
+    pub mesh: PPtr<Mesh>,
+}
@@ -1,10 +1,14 @@
 mod animation_clip;
+mod any_object;
 mod audio_clip;
 mod component;
+mod cubemap;
+mod field;
 mod game_object;
 mod id;
 mod material;
 mod mesh;
+mod mesh_filter;
 mod mesh_renderer;
 mod mono_behaviour;
 mod mono_script;
@@ -15,30 +19,74 @@ mod sprite;
 mod sprite_atlas;
 mod text_asset;
 mod texture2d;
+mod texture2d_array;
+mod texture3d;
+mod texture_container;
+mod texture_export;
 mod transform;
+mod type_tree;
 
-use crate::error::UnityResult;
+use crate::error::{UnityError, UnityResult};
 pub use id::ClassID;
+use serde_json::Value;
 
 use crate::env::Object;
+use crate::version::UnityVersion;
+pub use any_object::AnyObject;
 pub use audio_clip::AudioClip;
 pub use component::Component;
+pub use cubemap::Cubemap;
+#[doc(hidden)]
+pub use field::derive;
+pub use field::{Field, PPtrField};
 pub use game_object::GameObject;
 pub use material::Material;
 pub use mesh::Mesh;
+pub use mesh_filter::MeshFilter;
 pub use mesh_renderer::MeshRenderer;
 pub use mono_behaviour::MonoBehaviour;
 pub use mono_script::MonoScript;
+pub use pptr::PPtr;
 pub use renderer::Renderer;
+pub use shader::Shader;
 pub use sprite::Sprite;
+pub use sprite_atlas::SpriteAtlas;
 pub use text_asset::TextAsset;
-pub use texture2d::Texture2D;
-pub use transform::Transform;
+pub use texture2d::{ImageKey, Texture2D, TextureFormat};
+pub use texture2d_array::Texture2DArray;
+pub use texture3d::Texture3D;
+pub use texture_container::Container;
+pub use texture_export::{ExportOptions, LightmapEncoding};
+pub use transform::{RectTransform, Transform};
 
-pub trait FromObject<'a>
+pub use unity_rs_derive::FromObject;
+
+pub trait FromObject
 where
     Self: Sized,
 {
-    fn load(object: &'a Object<'a>) -> UnityResult<Self>;
+    fn load(object: &Object) -> UnityResult<Self>;
     fn class() -> ClassID;
+
+    /// Builds the value from the object's decoded TypeTree, used when `load` does not know the Unity version or fails.
+    fn load_type_tree(_object: &Object, _tree: &Value) -> UnityResult<Self> {
+        Err(UnityError::Unimplemented)
+    }
+
+    /// Whether `load` reads only the leading fields of objects of this Unity version, which exempts them from the
+    /// strict size check.
+    fn partial(_version: UnityVersion) -> bool {
+        false
+    }
+
+    /// Values `load` read, by TypeTree field name, which strict mode compares with the object's TypeTree once the
+    /// sizes match. Fields missing from the TypeTree are not compared.
+    fn checked_fields(&self) -> Vec<(&'static str, Value)> {
+        Vec::new()
+    }
+}
+
+/// Whether the hand-written loaders have been written against this Unity version.
+pub(crate) fn is_known_version(version: UnityVersion) -> bool {
+    (1..=2022).contains(&version.major)
 }
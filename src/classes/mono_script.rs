@@ -1,40 +1,20 @@
-use crate::classes::FromObject;
-use crate::env::Object;
-use crate::error::UnityResult;
+use super::FromObject;
 
+#[derive(FromObject)]
 pub struct MonoScript {
     pub name: String,
+    #[unity(since = "3.4")]
+    pub execution_order: i32,
+    #[unity(until = "5", rename = "m_PropertiesHash")]
+    pub legacy_properties_hash: u32,
+    #[unity(since = "5")]
+    pub properties_hash: [u8; 16],
+    #[unity(until = "3")]
+    pub path_name: String,
     pub class_name: String,
+    #[unity(since = "3")]
     pub namespace: Option<String>,
     pub assembly_name: String,
-}
-
-impl FromObject<'_> for MonoScript {
-    fn load(object: &Object) -> UnityResult<Self> {
-        let version = object.info.version;
-        let mut r = object.info.get_reader();
-        let name = r.read_aligned_string()?;
-        if version[0] > 3 || (version[0] == 3 && version[1] >= 4) {
-            let _execution_order = r.read_i32()?;
-        }
-        if version[0] < 5 {
-            let _properties_hash = r.read_u32()?;
-        } else {
-            let _properties_hash = r.read_u8_array::<16>()?;
-        }
-        if version[0] < 3 {
-            let _path_name = r.read_aligned_string()?;
-        }
-        let class_name = r.read_aligned_string()?;
-        let namespace = if version[0] >= 3 { Some(r.read_aligned_string()?) } else { None };
-        let assembly_name = r.read_aligned_string()?;
-        if version[0] < 2018 || (version[0] == 2018 && version[1] < 2) {
-            let _is_editor_script = r.read_bool()?;
-        }
-        Ok(Self { name, class_name, namespace, assembly_name })
-    }
-
-    fn class() -> super::ClassID {
-        super::ClassID::MonoScript
-    }
+    #[unity(until = "2018.2")]
+    pub is_editor_script: bool,
 }
@@ -1,57 +1,60 @@
 use crate::classes::FromObject;
-use crate::env::{Env, Object};
+use crate::env::{Object, Shared};
 use crate::error::UnityResult;
 use crate::reader::Reader;
+use serde_json::Value;
+use std::sync::Arc;
 use std::{any::type_name, marker::PhantomData};
 
-pub struct PPtr<'a, T: FromObject<'a> + 'a> {
-    env: &'a Env,
+/// A reference to another object, which keeps the bundles of the object it was read from alive.
+pub struct PPtr<T: FromObject> {
+    env: Arc<Shared>,
     pub file_id: i32,
     pub path_id: i64,
-    target: PhantomData<T>,
+    target: PhantomData<fn() -> T>,
 }
 
-impl<'a, T: FromObject<'a>> PPtr<'a, T> {
-    pub fn load(object: &'a Object, r: &mut Reader) -> UnityResult<Self> {
+impl<T: FromObject> PPtr<T> {
+    pub fn load(object: &Object, r: &mut Reader) -> UnityResult<Self> {
         let file_id = r.read_i32()?;
         let path_id = if object.info.asset_version < 14 { r.read_i32()? as i64 } else { r.read_i64()? };
         Ok(Self {
-            env: object.env,
+            env: object.env.clone(),
             file_id,
             path_id,
             target: PhantomData,
         })
     }
 
-    pub fn get_obj(&self) -> Option<Object<'a>> {
+    pub(super) fn load_type_tree(object: &Object, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            env: object.env.clone(),
+            file_id: super::type_tree::int(value, "m_FileID")? as i32,
+            path_id: super::type_tree::int(value, "m_PathID")?,
+            target: PhantomData,
+        })
+    }
+
+    pub fn get_obj(&self) -> Option<Object> {
         if self.path_id == 0 {
             return None;
         }
-        for bundle in &self.env.bundles {
-            for asset in &bundle.assets {
-                for info in &asset.objects_info {
-                    if info.path_id != self.path_id {
-                        continue;
-                    }
-                    if info.class() != T::class() {
-                        continue;
-                    }
-                    let obj = Object {
-                        env: self.env,
-                        bundle,
-                        asset,
-                        info: info.clone(),
-                        cache: self.env.cache.clone(),
-                    };
-                    return Some(obj);
-                }
-            }
+        self.env.find_object_with_class(self.path_id, T::class())
+    }
+}
+
+impl<T: FromObject> Clone for PPtr<T> {
+    fn clone(&self) -> Self {
+        Self {
+            env: self.env.clone(),
+            file_id: self.file_id,
+            path_id: self.path_id,
+            target: PhantomData,
         }
-        None
     }
 }
 
-impl<'a, T: FromObject<'a>> std::fmt::Debug for PPtr<'a, T> {
+impl<T: FromObject> std::fmt::Debug for PPtr<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let t = type_name::<T>();
         let (_, t) = t.rsplit_once("::").unwrap();
@@ -1,6 +1,6 @@
 use either::Either;
 
-use crate::{object::ObjectInfo, reader::Reader, UnityResult};
+use crate::{math::Vector4, object::ObjectInfo, reader::Reader, Object, UnityResult};
 
 use super::{pptr::PPtr, FromObject, GameObject, Material, Transform};
 
@@ -36,107 +36,119 @@ impl SubMeshInfo {
     }
 }
 
-pub struct Renderer<'a> {
-    pub game_object: PPtr<'a, GameObject<'a>>,
-    pub materials: Vec<PPtr<'a, Material<'a>>>,
+#[derive(FromObject)]
+#[unity(partial, finish = "Renderer::fold_legacy_fields")]
+pub struct Renderer {
+    #[unity(pptr)]
+    pub game_object: PPtr<GameObject>,
+    #[unity(align(since = "5", until = "5.4"))]
+    pub enabled: bool,
+    pub cast_shadows: u8,
+    #[unity(align(since = "5", until = "5.4"))]
+    pub receive_shadows: u8,
+    #[unity(until = "5", rename = "m_LightmapIndex")]
+    legacy_lightmap_index: u8,
+    #[unity(since = "2017.2")]
+    pub dynamic_occludee: u8,
+    #[unity(since = "2021")]
+    pub static_shadow_caster: u8,
+    #[unity(since = "5.4")]
+    pub motion_vectors: u8,
+    #[unity(since = "5.4")]
+    pub light_probe_usage: u8,
+    /// An `i32` before 5.4.
+    #[unity(since = "5.4")]
+    pub reflection_probe_usage: u8,
+    #[unity(since = "2019.3")]
+    pub ray_tracing_mode: u8,
+    #[unity(since = "2020", align(since = "5.4"))]
+    pub ray_trace_procedural: u8,
+    #[unity(since = "2018")]
+    pub rendering_layer_mask: u32,
+    #[unity(since = "2018.3")]
+    pub renderer_priority: i32,
+    /// A `u8` before 5.
+    #[unity(since = "5")]
+    pub lightmap_index: u16,
+    #[unity(since = "5")]
+    pub lightmap_index_dynamic: u16,
+    #[unity(since = "3")]
+    pub lightmap_tiling_offset: Vector4,
+    #[unity(since = "5")]
+    pub lightmap_tiling_offset_dynamic: Vector4,
+    #[unity(pptr)]
+    pub materials: Vec<PPtr<Material>>,
+    #[unity(until = "3", rename = "m_LightmapTilingOffset")]
+    legacy_lightmap_tiling_offset: Vector4,
+    #[unity(since = "3", flatten, with = "sub_mesh_info")]
     pub sub_mesh_info: Option<SubMeshInfo>,
+    #[unity(since = "3", pptr)]
+    pub static_batch_root: Option<PPtr<Transform>>,
+    /// `m_LightProbeAnchor` before 5.4.
+    #[unity(since = "5.4", pptr)]
+    pub probe_anchor: Option<PPtr<Transform>>,
+    #[unity(since = "5.4", pptr)]
+    pub light_probe_volume_override: Option<PPtr<GameObject>>,
+    #[unity(since = "3.5", until = "5.4", align)]
+    pub use_light_probes: bool,
+    #[unity(since = "5", until = "5.4", rename = "m_ReflectionProbeUsage")]
+    legacy_reflection_probe_usage: i32,
+    #[unity(since = "3.5", until = "5.4", pptr, rename = "m_LightProbeAnchor")]
+    legacy_probe_anchor: Option<PPtr<Transform>>,
+    #[unity(since = "4.4")]
+    pub sorting_layer_id: u32,
+    #[unity(when = "version >= (5, 6) || (version >= (4, 3) && version < (4, 4))")]
+    pub sorting_layer: i16,
+    #[unity(since = "4.3", align)]
+    pub sorting_order: i16,
 }
 
-impl<'a> FromObject<'a> for Renderer<'a> {
-    fn load(object: &'a crate::Object<'a>) -> UnityResult<Self> {
-        let version = object.info.version;
-        let mut r = object.info.get_reader();
-        let game_object = PPtr::load(object, &mut r)?;
-        if version[0] < 5 {
-            let _enabled = r.read_bool()?;
-            let _cast_shadows = r.read_bool()?;
-            let _receive_shadows = r.read_bool()?;
-            let _lightmap_index = r.read_u8()?;
-        } else {
-            if version[0] > 5 || (version[0] == 5 && version[1] >= 4) {
-                let _enabled = r.read_bool()?;
-                let _cast_shadows = r.read_u8()?;
-                let _receive_shadows = r.read_u8()?;
-                if version[0] > 2017 || (version[0] == 2017 && version[1] >= 2) {
-                    let _dynamic_occludee = r.read_u8()?;
-                }
-                if version[0] >= 2021 {
-                    let _static_shadow_caster = r.read_u8()?;
-                }
-                let _motion_vectors = r.read_u8()?;
-                let _light_probe_usage = r.read_u8()?;
-                let _reflection_probe_usage = r.read_u8()?;
-                if version[0] > 2019 || (version[0] == 2019 && version[1] >= 3) {
-                    let _ray_tracing_mode = r.read_u8()?;
-                }
-                if version[0] >= 2020 {
-                    let _ray_trace_procedural = r.read_u8()?;
-                }
-                r.align(4)?;
-            } else {
-                let _enabled = r.read_bool()?;
-                r.align(4)?;
-                let _cast_shadows = r.read_u8()?;
-                let _receive_shadows = r.read_bool()?;
-                r.align(4)?;
-            }
-            if version[0] >= 2018 {
-                let _rendering_layer_mask = r.read_u32()?;
-            }
-            if version[0] > 2018 || (version[0] == 2018 && version[1] >= 3) {
-                let _renderer_priority = r.read_i32()?;
-            }
-            let _lightmap_index = r.read_u16()?;
-            let _lightmap_index_dynamic = r.read_u16()?;
-        }
-        if version[0] >= 3 {
-            let _lightmap_tiling_offset = r.read_vector4()?;
+impl Renderer {
+    /// Moves the fields that changed type or name into the fields of the current layout.
+    fn fold_legacy_fields(&mut self, object: &Object) -> UnityResult<()> {
+        let version = object.version();
+        if version < (5, 0) {
+            self.lightmap_index = self.legacy_lightmap_index as u16;
         }
-        if version[0] >= 5 {
-            let _lightmap_tiling_offset_dynamic = r.read_vector4()?;
+        if version < (3, 0) {
+            self.lightmap_tiling_offset = self.legacy_lightmap_tiling_offset;
         }
-        let materials_size = r.read_i32()?;
-        let mut materials = Vec::with_capacity(materials_size as usize);
-        for _ in 0..materials_size {
-            materials.push(PPtr::load(object, &mut r)?);
-        }
-        let mut sub_mesh_info = None;
-        if version[0] < 3 {
-            let _lightmap_tiling_offset = r.read_vector4()?;
-        } else {
-            if version[0] > 5 || (version[0] == 5 && version[1] >= 5) {
-                sub_mesh_info = Some(SubMeshInfo::StaticBatchInfo(StaticBatchInfo::load(&object.info, &mut r)?))
-            } else {
-                let size = r.read_i32()? as usize;
-                sub_mesh_info = Some(SubMeshInfo::SubsetIndices(r.read_u32_list(size)?))
-            }
-            let _static_batch_root = PPtr::<Transform>::load(object, &mut r)?;
-        }
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 4) {
-            let _probe_anchor = PPtr::<Transform>::load(object, &mut r)?;
-            let _light_probe_volume_override = PPtr::<GameObject>::load(object, &mut r)?;
-        } else if version[0] > 3 || (version[0] == 3 && version[1] >= 5) {
-            let _use_light_probes = r.read_bool()?;
-            r.align(4)?;
-            if version[0] >= 5 {
-                let _reflection_probe_usage = r.read_i32()?;
-            }
-            let _light_probe_anchor = PPtr::<Transform>::load(object, &mut r)?;
+        if version < (5, 4) {
+            self.reflection_probe_usage = self.legacy_reflection_probe_usage as u8;
+            self.probe_anchor = self.legacy_probe_anchor.take();
         }
+        Ok(())
+    }
+}
 
-        if version[0] > 4 || (version[0] == 4 && version[1] >= 3) {
-            if version[0] == 4 && version[1] == 3 {
-                let _sorting_layer = r.read_i16()?;
-            } else {
-                let _sorting_layer_id = r.read_u32()?;
-            }
-            let _sorting_order = r.read_i16()?;
-            r.align(4)?;
+/// `m_StaticBatchInfo` from 5.5, `m_SubsetIndices` before it.
+mod sub_mesh_info {
+    use super::{StaticBatchInfo, SubMeshInfo};
+    use crate::classes::type_tree;
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<Option<SubMeshInfo>> {
+        if object.version() >= (5, 5) {
+            Ok(Some(SubMeshInfo::StaticBatchInfo(StaticBatchInfo::load(&object.info, r)?)))
+        } else {
+            let size = r.read_array_len()?;
+            Ok(Some(SubMeshInfo::SubsetIndices(r.read_u32_list(size)?)))
         }
-        Ok(Self { game_object, materials, sub_mesh_info })
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::Renderer
+    pub(super) fn from_value(_object: &Object, tree: &Value) -> UnityResult<Option<SubMeshInfo>> {
+        if let Ok(info) = type_tree::field(tree, "m_StaticBatchInfo") {
+            Ok(Some(SubMeshInfo::StaticBatchInfo(StaticBatchInfo {
+                first_sub_mesh: type_tree::int(info, "firstSubMesh")? as u16,
+                sub_mesh_count: type_tree::int(info, "subMeshCount")? as u16,
+            })))
+        } else if let Ok(indices) = type_tree::ints(tree, "m_SubsetIndices") {
+            Ok(Some(SubMeshInfo::SubsetIndices(indices.into_iter().map(|x| x as u32).collect())))
+        } else {
+            Ok(None)
+        }
     }
 }
@@ -1,15 +1,8 @@
-use crate::{Object, UnityError, UnityResult};
-
 use super::FromObject;
 
-pub struct Shader {}
-
-impl FromObject<'_> for Shader {
-    fn load(_object: &Object) -> UnityResult<Self> {
-        Err(UnityError::Unimplemented)
-    }
-
-    fn class() -> super::ClassID {
-        super::ClassID::Shader
-    }
+/// The name of a shader; its programs and properties are left unread.
+#[derive(FromObject)]
+#[unity(partial)]
+pub struct Shader {
+    pub name: String,
 }
@@ -1,7 +1,7 @@
 use crate::classes::mesh::{SubMesh, VertexData};
 use crate::classes::pptr::PPtr;
 use crate::classes::sprite_atlas::SpriteAtlas;
-use crate::classes::{FromObject, Texture2D};
+use crate::classes::{type_tree, FromObject, Texture2D};
 use crate::env::Object;
 use crate::error::UnityResult;
 use crate::math::{Matrix4x4, RectF32, Vector2, Vector3, Vector4};
@@ -11,22 +11,30 @@ use crate::UnityError;
 use image::imageops::FilterType;
 use image::{DynamicImage, RgbaImage};
 use imageproc::point::Point;
+use serde_json::Value;
 use std::borrow::Cow;
 
 use super::mesh::BoneWeights4;
 
 #[derive(Debug)]
-pub struct SecondarySpriteTexture<'a> {
-    pub texture: PPtr<'a, Texture2D>,
+pub struct SecondarySpriteTexture {
+    pub texture: PPtr<Texture2D>,
     pub name: String,
 }
 
-impl<'a> SecondarySpriteTexture<'a> {
-    pub fn load(object: &'a Object, r: &mut Reader) -> UnityResult<Self> {
+impl SecondarySpriteTexture {
+    pub fn load(object: &Object, r: &mut Reader) -> UnityResult<Self> {
         let texture = PPtr::load(object, r)?;
         let name = r.read_string_util_null()?;
         Ok(Self { texture, name })
     }
+
+    pub fn load_type_tree(object: &Object, value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            texture: PPtr::load_type_tree(object, type_tree::field(value, "texture")?)?,
+            name: type_tree::string(value, "name")?,
+        })
+    }
 }
 
 #[derive(Default, Debug)]
@@ -40,7 +48,7 @@ impl SpriteVertex {
         let mut result = Self::default();
         let version = object.version;
         result.pos = r.read_vector3()?;
-        if version[0] < 4 || (version[0] == 4 && version[1] <= 3) {
+        if version <= (4, 3) {
             result.uv = r.read_vector2()?;
         }
         Ok(result)
@@ -48,10 +56,10 @@ impl SpriteVertex {
 }
 
 #[derive(Debug)]
-pub struct SpriteRenderData<'a> {
-    pub texture: PPtr<'a, Texture2D>,
-    pub alpha_texture: Option<PPtr<'a, Texture2D>>,
-    pub secondary_textures: Vec<SecondarySpriteTexture<'a>>,
+pub struct SpriteRenderData {
+    pub texture: PPtr<Texture2D>,
+    pub alpha_texture: Option<PPtr<Texture2D>>,
+    pub secondary_textures: Vec<SecondarySpriteTexture>,
     pub sub_meshes: Vec<SubMesh>,
     pub index_buffer: Vec<u8>,
     pub vertex_data: VertexData,
@@ -67,8 +75,8 @@ pub struct SpriteRenderData<'a> {
     pub downscale_multiplier: f32,
 }
 
-impl<'a> SpriteRenderData<'a> {
-    pub fn load(object: &'a Object, r: &mut Reader) -> UnityResult<Self> {
+impl SpriteRenderData {
+    pub fn read(object: &Object, r: &mut Reader) -> UnityResult<Self> {
         let version = object.info.version;
         let mut result = Self {
             texture: PPtr::load(object, r)?,
@@ -88,38 +96,38 @@ impl<'a> SpriteRenderData<'a> {
             uv_transform: Vector4::default(),
             downscale_multiplier: 1.0,
         };
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 2) {
+        if version >= (5, 2) {
             result.alpha_texture = Some(PPtr::load(object, r)?);
         }
-        if version[0] >= 2019 {
-            let size = r.read_i32()?;
+        if version.major >= 2019 {
+            let size = r.read_array_len()?;
             for _ in 0..size {
                 result.secondary_textures.push(SecondarySpriteTexture::load(object, r)?)
             }
         }
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 6) {
-            let size = r.read_i32()?;
+        if version >= (5, 6) {
+            let size = r.read_array_len()?;
             for _ in 0..size {
                 result.sub_meshes.push(SubMesh::load(&object.info, r)?)
             }
-            let size = r.read_i32()?;
-            result.index_buffer = r.read_u8_list(size as usize)?;
+            let size = r.read_array_len()?;
+            result.index_buffer = r.read_u8_list(size)?;
             r.align(4)?;
             result.vertex_data = VertexData::load(&object.info, r)?;
         } else {
-            let size = r.read_i32()?;
+            let size = r.read_array_len()?;
             for _ in 0..size {
                 result.vertices.push(SpriteVertex::load(&object.info, r)?)
             }
-            let size = r.read_i32()?;
-            result.indices = r.read_u16_list(size as usize)?;
+            let size = r.read_array_len()?;
+            result.indices = r.read_u16_list(size)?;
             r.align(4)?;
         }
-        if version[0] > 2018 {
-            let size = r.read_i32()?;
-            result.bindpose = r.read_matrix4x4_list(size as usize)?;
-            if version[0] == 2018 && version[1] < 2 {
-                let size = r.read_i32()? as usize;
+        if version.major >= 2018 {
+            let size = r.read_array_len()?;
+            result.bindpose = r.read_matrix4x4_list(size)?;
+            if version.major == 2018 && version.minor < 2 {
+                let size = r.read_array_len()?;
                 result.source_skin = Vec::with_capacity(size);
                 for _ in 0..size {
                     result.source_skin.push(BoneWeights4::load(&object.info, r)?);
@@ -128,33 +136,70 @@ impl<'a> SpriteRenderData<'a> {
         }
         result.texture_rect = r.read_rect_f32()?;
         result.texture_rect_offset = r.read_vector2()?;
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 6) {
+        if version >= (5, 6) {
             result.atlas_rect_offset = r.read_vector2()?;
         }
         result.setting_raw = SpriteSettings::load(&object.info, r)?;
-        if version[0] > 4 || (version[0] == 4 && version[1] >= 5) {
+        if version >= (4, 5) {
             result.uv_transform = r.read_vector4()?;
         }
-        if version[0] >= 2017 {
+        if version.major >= 2017 {
             result.downscale_multiplier = r.read_f32()?;
         }
         Ok(result)
     }
 
+    pub fn from_value(object: &Object, value: &Value) -> UnityResult<Self> {
+        let info = &object.info;
+        let vector2 = |name: &str| type_tree::vector2(type_tree::field(value, name)?);
+        Ok(Self {
+            texture: PPtr::load_type_tree(object, type_tree::field(value, "texture")?)?,
+            alpha_texture: type_tree::field(value, "alphaTexture").ok().map(|x| PPtr::load_type_tree(object, x)).transpose()?,
+            secondary_textures: type_tree::array(value, "secondaryTextures")
+                .unwrap_or_default()
+                .iter()
+                .map(|x| SecondarySpriteTexture::load_type_tree(object, x))
+                .collect::<UnityResult<_>>()?,
+            sub_meshes: type_tree::array(value, "m_SubMeshes").unwrap_or_default().iter().map(|x| SubMesh::load_type_tree(info, x)).collect::<UnityResult<_>>()?,
+            index_buffer: type_tree::bytes(value, "m_IndexBuffer").unwrap_or_default(),
+            vertex_data: match type_tree::field(value, "m_VertexData") {
+                Ok(vertex_data) => VertexData::load_type_tree(info, vertex_data)?,
+                Err(_) => VertexData::default(),
+            },
+            vertices: type_tree::array(value, "vertices")
+                .unwrap_or_default()
+                .iter()
+                .map(|x| {
+                    Ok(SpriteVertex {
+                        pos: type_tree::vector3(type_tree::field(x, "pos")?)?,
+                        uv: type_tree::field(x, "uv").and_then(type_tree::vector2).unwrap_or_default(),
+                    })
+                })
+                .collect::<UnityResult<_>>()?,
+            indices: type_tree::ints(value, "indices").unwrap_or_default().into_iter().map(|x| x as u16).collect(),
+            bindpose: type_tree::array(value, "m_Bindpose").unwrap_or_default().iter().map(type_tree::matrix4x4).collect::<UnityResult<_>>()?,
+            source_skin: type_tree::array(value, "m_SourceSkin").unwrap_or_default().iter().map(BoneWeights4::load_type_tree).collect::<UnityResult<_>>()?,
+            texture_rect: type_tree::rect(type_tree::field(value, "textureRect")?)?,
+            texture_rect_offset: vector2("textureRectOffset")?,
+            atlas_rect_offset: vector2("atlasRectOffset").unwrap_or_default(),
+            setting_raw: SpriteSettings::from_raw(type_tree::int(value, "settingsRaw")? as u32)?,
+            uv_transform: type_tree::field(value, "uvTransform").and_then(type_tree::vector4).unwrap_or_default(),
+            downscale_multiplier: type_tree::float(value, "downscaleMultiplier").unwrap_or(1.0),
+        })
+    }
+
     pub fn get_triangles(&self) -> UnityResult<Vec<[Vector2; 3]>> {
         let mut result = Vec::new();
         if !self.vertices.is_empty() {
             let vertices: Vec<_> = self.vertices.iter().map(|i| Vector2 { x: i.pos.x, y: i.pos.y }).collect();
-            let triangle_count = self.indices.len() / 3;
-            for i in 0..triangle_count {
-                let first = self.indices[3 * i] as usize;
-                let second = self.indices[3 * i + 1] as usize;
-                let third = self.indices[3 * i + 2] as usize;
-                result.push([vertices[first], vertices[second], vertices[third]])
+            let vertex = |index: u16| vertices.get(index as usize).copied().ok_or(UnityError::InvalidValue);
+            for triangle in self.indices.chunks_exact(3) {
+                result.push([vertex(triangle[0])?, vertex(triangle[1])?, vertex(triangle[2])?])
             }
         } else {
-            let channel = &self.vertex_data.channels[0];
-            let stream = &self.vertex_data.streams[channel.stream as usize];
+            let channel = self.vertex_data.channels.first().ok_or(UnityError::InvalidValue)?;
+            let stream = self.vertex_data.streams.get(channel.stream as usize).ok_or(UnityError::InvalidValue)?;
+            let stride = (stream.stride as usize).checked_sub(12).ok_or(UnityError::InvalidValue)?;
             let mut vertex_r = Reader::new(&self.vertex_data.data_size, ByteOrder::Little);
             let mut index_r = Reader::new(&self.index_buffer, ByteOrder::Little);
             for sub_mesh in &self.sub_meshes {
@@ -162,20 +207,21 @@ impl<'a> SpriteRenderData<'a> {
                 offset += sub_mesh.first_vertex as usize * stream.stride as usize;
                 offset += channel.offset as usize;
                 vertex_r.set_offset(offset)?;
-                let mut vertices = Vec::with_capacity(sub_mesh.vertex_count as usize);
+                let mut vertices = Vec::with_capacity((sub_mesh.vertex_count as usize).min(vertex_r.len() / 12));
                 for _ in 0..sub_mesh.vertex_count {
                     let v3 = vertex_r.read_vector3()?;
                     vertices.push(Vector2 { x: v3.x, y: v3.y });
                     let offset = vertex_r.get_offset();
-                    vertex_r.set_offset(offset + stream.stride as usize - 12)?;
+                    vertex_r.set_offset(offset + stride)?;
                 }
                 index_r.set_offset(sub_mesh.first_bytes as usize)?;
                 let triangle_count = sub_mesh.index_count as usize / 3;
+                let mut vertex = || -> UnityResult<Vector2> {
+                    let index = index_r.read_u16()?.wrapping_sub(sub_mesh.first_bytes as u16);
+                    vertices.get(index as usize).copied().ok_or(UnityError::InvalidValue)
+                };
                 for _ in 0..triangle_count {
-                    let first = index_r.read_u16()? - sub_mesh.first_bytes as u16;
-                    let second = index_r.read_u16()? - sub_mesh.first_bytes as u16;
-                    let third = index_r.read_u16()? - sub_mesh.first_bytes as u16;
-                    result.push([vertices[first as usize], vertices[second as usize], vertices[third as usize]])
+                    result.push([vertex()?, vertex()?, vertex()?])
                 }
             }
         }
@@ -233,12 +279,14 @@ pub struct SpriteSettings {
 
 impl SpriteSettings {
     pub fn load(_object: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
-        let raw = r.read_u32()?;
+        Self::from_raw(r.read_u32()?)
+    }
+
+    pub(super) fn from_raw(raw: u32) -> UnityResult<Self> {
         let packed = raw & 1 == 1;
         let packing_mode = match (raw >> 1) & 1 {
             0 => SpritePackingMode::Tight,
-            1 => SpritePackingMode::Rectangle,
-            _ => unreachable!(),
+            _ => SpritePackingMode::Rectangle,
         };
         let packing_rotation = match (raw >> 2) & 0xf {
             0 => SpritePackingRotation::None,
@@ -246,12 +294,11 @@ impl SpriteSettings {
             2 => SpritePackingRotation::FlipVertical,
             3 => SpritePackingRotation::Rotate180,
             4 => SpritePackingRotation::Rotate90,
-            _ => unreachable!(),
+            rotation => return Err(UnityError::CustomError(format!("unknown sprite packing rotation {rotation}"))),
         };
         let mesh_type = match (raw >> 6) & 1 {
             0 => SpriteMeshType::FullRect,
-            1 => SpriteMeshType::Tight,
-            _ => unreachable!(),
+            _ => SpriteMeshType::Tight,
         };
         Ok(Self {
             raw,
@@ -263,83 +310,50 @@ impl SpriteSettings {
     }
 }
 
-#[derive(Debug)]
-pub struct Sprite<'a> {
+#[derive(Debug, FromObject)]
+#[unity(partial = "2018")]
+pub struct Sprite {
     pub name: String,
     pub rect: RectF32,
     pub offset: Vector2,
+    #[unity(since = "4.5")]
     pub border: Option<Vector4>,
     pub pixels_to_units: f32,
+    #[unity(when = "version >= (5, 4, 2) || (version == (5, 4, 1) && version.build_type.is_patch() && version.build >= 3)", default = "Vector2 { x: 0.5, y: 0.5 }")]
     pub pivot: Vector2,
-    pub extrude: u8,
+    pub extrude: u32,
+    #[unity(since = "5.3", align)]
     pub is_polygon: bool,
+    #[unity(since = "2017", with = "render_data_key")]
     pub render_data_key: ([u8; 16], i64),
+    #[unity(since = "2017")]
     pub atlas_tags: Vec<String>,
-    pub sprite_atlas: Option<PPtr<'a, SpriteAtlas<'a>>>,
-    pub rd: SpriteRenderData<'a>,
+    #[unity(since = "2017", pptr)]
+    pub sprite_atlas: Option<PPtr<SpriteAtlas>>,
+    #[unity(rename = "m_RD", with = "SpriteRenderData")]
+    pub rd: SpriteRenderData,
+    #[unity(since = "2017")]
+    pub physics_shape: Vec<Vec<Vector2>>,
 }
 
-impl<'a> FromObject<'a> for Sprite<'a> {
-    fn load(object: &'a Object) -> UnityResult<Self> {
-        let version = object.info.version;
-
-        let mut border: Option<Vector4> = None;
-
-        let mut pivot: Vector2 = Vector2 { x: 0.5, y: 0.5 };
+/// The key of the sprite's render data in its atlas, a GUID and a 64-bit id.
+mod render_data_key {
+    use crate::classes::type_tree;
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
 
-        let mut is_polygon: bool = false;
-        let mut render_data_key: ([u8; 16], i64) = ([0u8; 16], 0);
-        let mut atlas_tags: Vec<String> = Vec::new();
-        let mut sprite_atlas: Option<PPtr<SpriteAtlas>> = None;
-
-        let mut r = object.info.get_reader();
-        let name: String = r.read_aligned_string()?;
-        let rect: RectF32 = r.read_rect_f32()?;
-        let offset: Vector2 = r.read_vector2()?;
-        if version[0] > 4 || (version[0] == 4 && version[1] >= 5) {
-            border = Some(r.read_vector4()?);
-        }
-        let pixels_to_units: f32 = r.read_f32()?;
-        if version[0] > 5 || (version[0] == 5 && version[1] > 4) || (version[0] == 5 && version[1] == 4 && version[2] >= 2) || (version[0] == 5 && version[1] == 4 && version[2] == 1 && object.info.build_type.is_patch() && version[3] >= 3) {
-            pivot = r.read_vector2()?;
-        }
-        let extrude: u8 = r.read_u32()? as u8;
-        if version[0] > 5 || (version[0] == 5 && version[1] >= 3) {
-            is_polygon = r.read_bool()?;
-            r.align(4)?;
-        }
-        if version[0] >= 2017
-        //2017 and up
-        {
-            let first = r.read_u8_array()?;
-            let second = r.read_i64()?;
-            render_data_key = (first, second);
-            atlas_tags = r.read_string_list()?;
-            sprite_atlas = Some(PPtr::load(object, &mut r)?);
-        }
-        let rd: SpriteRenderData = SpriteRenderData::load(object, &mut r)?;
-        Ok(Self {
-            name,
-            rect,
-            offset,
-            border,
-            pixels_to_units,
-            pivot,
-            extrude,
-            is_polygon,
-            render_data_key,
-            atlas_tags,
-            sprite_atlas,
-            rd,
-        })
+    pub(super) fn read(_object: &Object, r: &mut Reader) -> UnityResult<([u8; 16], i64)> {
+        Ok((r.read_u8_array()?, r.read_i64()?))
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::Sprite
+    pub(super) fn from_value(object: &Object, value: &Value) -> UnityResult<([u8; 16], i64)> {
+        Ok((type_tree::guid(object, type_tree::field(value, "first")?)?, type_tree::int(value, "second")?))
     }
 }
 
-impl Sprite<'_> {
+impl Sprite {
     pub fn decode_image(&self) -> UnityResult<RgbaImage> {
         if let Some(sprite_atlas) = self.sprite_atlas.as_ref().and_then(|x| x.get_obj()) {
             if let Some(sprite_atlas_data) = sprite_atlas.read::<SpriteAtlas>()?.render_data_map.get(&self.render_data_key) {
@@ -1,37 +1,38 @@
 use crate::classes::pptr::PPtr;
 use crate::classes::sprite::{SecondarySpriteTexture, SpriteSettings};
-use crate::classes::{FromObject, Sprite, Texture2D};
+use crate::classes::{type_tree, FromObject, Sprite, Texture2D};
 use crate::env::Object;
 use crate::error::UnityResult;
 use crate::math::{RectF32, Vector2, Vector4};
 use crate::reader::Reader;
+use serde_json::Value;
 use std::collections::HashMap;
 
-pub struct SpriteAtlasData<'a> {
-    pub texture: PPtr<'a, Texture2D>,
-    pub alpha_texture: PPtr<'a, Texture2D>,
+pub struct SpriteAtlasData {
+    pub texture: PPtr<Texture2D>,
+    pub alpha_texture: PPtr<Texture2D>,
     pub texture_rect: RectF32,
     pub texture_rect_offset: Vector2,
     pub atlas_rect_offset: Vector2,
     pub uv_transform: Vector4,
     pub downscale_multiplier: f32,
     pub settings_raw: SpriteSettings,
-    pub secondary_textures: Vec<SecondarySpriteTexture<'a>>,
+    pub secondary_textures: Vec<SecondarySpriteTexture>,
 }
 
-impl<'a> SpriteAtlasData<'a> {
-    pub fn load(object: &'a Object, r: &mut Reader) -> UnityResult<Self> {
+impl SpriteAtlasData {
+    pub fn load(object: &Object, r: &mut Reader) -> UnityResult<Self> {
         let version = object.info.version;
         let texture = PPtr::load(object, r)?;
         let alpha_texture = PPtr::load(object, r)?;
         let texture_rect = r.read_rect_f32()?;
         let texture_rect_offset = r.read_vector2()?;
-        let atlas_rect_offset = if version[0] > 2017 || (version[0] == 2017 && version[1] >= 2) { r.read_vector2()? } else { Vector2::default() };
+        let atlas_rect_offset = if version >= (2017, 2) { r.read_vector2()? } else { Vector2::default() };
         let uv_transform = r.read_vector4()?;
         let downscale_multiplier = r.read_f32()?;
         let settings_raw = SpriteSettings::load(&object.info, r)?;
         let mut secondary_textures = Vec::new();
-        if version[0] > 2020 || (version[0] == 2020 && version[1] >= 2) {
+        if version >= (2020, 2) {
             for _ in 0..r.read_i32()? {
                 secondary_textures.push(SecondarySpriteTexture::load(object, r)?)
             }
@@ -49,41 +50,67 @@ impl<'a> SpriteAtlasData<'a> {
             secondary_textures,
         })
     }
+
+    pub fn load_type_tree(object: &Object, value: &Value) -> UnityResult<Self> {
+        let vector2 = |name: &str| type_tree::vector2(type_tree::field(value, name)?);
+        Ok(Self {
+            texture: PPtr::load_type_tree(object, type_tree::field(value, "texture")?)?,
+            alpha_texture: PPtr::load_type_tree(object, type_tree::field(value, "alphaTexture")?)?,
+            texture_rect: type_tree::rect(type_tree::field(value, "textureRect")?)?,
+            texture_rect_offset: vector2("textureRectOffset")?,
+            atlas_rect_offset: vector2("atlasRectOffset").unwrap_or_default(),
+            uv_transform: type_tree::vector4(type_tree::field(value, "uvTransform")?)?,
+            downscale_multiplier: type_tree::float(value, "downscaleMultiplier")?,
+            settings_raw: SpriteSettings::from_raw(type_tree::int(value, "settingsRaw")? as u32)?,
+            secondary_textures: type_tree::array(value, "secondaryTextures")
+                .unwrap_or_default()
+                .iter()
+                .map(|x| SecondarySpriteTexture::load_type_tree(object, x))
+                .collect::<UnityResult<_>>()?,
+        })
+    }
 }
-pub struct SpriteAtlas<'a> {
+
+#[derive(FromObject)]
+pub struct SpriteAtlas {
     pub name: String,
-    pub packed_sprites: Vec<PPtr<'a, Sprite<'a>>>,
-    pub render_data_map: HashMap<([u8; 16], i64), SpriteAtlasData<'a>>,
+    #[unity(pptr)]
+    pub packed_sprites: Vec<PPtr<Sprite>>,
+    pub packed_sprite_names_to_index: Vec<String>,
+    #[unity(with = "render_data_map")]
+    pub render_data_map: HashMap<([u8; 16], i64), SpriteAtlasData>,
+    pub tag: String,
     pub is_variant: bool,
 }
-impl<'a> FromObject<'a> for SpriteAtlas<'a> {
-    fn load(object: &'a Object) -> UnityResult<Self> {
-        let mut r = object.info.get_reader();
-        let name = r.read_aligned_string()?;
-        let mut packed_sprites = Vec::new();
-        for _ in 0..r.read_i32()? {
-            packed_sprites.push(PPtr::load(object, &mut r)?);
-        }
-        let _packed_sprite_names_to_index = r.read_string_list()?;
-        let render_data_map_size = r.read_i32()?;
+
+mod render_data_map {
+    use super::SpriteAtlasData;
+    use crate::classes::type_tree;
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+    use std::collections::HashMap;
+
+    /// The render data of the sprites, by their `m_RenderDataKey`.
+    pub(super) fn read(object: &Object, r: &mut Reader) -> UnityResult<HashMap<([u8; 16], i64), SpriteAtlasData>> {
+        let size = r.read_array_len()?;
         let mut render_data_map = HashMap::new();
-        for _ in 0..render_data_map_size {
+        for _ in 0..size {
             let first = r.read_u8_array::<16>()?;
             let second = r.read_i64()?;
-            let value = SpriteAtlasData::load(object, &mut r)?;
-            render_data_map.insert((first, second), value);
+            render_data_map.insert((first, second), SpriteAtlasData::load(object, r)?);
         }
-        let _tag = r.read_aligned_string()?;
-        let is_variant = r.read_bool()?;
-        Ok(Self {
-            name,
-            packed_sprites,
-            render_data_map,
-            is_variant,
-        })
+        Ok(render_data_map)
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::SpriteAtlas
+    pub(super) fn from_value(object: &Object, value: &Value) -> UnityResult<HashMap<([u8; 16], i64), SpriteAtlasData>> {
+        let mut render_data_map = HashMap::new();
+        for (key, value) in type_tree::entries(value)? {
+            let first = type_tree::guid(object, type_tree::field(&key, "first")?)?;
+            let second = type_tree::int(&key, "second")?;
+            render_data_map.insert((first, second), SpriteAtlasData::load_type_tree(object, value)?);
+        }
+        Ok(render_data_map)
     }
 }
@@ -1,27 +1,39 @@
-use crate::classes::FromObject;
 use crate::env::Object;
 use crate::error::UnityResult;
 
+use super::FromObject;
+
+#[derive(FromObject)]
 pub struct TextAsset {
     pub name: String,
+    #[unity(with = "script")]
     pub script: Vec<u8>,
+    #[unity(object = "path_id")]
     pub path_id: i64,
 }
 
-impl FromObject<'_> for TextAsset {
-    fn load(object: &Object) -> UnityResult<Self> {
-        let mut r = object.info.get_reader();
-        let name = r.read_aligned_string()?;
+mod script {
+    use crate::classes::type_tree;
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    /// The script is a string of any bytes, which its TypeTree decodes as text.
+    pub(super) fn read(_object: &Object, r: &mut Reader) -> UnityResult<Vec<u8>> {
         let length = r.read_i32()?;
-        let script = r.read_u8_list(length as usize)?;
-        Ok(Self { name, script, path_id: object.info.path_id })
+        r.read_u8_list(length as usize)
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::TextAsset
+    pub(super) fn from_value(_object: &Object, value: &Value) -> UnityResult<Vec<u8>> {
+        Ok(value.as_str().ok_or_else(|| type_tree::mistyped("m_Script"))?.as_bytes().to_vec())
     }
 }
 
+fn path_id(object: &Object) -> i64 {
+    object.info.path_id
+}
+
 impl TextAsset {
     pub fn script_string(&self) -> UnityResult<&str> {
         Ok(std::str::from_utf8(&self.script)?)
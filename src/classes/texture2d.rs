@@ -1,16 +1,28 @@
 #![allow(dead_code, non_upper_case_globals)]
-use crate::classes::FromObject;
+use crate::classes::field::bytes;
+use crate::classes::type_tree;
+use crate::classes::{Field, FromObject};
 use crate::env::Object;
 use crate::error::{UnityError, UnityResult};
 use crate::object::ObjectInfo;
-use crate::reader::{ByteOrder, Reader};
+use crate::reader::{ByteOrder, ReadLimits, Reader};
+use crate::version::UnityVersion;
 use dashmap::mapref::one::Ref;
 use dashmap::DashMap;
-use image::{ImageBuffer, Rgba, RgbaImage};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, GrayImage, ImageBuffer, Rgba, Rgba32FImage, RgbaImage};
 use num_enum::FromPrimitive;
+use serde_json::{json, Value};
 use std::sync::Arc;
-use texture_decoder::implements::{Alpha8, RFloat, RGB9e5Float, RGBAFloat, RGBAHalf, RGFloat, RGHalf, RHalf, ARGB32, ARGB4444, BGRA32, R16, R8, RG16, RGB24, RGB565, RGBA32, RGBA4444, YUY2};
-use texture_decoder::{ImageSize, Texture2DDecoder};
+use texture_decoder::crunch::{self, CrunchFormat};
+use texture_decoder::implements::{Alpha8, RFloat, RGB9e5Float, RGBAFloat, RGBAHalf, RGFloat, RGHalf, RHalf, ARGB32, ARGB4444, BGRA32, R16, R8, RG16, RG32, RGB24, RGB48, RGB565, RGBA32, RGBA4444, RGBA64, YUY2};
+use texture_decoder::{block, encode, swizzle};
+use texture_decoder::{ImageSize, Rgba16Image, Texture2DDecoder, Texture2DEncoder};
+
+/// `BuildTarget` values of the consoles that swizzle textures.
+const BUILD_TARGET_PS4: i32 = 31;
+const BUILD_TARGET_SWITCH: i32 = 38;
+const BUILD_TARGET_PS5: i32 = 44;
 
 #[allow(non_camel_case_types, non_upper_case_globals)]
 #[derive(Debug, Eq, PartialEq, FromPrimitive, Clone, Copy, Default)]
@@ -82,9 +94,86 @@ pub enum TextureFormat {
     ASTC_HDR_8x8,
     ASTC_HDR_10x10,
     ASTC_HDR_12x12,
+    RG32,
+    RGB48,
+    RGBA64,
+}
+
+impl TextureFormat {
+    /// The format of a texture whose layout is given as a `GraphicsFormat`, as Texture2DArray and Texture3D have since
+    /// 2019.1. sRGB and linear variants map to the same format; formats without a decoder map to `UnknownType`.
+    pub fn from_graphics_format(format: i32) -> Self {
+        match format {
+            1 | 5 => Self::R8,
+            2 | 6 => Self::RG16,
+            3 | 7 => Self::RGB24,
+            4 | 8 => Self::RGBA32,
+            21 => Self::R16,
+            22 => Self::RG32,
+            23 => Self::RGB48,
+            24 => Self::RGBA64,
+            45 => Self::RHalf,
+            46 => Self::RGHalf,
+            48 => Self::RGBAHalf,
+            49 => Self::RFloat,
+            50 => Self::RGFloat,
+            52 => Self::RGBAFloat,
+            57 | 59 => Self::BGRA32,
+            66 => Self::RGBA4444,
+            69 => Self::RGB565,
+            73 => Self::RGB9e5Float,
+            96 | 97 => Self::DXT1,
+            100 | 101 => Self::DXT5,
+            102 => Self::BC4,
+            104 => Self::BC5,
+            106 => Self::BC6H,
+            108 | 109 => Self::BC7,
+            110 | 111 => Self::PVRTC_RGB2,
+            112 | 113 => Self::PVRTC_RGB4,
+            114 | 115 => Self::PVRTC_RGBA2,
+            116 | 117 => Self::PVRTC_RGBA4,
+            118 => Self::ETC_RGB4,
+            119 | 120 => Self::ETC2_RGB,
+            121 | 122 => Self::ETC2_RGBA1,
+            123 | 124 => Self::ETC2_RGBA8,
+            125 => Self::EAC_R,
+            126 => Self::EAC_R_SIGNED,
+            127 => Self::EAC_RG,
+            128 => Self::EAC_RG_SIGNED,
+            129 | 130 => Self::ASTC_RGBA_4x4,
+            131 | 132 => Self::ASTC_RGBA_5x5,
+            133 | 134 => Self::ASTC_RGBA_6x6,
+            135 | 136 => Self::ASTC_RGBA_8x8,
+            137 | 138 => Self::ASTC_RGBA_10x10,
+            139 | 140 => Self::ASTC_RGBA_12x12,
+            141 => Self::YUY2,
+            145 => Self::ASTC_HDR_4x4,
+            146 => Self::ASTC_HDR_5x5,
+            147 => Self::ASTC_HDR_6x6,
+            148 => Self::ASTC_HDR_8x8,
+            149 => Self::ASTC_HDR_10x10,
+            150 => Self::ASTC_HDR_12x12,
+            _ => Self::UnknownType,
+        }
+    }
 }
 
-#[derive(Default)]
+/// Formats without a decoder are all read as `UnknownType`, which leaves them out of strict mode's comparison.
+impl Field for TextureFormat {
+    fn read(_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Ok(Self::from(r.read_i32()?))
+    }
+
+    fn from_value(info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Ok(Self::from(i32::from_value(info, value)?))
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        (*self != Self::UnknownType).then(|| json!(*self as i32))
+    }
+}
+
+#[derive(Default, Clone)]
 pub struct GLTextureSettings {
     filter_mode: i32,
     aniso: i32,
@@ -100,7 +189,7 @@ impl GLTextureSettings {
             mip_bias: r.read_f32()?,
             ..Self::default()
         };
-        if object_info.version[0] >= 2017 {
+        if object_info.version.major >= 2017 {
             result.wrap_mode = r.read_i32()?;
             let _wrap_w = r.read_i32()?;
             let _wrap_h = r.read_i32()?;
@@ -109,6 +198,25 @@ impl GLTextureSettings {
         }
         Ok(result)
     }
+
+    pub(super) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            filter_mode: type_tree::int(value, "m_FilterMode")? as i32,
+            aniso: type_tree::int(value, "m_Aniso")? as i32,
+            mip_bias: type_tree::float(value, "m_MipBias")?,
+            wrap_mode: type_tree::int(value, "m_WrapU").or_else(|_| type_tree::int(value, "m_WrapMode"))? as i32,
+        })
+    }
+}
+
+impl Field for GLTextureSettings {
+    fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Self::load(info, r)
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Self::load_type_tree(value)
+    }
 }
 
 #[derive(Default, Debug)]
@@ -121,7 +229,7 @@ pub struct StreamingInfo {
 impl StreamingInfo {
     pub fn load(object_info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
         let mut result = Self::default();
-        if object_info.version[0] >= 2020 {
+        if object_info.version.major >= 2020 {
             result.offset = r.read_u64()?;
         } else {
             result.offset = r.read_u32()? as u64;
@@ -130,144 +238,565 @@ impl StreamingInfo {
         result.path = r.read_aligned_string()?;
         Ok(result)
     }
+
+    pub(crate) fn load_type_tree(value: &Value) -> UnityResult<Self> {
+        Ok(Self {
+            offset: type_tree::int(value, "offset")? as u64,
+            size: type_tree::int(value, "size")? as u32,
+            path: type_tree::string(value, "path")?,
+        })
+    }
+
+    /// Reads the streamed bytes from the bundle node named by `path`, if the bundle contains it.
+    pub(crate) fn read_data(&self, object: &Object) -> UnityResult<Option<Vec<u8>>> {
+        let path = self.path.split('/').last().ok_or(UnityError::InvalidValue)?;
+        for i in 0..object.bundle.nodes.len() {
+            if object.bundle.nodes[i].path != path {
+                continue;
+            }
+            let mut r = Reader::new(object.bundle.files[i].as_slice(), ByteOrder::Big).with_limits(object.info.limits);
+            r.set_offset(self.offset as usize)?;
+            return Ok(Some(r.read_u8_list(self.size as usize)?));
+        }
+        Ok(None)
+    }
+}
+
+impl Field for StreamingInfo {
+    fn read(info: &ObjectInfo, r: &mut Reader) -> UnityResult<Self> {
+        Self::load(info, r)
+    }
+
+    fn from_value(_info: &ObjectInfo, value: &Value) -> UnityResult<Self> {
+        Self::load_type_tree(value)
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        Some(json!({"offset": self.offset, "size": self.size, "path": self.path}))
+    }
 }
 
-#[derive(Default)]
+/// Replaces the image data with the data of the streamed file when there is one.
+pub(super) fn resolve_image_data(object: &Object, stream_info: &StreamingInfo, data: &mut Vec<u8>) -> UnityResult<()> {
+    if !stream_info.path.is_empty() {
+        *data = stream_info.read_data(object)?.unwrap_or_default();
+    }
+    Ok(())
+}
+
+/// `m_PlatformBlob` of texture arrays and volumes, read only from the TypeTree since where it is in their data is unknown.
+pub(super) mod tree_platform_blob {
+    use super::*;
+
+    pub(in crate::classes) fn read(_object: &Object, _r: &mut Reader) -> UnityResult<Vec<u8>> {
+        Ok(Vec::new())
+    }
+
+    pub(in crate::classes) fn from_value(_object: &Object, tree: &Value) -> UnityResult<Vec<u8>> {
+        Ok(type_tree::bytes(tree, "m_PlatformBlob").unwrap_or_default())
+    }
+}
+
+/// Where a texture's image is in the shared cache: the path of its asset and its path_id, which is only unique
+/// within the asset.
+pub type ImageKey = (String, i64);
+
+#[derive(Default, FromObject)]
+#[unity(finish = "Texture2D::resolve_image_data")]
 pub struct Texture2D {
-    cache: Arc<DashMap<i64, RgbaImage>>,
+    #[unity(object = "cache")]
+    pub(super) cache: Arc<DashMap<ImageKey, RgbaImage>>,
+    #[unity(object = "asset")]
+    pub(super) asset: String,
+    #[unity(object = "limits")]
+    pub(super) limits: ReadLimits,
+    #[unity(object = "unity_version")]
+    pub(super) version: UnityVersion,
+    #[unity(object = "path_id")]
     pub path_id: i64,
     pub name: String,
+    #[unity(since = "2017.3")]
     pub forced_fallback_format: i32,
+    #[unity(since = "2017.3")]
     pub downscale_fallback: bool,
+    #[unity(since = "2020.2", align(since = "2017.3"))]
+    pub is_alpha_channel_optional: bool,
+    /// Where the width starts, followed by the height and the complete image size, for [`Self::write_object`].
+    #[unity(offset)]
+    pub(super) size_offset: Option<usize>,
     pub width: i32,
     pub height: i32,
     pub complete_image_size: i32,
+    #[unity(since = "2020")]
+    pub mips_stripped: i32,
+    #[unity(rename = "m_TextureFormat")]
     pub format: TextureFormat,
+    #[unity(until = "5.2")]
     pub mip_map: bool,
+    #[unity(since = "5.2", offset)]
+    pub(super) mip_count_offset: Option<usize>,
+    #[unity(since = "5.2")]
     pub mip_count: i32,
+    #[unity(since = "2.6", rename = "m_IsReadable")]
     pub is_read_able: bool,
+    #[unity(since = "2020")]
+    pub is_pre_processed: bool,
+    #[unity(since = "2019.3")]
+    pub ignore_master_texture_limit: bool,
+    #[unity(since = "3", until = "5.5")]
+    pub read_allowed: bool,
+    #[unity(since = "2018.2", align(always))]
+    pub streaming_mipmaps: bool,
+    #[unity(since = "2018.2")]
+    pub streaming_mipmaps_priority: i32,
     pub image_count: i32,
     pub texture_dimension: i32,
+    #[unity(rename = "m_TextureSettings")]
+    pub texture_setting: GLTextureSettings,
+    #[unity(since = "3", rename = "m_LightmapFormat")]
     pub light_map_format: i32,
+    #[unity(since = "3.5")]
     pub color_space: i32,
-    pub size: i32,
-    pub stream_info: StreamingInfo,
-    pub texture_setting: GLTextureSettings,
+    /// Platform specific data, which for the Switch holds how high the blocks of its swizzle are.
+    #[unity(since = "2020.2", with = "bytes", align)]
+    pub platform_blob: Vec<u8>,
+    /// Where the image data starts, up to the end of the streaming info, for [`Self::write_object`].
+    #[unity(offset)]
+    pub(super) image_data_offset: Option<usize>,
+    /// The image data, taken from the streamed file when there is one.
+    #[unity(with = "bytes", rename = "image data")]
     pub data: Vec<u8>,
+    #[unity(since = "5.3", rename = "m_StreamData")]
+    pub stream_info: StreamingInfo,
+    #[unity(offset)]
+    pub(super) end_offset: Option<usize>,
+    /// The size of the image data stored in the object itself.
+    #[unity(skip)]
+    pub size: i32,
+    /// The `BuildTarget` of the file the texture was read from, which decides how its data is swizzled.
+    #[unity(object = "target_platform")]
+    pub target_platform: i32,
 }
 
-impl FromObject<'_> for Texture2D {
-    fn load(object: &Object) -> UnityResult<Self> {
-        let mut r = object.info.get_reader();
-        let mut result = Self {
-            cache: object.cache.clone(),
-            path_id: object.info.path_id,
-            name: r.read_aligned_string()?,
+fn cache(object: &Object) -> Arc<DashMap<ImageKey, RgbaImage>> {
+    object.cache.clone()
+}
 
-            ..Self::default()
-        };
-        let version = &object.info.version;
-        if version[0] > 2017 || (version[0] == 2017 && version[1] >= 3) {
-            result.forced_fallback_format = r.read_i32()?;
-            result.downscale_fallback = r.read_bool()?;
-            if version[0] > 2020 || (version[0] == 2020 && version[1] >= 2) {
-                let _is_alpha_channel_optional = r.read_bool()?;
-            }
-            r.align(4)?;
+fn asset(object: &Object) -> String {
+    object.asset().path.clone()
+}
+
+pub(super) fn limits(object: &Object) -> ReadLimits {
+    object.info.limits
+}
+
+pub(super) fn unity_version(object: &Object) -> UnityVersion {
+    object.info.version
+}
+
+pub(super) fn path_id(object: &Object) -> i64 {
+    object.info.path_id
+}
+
+pub(super) fn target_platform(object: &Object) -> i32 {
+    object.asset().target_platform
+}
+
+impl Texture2D {
+    /// Takes the image data from the streamed file when there is one.
+    fn resolve_image_data(&mut self, object: &Object) -> UnityResult<()> {
+        resolve_image_data(object, &self.stream_info, &mut self.data)?;
+        self.size = self.inline_size();
+        Ok(())
+    }
+
+    /// Decodes the image through the shared cache. When several threads decode the same texture at once,
+    /// the first image stored is the one every caller gets.
+    pub fn decode_image(&self) -> UnityResult<Ref<ImageKey, RgbaImage>> {
+        let key = self.image_key();
+        if let Some(img) = self.cache.get(&key) {
+            return Ok(img);
         }
-        result.width = r.read_i32()?;
-        result.height = r.read_i32()?;
-        result.complete_image_size = r.read_i32()?;
-        if object.info.version[0] >= 2020 {
-            let _mips_stripped = r.read_i32()?;
+        let img = self.decode_image_without_cache()?;
+        Ok(self.cache.entry(key).or_insert(img).downgrade())
+    }
+
+    /// The key of the texture's image in the shared cache.
+    pub fn image_key(&self) -> ImageKey {
+        (self.asset.clone(), self.path_id)
+    }
+
+    /// Decodes the image keeping what RGBA8 would lose: BC4 and EAC R as a grayscale image of their red channel,
+    /// BC5 and EAC RG with red in the luma and green in the alpha channel, and BC6H as float RGB.
+    /// Every other format is decoded as by [`Self::decode_image_without_cache`].
+    pub fn decode_channels(&self) -> UnityResult<DynamicImage> {
+        if let Some(linear) = self.deswizzled()? {
+            return linear.decode_channels();
         }
-        result.format = TextureFormat::from(r.read_i32()?);
-        let mut _mip_map = false;
-        if object.info.version[0] < 5 || (object.info.version[0] == 5 && object.info.version[1] < 2) {
-            _mip_map = r.read_bool()?;
-        } else {
-            result.mip_count = r.read_i32()?;
+        let size = self.image_size()?;
+        match self.format {
+            TextureFormat::BC4 => Ok(DynamicImage::ImageLuma8(block::decode_bc4(&size, &self.data)?)),
+            TextureFormat::BC5 => Ok(DynamicImage::ImageLumaA8(block::decode_bc5(&size, &self.data)?)),
+            TextureFormat::BC6H => Ok(DynamicImage::ImageRgb32F(block::decode_bc6h(&size, &self.data, false)?)),
+            TextureFormat::EAC_R | TextureFormat::EAC_R_SIGNED => Ok(DynamicImage::ImageLuma8(block::decode_eac_r(&size, &self.data, self.format == TextureFormat::EAC_R_SIGNED)?)),
+            TextureFormat::EAC_RG | TextureFormat::EAC_RG_SIGNED => Ok(DynamicImage::ImageLumaA8(block::decode_eac_rg(&size, &self.data, self.format == TextureFormat::EAC_RG_SIGNED)?)),
+            _ => self.decode_image_without_cache().map(DynamicImage::ImageRgba8),
         }
-        if version[0] > 2 || (version[0] == 2 && version[1] >= 6) {
-            result.is_read_able = r.read_bool()?;
+    }
+
+    /// Decodes the image to float RGBA. Half and float formats, RGB9e5Float, BC6H and ASTC HDR keep values outside
+    /// 0..=1 and the 16-bit formats keep their precision; save the result as EXR to keep both. Other formats are
+    /// decoded as by [`Self::decode_image_without_cache`].
+    pub fn decode_image_f32(&self) -> UnityResult<Rgba32FImage> {
+        if let Some(linear) = self.deswizzled()? {
+            return linear.decode_image_f32();
         }
-        if version[0] >= 2020 {
-            let _is_pre_processed = r.read_bool()?;
+        let size = self.image_size_for(16)?;
+        if let Some((block_width, block_height)) = astc_hdr_block_size(self.format) {
+            return Ok(block::decode_astc(&size, &self.data, block_width, block_height)?);
         }
-        if version[0] > 2019 || (version[0] == 2019 && version[1] >= 3) {
-            let _is_ignore_master_texture_limit = r.read_bool()?;
+        let img = match self.format {
+            TextureFormat::RHalf => Texture2DDecoder::decode_f32(RHalf, &size, &self.data, true)?,
+            TextureFormat::RGHalf => Texture2DDecoder::decode_f32(RGHalf, &size, &self.data, true)?,
+            TextureFormat::RGBAHalf => Texture2DDecoder::decode_f32(RGBAHalf, &size, &self.data, true)?,
+            TextureFormat::RFloat => Texture2DDecoder::decode_f32(RFloat, &size, &self.data, true)?,
+            TextureFormat::RGFloat => Texture2DDecoder::decode_f32(RGFloat, &size, &self.data, true)?,
+            TextureFormat::RGBAFloat => Texture2DDecoder::decode_f32(RGBAFloat, &size, &self.data, true)?,
+            TextureFormat::RGB9e5Float => Texture2DDecoder::decode_f32(RGB9e5Float, &size, &self.data, true)?,
+            TextureFormat::BC6H => DynamicImage::ImageRgb32F(block::decode_bc6h(&size, &self.data, false)?).into_rgba32f(),
+            TextureFormat::R16 | TextureFormat::RG32 | TextureFormat::RGB48 | TextureFormat::RGBA64 => DynamicImage::ImageRgba16(self.decode_image_u16()?).into_rgba32f(),
+            _ => DynamicImage::ImageRgba8(self.decode_image_without_cache()?).into_rgba32f(),
+        };
+        Ok(img)
+    }
+
+    /// Decodes the image to 16 bits per channel, exactly for R16, RG32, RGB48 and RGBA64; save the result as PNG for a
+    /// 16-bit PNG. Other formats are decoded as by [`Self::decode_image_f32`] and clamped to 0..=1.
+    pub fn decode_image_u16(&self) -> UnityResult<Rgba16Image> {
+        if let Some(linear) = self.deswizzled()? {
+            return linear.decode_image_u16();
         }
-        if version[0] >= 3 && (version[0] < 5 || (version[0] == 5 && version[1] <= 4)) {
-            let _read_allowed = r.read_bool()?;
+        let size = self.image_size_for(8)?;
+        let img = match self.format {
+            TextureFormat::R16 => Texture2DDecoder::decode_u16(R16, &size, &self.data, true)?,
+            TextureFormat::RG32 => Texture2DDecoder::decode_u16(RG32, &size, &self.data, true)?,
+            TextureFormat::RGB48 => Texture2DDecoder::decode_u16(RGB48, &size, &self.data, true)?,
+            TextureFormat::RGBA64 => Texture2DDecoder::decode_u16(RGBA64, &size, &self.data, true)?,
+            _ => DynamicImage::ImageRgba32F(self.decode_image_f32()?).into_rgba16(),
+        };
+        Ok(img)
+    }
+
+    /// The size of the image data stored in the object itself rather than streamed.
+    fn inline_size(&self) -> i32 {
+        if self.stream_info.path.is_empty() {
+            self.data.len() as i32
+        } else {
+            0
         }
-        if version[0] > 2018 || (version[0] == 2018 && version[1] >= 2) {
-            let _streaming_mip_maps = r.read_bool()?;
+    }
+
+    /// The number of mip levels stored for each image. Before 5.2 only whether there are mipmaps is stored, in which
+    /// case the chain goes down to 1x1.
+    pub fn mip_levels(&self) -> usize {
+        if self.mip_count > 0 {
+            self.mip_count as usize
+        } else if self.mip_map {
+            self.width.max(self.height).max(1).ilog2() as usize + 1
+        } else {
+            1
         }
-        r.align(4)?;
-        if version[0] > 2018 || (version[0] == 2018 && version[1] >= 2) {
-            let _streaming_mip_maps_priority = r.read_i32()?;
+    }
+
+    /// The width and height of mip level `mip`.
+    pub fn mip_size(&self, mip: usize) -> (i32, i32) {
+        let shift = mip.min(31) as u32;
+        ((self.width >> shift).max(1), (self.height >> shift).max(1))
+    }
+
+    /// The texture cut down to mip level `mip` of image `slice`, so that every decode method applies to it.
+    /// Images are stored one after another, each with its levels from the largest down. Crunched textures are
+    /// transcoded to the DXT or ETC format they were compressed with, and hold the faces of a cubemap in one file.
+    pub fn mip_level(&self, mip: usize, slice: usize) -> UnityResult<Texture2D> {
+        if let Some(linear) = self.deswizzled()? {
+            return linear.mip_level(mip, slice);
         }
-        result.image_count = r.read_i32()?;
-        result.texture_dimension = r.read_i32()?;
-        result.texture_setting = GLTextureSettings::load(&object.info, &mut r)?;
-        if version[0] >= 3 {
-            result.light_map_format = r.read_i32()?;
+        if mip >= self.mip_levels() || slice >= self.image_count.max(1) as usize {
+            return Err(UnityError::InvalidValue);
         }
-        if version[0] > 3 || (version[0] == 3 && version[1] >= 5) {
-            result.color_space = r.read_i32()?;
+        let (width, height) = self.mip_size(mip);
+        let (format, data) = match self.format {
+            TextureFormat::DXT1Crunched | TextureFormat::DXT5Crunched | TextureFormat::ETC_RGB4Crunched | TextureFormat::ETC2_RGBA8Crunched => {
+                // Unity's revision of crunch came with 2017.3, and is the only one with ETC.
+                let unity = self.version >= (2017, 3) || matches!(self.format, TextureFormat::ETC_RGB4Crunched | TextureFormat::ETC2_RGBA8Crunched);
+                let level = crunch::transcode(&self.data, mip, unity)?;
+                let format = match level.format {
+                    CrunchFormat::Dxt1 => TextureFormat::DXT1,
+                    CrunchFormat::Dxt5 => TextureFormat::DXT5,
+                    CrunchFormat::Etc1 | CrunchFormat::Etc1S => TextureFormat::ETC_RGB4,
+                    CrunchFormat::Etc2 => TextureFormat::ETC2_RGB,
+                    CrunchFormat::Etc2A | CrunchFormat::Etc2AS => TextureFormat::ETC2_RGBA8,
+                    _ => return Err(UnityError::Unimplemented),
+                };
+                let face_bytes = level.data.len() / level.faces;
+                let data = level.data.get(slice * face_bytes..(slice + 1) * face_bytes).ok_or(UnityError::Unimplemented)?;
+                (format, data.to_vec())
+            }
+            format => {
+                let level_bytes = |mip| {
+                    let (width, height) = self.mip_size(mip);
+                    surface_bytes(format, width as usize, height as usize).ok_or(UnityError::Unimplemented)
+                };
+                let mut offset = 0;
+                for i in 0..mip {
+                    offset += level_bytes(i)?;
+                }
+                if slice > 0 {
+                    let image_bytes = if self.complete_image_size > 0 {
+                        self.complete_image_size as usize
+                    } else {
+                        (0..self.mip_levels()).map(level_bytes).sum::<UnityResult<usize>>()?
+                    };
+                    offset += slice * image_bytes;
+                }
+                let size = level_bytes(mip)?;
+                let data = self.data.get(offset..offset + size).ok_or(UnityError::Eof)?;
+                (format, data.to_vec())
+            }
+        };
+        Ok(self.surface(format, width, height, data))
+    }
+
+    /// A texture of the single level `data`, in the linear layout, with the other fields of this one.
+    fn surface(&self, format: TextureFormat, width: i32, height: i32, data: Vec<u8>) -> Texture2D {
+        Texture2D {
+            // The surface shares its cache key with the whole texture.
+            cache: Arc::default(),
+            asset: self.asset.clone(),
+            limits: self.limits,
+            version: self.version,
+            path_id: self.path_id,
+            name: self.name.clone(),
+            width,
+            height,
+            complete_image_size: data.len() as i32,
+            format,
+            mip_count: 1,
+            is_read_able: self.is_read_able,
+            image_count: 1,
+            texture_dimension: self.texture_dimension,
+            light_map_format: self.light_map_format,
+            color_space: self.color_space,
+            size: data.len() as i32,
+            texture_setting: self.texture_setting.clone(),
+            data,
+            ..Self::default()
         }
-        if version[0] > 2020 || (version[0] == 2020 && version[1] >= 2) {
-            let length = r.read_i32()?;
-            let _platform_blob = r.read_u8_slice(length as usize)?;
-            r.align(4)?;
+    }
+
+    /// Whether the data is in the swizzled layout of a console, see [`Self::deswizzled`].
+    fn swizzled(&self) -> bool {
+        match self.target_platform {
+            BUILD_TARGET_SWITCH => self.platform_blob.len() >= 12,
+            BUILD_TARGET_PS4 | BUILD_TARGET_PS5 => true,
+            _ => false,
         }
-        result.size = r.read_i32()?;
-        if result.size == 0 && ((version[0] == 5 && version[1] >= 3) || version[0] > 5) {
-            result.stream_info = StreamingInfo::load(&object.info, &mut r)?;
+    }
+
+    /// How the data is swizzled, for textures of consoles that store it so. The Switch swizzles when the platform
+    /// blob gives the height of its blocks, PS4 and PS5 always tile.
+    fn swizzle(&self) -> UnityResult<Option<Swizzle>> {
+        if !self.swizzled() {
+            return Ok(None);
         }
-        if result.stream_info.path.is_empty() {
-            result.data = r.read_u8_list(result.size as usize)?;
+        let gobs_per_block = if self.target_platform == BUILD_TARGET_SWITCH {
+            let log2 = u32::from_le_bytes(self.platform_blob[8..12].try_into().unwrap());
+            Some(1usize.checked_shl(log2).ok_or(UnityError::InvalidValue)?)
         } else {
-            let path = result.stream_info.path.split('/').last().ok_or(UnityError::InvalidValue)?;
-            for i in 0..object.bundle.nodes.len() {
-                let node = &object.bundle.nodes[i];
-                if node.path != path {
-                    continue;
+            None
+        };
+        // The Switch has no 24-bit formats, RGB24 is stored as RGBA32.
+        let format = if gobs_per_block.is_some() && self.format == TextureFormat::RGB24 { TextureFormat::RGBA32 } else { self.format };
+        let layout = BlockLayout::of(format).ok_or(UnityError::Unimplemented)?;
+        Ok(Some(Swizzle { format, layout, gobs_per_block }))
+    }
+
+    /// The bytes a `width` by `height` level takes in the data, padded to whole tiles when it is swizzled.
+    pub(super) fn stored_bytes(&self, width: i32, height: i32) -> UnityResult<usize> {
+        match self.swizzle()? {
+            Some(swizzle) => Ok(swizzle.stored_bytes(width.max(0) as usize, height.max(0) as usize)),
+            None => surface_bytes(self.format, width.max(0) as usize, height.max(0) as usize).ok_or(UnityError::Unimplemented),
+        }
+    }
+
+    /// The texture with the blocks of every level of every image in rows, or `None` when the data is already
+    /// linear. Each level is swizzled on its own, at the offset the padded levels before it end at.
+    pub(super) fn deswizzled(&self) -> UnityResult<Option<Texture2D>> {
+        let Some(swizzle) = self.swizzle()? else {
+            return Ok(None);
+        };
+        self.image_size()?;
+        let mut data = Vec::new();
+        let mut offset = 0;
+        'images: for _ in 0..self.image_count.max(1) {
+            for mip in 0..self.mip_levels() {
+                let (width, height) = self.mip_size(mip);
+                let size = swizzle.stored_bytes(width as usize, height as usize);
+                // Levels missing from the data fail once they are asked for.
+                if offset + size > self.data.len() && !data.is_empty() {
+                    break 'images;
                 }
-                let file = &object.bundle.files[i];
-                let mut r = Reader::new(file.as_slice(), ByteOrder::Big);
-                r.set_offset(result.stream_info.offset as usize)?;
-                result.data = r.read_u8_list(result.stream_info.size as usize)?;
+                data.extend(swizzle.deswizzle(self.data.get(offset..).unwrap_or_default(), width as usize, height as usize)?);
+                offset += size;
             }
         }
-        Ok(result)
+        let image_bytes = (0..self.mip_levels())
+            .map(|mip| {
+                let (width, height) = self.mip_size(mip);
+                surface_bytes(swizzle.format, width as usize, height as usize).unwrap_or_default()
+            })
+            .sum::<usize>();
+        Ok(Some(Texture2D {
+            mip_count: self.mip_count,
+            mip_map: self.mip_map,
+            image_count: self.image_count,
+            complete_image_size: image_bytes as i32,
+            ..self.surface(swizzle.format, self.width, self.height, data)
+        }))
     }
 
-    fn class() -> super::ClassID {
-        super::ClassID::Texture2D
+    /// Replaces the image with `img`, encoded in the format of the texture, and regenerates the mipmaps when the
+    /// texture has them. RGBA32, ARGB32, RGB24, RGBA4444, RGB565 and Alpha8 give back every color they can hold;
+    /// ETC2, BC7 and LDR ASTC go through the encoders of [`texture_decoder::encode`]. Data that was streamed is
+    /// stored in the texture from then on. Use [`Self::write_object`] to put the result back into the object.
+    pub fn set_image(&mut self, img: &RgbaImage) -> UnityResult<()> {
+        if self.swizzled() {
+            return Err(UnityError::Unimplemented);
+        }
+        if img.width() == 0 || img.height() == 0 {
+            return Err(UnityError::ZeroSizeImage);
+        }
+        let full_chain = img.width().max(img.height()).ilog2() as usize + 1;
+        let levels = if self.mip_count > 0 {
+            (self.mip_count as usize).min(full_chain)
+        } else if self.mip_map {
+            full_chain
+        } else {
+            1
+        };
+        let mut data = self.encode_surface(img)?;
+        for mip in 1..levels {
+            let (width, height) = ((img.width() >> mip).max(1), (img.height() >> mip).max(1));
+            data.extend(self.encode_surface(&imageops::resize(img, width, height, FilterType::Triangle))?);
+        }
+
+        self.width = img.width() as i32;
+        self.height = img.height() as i32;
+        if self.mip_count > 0 {
+            self.mip_count = levels as i32;
+        }
+        self.complete_image_size = data.len() as i32;
+        self.data = data;
+        self.stream_info = StreamingInfo::default();
+        self.size = self.inline_size();
+        self.cache = Arc::default();
+        Ok(())
     }
-}
-impl Texture2D {
-    pub fn decode_image(&self) -> UnityResult<Ref<i64, RgbaImage>> {
-        if let Some(img) = self.cache.get(&self.path_id) {
-            return Ok(img);
+
+    /// Encodes a single surface in the format of the texture, bottom row first.
+    fn encode_surface(&self, img: &RgbaImage) -> UnityResult<Vec<u8>> {
+        let data = match self.format {
+            TextureFormat::RGBA32 => Texture2DEncoder::encode(RGBA32, img),
+            TextureFormat::ARGB32 => Texture2DEncoder::encode(ARGB32, img),
+            TextureFormat::RGB24 => Texture2DEncoder::encode(RGB24, img),
+            TextureFormat::RGBA4444 => Texture2DEncoder::encode(RGBA4444, img),
+            TextureFormat::RGB565 => Texture2DEncoder::encode(RGB565, img),
+            TextureFormat::Alpha8 => Texture2DEncoder::encode(Alpha8, img),
+            TextureFormat::ETC2_RGB => encode::encode_etc2_rgb(img),
+            TextureFormat::ETC2_RGBA8 => encode::encode_etc2_rgba8(img),
+            TextureFormat::BC7 => encode::encode_bc7(img),
+            format => match astc_block_size(format) {
+                Some((block_width, block_height)) if astc_hdr_block_size(format).is_none() => encode::encode_astc(img, block_width, block_height),
+                _ => return Err(UnityError::Unimplemented),
+            },
+        };
+        Ok(data)
+    }
+
+    /// The bytes of `object`, which the texture was read from, with the size, mip count and image data the texture
+    /// has now, as after [`Self::set_image`]. Only textures read by the binary loader know where their fields are.
+    pub fn write_object(&self, object: &Object) -> UnityResult<Vec<u8>> {
+        let (Some(size_offset), Some(image_data_offset), Some(end_offset)) = (self.size_offset, self.image_data_offset, self.end_offset) else {
+            return Err(UnityError::Unimplemented);
+        };
+        let info = &object.info;
+        let old = info.data.get(info.bytes_start..info.bytes_start + info.bytes_size).ok_or(UnityError::Eof)?;
+        let int = |value: i32| match info.bytes_order {
+            ByteOrder::Big => value.to_be_bytes(),
+            ByteOrder::Little => value.to_le_bytes(),
+        };
+
+        let mut image_data = int(self.data.len() as i32).to_vec();
+        image_data.extend(&self.data);
+        if info.version >= (5, 3) {
+            // An empty streaming info: offset, size and path.
+            let offset_bytes = if info.version.major >= 2020 { 8 } else { 4 };
+            image_data.extend(vec![0; offset_bytes + 8]);
         }
-        let img = self.decode_image_without_cache()?;
-        Ok(self.cache.entry(self.path_id).insert(img).downgrade())
+        let mut patches = vec![(size_offset, 12, [self.width, self.height, self.complete_image_size].into_iter().flat_map(int).collect::<Vec<_>>())];
+        if let Some(offset) = self.mip_count_offset {
+            patches.push((offset, 4, int(self.mip_count).to_vec()));
+        }
+        patches.push((image_data_offset, end_offset - image_data_offset, image_data));
+
+        let mut bytes = Vec::with_capacity(old.len() + self.data.len());
+        let mut position = 0;
+        for (offset, length, patch) in patches {
+            bytes.extend(old.get(position..offset).ok_or(UnityError::Eof)?);
+            bytes.extend(patch);
+            position = offset + length;
+        }
+        bytes.extend(old.get(position..).ok_or(UnityError::Eof)?);
+        Ok(bytes)
+    }
+
+    /// Decodes mip level `mip` of image `slice`.
+    pub fn decode_mip(&self, mip: usize, slice: usize) -> UnityResult<RgbaImage> {
+        self.mip_level(mip, slice)?.decode_image_without_cache()
+    }
+
+    /// Decodes every mip level of the first image, largest first.
+    pub fn decode_all_mips(&self) -> UnityResult<Vec<RgbaImage>> {
+        (0..self.mip_levels()).map(|mip| self.decode_mip(mip, 0)).collect()
+    }
+
+    fn image_size(&self) -> UnityResult<ImageSize> {
+        self.image_size_for(4)
+    }
+
+    /// Checks the size of the texture against the limits for an image of `pixel_bytes` per pixel.
+    fn image_size_for(&self, pixel_bytes: usize) -> UnityResult<ImageSize> {
+        if self.width <= 0 || self.height <= 0 {
+            return Err(UnityError::ZeroSizeImage);
+        }
+        if (self.width as usize).saturating_mul(self.height as usize).saturating_mul(pixel_bytes) > self.limits.max_alloc {
+            return Err(UnityError::LimitExceeded("image size"));
+        }
+        Ok(ImageSize::new(self.width as usize, self.height as usize))
     }
 
     pub fn decode_image_without_cache(&self) -> UnityResult<RgbaImage> {
+        if let Some(linear) = self.deswizzled()? {
+            return linear.decode_image_without_cache();
+        }
+        let size = self.image_size()?;
         let width = self.width;
         let height = self.height;
-        if width <= 0 || height <= 0 {
-            return Err(UnityError::ZeroSizeImage);
-        }
         let format = self.format;
-        let size = ImageSize::new(width as usize, height as usize);
         let mut result: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::new(width as u32, height as u32);
         let image = result.as_mut_ptr();
         let image = image.cast::<u32>();
-        let image = unsafe { std::slice::from_raw_parts_mut(image, (width * height) as usize) };
+        let image = unsafe { std::slice::from_raw_parts_mut(image, width as usize * height as usize) };
         match format {
             TextureFormat::ETC2_RGBA8 => {
                 texture2ddecoder::decode_etc2_rgba8(&self.data, width as usize, height as usize, image)?;
@@ -355,9 +884,7 @@ impl Texture2D {
             TextureFormat::RFloat => Texture2DDecoder::decode(RFloat, &size, &self.data, true).map_err(Into::into),
             TextureFormat::RHalf => Texture2DDecoder::decode(RHalf, &size, &self.data, true).map_err(Into::into),
             TextureFormat::RG16 => Texture2DDecoder::decode(RG16, &size, &self.data, true).map_err(Into::into),
-            // TextureFormat::RG32=>{
-            //     Texture2DDecoder::texture_decode_image(RG32,&size,&self.data,true).map_err(Into::into)
-            // }
+            TextureFormat::RG32 => Texture2DDecoder::decode(RG32, &size, &self.data, true).map_err(Into::into),
             TextureFormat::RGFloat => Texture2DDecoder::decode(RGFloat, &size, &self.data, true).map_err(Into::into),
             TextureFormat::RGHalf => Texture2DDecoder::decode(RGHalf, &size, &self.data, true).map_err(Into::into),
 
@@ -370,16 +897,12 @@ impl Texture2D {
                 Ok(img)
             }
             TextureFormat::RGB9e5Float => Texture2DDecoder::decode(RGB9e5Float, &size, &self.data, true).map_err(Into::into),
-            // TextureFormat::RGB48=>{
-            //     Texture2DDecoder::texture_decode_image::<RGB48>(&size,&self.data,true).map_err(Into::into)
-            // }
+            TextureFormat::RGB48 => Texture2DDecoder::decode(RGB48, &size, &self.data, true).map_err(Into::into),
             TextureFormat::RGBA32 => {
                 let img = Texture2DDecoder::decode(RGBA32, &size, &self.data, true)?;
                 Ok(img)
             }
-            // TextureFormat::RGBA64=>{
-            //     Texture2DDecoder::texture_decode_image::<RGBA64>(&size,&self.data,true).map_err(Into::into)
-            // }
+            TextureFormat::RGBA64 => Texture2DDecoder::decode(RGBA64, &size, &self.data, true).map_err(Into::into),
             TextureFormat::RGBA4444 => {
                 let img = Texture2DDecoder::decode(RGBA4444, &size, &self.data, true)?;
                 Ok(img)
@@ -391,7 +914,146 @@ impl Texture2D {
                 texture2ddecoder::decode_bc7(&self.data, width as usize, height as usize, image)?;
                 Ok(result)
             }
+            TextureFormat::DXT1 => block::decode_bc1(&size, &self.data).map_err(Into::into),
+            TextureFormat::DXT5 => block::decode_bc3(&size, &self.data).map_err(Into::into),
+            TextureFormat::BC4 => Ok(red_to_rgba(&block::decode_bc4(&size, &self.data)?)),
+            TextureFormat::BC5 => Ok(red_green_to_rgba(&block::decode_bc5(&size, &self.data)?)),
+            TextureFormat::BC6H => Ok(DynamicImage::ImageRgb32F(block::decode_bc6h(&size, &self.data, false)?).into_rgba8()),
+            TextureFormat::ASTC_HDR_4x4 | TextureFormat::ASTC_HDR_5x5 | TextureFormat::ASTC_HDR_6x6 | TextureFormat::ASTC_HDR_8x8 | TextureFormat::ASTC_HDR_10x10 | TextureFormat::ASTC_HDR_12x12 => {
+                Ok(DynamicImage::ImageRgba32F(self.decode_image_f32()?).into_rgba8())
+            }
+            TextureFormat::EAC_R | TextureFormat::EAC_R_SIGNED => Ok(red_to_rgba(&block::decode_eac_r(&size, &self.data, format == TextureFormat::EAC_R_SIGNED)?)),
+            TextureFormat::EAC_RG | TextureFormat::EAC_RG_SIGNED => Ok(red_green_to_rgba(&block::decode_eac_rg(&size, &self.data, format == TextureFormat::EAC_RG_SIGNED)?)),
+            TextureFormat::ETC_RGB4_3DS => block::decode_etc1_3ds(&size, &self.data, false).map_err(Into::into),
+            TextureFormat::ETC_RGBA8_3DS => block::decode_etc1_3ds(&size, &self.data, true).map_err(Into::into),
+            TextureFormat::ETC2_RGBA1 => {
+                texture2ddecoder::decode_etc2_rgba1(&self.data, width as usize, height as usize, image)?;
+                Ok(result)
+            }
+            TextureFormat::PVRTC_RGB2 | TextureFormat::PVRTC_RGBA2 => {
+                texture2ddecoder::decode_pvrtc_2bpp(&self.data, width as usize, height as usize, image)?;
+                Ok(result)
+            }
+            TextureFormat::PVRTC_RGB4 | TextureFormat::PVRTC_RGBA4 => {
+                texture2ddecoder::decode_pvrtc_4bpp(&self.data, width as usize, height as usize, image)?;
+                Ok(result)
+            }
+            TextureFormat::DXT1Crunched | TextureFormat::DXT5Crunched | TextureFormat::ETC_RGB4Crunched | TextureFormat::ETC2_RGBA8Crunched => self.mip_level(0, 0)?.decode_image_without_cache(),
             _ => Err(UnityError::Unimplemented),
         }
     }
 }
+
+/// How a format divides a surface into blocks; uncompressed formats have blocks of a single pixel.
+struct BlockLayout {
+    width: usize,
+    height: usize,
+    bytes: usize,
+    /// The smallest size a surface is padded to.
+    min_width: usize,
+    min_height: usize,
+}
+
+impl BlockLayout {
+    /// The layout of `format`, or `None` for crunched and unknown formats. PVRTC surfaces are at least 2x2 blocks,
+    /// and the 3DS formats are counted in their 8x8 tiles of four blocks.
+    fn of(format: TextureFormat) -> Option<Self> {
+        let (width, height, bytes, min_width, min_height) = match format {
+            TextureFormat::Alpha8 | TextureFormat::R8 => (1, 1, 1, 1, 1),
+            TextureFormat::ARGB4444 | TextureFormat::RGB565 | TextureFormat::R16 | TextureFormat::RGBA4444 | TextureFormat::RHalf | TextureFormat::YUY2 | TextureFormat::RG16 => (1, 1, 2, 1, 1),
+            TextureFormat::RGB24 => (1, 1, 3, 1, 1),
+            TextureFormat::RGBA32 | TextureFormat::ARGB32 | TextureFormat::BGRA32 | TextureFormat::RGHalf | TextureFormat::RFloat | TextureFormat::RGB9e5Float | TextureFormat::RG32 => (1, 1, 4, 1, 1),
+            TextureFormat::RGB48 => (1, 1, 6, 1, 1),
+            TextureFormat::RGBAHalf | TextureFormat::RGFloat | TextureFormat::RGBA64 => (1, 1, 8, 1, 1),
+            TextureFormat::RGBAFloat => (1, 1, 16, 1, 1),
+            TextureFormat::DXT1 | TextureFormat::BC4 | TextureFormat::ETC_RGB4 | TextureFormat::ATC_RGB4 | TextureFormat::EAC_R | TextureFormat::EAC_R_SIGNED | TextureFormat::ETC2_RGB | TextureFormat::ETC2_RGBA1 => (4, 4, 8, 4, 4),
+            TextureFormat::DXT5 | TextureFormat::BC5 | TextureFormat::BC6H | TextureFormat::BC7 | TextureFormat::ATC_RGBA8 | TextureFormat::EAC_RG | TextureFormat::EAC_RG_SIGNED | TextureFormat::ETC2_RGBA8 => (4, 4, 16, 4, 4),
+            TextureFormat::PVRTC_RGB2 | TextureFormat::PVRTC_RGBA2 => (8, 4, 8, 16, 8),
+            TextureFormat::PVRTC_RGB4 | TextureFormat::PVRTC_RGBA4 => (4, 4, 8, 8, 8),
+            TextureFormat::ETC_RGB4_3DS => (8, 8, 32, 8, 8),
+            TextureFormat::ETC_RGBA8_3DS => (8, 8, 64, 8, 8),
+            format => {
+                let (width, height) = astc_block_size(format)?;
+                (width, height, 16, width, height)
+            }
+        };
+        Some(Self { width, height, bytes, min_width, min_height })
+    }
+
+    /// The number of blocks across and down a `width` by `height` surface.
+    fn blocks(&self, width: usize, height: usize) -> (usize, usize) {
+        (width.max(self.min_width).div_ceil(self.width), height.max(self.min_height).div_ceil(self.height))
+    }
+}
+
+/// The swizzled layout of a console texture, see [`Texture2D::deswizzled`].
+struct Swizzle {
+    /// The format the data is stored in.
+    format: TextureFormat,
+    layout: BlockLayout,
+    /// How many GOBs high the blocks of the largest level are on the Switch, `None` for the tiles of PS4 and PS5.
+    gobs_per_block: Option<usize>,
+}
+
+impl Swizzle {
+    /// The bytes a `width` by `height` level takes, padded to whole tiles.
+    fn stored_bytes(&self, width: usize, height: usize) -> usize {
+        let (blocks_x, blocks_y) = self.layout.blocks(width, height);
+        match self.gobs_per_block {
+            Some(gobs) => swizzle::switch_size(blocks_x, blocks_y, self.layout.bytes, swizzle::switch_gobs_per_block(blocks_y, gobs)),
+            None => swizzle::ps4_size(blocks_x, blocks_y, self.layout.bytes),
+        }
+    }
+
+    /// The `width` by `height` level at the start of `data`, with its blocks in rows.
+    fn deswizzle(&self, data: &[u8], width: usize, height: usize) -> UnityResult<Vec<u8>> {
+        let (blocks_x, blocks_y) = self.layout.blocks(width, height);
+        Ok(match self.gobs_per_block {
+            Some(gobs) => swizzle::deswizzle_switch(data, blocks_x, blocks_y, self.layout.bytes, swizzle::switch_gobs_per_block(blocks_y, gobs))?,
+            None => swizzle::untile_ps4(data, blocks_x, blocks_y, self.layout.bytes)?,
+        })
+    }
+}
+
+/// The bytes taken by a `width` by `height` surface of `format`, or `None` for crunched and unknown formats.
+pub(super) fn surface_bytes(format: TextureFormat, width: usize, height: usize) -> Option<usize> {
+    let layout = BlockLayout::of(format)?;
+    let (blocks_x, blocks_y) = layout.blocks(width, height);
+    Some(blocks_x * blocks_y * layout.bytes)
+}
+
+fn astc_block_size(format: TextureFormat) -> Option<(usize, usize)> {
+    match format {
+        TextureFormat::ASTC_RGB_4x4 | TextureFormat::ASTC_RGBA_4x4 => Some((4, 4)),
+        TextureFormat::ASTC_RGB_5x5 | TextureFormat::ASTC_RGBA_5x5 => Some((5, 5)),
+        TextureFormat::ASTC_RGB_6x6 | TextureFormat::ASTC_RGBA_6x6 => Some((6, 6)),
+        TextureFormat::ASTC_RGB_8x8 | TextureFormat::ASTC_RGBA_8x8 => Some((8, 8)),
+        TextureFormat::ASTC_RGB_10x10 | TextureFormat::ASTC_RGBA_10x10 => Some((10, 10)),
+        TextureFormat::ASTC_RGB_12x12 | TextureFormat::ASTC_RGBA_12x12 => Some((12, 12)),
+        format => astc_hdr_block_size(format),
+    }
+}
+
+fn astc_hdr_block_size(format: TextureFormat) -> Option<(usize, usize)> {
+    match format {
+        TextureFormat::ASTC_HDR_4x4 => Some((4, 4)),
+        TextureFormat::ASTC_HDR_5x5 => Some((5, 5)),
+        TextureFormat::ASTC_HDR_6x6 => Some((6, 6)),
+        TextureFormat::ASTC_HDR_8x8 => Some((8, 8)),
+        TextureFormat::ASTC_HDR_10x10 => Some((10, 10)),
+        TextureFormat::ASTC_HDR_12x12 => Some((12, 12)),
+        _ => None,
+    }
+}
+
+// Like R8 and RG16, single and dual channel formats leave the missing channels at zero.
+fn red_to_rgba(img: &GrayImage) -> RgbaImage {
+    RgbaImage::from_fn(img.width(), img.height(), |x, y| Rgba([img.get_pixel(x, y)[0], 0, 0, 255]))
+}
+
+fn red_green_to_rgba(img: &block::GrayAlphaImage) -> RgbaImage {
+    RgbaImage::from_fn(img.width(), img.height(), |x, y| {
+        let [r, g] = img.get_pixel(x, y).0;
+        Rgba([r, g, 0, 255])
+    })
+}
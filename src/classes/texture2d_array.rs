@@ -0,0 +1,117 @@
+use image::RgbaImage;
+
+use crate::classes::field::bytes;
+use crate::classes::texture2d::{limits, path_id, resolve_image_data, target_platform, tree_platform_blob, unity_version, GLTextureSettings, StreamingInfo};
+use crate::classes::{FromObject, Texture2D, TextureFormat};
+use crate::env::Object;
+use crate::error::{UnityError, UnityResult};
+use crate::reader::ReadLimits;
+use crate::version::UnityVersion;
+
+/// An array of 2D textures of the same size and format, each stored with its mip levels after the previous one.
+#[derive(Default, FromObject)]
+#[unity(finish = "Texture2DArray::resolve_image_data")]
+pub struct Texture2DArray {
+    #[unity(object = "limits")]
+    limits: ReadLimits,
+    #[unity(object = "unity_version")]
+    version: UnityVersion,
+    #[unity(object = "path_id")]
+    pub path_id: i64,
+    pub name: String,
+    #[unity(since = "2017.3")]
+    pub forced_fallback_format: i32,
+    #[unity(since = "2017.3")]
+    pub downscale_fallback: bool,
+    #[unity(since = "2020.2", align(since = "2017.3"))]
+    pub is_alpha_channel_optional: bool,
+    pub color_space: i32,
+    #[unity(rename = "m_Format", with = "texture_format")]
+    pub format: TextureFormat,
+    pub width: i32,
+    pub height: i32,
+    pub depth: i32,
+    pub mip_count: i32,
+    pub data_size: u32,
+    #[unity(rename = "m_TextureSettings")]
+    pub texture_setting: GLTextureSettings,
+    #[unity(since = "2020")]
+    pub usage_mode: i32,
+    #[unity(rename = "m_IsReadable", align)]
+    pub is_read_able: bool,
+    #[unity(with = "bytes", rename = "image data")]
+    pub data: Vec<u8>,
+    #[unity(since = "5.3", rename = "m_StreamData")]
+    pub stream_info: StreamingInfo,
+    /// The `BuildTarget` of the file the texture was read from, which decides how its data is swizzled.
+    #[unity(object = "target_platform")]
+    pub target_platform: i32,
+    /// Platform specific data, which for the Switch holds how high the blocks of its swizzle are.
+    #[unity(flatten, with = "tree_platform_blob")]
+    pub platform_blob: Vec<u8>,
+}
+
+impl Texture2DArray {
+    fn resolve_image_data(&mut self, object: &Object) -> UnityResult<()> {
+        resolve_image_data(object, &self.stream_info, &mut self.data)
+    }
+
+    /// The texture at index `slice`, with all its mip levels.
+    pub fn slice(&self, slice: usize) -> UnityResult<Texture2D> {
+        if slice >= self.depth.max(0) as usize {
+            return Err(UnityError::InvalidValue);
+        }
+        let slice_bytes = self.data.len() / self.depth as usize;
+        let data = self.data[slice * slice_bytes..(slice + 1) * slice_bytes].to_vec();
+        Ok(Texture2D {
+            limits: self.limits,
+            version: self.version,
+            path_id: self.path_id,
+            name: self.name.clone(),
+            width: self.width,
+            height: self.height,
+            complete_image_size: slice_bytes as i32,
+            format: self.format,
+            mip_count: self.mip_count,
+            is_read_able: self.is_read_able,
+            image_count: 1,
+            color_space: self.color_space,
+            size: slice_bytes as i32,
+            texture_setting: self.texture_setting.clone(),
+            target_platform: self.target_platform,
+            platform_blob: self.platform_blob.clone(),
+            data,
+            ..Texture2D::default()
+        })
+    }
+
+    /// Decodes the largest level of every texture, in order.
+    pub fn decode_slices(&self) -> UnityResult<Vec<RgbaImage>> {
+        (0..self.depth.max(0) as usize).map(|slice| self.slice(slice)?.decode_image_without_cache()).collect()
+    }
+}
+
+/// `m_Format` of a Texture2DArray or Texture3D, a `GraphicsFormat` since 2019.1 and a `TextureFormat` before.
+pub(super) mod texture_format {
+    use crate::classes::{Field, TextureFormat};
+    use crate::env::Object;
+    use crate::error::UnityResult;
+    use crate::reader::Reader;
+    use serde_json::Value;
+
+    pub(in crate::classes) fn read(object: &Object, r: &mut Reader) -> UnityResult<TextureFormat> {
+        Ok(convert(object, r.read_i32()?))
+    }
+
+    pub(in crate::classes) fn from_value(object: &Object, value: &Value) -> UnityResult<TextureFormat> {
+        Ok(convert(object, i32::from_value(&object.info, value)?))
+    }
+
+    fn convert(object: &Object, format: i32) -> TextureFormat {
+        if object.version().major >= 2019 {
+            TextureFormat::from_graphics_format(format)
+        } else {
+            TextureFormat::from(format)
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use image::{imageops, RgbaImage};
+
+use crate::classes::field::bytes;
+use crate::classes::texture2d::{limits, path_id, resolve_image_data, target_platform, tree_platform_blob, unity_version, GLTextureSettings, StreamingInfo};
+use crate::classes::texture2d_array::texture_format;
+use crate::classes::{FromObject, Texture2D, TextureFormat};
+use crate::env::Object;
+use crate::error::{UnityError, UnityResult};
+use crate::reader::ReadLimits;
+use crate::version::UnityVersion;
+
+/// A volume texture. Each mip level holds all of its depth slices, front to back, and halves the depth too.
+#[derive(Default, FromObject)]
+#[unity(finish = "Texture3D::resolve_image_data")]
+pub struct Texture3D {
+    #[unity(object = "limits")]
+    limits: ReadLimits,
+    #[unity(object = "unity_version")]
+    version: UnityVersion,
+    #[unity(object = "path_id")]
+    pub path_id: i64,
+    pub name: String,
+    #[unity(since = "2017.3")]
+    pub forced_fallback_format: i32,
+    #[unity(since = "2017.3")]
+    pub downscale_fallback: bool,
+    #[unity(since = "2020.2", align(since = "2017.3"))]
+    pub is_alpha_channel_optional: bool,
+    pub color_space: i32,
+    #[unity(rename = "m_Format", with = "texture_format")]
+    pub format: TextureFormat,
+    pub width: i32,
+    pub height: i32,
+    pub depth: i32,
+    #[unity(until = "2019", align)]
+    pub mip_map: bool,
+    #[unity(since = "2019")]
+    pub mip_count: i32,
+    pub data_size: u32,
+    #[unity(rename = "m_TextureSettings")]
+    pub texture_setting: GLTextureSettings,
+    #[unity(since = "2020")]
+    pub usage_mode: i32,
+    #[unity(rename = "m_IsReadable", align)]
+    pub is_read_able: bool,
+    #[unity(with = "bytes", rename = "image data")]
+    pub data: Vec<u8>,
+    #[unity(since = "5.3", rename = "m_StreamData")]
+    pub stream_info: StreamingInfo,
+    /// The `BuildTarget` of the file the texture was read from, which decides how its data is swizzled.
+    #[unity(object = "target_platform")]
+    pub target_platform: i32,
+    /// Platform specific data, which for the Switch holds how high the blocks of its swizzle are.
+    #[unity(flatten, with = "tree_platform_blob")]
+    pub platform_blob: Vec<u8>,
+}
+
+impl Texture3D {
+    fn resolve_image_data(&mut self, object: &Object) -> UnityResult<()> {
+        resolve_image_data(object, &self.stream_info, &mut self.data)
+    }
+
+    /// The depth slice `slice` of the largest level, as a 2D texture.
+    pub fn slice(&self, slice: usize) -> UnityResult<Texture2D> {
+        if slice >= self.depth.max(0) as usize {
+            return Err(UnityError::InvalidValue);
+        }
+        let mut texture = Texture2D {
+            limits: self.limits,
+            version: self.version,
+            path_id: self.path_id,
+            name: self.name.clone(),
+            width: self.width,
+            height: self.height,
+            format: self.format,
+            mip_count: 1,
+            is_read_able: self.is_read_able,
+            image_count: 1,
+            color_space: self.color_space,
+            texture_setting: self.texture_setting.clone(),
+            target_platform: self.target_platform,
+            platform_blob: self.platform_blob.clone(),
+            ..Texture2D::default()
+        };
+        // Slices of consoles that swizzle are padded like the levels of a 2D texture.
+        let slice_bytes = texture.stored_bytes(self.width, self.height)?;
+        texture.data = self.data.get(slice * slice_bytes..(slice + 1) * slice_bytes).ok_or(UnityError::Eof)?.to_vec();
+        texture.complete_image_size = slice_bytes as i32;
+        texture.size = slice_bytes as i32;
+        Ok(texture)
+    }
+
+    /// Decodes the depth slices of the largest level, front to back.
+    pub fn decode_slices(&self) -> UnityResult<Vec<RgbaImage>> {
+        (0..self.depth.max(0) as usize).map(|slice| self.slice(slice)?.decode_image_without_cache()).collect()
+    }
+
+    /// Stacks the depth slices of the largest level into a vertical strip, the first slice on top, the usual layout
+    /// for color grading lookup tables.
+    pub fn decode_strip(&self) -> UnityResult<RgbaImage> {
+        let slices = self.decode_slices()?;
+        let (width, height) = (self.width.max(0) as u32, self.height.max(0) as u32);
+        let mut strip = RgbaImage::new(width, height * slices.len() as u32);
+        for (i, slice) in slices.iter().enumerate() {
+            imageops::replace(&mut strip, slice, 0, i as i64 * height as i64);
+        }
+        Ok(strip)
+    }
+}
@@ -0,0 +1,450 @@
+//! Writing textures to DDS and KTX2 files as they are stored, without decoding or recompressing them.
+//!
+//! Rows stay in Unity's bottom-up order: KTX2 files say so with their `KTXorientation`, DDS has no way to.
+
+use crate::classes::{Cubemap, Texture2D, Texture2DArray, TextureFormat};
+use crate::error::{UnityError, UnityResult};
+
+/// The file formats textures are exported to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Container {
+    /// DirectDraw Surface, for the DXT and BC formats and the uncompressed ones.
+    Dds,
+    /// KTX 2.0, for the ETC, EAC, ASTC and PVRTC formats.
+    Ktx2,
+}
+
+impl Container {
+    /// The container textures of `format` are exported to, or `None` when neither can hold it.
+    pub fn for_format(format: TextureFormat) -> Option<Self> {
+        match format {
+            TextureFormat::DXT1Crunched | TextureFormat::DXT5Crunched => Some(Self::Dds),
+            TextureFormat::ETC_RGB4Crunched | TextureFormat::ETC2_RGBA8Crunched => Some(Self::Ktx2),
+            format if dds_format(format, false).is_some() => Some(Self::Dds),
+            format if ktx2_format(format, false).is_some() => Some(Self::Ktx2),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Dds => "dds",
+            Self::Ktx2 => "ktx2",
+        }
+    }
+}
+
+/// Every level of every image of a texture, in the layout the containers are built from.
+struct Surfaces {
+    format: TextureFormat,
+    width: usize,
+    height: usize,
+    srgb: bool,
+    /// The number of array layers, or 0 when the texture is not an array.
+    layers: usize,
+    /// 6 for cubemaps, 1 otherwise.
+    faces: usize,
+    levels: usize,
+    /// The levels of each image, largest first, with the faces of a layer next to each other.
+    images: Vec<Vec<Vec<u8>>>,
+}
+
+impl Surfaces {
+    /// Takes the images of `textures` in order, as the layers of an array when `array` is set. Crunched levels are
+    /// transcoded to the blocks they were compressed from, and swizzled ones are laid out linearly first.
+    fn new(textures: &[&Texture2D], faces: usize, array: bool) -> UnityResult<Self> {
+        let first = textures.first().ok_or(UnityError::InvalidValue)?;
+        let mut surfaces = Self {
+            format: first.format,
+            width: first.width.max(0) as usize,
+            height: first.height.max(0) as usize,
+            srgb: first.color_space == 1,
+            layers: 0,
+            faces,
+            levels: 0,
+            images: Vec::new(),
+        };
+        for texture in textures {
+            let linear = texture.deswizzled()?;
+            let texture = linear.as_ref().unwrap_or(texture);
+            surfaces.levels = texture.mip_levels();
+            for image in 0..texture.image_count.max(1) as usize {
+                let mut levels = Vec::new();
+                for mip in 0..surfaces.levels {
+                    let level = texture.mip_level(mip, image)?;
+                    surfaces.format = level.format;
+                    levels.push(level.data);
+                }
+                surfaces.images.push(levels);
+            }
+        }
+        if !surfaces.images.len().is_multiple_of(faces) {
+            return Err(UnityError::InvalidValue);
+        }
+        if array || surfaces.images.len() > faces {
+            surfaces.layers = surfaces.images.len() / faces;
+        }
+        Ok(surfaces)
+    }
+
+    fn to_dds(&self) -> UnityResult<Vec<u8>> {
+        let format = dds_format(self.format, self.srgb).ok_or(UnityError::Unimplemented)?;
+        let cube = self.faces == 6;
+        let mut flags = 0x1 | 0x2 | 0x4 | 0x1000;
+        let mut caps = 0x1000;
+        if self.levels > 1 {
+            flags |= 0x20000;
+            caps |= 0x8 | 0x400000;
+        }
+        if cube {
+            caps |= 0x8;
+        }
+        let level_bytes = self.images.first().map_or(0, |levels| levels[0].len());
+        let pitch = match format {
+            DdsFormat::Dxgi(_) if self.format.is_compressed() => {
+                flags |= 0x80000;
+                level_bytes
+            }
+            _ => {
+                flags |= 0x8;
+                level_bytes / self.height.max(1)
+            }
+        };
+
+        let mut out = b"DDS ".to_vec();
+        let mut header = vec![124, flags, self.height as u32, self.width as u32, pitch as u32, 0, self.levels as u32];
+        header.extend([0; 11]);
+        match format {
+            DdsFormat::Dxgi(_) => header.extend([32, 0x4, u32::from_le_bytes(*b"DX10"), 0, 0, 0, 0, 0]),
+            DdsFormat::Masks { bits, masks, alpha } => header.extend([32, 0x40 | if alpha { 0x1 } else { 0 }, 0, bits, masks[0], masks[1], masks[2], masks[3]]),
+        }
+        header.extend([caps, if cube { 0x200 | 0xfc00 } else { 0 }, 0, 0, 0]);
+        if let DdsFormat::Dxgi(dxgi) = format {
+            // A 2D texture, as a cube when it has six faces, counted in whole cubes.
+            header.extend([dxgi, 3, if cube { 0x4 } else { 0 }, self.layers.max(1) as u32, 0]);
+        } else if self.layers > 1 {
+            return Err(UnityError::Unimplemented);
+        }
+        out.extend(header.iter().flat_map(|v| v.to_le_bytes()));
+        // Images one after another, each with its levels from the largest, as Unity stores them.
+        for level in self.images.iter().flatten() {
+            match format {
+                // The 16-bit formats are read as big-endian pixels, DDS takes them little-endian.
+                DdsFormat::Masks { bits: 16, .. } => out.extend(level.chunks_exact(2).flat_map(|pixel| [pixel[1], pixel[0]])),
+                _ => out.extend(level),
+            }
+        }
+        Ok(out)
+    }
+
+    fn to_ktx2(&self) -> UnityResult<Vec<u8>> {
+        let format = ktx2_format(self.format, self.srgb).ok_or(UnityError::Unimplemented)?;
+        let dfd = format.descriptor();
+        // A single key and value, both null terminated: the rows go up, as Unity stores them.
+        let entry = b"KTXorientation\0ru\0";
+        let mut kvd = (entry.len() as u32).to_le_bytes().to_vec();
+        kvd.extend(entry);
+        kvd.resize(kvd.len().next_multiple_of(4), 0);
+
+        let level_index = 80;
+        let dfd_offset = level_index + self.levels * 24;
+        let kvd_offset = dfd_offset + dfd.len();
+        let mut out = vec![0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, b'\r', b'\n', 0x1a, b'\n'];
+        let header = [format.vk_format, 1, self.width as u32, self.height as u32, 0, self.layers as u32, self.faces as u32, self.levels as u32, 0];
+        out.extend(header.iter().flat_map(|v| v.to_le_bytes()));
+        for v in [dfd_offset, dfd.len(), kvd_offset, kvd.len()] {
+            out.extend((v as u32).to_le_bytes());
+        }
+        out.extend([0; 16]);
+        out.resize(dfd_offset, 0);
+        out.extend(dfd);
+        out.extend(kvd);
+
+        // Levels are stored from the smallest, each with all its layers and faces.
+        let alignment = format.block_bytes.max(4);
+        let mut index = vec![(0, 0); self.levels];
+        for mip in (0..self.levels).rev() {
+            out.resize(out.len().next_multiple_of(alignment), 0);
+            let start = out.len();
+            for levels in &self.images {
+                out.extend(&levels[mip]);
+            }
+            index[mip] = (start, out.len() - start);
+        }
+        for (mip, (offset, length)) in index.into_iter().enumerate() {
+            let at = level_index + mip * 24;
+            for (i, v) in [offset, length, length].into_iter().enumerate() {
+                out[at + i * 8..at + i * 8 + 8].copy_from_slice(&(v as u64).to_le_bytes());
+            }
+        }
+        Ok(out)
+    }
+
+    fn export(&self) -> UnityResult<(Container, Vec<u8>)> {
+        match Container::for_format(self.format) {
+            Some(Container::Dds) => Ok((Container::Dds, self.to_dds()?)),
+            Some(Container::Ktx2) => Ok((Container::Ktx2, self.to_ktx2()?)),
+            None => Err(UnityError::Unimplemented),
+        }
+    }
+}
+
+impl TextureFormat {
+    fn is_compressed(self) -> bool {
+        matches!(self, TextureFormat::DXT1 | TextureFormat::DXT5 | TextureFormat::BC4 | TextureFormat::BC5 | TextureFormat::BC6H | TextureFormat::BC7)
+    }
+}
+
+enum DdsFormat {
+    Dxgi(u32),
+    /// A legacy uncompressed format given by the masks of its red, green, blue and alpha bits.
+    Masks {
+        bits: u32,
+        masks: [u32; 4],
+        alpha: bool,
+    },
+}
+
+fn dds_format(format: TextureFormat, srgb: bool) -> Option<DdsFormat> {
+    let dxgi = match format {
+        TextureFormat::DXT1 => 71 + srgb as u32,
+        TextureFormat::DXT5 => 77 + srgb as u32,
+        TextureFormat::BC4 => 80,
+        TextureFormat::BC5 => 83,
+        TextureFormat::BC6H => 95,
+        TextureFormat::BC7 => 98 + srgb as u32,
+        TextureFormat::RGBA32 => 28 + srgb as u32,
+        TextureFormat::BGRA32 => 87 + srgb as u32 * 4,
+        TextureFormat::R8 => 61,
+        TextureFormat::Alpha8 => 65,
+        TextureFormat::RG16 => 49,
+        TextureFormat::R16 => 56,
+        TextureFormat::RG32 => 35,
+        TextureFormat::RGBA64 => 11,
+        TextureFormat::RHalf => 54,
+        TextureFormat::RGHalf => 34,
+        TextureFormat::RGBAHalf => 10,
+        TextureFormat::RFloat => 41,
+        TextureFormat::RGFloat => 16,
+        TextureFormat::RGBAFloat => 2,
+        TextureFormat::RGB9e5Float => 67,
+        TextureFormat::RGB24 => {
+            return Some(DdsFormat::Masks {
+                bits: 24,
+                masks: [0xff, 0xff00, 0xff0000, 0],
+                alpha: false,
+            })
+        }
+        TextureFormat::ARGB32 => {
+            return Some(DdsFormat::Masks {
+                bits: 32,
+                masks: [0xff00, 0xff0000, 0xff000000, 0xff],
+                alpha: true,
+            })
+        }
+        TextureFormat::RGB565 => {
+            return Some(DdsFormat::Masks {
+                bits: 16,
+                masks: [0xf800, 0x07e0, 0x001f, 0],
+                alpha: false,
+            })
+        }
+        TextureFormat::ARGB4444 => {
+            return Some(DdsFormat::Masks {
+                bits: 16,
+                masks: [0x0f00, 0x00f0, 0x000f, 0xf000],
+                alpha: true,
+            })
+        }
+        TextureFormat::RGBA4444 => {
+            return Some(DdsFormat::Masks {
+                bits: 16,
+                masks: [0xf000, 0x0f00, 0x00f0, 0x000f],
+                alpha: true,
+            })
+        }
+        _ => return None,
+    };
+    Some(DdsFormat::Dxgi(dxgi))
+}
+
+/// A `VkFormat` with what its data format descriptor needs.
+struct Ktx2Format {
+    vk_format: u32,
+    srgb: bool,
+    model: u8,
+    block: (u8, u8),
+    block_bytes: usize,
+    /// The channel and qualifier bits of each 64-bit or 128-bit sample.
+    samples: &'static [u8],
+}
+
+impl Ktx2Format {
+    /// The basic data format descriptor, with the total size in front.
+    fn descriptor(&self) -> Vec<u8> {
+        let sample_bits = (self.block_bytes * 8 / self.samples.len()) as u32;
+        let block_size = 24 + 16 * self.samples.len();
+        let mut dfd = Vec::new();
+        dfd.extend(((4 + block_size) as u32).to_le_bytes());
+        dfd.extend(0u32.to_le_bytes());
+        dfd.extend(2u16.to_le_bytes());
+        dfd.extend((block_size as u16).to_le_bytes());
+        // BT.709 primaries, with the sRGB or linear transfer function.
+        dfd.extend([self.model, 1, if self.srgb { 2 } else { 1 }, 0]);
+        dfd.extend([self.block.0 - 1, self.block.1 - 1, 0, 0]);
+        dfd.extend([self.block_bytes as u8, 0, 0, 0, 0, 0, 0, 0]);
+        for (i, &channel) in self.samples.iter().enumerate() {
+            let (lower, upper): (u32, u32) = match channel & 0xc0 {
+                0xc0 => (0xbf800000, 0x3f800000),
+                0x40 => (0x80000000, 0x7fffffff),
+                _ => (0, u32::MAX),
+            };
+            dfd.extend(((i as u32 * sample_bits) as u16).to_le_bytes());
+            dfd.extend([(sample_bits - 1) as u8, channel, 0, 0, 0, 0]);
+            dfd.extend(lower.to_le_bytes());
+            dfd.extend(upper.to_le_bytes());
+        }
+        dfd
+    }
+}
+
+const MODEL_ETC2: u8 = 161;
+const MODEL_ASTC: u8 = 162;
+const MODEL_PVRTC: u8 = 164;
+const SIGNED: u8 = 0x40;
+const FLOAT: u8 = 0x80;
+// ETC2 channels.
+const RED: u8 = 0;
+const GREEN: u8 = 1;
+const COLOR: u8 = 2;
+const ALPHA: u8 = 15;
+
+fn ktx2_format(format: TextureFormat, srgb: bool) -> Option<Ktx2Format> {
+    let srgb = has_srgb(format) && srgb;
+    let offset = srgb as u32;
+    let etc2 = |vk_format, block_bytes, samples| Ktx2Format {
+        vk_format,
+        srgb,
+        model: MODEL_ETC2,
+        block: (4, 4),
+        block_bytes,
+        samples,
+    };
+    let astc = |vk_format, size: u8, samples| Ktx2Format {
+        vk_format,
+        srgb,
+        model: MODEL_ASTC,
+        block: (size, size),
+        block_bytes: 16,
+        samples,
+    };
+    let pvrtc = |vk_format, width| Ktx2Format {
+        vk_format,
+        srgb,
+        model: MODEL_PVRTC,
+        block: (width, 4),
+        block_bytes: 8,
+        samples: &[0],
+    };
+    // The sRGB formats follow their UNORM ones, and the ASTC ones go 4x4, 5x4, 5x5, 6x5, 6x6, 8x5, 8x6, 8x8, 10x5,
+    // 10x6, 10x8, 10x10, 12x10, 12x12.
+    let format = match format {
+        // ETC1 is a subset of ETC2.
+        TextureFormat::ETC_RGB4 | TextureFormat::ETC2_RGB => etc2(147 + offset, 8, &[COLOR]),
+        TextureFormat::ETC2_RGBA1 => etc2(149 + offset, 8, &[COLOR]),
+        TextureFormat::ETC2_RGBA8 => etc2(151 + offset, 16, &[ALPHA, COLOR]),
+        TextureFormat::EAC_R => etc2(153, 8, &[RED]),
+        TextureFormat::EAC_R_SIGNED => etc2(154, 8, &[RED | SIGNED]),
+        TextureFormat::EAC_RG => etc2(155, 16, &[RED, GREEN]),
+        TextureFormat::EAC_RG_SIGNED => etc2(156, 16, &[RED | SIGNED, GREEN | SIGNED]),
+        TextureFormat::ASTC_RGB_4x4 | TextureFormat::ASTC_RGBA_4x4 => astc(157 + offset, 4, &[0]),
+        TextureFormat::ASTC_RGB_5x5 | TextureFormat::ASTC_RGBA_5x5 => astc(161 + offset, 5, &[0]),
+        TextureFormat::ASTC_RGB_6x6 | TextureFormat::ASTC_RGBA_6x6 => astc(165 + offset, 6, &[0]),
+        TextureFormat::ASTC_RGB_8x8 | TextureFormat::ASTC_RGBA_8x8 => astc(171 + offset, 8, &[0]),
+        TextureFormat::ASTC_RGB_10x10 | TextureFormat::ASTC_RGBA_10x10 => astc(179 + offset, 10, &[0]),
+        TextureFormat::ASTC_RGB_12x12 | TextureFormat::ASTC_RGBA_12x12 => astc(183 + offset, 12, &[0]),
+        TextureFormat::ASTC_HDR_4x4 => astc(1000066000, 4, &[FLOAT | SIGNED]),
+        TextureFormat::ASTC_HDR_5x5 => astc(1000066002, 5, &[FLOAT | SIGNED]),
+        TextureFormat::ASTC_HDR_6x6 => astc(1000066004, 6, &[FLOAT | SIGNED]),
+        TextureFormat::ASTC_HDR_8x8 => astc(1000066007, 8, &[FLOAT | SIGNED]),
+        TextureFormat::ASTC_HDR_10x10 => astc(1000066011, 10, &[FLOAT | SIGNED]),
+        TextureFormat::ASTC_HDR_12x12 => astc(1000066013, 12, &[FLOAT | SIGNED]),
+        TextureFormat::PVRTC_RGB2 | TextureFormat::PVRTC_RGBA2 => pvrtc(1000054000 + offset * 4, 8),
+        TextureFormat::PVRTC_RGB4 | TextureFormat::PVRTC_RGBA4 => pvrtc(1000054001 + offset * 4, 4),
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Whether `format` has an sRGB variant, which all but the single and dual channel and the HDR formats have.
+fn has_srgb(format: TextureFormat) -> bool {
+    !matches!(
+        format,
+        TextureFormat::EAC_R
+            | TextureFormat::EAC_R_SIGNED
+            | TextureFormat::EAC_RG
+            | TextureFormat::EAC_RG_SIGNED
+            | TextureFormat::ASTC_HDR_4x4
+            | TextureFormat::ASTC_HDR_5x5
+            | TextureFormat::ASTC_HDR_6x6
+            | TextureFormat::ASTC_HDR_8x8
+            | TextureFormat::ASTC_HDR_10x10
+            | TextureFormat::ASTC_HDR_12x12
+    )
+}
+
+impl Texture2D {
+    /// The texture as a DDS file, with every level of every image; several images make an array.
+    pub fn to_dds(&self) -> UnityResult<Vec<u8>> {
+        Surfaces::new(&[self], 1, false)?.to_dds()
+    }
+
+    /// The texture as a KTX2 file, with every level of every image; several images make an array.
+    pub fn to_ktx2(&self) -> UnityResult<Vec<u8>> {
+        Surfaces::new(&[self], 1, false)?.to_ktx2()
+    }
+
+    /// The texture in the container [`Container::for_format`] picks for its format.
+    pub fn export_container(&self) -> UnityResult<(Container, Vec<u8>)> {
+        Surfaces::new(&[self], 1, false)?.export()
+    }
+}
+
+impl Cubemap {
+    /// The cubemap as a DDS cube texture.
+    pub fn to_dds(&self) -> UnityResult<Vec<u8>> {
+        Surfaces::new(&[&self.texture], 6, false)?.to_dds()
+    }
+
+    /// The cubemap as a KTX2 file of six faces.
+    pub fn to_ktx2(&self) -> UnityResult<Vec<u8>> {
+        Surfaces::new(&[&self.texture], 6, false)?.to_ktx2()
+    }
+
+    /// The cubemap in the container [`Container::for_format`] picks for its format.
+    pub fn export_container(&self) -> UnityResult<(Container, Vec<u8>)> {
+        Surfaces::new(&[&self.texture], 6, false)?.export()
+    }
+}
+
+impl Texture2DArray {
+    fn surfaces(&self) -> UnityResult<Surfaces> {
+        let slices = (0..self.depth.max(0) as usize).map(|slice| self.slice(slice)).collect::<UnityResult<Vec<_>>>()?;
+        Surfaces::new(&slices.iter().collect::<Vec<_>>(), 1, true)
+    }
+
+    /// The array as a DDS texture array.
+    pub fn to_dds(&self) -> UnityResult<Vec<u8>> {
+        self.surfaces()?.to_dds()
+    }
+
+    /// The array as a KTX2 array texture.
+    pub fn to_ktx2(&self) -> UnityResult<Vec<u8>> {
+        self.surfaces()?.to_ktx2()
+    }
+
+    /// The array in the container [`Container::for_format`] picks for its format.
+    pub fn export_container(&self) -> UnityResult<(Container, Vec<u8>)> {
+        self.surfaces()?.export()
+    }
+}
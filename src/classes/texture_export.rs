@@ -0,0 +1,133 @@
+//! Turning the values a texture stores into an image to look at: converting them between the color space of the
+//! texture and the one of the output, decoding lightmaps to HDR and rebuilding normal maps.
+
+use crate::classes::{Texture2D, TextureFormat};
+use crate::error::UnityResult;
+use image::{DynamicImage, Rgba, Rgba32FImage, RgbaImage};
+
+/// The `color_space` of textures whose colors are stored sRGB encoded; 0 is for data stored linear.
+const COLOR_SPACE_SRGB: i32 = 1;
+
+/// The conversions [`Texture2D::export_image`] and [`Texture2D::export_image_f32`] apply after decoding. None of them
+/// by default, which gives the values as stored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExportOptions {
+    /// Converts colors from the space `color_space` gives the texture to the one of the output: sRGB for 8 bits,
+    /// linear for floats.
+    pub color_space: bool,
+    /// Decodes RGBM and double LDR lightmaps to linear HDR.
+    pub lightmaps: bool,
+    /// Rebuilds normal maps stored in two channels into XYZ, mapped from -1..=1 to 0..=1.
+    pub normal_maps: bool,
+}
+
+impl ExportOptions {
+    /// Every conversion.
+    pub fn all() -> Self {
+        Self {
+            color_space: true,
+            lightmaps: true,
+            normal_maps: true,
+        }
+    }
+}
+
+/// How a lightmap stores light, from the `light_map_format` of its texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightmapEncoding {
+    /// Half the light, in gamma space.
+    DoubleLdr,
+    /// The light divided by 5, as a color scaled by its alpha, in gamma space.
+    Rgbm,
+    /// The light itself, in a half float or BC6H format.
+    FullHdr,
+}
+
+impl Texture2D {
+    /// The encoding of the texture when it is a lightmap. `light_map_format` holds Unity's `TextureUsageMode`.
+    pub fn lightmap_encoding(&self) -> Option<LightmapEncoding> {
+        match self.light_map_format {
+            1 | 7 | 8 => Some(LightmapEncoding::DoubleLdr),
+            2 | 5 | 9 | 11 => Some(LightmapEncoding::Rgbm),
+            10 => Some(LightmapEncoding::FullHdr),
+            _ => None,
+        }
+    }
+
+    /// Whether the texture is a normal map of which only X and Y are stored: in red and green for the two channel
+    /// formats, in alpha and green when `light_map_format` says DXT5nm or ASTCnm.
+    pub fn is_normal_map(&self) -> bool {
+        matches!(self.format, TextureFormat::BC5 | TextureFormat::EAC_RG | TextureFormat::EAC_RG_SIGNED) || matches!(self.light_map_format, 3 | 12)
+    }
+
+    /// Decodes the image to float RGBA as [`Self::decode_image_f32`] does, then applies `options`. Normal maps come
+    /// out as XYZ, lightmaps and, with `color_space`, sRGB textures as linear values.
+    pub fn export_image_f32(&self, options: &ExportOptions) -> UnityResult<Rgba32FImage> {
+        let mut img = self.decode_image_f32()?;
+        let encoding = self.lightmap_encoding().filter(|_| options.lightmaps);
+        if options.normal_maps && self.is_normal_map() {
+            img.pixels_mut().for_each(|pixel| *pixel = normal(*pixel));
+        } else if let Some(encoding) = encoding {
+            // Unity decodes the gamma space encodings with a gamma of 2.2.
+            let scale = |[r, g, b, _]: [f32; 4], scale: f32| {
+                let [r, g, b] = [r, g, b].map(|c| (c * scale).max(0.0).powf(2.2));
+                Rgba([r, g, b, 1.0])
+            };
+            for pixel in img.pixels_mut() {
+                *pixel = match encoding {
+                    LightmapEncoding::DoubleLdr => scale(pixel.0, 2.0),
+                    LightmapEncoding::Rgbm => scale(pixel.0, 5.0 * pixel[3]),
+                    LightmapEncoding::FullHdr => *pixel,
+                };
+            }
+        } else if options.color_space && self.color_space == COLOR_SPACE_SRGB {
+            for pixel in img.pixels_mut() {
+                pixel.0[..3].iter_mut().for_each(|c| *c = srgb_to_linear(*c));
+            }
+        }
+        Ok(img)
+    }
+
+    /// Decodes the image to 8-bit RGBA as [`Self::decode_image_without_cache`] does, then applies `options`. Normal
+    /// maps come out as XYZ; lightmaps and, with `color_space`, linear textures are encoded as sRGB.
+    pub fn export_image(&self, options: &ExportOptions) -> UnityResult<RgbaImage> {
+        let normal_map = options.normal_maps && self.is_normal_map();
+        let lightmap = options.lightmaps && self.lightmap_encoding().is_some();
+        if !normal_map && !lightmap && (!options.color_space || self.color_space == COLOR_SPACE_SRGB) {
+            return self.decode_image_without_cache();
+        }
+        let mut img = self.export_image_f32(options)?;
+        if !normal_map {
+            for pixel in img.pixels_mut() {
+                pixel.0[..3].iter_mut().for_each(|c| *c = linear_to_srgb(*c));
+            }
+        }
+        Ok(DynamicImage::ImageRgba32F(img).into_rgba8())
+    }
+}
+
+/// Rebuilds a normal from X and Y in 0..=1. X is red times alpha, which works for both layouts since the channel
+/// not holding it is left at 1.
+fn normal(pixel: Rgba<f32>) -> Rgba<f32> {
+    let [r, g, _, a] = pixel.0;
+    let (x, y) = (r * a * 2.0 - 1.0, g * 2.0 - 1.0);
+    let z = (1.0 - x * x - y * y).max(0.0).sqrt();
+    let [x, y, z] = [x, y, z].map(|v| v * 0.5 + 0.5);
+    Rgba([x, y, z, 1.0])
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
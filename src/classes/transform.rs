@@ -1,41 +1,36 @@
-use crate::classes::FromObject;
-use crate::env::Object;
-use crate::error::UnityResult;
-use crate::math::{Quaternion, Vector3};
+use crate::math::{Quaternion, Vector2, Vector3};
 
 use super::game_object::GameObject;
 use super::pptr::PPtr;
+use super::FromObject;
 
-pub struct Transform<'a> {
-    pub game_object: PPtr<'a, GameObject<'a>>,
+#[derive(FromObject)]
+pub struct Transform {
+    #[unity(pptr)]
+    pub game_object: PPtr<GameObject>,
     pub local_rotation: Quaternion,
     pub local_position: Vector3,
     pub local_scale: Vector3,
-    pub children: Vec<PPtr<'a, Self>>,
-    pub father: PPtr<'a, Self>,
+    #[unity(pptr)]
+    pub children: Vec<PPtr<Self>>,
+    #[unity(pptr)]
+    pub father: PPtr<Self>,
 }
 
-impl<'a> FromObject<'a> for Transform<'a> {
-    fn load(object: &'a Object) -> UnityResult<Self> {
-        let mut r = object.info.get_reader();
-        Ok(Self {
-            game_object: PPtr::<GameObject>::load(object, &mut r)?,
-            local_rotation: Quaternion::from_array(r.read_f32_array::<4>()?),
-            local_position: r.read_vector3()?,
-            local_scale: r.read_vector3()?,
-            children: {
-                let count = r.read_i32()? as usize;
-                let mut children = Vec::with_capacity(count);
-                for _ in 0..count {
-                    children.push(PPtr::load(object, &mut r)?);
-                }
-                children
-            },
-            father: PPtr::<Self>::load(object, &mut r)?,
-        })
-    }
-
-    fn class() -> super::ClassID {
-        super::ClassID::Transform
-    }
+#[derive(FromObject)]
+pub struct RectTransform {
+    #[unity(pptr)]
+    pub game_object: PPtr<GameObject>,
+    pub local_rotation: Quaternion,
+    pub local_position: Vector3,
+    pub local_scale: Vector3,
+    #[unity(pptr)]
+    pub children: Vec<PPtr<Transform>>,
+    #[unity(pptr)]
+    pub father: PPtr<Transform>,
+    pub anchor_min: Vector2,
+    pub anchor_max: Vector2,
+    pub anchored_position: Vector2,
+    pub size_delta: Vector2,
+    pub pivot: Vector2,
 }
@@ -0,0 +1,151 @@
+use crate::env::Object;
+use crate::error::{UnityError, UnityResult};
+use crate::math::{Color, Matrix4x4, Quaternion, RectF32, Vector2, Vector3, Vector4};
+use crate::reader::ByteOrder;
+use serde_json::Value;
+
+pub(super) fn mistyped(name: &str) -> UnityError {
+    UnityError::CustomError(format!("TypeTree field {name} is missing or has an unexpected type"))
+}
+
+pub(super) fn field<'v>(value: &'v Value, name: &str) -> UnityResult<&'v Value> {
+    value.get(name).ok_or_else(|| mistyped(name))
+}
+
+pub(super) fn int(value: &Value, name: &str) -> UnityResult<i64> {
+    match field(value, name)? {
+        Value::Bool(b) => Ok(*b as i64),
+        v => v.as_i64().or_else(|| v.as_u64().map(|x| x as i64)).ok_or_else(|| mistyped(name)),
+    }
+}
+
+pub(super) fn float(value: &Value, name: &str) -> UnityResult<f32> {
+    match field(value, name)? {
+        // serde_json stores NaN and infinities as null
+        Value::Null => Ok(f32::NAN),
+        v => v.as_f64().map(|x| x as f32).ok_or_else(|| mistyped(name)),
+    }
+}
+
+pub(super) fn boolean(value: &Value, name: &str) -> UnityResult<bool> {
+    match field(value, name)? {
+        Value::Bool(b) => Ok(*b),
+        v => v.as_i64().map(|x| x != 0).ok_or_else(|| mistyped(name)),
+    }
+}
+
+pub(super) fn string(value: &Value, name: &str) -> UnityResult<String> {
+    field(value, name)?.as_str().map(|x| x.to_string()).ok_or_else(|| mistyped(name))
+}
+
+pub(super) fn array<'v>(value: &'v Value, name: &str) -> UnityResult<&'v [Value]> {
+    field(value, name)?.as_array().map(|x| x.as_slice()).ok_or_else(|| mistyped(name))
+}
+
+pub(super) fn bytes(value: &Value, name: &str) -> UnityResult<Vec<u8>> {
+    array(value, name)?.iter().map(|x| x.as_u64().map(|x| x as u8).ok_or_else(|| mistyped(name))).collect()
+}
+
+pub(super) fn ints(value: &Value, name: &str) -> UnityResult<Vec<i64>> {
+    array(value, name)?.iter().map(|x| x.as_i64().ok_or_else(|| mistyped(name))).collect()
+}
+
+pub(super) fn floats(value: &Value, name: &str) -> UnityResult<Vec<f32>> {
+    array(value, name)?.iter().map(|x| if x.is_null() { Some(f32::NAN) } else { x.as_f64().map(|x| x as f32) }.ok_or_else(|| mistyped(name))).collect()
+}
+
+pub(super) fn vector2(value: &Value) -> UnityResult<Vector2> {
+    Ok(Vector2 { x: float(value, "x")?, y: float(value, "y")? })
+}
+
+pub(super) fn vector3(value: &Value) -> UnityResult<Vector3> {
+    Ok(Vector3 {
+        x: float(value, "x")?,
+        y: float(value, "y")?,
+        z: float(value, "z")?,
+    })
+}
+
+pub(super) fn vector4(value: &Value) -> UnityResult<Vector4> {
+    Ok(Vector4 {
+        x: float(value, "x")?,
+        y: float(value, "y")?,
+        z: float(value, "z")?,
+        w: float(value, "w")?,
+    })
+}
+
+pub(super) fn quaternion(value: &Value) -> UnityResult<Quaternion> {
+    Ok(Quaternion::new(float(value, "x")?, float(value, "y")?, float(value, "z")?, float(value, "w")?))
+}
+
+pub(super) fn color(value: &Value) -> UnityResult<Color> {
+    Ok(Color::new(float(value, "r")?, float(value, "g")?, float(value, "b")?, float(value, "a")?))
+}
+
+pub(super) fn rect(value: &Value) -> UnityResult<RectF32> {
+    Ok(RectF32 {
+        x: float(value, "x")?,
+        y: float(value, "y")?,
+        w: float(value, "width")?,
+        h: float(value, "height")?,
+    })
+}
+
+pub(super) fn matrix4x4(value: &Value) -> UnityResult<Matrix4x4> {
+    let e = |row: usize, column: usize| float(value, &format!("e{row}{column}"));
+    Ok(Matrix4x4 {
+        m00: e(0, 0)?,
+        m10: e(1, 0)?,
+        m20: e(2, 0)?,
+        m30: e(3, 0)?,
+        m01: e(0, 1)?,
+        m11: e(1, 1)?,
+        m21: e(2, 1)?,
+        m31: e(3, 1)?,
+        m02: e(0, 2)?,
+        m12: e(1, 2)?,
+        m22: e(2, 2)?,
+        m32: e(3, 2)?,
+        m03: e(0, 3)?,
+        m13: e(1, 3)?,
+        m23: e(2, 3)?,
+        m33: e(3, 3)?,
+    })
+}
+
+/// Rebuilds the 16 raw bytes of a `GUID`, which is serialized as four `UInt32`s.
+pub(super) fn guid(object: &Object, value: &Value) -> UnityResult<[u8; 16]> {
+    let mut result = [0u8; 16];
+    for (i, chunk) in result.chunks_exact_mut(4).enumerate() {
+        let data = int(value, &format!("data[{i}]"))? as u32;
+        chunk.copy_from_slice(&match object.info.bytes_order {
+            ByteOrder::Big => data.to_be_bytes(),
+            ByteOrder::Little => data.to_le_bytes(),
+        });
+    }
+    Ok(result)
+}
+
+/// Lists the entries of a `map`, or of a `vector` of `pair`s, as key/value pairs.
+pub(super) fn pairs<'v>(value: &'v Value, name: &str) -> UnityResult<Vec<(Value, &'v Value)>> {
+    entries(field(value, name)?).map_err(|_| mistyped(name))
+}
+
+/// The entries of a decoded `map`, or `vector` of `pair`s.
+pub(super) fn entries(map: &Value) -> UnityResult<Vec<(Value, &Value)>> {
+    match map {
+        Value::Array(items) => items.iter().map(|x| Ok((field(x, "first")?.clone(), field(x, "second")?))).collect(),
+        Value::Object(map) => Ok(map.iter().map(|(k, v)| (serde_json::from_str(k).unwrap_or_else(|_| Value::String(k.clone())), v)).collect()),
+        _ => Err(mistyped("map")),
+    }
+}
+
+/// The name of a material property key, which is either a plain string or a `FastPropertyName`.
+pub(super) fn property_name(key: &Value) -> UnityResult<String> {
+    match key {
+        Value::String(s) => Ok(s.clone()),
+        Value::Object(_) => string(key, "name"),
+        other => Ok(other.to_string()),
+    }
+}
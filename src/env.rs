@@ -1,27 +1,145 @@
 use crate::asset::Asset;
 use crate::bundle::AssetBundle;
-use crate::classes::{ClassID, FromObject};
-use crate::error::UnityResult;
+use crate::classes::{is_known_version, AnyObject, ClassID, FromObject, GameObject, ImageKey, MonoBehaviour, MonoScript, Sprite, Texture2D};
+use crate::error::{ErrorContext, FieldMismatchError, UnityError, UnityResult};
+use crate::generator::TypeTreeGenerator;
 use crate::object::ObjectInfo;
+use crate::reader::ReadLimits;
+use crate::typetree::TypeTree;
+use crate::version::{BuildType, UnityVersion};
 use dashmap::DashMap;
 use image::RgbaImage;
+use rayon::prelude::*;
+use rayon::ThreadPool;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
 
-use std::sync::Arc;
+/// Looks for an aligned string that reads as a full Unity version such as `2020.3.41f1`.
+fn scan_version_string(info: &ObjectInfo) -> Option<UnityVersion> {
+    let mut r = info.get_reader();
+    let size = info.bytes_size;
+    (0..size.saturating_sub(4)).step_by(4).find_map(|offset| {
+        r.set_offset(offset).ok()?;
+        let length = r.read_i32().ok()?;
+        if !(5..=32).contains(&length) || offset + 4 + length as usize > size {
+            return None;
+        }
+        let s = r.read_string_with_length(length as usize).ok()?;
+        UnityVersion::parse(&s).filter(|x| !x.is_stripped() && x.build_type != BuildType::Unknown)
+    })
+}
+
+fn has_stripped_version(bundles: &[Arc<AssetBundle>]) -> bool {
+    bundles.iter().flat_map(|x| &x.assets).any(|x| x.version.is_stripped())
+}
+
+/// Sets the version of the stripped assets, copying the bundles that objects still hold on to.
+fn apply_to_stripped(bundles: &mut [Arc<AssetBundle>], version: UnityVersion) {
+    for bundle in bundles.iter_mut().filter(|x| x.assets.iter().any(|x| x.version.is_stripped())) {
+        for asset in Arc::make_mut(bundle).assets.iter_mut().filter(|x| x.version.is_stripped()) {
+            asset.set_version(version);
+        }
+    }
+}
+
+fn version_from_build_settings(bundles: &[Arc<AssetBundle>]) -> Option<UnityVersion> {
+    for asset in bundles.iter().flat_map(|x| &x.assets) {
+        let is_manager = |x: &ObjectInfo| matches!(x.class(), ClassID::BuildSettings | ClassID::PlayerSettings);
+        if !asset.objects_info.iter().any(is_manager) {
+            continue;
+        }
+        if !asset.version.is_stripped() {
+            return Some(asset.version);
+        }
+        let version = asset.objects_info.iter().filter(|x| x.class() == ClassID::BuildSettings).find_map(scan_version_string);
+        if version.is_some() {
+            return version;
+        }
+    }
+    None
+}
+
+/// The part of an [`Env`] kept alive by the objects listed from it: its bundles, including the ones loaded after the
+/// objects were listed, and the settings used to read them.
+pub(crate) struct Shared {
+    bundles: Arc<RwLock<Vec<Arc<AssetBundle>>>>,
+    cache: Arc<DashMap<ImageKey, RgbaImage>>,
+    type_tree_generator: Arc<TypeTreeGenerator>,
+    strict: bool,
+}
+
+impl Shared {
+    /// The bundles loaded so far.
+    fn bundles(&self) -> Vec<Arc<AssetBundle>> {
+        self.bundles.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn objects(self: &Arc<Self>) -> ObjectIter {
+        ObjectIter {
+            env: self.clone(),
+            bundle_index: 0,
+            asset_index: 0,
+            obj_index: 0,
+        }
+    }
+
+    fn object(self: &Arc<Self>, bundle: &Arc<AssetBundle>, asset_index: usize, info: ObjectInfo) -> Object {
+        Object {
+            env: self.clone(),
+            bundle: bundle.clone(),
+            asset_index,
+            info,
+            cache: self.cache.clone(),
+        }
+    }
+
+    /// The positions of the objects accepted by `filter`, as bundle, asset and object indices.
+    fn positions(bundles: &[Arc<AssetBundle>], filter: impl Fn(&ObjectInfo) -> bool) -> Vec<(usize, usize, usize)> {
+        let mut positions = Vec::new();
+        for (bundle_index, bundle) in bundles.iter().enumerate() {
+            for (asset_index, asset) in bundle.assets.iter().enumerate() {
+                let infos = asset.objects_info.iter().enumerate().filter(|(_, info)| filter(info));
+                positions.extend(infos.map(|(obj_index, _)| (bundle_index, asset_index, obj_index)));
+            }
+        }
+        positions
+    }
+
+    fn par_objects(self: Arc<Self>, filter: impl Fn(&ObjectInfo) -> bool) -> impl IndexedParallelIterator<Item = Object> {
+        let bundles = self.bundles();
+        Self::positions(&bundles, filter).into_par_iter().map(move |(bundle_index, asset_index, obj_index)| {
+            let bundle = &bundles[bundle_index];
+            self.object(bundle, asset_index, bundle.assets[asset_index].objects_info[obj_index].clone())
+        })
+    }
+
+    pub(crate) fn find_object_with_class(self: &Arc<Self>, path_id: i64, class: ClassID) -> Option<Object> {
+        for bundle in &self.bundles() {
+            for (asset_index, asset) in bundle.assets.iter().enumerate() {
+                if let Some(info) = asset.objects_info.iter().find(|x| x.path_id == path_id && x.class() == class) {
+                    return Some(self.object(bundle, asset_index, info.clone()));
+                }
+            }
+        }
+        None
+    }
+}
 
-pub struct ObjectIter<'a> {
-    env: &'a Env,
+/// Iterates over the objects of every bundle, keeping the bundles alive on its own.
+pub struct ObjectIter {
+    env: Arc<Shared>,
     bundle_index: usize,
     asset_index: usize,
     obj_index: usize,
 }
 
-impl<'a> Iterator for ObjectIter<'a> {
-    type Item = Object<'a>;
+impl Iterator for ObjectIter {
+    type Item = Object;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let bundle = self.env.bundles.get(self.bundle_index)?;
+        let bundle = self.env.bundles.read().unwrap_or_else(PoisonError::into_inner).get(self.bundle_index)?.clone();
         let Some(asset) = bundle.assets.get(self.asset_index) else {
             self.asset_index = 0;
             self.bundle_index += 1;
@@ -33,21 +151,41 @@ impl<'a> Iterator for ObjectIter<'a> {
             return self.next();
         };
         self.obj_index += 1;
-        Some(Object {
-            env: self.env,
-            bundle,
-            asset,
-            info: info.clone(),
-            cache: self.env.cache.clone(),
-        })
+        Some(self.env.object(&bundle, self.asset_index, info.clone()))
     }
 }
 
 pub struct Env {
-    pub bundles: Vec<AssetBundle>,
-    pub cache: Arc<DashMap<i64, RgbaImage>>,
+    bundles: Arc<RwLock<Vec<Arc<AssetBundle>>>>,
+    pub cache: Arc<DashMap<ImageKey, RgbaImage>>,
+    pub type_tree_generator: Arc<TypeTreeGenerator>,
+    /// Makes [`Object::read`] reject what a loader read when it does not consume exactly the serialized size of the
+    /// object, or reads a field differently from the object's TypeTree. The object is then read through its TypeTree,
+    /// and the read fails when it has none.
+    pub strict: bool,
+    /// Bounds applied while parsing the bundles loaded afterwards and their objects.
+    pub limits: ReadLimits,
+    /// Pool used by [`Env::install`] and the batch helpers, instead of the global rayon pool.
+    pub thread_pool: Option<Arc<ThreadPool>>,
+    forced_version: Option<UnityVersion>,
 }
 
+/// Versions tried by [`Env::probe_unity_version`], newest first, one per layout change of `Texture2D` and `Sprite`.
+const PROBE_VERSIONS: [UnityVersion; 12] = [
+    UnityVersion::new(2022, 3, 0),
+    UnityVersion::new(2021, 3, 0),
+    UnityVersion::new(2020, 2, 0),
+    UnityVersion::new(2020, 1, 0),
+    UnityVersion::new(2019, 3, 0),
+    UnityVersion::new(2019, 1, 0),
+    UnityVersion::new(2018, 2, 0),
+    UnityVersion::new(2017, 3, 0),
+    UnityVersion::new(2017, 1, 0),
+    UnityVersion::new(5, 6, 0),
+    UnityVersion::new(5, 4, 2),
+    UnityVersion::new(5, 3, 0),
+];
+
 impl Default for Env {
     fn default() -> Self {
         Self::new()
@@ -57,53 +195,406 @@ impl Default for Env {
 impl Env {
     pub fn new() -> Self {
         Self {
-            bundles: Vec::new(),
+            bundles: Arc::default(),
             cache: Arc::new(DashMap::new()),
+            type_tree_generator: Arc::new(TypeTreeGenerator::new()),
+            strict: false,
+            limits: ReadLimits::default(),
+            thread_pool: None,
+            forced_version: None,
         }
     }
 
-    pub fn load_from_slice(&mut self, src: &[u8]) -> UnityResult<()> {
-        let bundle = AssetBundle::from_slice(src)?;
-        self.bundles.push(bundle);
+    /// Parses a bundle and adds it to the environment. Bundles are parsed without holding any lock, so several
+    /// threads can load into the same `Env` while others read objects from the bundles already loaded.
+    pub fn load_from_slice(&self, src: &[u8]) -> UnityResult<()> {
+        let bundle = AssetBundle::from_slice_with_limits(src, self.limits)?;
+        self.add_bundles(vec![bundle]);
         Ok(())
     }
 
-    pub fn objects(&self) -> ObjectIter {
-        ObjectIter {
-            env: self,
-            bundle_index: 0,
-            asset_index: 0,
-            obj_index: 0,
+    /// Loads a bundle from disk, naming it by its path in errors.
+    pub fn load_from_file(&self, path: impl AsRef<Path>) -> UnityResult<()> {
+        let bundle = self.read_bundle(path.as_ref())?;
+        self.add_bundles(vec![bundle]);
+        Ok(())
+    }
+
+    /// Reads and parses the bundles at `paths` in parallel in the configured pool, then adds the ones that loaded
+    /// in the order of `paths`. Returns the result of each path.
+    pub fn load_many<P: AsRef<Path> + Sync>(&self, paths: &[P]) -> Vec<UnityResult<()>> {
+        let loaded: Vec<_> = self.install(|| paths.par_iter().map(|path| self.read_bundle(path.as_ref())).collect());
+        let mut bundles = Vec::new();
+        let results = loaded
+            .into_iter()
+            .map(|result| {
+                bundles.push(result?);
+                Ok(())
+            })
+            .collect();
+        self.add_bundles(bundles);
+        results
+    }
+
+    fn read_bundle(&self, path: &Path) -> UnityResult<AssetBundle> {
+        let source = path.display().to_string();
+        let context = || ErrorContext {
+            bundle: Some(source.clone()),
+            ..ErrorContext::default()
+        };
+        let data = std::fs::read(path).map_err(|e| UnityError::from(e).with_context(context()))?;
+        let mut bundle = AssetBundle::from_slice_with_limits(&data, self.limits).map_err(|e| e.with_context(context()))?;
+        bundle.source = source;
+        Ok(bundle)
+    }
+
+    fn add_bundles(&self, loaded: Vec<AssetBundle>) {
+        let mut bundles = self.bundles.write().unwrap_or_else(PoisonError::into_inner);
+        for mut bundle in loaded {
+            if let Some(version) = self.forced_version {
+                bundle.set_unity_version(version);
+            }
+            bundles.push(Arc::new(bundle));
         }
+        if self.forced_version.is_none() && has_stripped_version(&bundles) {
+            if let Some(version) = version_from_build_settings(&bundles) {
+                apply_to_stripped(&mut bundles, version);
+            }
+        }
+    }
+
+    /// The bundles loaded so far.
+    pub fn bundles(&self) -> Vec<Arc<AssetBundle>> {
+        self.bundles.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    /// Forces the Unity version of every loaded asset, and of the ones loaded afterwards.
+    ///
+    /// Objects listed before the call keep reading with the version their bundle had.
+    pub fn set_unity_version(&mut self, version: UnityVersion) {
+        self.forced_version = Some(version);
+        for bundle in self.bundles.write().unwrap_or_else(PoisonError::into_inner).iter_mut() {
+            Arc::make_mut(bundle).set_unity_version(version);
+        }
+    }
+
+    /// Finds the version of the build in a loaded `globalgamemanagers`, from its header or from the `m_Version` of its BuildSettings.
+    pub fn version_from_build_settings(&self) -> Option<UnityVersion> {
+        version_from_build_settings(&self.bundles.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Guesses the version of stripped assets by reading their `Texture2D` and `Sprite` objects with candidate versions
+    /// and keeping the newest one that consumes every probed object exactly. Sprites only count for the candidates
+    /// their loader reads whole.
+    pub fn probe_unity_version(&self) -> Option<UnityVersion> {
+        const MAX_PROBES: usize = 8;
+        let probes: Vec<_> = self.objects().filter(|x| x.asset().version.is_stripped() && matches!(x.class(), ClassID::Texture2D | ClassID::Sprite)).take(MAX_PROBES).collect();
+        if probes.is_empty() {
+            return None;
+        }
+        PROBE_VERSIONS.into_iter().find(|version| {
+            let mut exact = probes.iter().filter(|x| x.class() == ClassID::Texture2D || !Sprite::partial(*version)).peekable();
+            exact.peek().is_some()
+                && exact.all(|probe| {
+                    let mut info = probe.info.clone();
+                    info.version = *version;
+                    let object = Object { info, ..probe.clone() };
+                    match object.class() {
+                        ClassID::Texture2D => object.parses_exactly::<Texture2D>(),
+                        _ => object.parses_exactly::<Sprite>(),
+                    }
+                })
+        })
+    }
+
+    /// Fills in the version of stripped assets from the loaded BuildSettings, or else by probing, returning the version applied.
+    pub fn infer_unity_version(&mut self) -> Option<UnityVersion> {
+        if !has_stripped_version(&self.bundles.read().unwrap_or_else(PoisonError::into_inner)) {
+            return None;
+        }
+        let version = self.version_from_build_settings().or_else(|| self.probe_unity_version())?;
+        apply_to_stripped(&mut self.bundles.write().unwrap_or_else(PoisonError::into_inner), version);
+        Some(version)
+    }
+
+    /// Loads a managed assembly used to generate type trees for stripped MonoBehaviours.
+    pub fn load_managed_assembly(&mut self, data: &[u8]) -> UnityResult<()> {
+        Arc::make_mut(&mut self.type_tree_generator).load_assembly(data)
+    }
+
+    /// Loads every assembly of a Mono build's `Managed` directory.
+    pub fn load_managed_dir(&mut self, dir: impl AsRef<Path>) -> UnityResult<()> {
+        Arc::make_mut(&mut self.type_tree_generator).load_managed_dir(dir)
+    }
+
+    /// Loads the type definitions of an IL2CPP build from its `global-metadata.dat` and native binary.
+    pub fn load_il2cpp(&mut self, metadata: &[u8], binary: &[u8]) -> UnityResult<()> {
+        Arc::make_mut(&mut self.type_tree_generator).load_il2cpp(metadata, binary)
+    }
+
+    /// Generates the TypeTrees of every MonoScript listed in the assets' `script_types`, returning how many were built.
+    pub fn generate_script_type_trees(&self) -> usize {
+        let env = self.shared();
+        let mut count = 0;
+        for bundle in &env.bundles() {
+            for (asset_index, asset) in bundle.assets.iter().enumerate() {
+                for script_type in &asset.script_types {
+                    let path_id = script_type.local_identifier_in_file;
+                    let info = match script_type.local_serialized_file_index {
+                        0 => asset.objects_info.iter().find(|x| x.path_id == path_id && x.class() == ClassID::MonoScript).cloned(),
+                        _ => env.find_object_with_class(path_id, ClassID::MonoScript).map(|x| x.info),
+                    };
+                    let Some(info) = info else {
+                        continue;
+                    };
+                    let object = env.object(bundle, asset_index, info);
+                    let Ok(script) = object.read::<MonoScript>() else {
+                        continue;
+                    };
+                    let namespace = script.namespace.as_deref().unwrap_or_default();
+                    if self.type_tree_generator.generate_cached(&script.assembly_name, namespace, &script.class_name, &object.info).is_ok() {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// The bundles and a snapshot of the settings that the objects listed from now on keep alive.
+    fn shared(&self) -> Arc<Shared> {
+        Arc::new(Shared {
+            bundles: self.bundles.clone(),
+            cache: self.cache.clone(),
+            type_tree_generator: self.type_tree_generator.clone(),
+            strict: self.strict,
+        })
+    }
+
+    /// Lists the objects of every loaded bundle. The objects own their bundle and outlive the `Env`.
+    pub fn objects(&self) -> ObjectIter {
+        self.shared().objects()
     }
 
     pub fn find_object(&self, path_id: i64) -> Option<Object> {
         self.objects().find(|i| i.info.path_id == path_id)
     }
 
-    pub fn find_object_with_class<'a, T: FromObject<'a>>(&self, path_id: i64) -> Option<Object> {
-        self.objects().find(|i| i.info.path_id == path_id && i.info.class() == T::class())
+    pub fn find_object_with_class<T: FromObject>(&self, path_id: i64) -> Option<Object> {
+        self.shared().find_object_with_class(path_id, T::class())
+    }
+
+    /// Reads every object of class `T`.
+    pub fn objects_of<T: FromObject>(&self) -> impl Iterator<Item = UnityResult<T>> {
+        self.objects().filter(|x| x.class() == T::class()).map(|x| x.read())
+    }
+
+    /// Reads the objects of class `T` whose name is accepted by `filter`. Names are read with [`Object::peek_name`],
+    /// so only the matching objects are parsed in full.
+    pub fn objects_named<T: FromObject>(&self, filter: impl Fn(&str) -> bool) -> impl Iterator<Item = UnityResult<T>> {
+        self.objects().filter(move |x| x.class() == T::class() && x.peek_name().ok().flatten().is_some_and(|name| filter(&name))).map(|x| x.read())
+    }
+
+    /// Reads the first object of class `T` named `name`.
+    pub fn find_by_name<T: FromObject>(&self, name: &str) -> UnityResult<Option<T>> {
+        self.objects_named(|x| x == name).next().transpose()
+    }
+
+    /// Reads the objects of class `T` whose name matches the glob `pattern`, such as `char_*_1`.
+    pub fn objects_by_glob<T: FromObject>(&self, pattern: &str) -> UnityResult<impl Iterator<Item = UnityResult<T>>> {
+        let pattern = glob::Pattern::new(pattern).map_err(|e| UnityError::CustomError(e.to_string()))?;
+        Ok(self.objects_named(move |x| pattern.matches(x)))
+    }
+
+    /// Reads the objects of class `T` whose name matches the regular expression `pattern`.
+    pub fn objects_by_regex<T: FromObject>(&self, pattern: &str) -> UnityResult<impl Iterator<Item = UnityResult<T>>> {
+        let regex = regex::Regex::new(pattern).map_err(|e| UnityError::CustomError(e.to_string()))?;
+        Ok(self.objects_named(move |x| regex.is_match(x)))
+    }
+
+    /// Runs `op` in the configured [`thread_pool`](Self::thread_pool), or in the global rayon pool when there is none.
+    pub fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        match &self.thread_pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Lists the objects of every loaded bundle in parallel. Drive the iterator inside [`install`](Self::install)
+    /// to run it in the configured pool.
+    pub fn par_objects(&self) -> impl IndexedParallelIterator<Item = Object> {
+        self.shared().par_objects(|_| true)
+    }
+
+    /// Like [`par_objects`](Self::par_objects), but only for the objects of one class.
+    pub fn par_objects_with_class(&self, class: ClassID) -> impl IndexedParallelIterator<Item = Object> {
+        self.shared().par_objects(move |x| x.class() == class)
+    }
+
+    /// Reads and decodes every `Texture2D` in parallel in the configured pool, filling the shared image cache
+    /// so that [`Texture2D::decode_image`] returns the stored image.
+    pub fn decode_all_textures(&self) -> Vec<UnityResult<Texture2D>> {
+        self.install(|| {
+            self.par_objects_with_class(ClassID::Texture2D)
+                .map(|object| {
+                    let texture = object.read::<Texture2D>()?;
+                    texture.decode_image().map_err(|e| object.attach_context(e))?;
+                    Ok(texture)
+                })
+                .collect()
+        })
     }
 }
 
-pub struct Object<'a> {
-    pub env: &'a Env,
-    pub bundle: &'a AssetBundle,
-    pub asset: &'a Asset,
+/// A handle to one object, which keeps its bundle alive and can be stored or sent to other threads.
+#[derive(Clone)]
+pub struct Object {
+    pub(crate) env: Arc<Shared>,
+    pub bundle: Arc<AssetBundle>,
+    asset_index: usize,
     pub info: ObjectInfo,
-    pub cache: Arc<DashMap<i64, RgbaImage>>,
+    pub cache: Arc<DashMap<ImageKey, RgbaImage>>,
 }
 
-impl<'a> Object<'a> {
-    pub fn read<T: FromObject<'a>>(&'a self) -> UnityResult<T> {
-        T::load(self)
+impl Object {
+    /// Reads the object with its hand-written loader, falling back to its TypeTree when the Unity version is unknown
+    /// to the loader, the loader fails, or strict mode rejects what it read.
+    pub fn read<T: FromObject>(&self) -> UnityResult<T> {
+        let has_type_tree = !self.info.serialized_type.type_tree.nodes.is_empty();
+        if has_type_tree && !is_known_version(self.info.version) {
+            return match self.read_from_type_tree() {
+                Err(e) => self.read_raw().map_err(|_| e),
+                result => result,
+            };
+        }
+        match self.read_raw() {
+            Err(e) if has_type_tree => self.read_from_type_tree().map_err(|_| e),
+            result => result,
+        }
+    }
+
+    /// Reads the object into whichever typed class implements it, falling back to its TypeTree,
+    /// or to its serialized bytes when it has no TypeTree.
+    pub fn read_any(&self) -> UnityResult<AnyObject> {
+        AnyObject::read(self)
+    }
+
+    fn read_raw<T: FromObject>(&self) -> UnityResult<T> {
+        // A clone of the handle keeps where the loader stopped apart from reads of this object on other threads.
+        let object = self.clone();
+        let value = T::load(&object).map_err(|e| object.attach_context(e))?;
+        if self.env.strict && !T::partial(self.info.version) {
+            object.info.check_consumption().map_err(|e| object.attach_context(e))?;
+            object.check_fields(&value).map_err(|e| object.attach_context(e))?;
+        }
+        Ok(value)
+    }
+
+    /// Compares the fields a loader read with the object's TypeTree, when it has one.
+    fn check_fields<T: FromObject>(&self, value: &T) -> UnityResult<()> {
+        let fields = value.checked_fields();
+        if fields.is_empty() || self.info.serialized_type.type_tree.nodes.is_empty() {
+            return Ok(());
+        }
+        let tree = self.info.read_type_tree()?;
+        for (field, loaded) in fields {
+            match tree.get(field) {
+                Some(type_tree) if *type_tree != loaded => {
+                    return Err(UnityError::FieldMismatch(Box::new(FieldMismatchError {
+                        class: self.class(),
+                        version: self.info.version,
+                        field: field.to_string(),
+                        loaded,
+                        type_tree: type_tree.clone(),
+                    })))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn parses_exactly<T: FromObject>(&self) -> bool {
+        let object = self.clone();
+        T::load(&object).is_ok() && object.info.check_consumption().is_ok()
+    }
+
+    /// Reads the object by mapping its decoded TypeTree onto the typed struct.
+    pub fn read_from_type_tree<T: FromObject>(&self) -> UnityResult<T> {
+        let object = self.clone();
+        let tree = Value::Object(object.info.read_type_tree().map_err(|e| object.attach_context(e))?.into_iter().collect());
+        T::load_type_tree(&object, &tree).map_err(|e| object.attach_context(e))
+    }
+
+    /// Where the object is, with the offset its last reader stopped at and the TypeTree field at that offset.
+    pub fn error_context(&self) -> ErrorContext {
+        let offset = self.info.consumed_offset();
+        ErrorContext {
+            bundle: Some(self.bundle.source.clone()).filter(|x| !x.is_empty()),
+            node: None,
+            asset: Some(self.asset().path.clone()),
+            path_id: Some(self.info.path_id),
+            class: Some(self.class()),
+            version: Some(self.info.version),
+            offset: Some(offset),
+            field: self.info.type_tree_field_at(offset),
+        }
+    }
+
+    fn attach_context(&self, error: UnityError) -> UnityError {
+        error.with_context(self.error_context())
+    }
+
+    /// The serialized file of the bundle that holds the object.
+    pub fn asset(&self) -> &Asset {
+        &self.bundle.assets[self.asset_index]
     }
 
     pub fn class(&self) -> ClassID {
         ClassID::from(self.info.class_id)
     }
 
+    /// Reads only the name of the object: its leading `m_Name`, or the name in the short header of a GameObject
+    /// or MonoBehaviour. Returns `None` for classes without a name.
+    pub fn peek_name(&self) -> UnityResult<Option<String>> {
+        let leading = match self.info.serialized_type.type_tree.nodes.get(1) {
+            Some(node) => node.name == "m_Name",
+            None => self.class().has_leading_name(),
+        };
+        if leading {
+            return Ok(Some(self.info.get_reader().read_aligned_string()?));
+        }
+        match self.class() {
+            ClassID::GameObject => Ok(Some(GameObject::load(self)?.name)),
+            ClassID::MonoBehaviour => Ok(Some(MonoBehaviour::load(self)?.name)),
+            _ => Ok(None),
+        }
+    }
+
+    /// The Unity version of the serialized file the object comes from.
+    pub fn version(&self) -> UnityVersion {
+        self.info.version
+    }
+
+    /// Reads the object through its TypeTree, generating one from the loaded managed assemblies for stripped MonoBehaviours.
     pub fn read_type_tree(&self) -> UnityResult<HashMap<String, Value>> {
+        if self.info.serialized_type.type_tree.nodes.is_empty() && self.class() == ClassID::MonoBehaviour && !self.env.type_tree_generator.is_empty() {
+            let type_tree = self.generate_cached_type_tree()?;
+            return self.info.read_type_tree_nodes(&type_tree.nodes);
+        }
         self.info.read_type_tree()
     }
+
+    /// Synthesizes the TypeTree of a MonoBehaviour from the MonoScript it references.
+    pub fn generate_type_tree(&self) -> UnityResult<TypeTree> {
+        Ok(self.generate_cached_type_tree()?.as_ref().clone())
+    }
+
+    fn generate_cached_type_tree(&self) -> UnityResult<Arc<TypeTree>> {
+        let behaviour: MonoBehaviour = self.read()?;
+        let script = behaviour.script.get_obj().ok_or(UnityError::CustomError("can not find MonoScript".to_string()))?;
+        let script: MonoScript = script.read()?;
+        let namespace = script.namespace.as_deref().unwrap_or_default();
+        self.env.type_tree_generator.generate_cached(&script.assembly_name, namespace, &script.class_name, &self.info)
+    }
 }
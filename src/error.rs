@@ -1,6 +1,10 @@
+use std::fmt::{Display, Formatter};
 use std::string::FromUtf8Error;
 
 use thiserror::Error;
+
+use crate::classes::ClassID;
+use crate::version::UnityVersion;
 #[derive(Error, Debug)]
 pub enum UnityError {
     #[error("Eof")]
@@ -25,6 +29,130 @@ pub enum UnityError {
     FromUtf8Error(#[from] FromUtf8Error),
     #[error("Unimplemented")]
     Unimplemented,
+    #[error("{0}")]
+    Consumption(Box<ConsumptionError>),
+    #[error("{0}")]
+    FieldMismatch(Box<FieldMismatchError>),
+    #[error("{0} exceeds the configured limit")]
+    LimitExceeded(&'static str),
+    #[error("Io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("{error}\n  in {context}")]
+    Context { error: Box<UnityError>, context: Box<ErrorContext> },
+}
+
+impl UnityError {
+    /// Wraps the error with where it happened, keeping any context it already has as the inner part of the chain.
+    pub fn with_context(self, context: ErrorContext) -> Self {
+        Self::Context {
+            error: Box::new(self),
+            context: Box::new(context),
+        }
+    }
+
+    /// The error without its context.
+    pub fn root(&self) -> &UnityError {
+        match self {
+            Self::Context { error, .. } => error.root(),
+            error => error,
+        }
+    }
+
+    /// The context chain, innermost first.
+    pub fn contexts(&self) -> Vec<&ErrorContext> {
+        let mut result = Vec::new();
+        let mut error = self;
+        while let Self::Context { error: inner, context } = error {
+            result.push(context.as_ref());
+            error = inner;
+        }
+        result.reverse();
+        result
+    }
+}
+
+/// Where an error happened, from the bundle down to the field being read. Unknown parts are left as `None`.
+#[derive(Debug, Clone, Default)]
+pub struct ErrorContext {
+    pub bundle: Option<String>,
+    pub node: Option<String>,
+    pub asset: Option<String>,
+    pub path_id: Option<i64>,
+    pub class: Option<ClassID>,
+    pub version: Option<UnityVersion>,
+    pub offset: Option<usize>,
+    pub field: Option<String>,
+}
+
+impl Display for ErrorContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(bundle) = &self.bundle {
+            parts.push(format!("bundle {bundle}"));
+        }
+        if let Some(node) = &self.node {
+            parts.push(format!("node {node}"));
+        }
+        if let Some(asset) = &self.asset {
+            parts.push(format!("asset {asset}"));
+        }
+        if let Some(path_id) = self.path_id {
+            parts.push(format!("path_id {path_id}"));
+        }
+        if let Some(class) = self.class {
+            parts.push(format!("{class:?}"));
+        }
+        if let Some(version) = self.version {
+            parts.push(format!("Unity {version}"));
+        }
+        if let Some(offset) = self.offset {
+            parts.push(format!("offset {offset:#x}"));
+        }
+        if let Some(field) = &self.field {
+            parts.push(format!("field {field}"));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// A loader that did not consume exactly the serialized size of its object, reported in strict mode.
+#[derive(Debug)]
+pub struct ConsumptionError {
+    pub class: ClassID,
+    pub version: UnityVersion,
+    pub expected: usize,
+    pub consumed: usize,
+    /// The TypeTree field the loader stopped in, when the object has a TypeTree.
+    pub field: Option<String>,
+}
+
+impl Display for ConsumptionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} on Unity {} consumed {} of {} bytes", self.class, self.version, self.consumed, self.expected)?;
+        match &self.field {
+            Some(field) => write!(f, ", diverging at field {field}"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A field a loader read differently from the object's TypeTree, reported in strict mode.
+#[derive(Debug)]
+pub struct FieldMismatchError {
+    pub class: ClassID,
+    pub version: UnityVersion,
+    /// The TypeTree name of the field.
+    pub field: String,
+    /// The value the loader read.
+    pub loaded: serde_json::Value,
+    /// The value the TypeTree gives.
+    pub type_tree: serde_json::Value,
+}
+
+impl Display for FieldMismatchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} on Unity {} read {} for field {}, where the TypeTree has {}", self.class, self.version, self.loaded, self.field, self.type_tree)
+    }
 }
 
 pub type UnityResult<T> = Result<T, UnityError>;
@@ -0,0 +1,634 @@
+use super::{ElementType, ManagedAssembly, ManagedField, ManagedType, TypeSig};
+use crate::error::{UnityError, UnityResult};
+use crate::reader::{ByteOrder, Reader};
+use std::collections::HashMap;
+
+const TABLE_COUNT: usize = 0x2D;
+
+const MODULE: usize = 0x00;
+const TYPE_REF: usize = 0x01;
+const TYPE_DEF: usize = 0x02;
+const FIELD_PTR: usize = 0x03;
+const FIELD: usize = 0x04;
+const METHOD_DEF: usize = 0x06;
+const PARAM: usize = 0x08;
+const INTERFACE_IMPL: usize = 0x09;
+const MEMBER_REF: usize = 0x0A;
+const CUSTOM_ATTRIBUTE: usize = 0x0C;
+const DECL_SECURITY: usize = 0x0E;
+const STAND_ALONE_SIG: usize = 0x11;
+const EVENT: usize = 0x14;
+const PROPERTY: usize = 0x17;
+const MODULE_REF: usize = 0x1A;
+const TYPE_SPEC: usize = 0x1B;
+const ASSEMBLY: usize = 0x20;
+const ASSEMBLY_REF: usize = 0x23;
+const FILE: usize = 0x26;
+const EXPORTED_TYPE: usize = 0x27;
+const MANIFEST_RESOURCE: usize = 0x28;
+const NESTED_CLASS: usize = 0x29;
+const GENERIC_PARAM: usize = 0x2A;
+const METHOD_SPEC: usize = 0x2B;
+const GENERIC_PARAM_CONSTRAINT: usize = 0x2C;
+
+#[derive(Clone, Copy)]
+enum Coded {
+    TypeDefOrRef,
+    HasConstant,
+    HasCustomAttribute,
+    HasFieldMarshal,
+    HasDeclSecurity,
+    MemberRefParent,
+    HasSemantics,
+    MethodDefOrRef,
+    MemberForwarded,
+    Implementation,
+    CustomAttributeType,
+    ResolutionScope,
+    TypeOrMethodDef,
+}
+
+impl Coded {
+    fn tables(&self) -> &'static [usize] {
+        match self {
+            Coded::TypeDefOrRef => &[TYPE_DEF, TYPE_REF, TYPE_SPEC],
+            Coded::HasConstant => &[FIELD, PARAM, PROPERTY],
+            Coded::HasCustomAttribute => &[
+                METHOD_DEF,
+                FIELD,
+                TYPE_REF,
+                TYPE_DEF,
+                PARAM,
+                INTERFACE_IMPL,
+                MEMBER_REF,
+                MODULE,
+                DECL_SECURITY,
+                PROPERTY,
+                EVENT,
+                STAND_ALONE_SIG,
+                MODULE_REF,
+                TYPE_SPEC,
+                ASSEMBLY,
+                ASSEMBLY_REF,
+                FILE,
+                EXPORTED_TYPE,
+                MANIFEST_RESOURCE,
+                GENERIC_PARAM,
+                GENERIC_PARAM_CONSTRAINT,
+                METHOD_SPEC,
+            ],
+            Coded::HasFieldMarshal => &[FIELD, PARAM],
+            Coded::HasDeclSecurity => &[TYPE_DEF, METHOD_DEF, ASSEMBLY],
+            Coded::MemberRefParent => &[TYPE_DEF, TYPE_REF, MODULE_REF, METHOD_DEF, TYPE_SPEC],
+            Coded::HasSemantics => &[EVENT, PROPERTY],
+            Coded::MethodDefOrRef => &[METHOD_DEF, MEMBER_REF],
+            Coded::MemberForwarded => &[FIELD, METHOD_DEF],
+            Coded::Implementation => &[FILE, ASSEMBLY_REF, EXPORTED_TYPE],
+            Coded::CustomAttributeType => &[usize::MAX, usize::MAX, METHOD_DEF, MEMBER_REF, usize::MAX],
+            Coded::ResolutionScope => &[MODULE, MODULE_REF, ASSEMBLY_REF, TYPE_REF],
+            Coded::TypeOrMethodDef => &[TYPE_DEF, METHOD_DEF],
+        }
+    }
+
+    fn tag_bits(&self) -> u32 {
+        let n = self.tables().len() as u32;
+        u32::BITS - (n - 1).leading_zeros()
+    }
+
+    fn decode(&self, value: u32) -> Option<(usize, usize)> {
+        let bits = self.tag_bits();
+        let table = *self.tables().get((value & ((1 << bits) - 1)) as usize)?;
+        let index = (value >> bits) as usize;
+        if table == usize::MAX || index == 0 {
+            return None;
+        }
+        Some((table, index - 1))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Column {
+    U16,
+    U32,
+    Str,
+    Guid,
+    Blob,
+    Index(usize),
+    Coded(Coded),
+}
+
+fn schema(table: usize) -> &'static [Column] {
+    use Column::*;
+    match table {
+        0x00 => &[U16, Str, Guid, Guid, Guid],
+        0x01 => &[Coded(self::Coded::ResolutionScope), Str, Str],
+        0x02 => &[U32, Str, Str, Coded(self::Coded::TypeDefOrRef), Index(FIELD), Index(METHOD_DEF)],
+        0x03 => &[Index(FIELD)],
+        0x04 => &[U16, Str, Blob],
+        0x05 => &[Index(METHOD_DEF)],
+        0x06 => &[U32, U16, U16, Str, Blob, Index(PARAM)],
+        0x07 => &[Index(PARAM)],
+        0x08 => &[U16, U16, Str],
+        0x09 => &[Index(TYPE_DEF), Coded(self::Coded::TypeDefOrRef)],
+        0x0A => &[Coded(self::Coded::MemberRefParent), Str, Blob],
+        0x0B => &[U16, Coded(self::Coded::HasConstant), Blob],
+        0x0C => &[Coded(self::Coded::HasCustomAttribute), Coded(self::Coded::CustomAttributeType), Blob],
+        0x0D => &[Coded(self::Coded::HasFieldMarshal), Blob],
+        0x0E => &[U16, Coded(self::Coded::HasDeclSecurity), Blob],
+        0x0F => &[U16, U32, Index(TYPE_DEF)],
+        0x10 => &[U32, Index(FIELD)],
+        0x11 => &[Blob],
+        0x12 => &[Index(TYPE_DEF), Index(EVENT)],
+        0x13 => &[Index(EVENT)],
+        0x14 => &[U16, Str, Coded(self::Coded::TypeDefOrRef)],
+        0x15 => &[Index(TYPE_DEF), Index(PROPERTY)],
+        0x16 => &[Index(PROPERTY)],
+        0x17 => &[U16, Str, Blob],
+        0x18 => &[U16, Index(METHOD_DEF), Coded(self::Coded::HasSemantics)],
+        0x19 => &[Index(TYPE_DEF), Coded(self::Coded::MethodDefOrRef), Coded(self::Coded::MethodDefOrRef)],
+        0x1A => &[Str],
+        0x1B => &[Blob],
+        0x1C => &[U16, Coded(self::Coded::MemberForwarded), Str, Index(MODULE_REF)],
+        0x1D => &[U32, Index(FIELD)],
+        0x1E => &[U32, U32],
+        0x1F => &[U32],
+        0x20 => &[U32, U16, U16, U16, U16, U32, Blob, Str, Str],
+        0x21 => &[U32],
+        0x22 => &[U32, U32, U32],
+        0x23 => &[U16, U16, U16, U16, U32, Blob, Str, Str, Blob],
+        0x24 => &[U32, Index(ASSEMBLY_REF)],
+        0x25 => &[U32, U32, U32, Index(ASSEMBLY_REF)],
+        0x26 => &[U32, Str, Blob],
+        0x27 => &[U32, U32, Str, Str, Coded(self::Coded::Implementation)],
+        0x28 => &[U32, U32, Str, Coded(self::Coded::Implementation)],
+        0x29 => &[Index(TYPE_DEF), Index(TYPE_DEF)],
+        0x2A => &[U16, U16, Coded(self::Coded::TypeOrMethodDef), Str],
+        0x2B => &[Coded(self::Coded::MethodDefOrRef), Blob],
+        0x2C => &[Index(GENERIC_PARAM), Coded(self::Coded::TypeDefOrRef)],
+        _ => &[],
+    }
+}
+
+struct Metadata<'a> {
+    strings: &'a [u8],
+    blobs: &'a [u8],
+    tables: Vec<Vec<Vec<u32>>>,
+}
+
+impl<'a> Metadata<'a> {
+    fn string(&self, index: u32) -> String {
+        let Some(s) = self.strings.get(index as usize..) else {
+            return String::new();
+        };
+        let end = s.iter().position(|x| *x == 0).unwrap_or(s.len());
+        String::from_utf8_lossy(&s[..end]).to_string()
+    }
+
+    fn blob(&self, index: u32) -> UnityResult<&'a [u8]> {
+        let data = self.blobs.get(index as usize..).ok_or(UnityError::Eof)?;
+        let mut r = Reader::new(data, ByteOrder::Little);
+        let length = read_compressed_u32(&mut r)? as usize;
+        let start = r.get_offset();
+        data.get(start..start + length).ok_or(UnityError::Eof)
+    }
+
+    fn rows(&self, table: usize) -> &[Vec<u32>] {
+        &self.tables[table]
+    }
+
+    fn row(&self, table: usize, index: usize) -> Option<&[u32]> {
+        self.tables[table].get(index).map(|x| x.as_slice())
+    }
+}
+
+fn read_compressed_u32(r: &mut Reader) -> UnityResult<u32> {
+    let b = r.read_u8()? as u32;
+    if b & 0x80 == 0 {
+        Ok(b)
+    } else if b & 0xC0 == 0x80 {
+        Ok(((b & 0x3F) << 8) | r.read_u8()? as u32)
+    } else {
+        let b1 = r.read_u8()? as u32;
+        let b2 = r.read_u8()? as u32;
+        let b3 = r.read_u8()? as u32;
+        Ok(((b & 0x1F) << 24) | (b1 << 16) | (b2 << 8) | b3)
+    }
+}
+
+struct Section {
+    virtual_address: u32,
+    virtual_size: u32,
+    raw_pointer: u32,
+    raw_size: u32,
+}
+
+fn rva_to_offset(sections: &[Section], rva: u32) -> UnityResult<usize> {
+    for section in sections {
+        let size = section.virtual_size.max(section.raw_size);
+        let end = section.virtual_address.checked_add(size);
+        if rva >= section.virtual_address && end.is_some_and(|end| rva < end) {
+            return Ok((rva - section.virtual_address) as usize + section.raw_pointer as usize);
+        }
+    }
+    Err(UnityError::CustomError(format!("rva {rva:#x} is outside of every section")))
+}
+
+/// Locates the CLI metadata root inside a PE image.
+fn metadata_root(data: &[u8]) -> UnityResult<&[u8]> {
+    let mut r = Reader::new(data, ByteOrder::Little);
+    if r.read_u8_array::<2>()? != *b"MZ" {
+        return Err(UnityError::UnsupportFileType("not a PE image".into()));
+    }
+    r.set_offset(0x3C)?;
+    let pe_offset = r.read_u32()? as usize;
+    r.set_offset(pe_offset)?;
+    if r.read_u8_array::<4>()? != *b"PE\0\0" {
+        return Err(UnityError::UnsupportFileType("not a PE image".into()));
+    }
+    let _machine = r.read_u16()?;
+    let section_count = r.read_u16()?;
+    r.set_offset(r.get_offset() + 12)?;
+    let optional_header_size = r.read_u16()? as usize;
+    let _characteristics = r.read_u16()?;
+    let optional_header = r.get_offset();
+    let magic = r.read_u16()?;
+    let data_directories = match magic {
+        0x10B => optional_header + 96,
+        0x20B => optional_header + 112,
+        _ => return Err(UnityError::InvalidValue),
+    };
+    r.set_offset(data_directories + 14 * 8)?;
+    let cli_rva = r.read_u32()?;
+    if cli_rva == 0 {
+        return Err(UnityError::UnsupportFileType("PE image without CLI header".into()));
+    }
+    r.set_offset(optional_header + optional_header_size)?;
+    let mut sections = Vec::with_capacity(section_count as usize);
+    for _ in 0..section_count {
+        let _name = r.read_u8_array::<8>()?;
+        let virtual_size = r.read_u32()?;
+        let virtual_address = r.read_u32()?;
+        let raw_size = r.read_u32()?;
+        let raw_pointer = r.read_u32()?;
+        r.set_offset(r.get_offset() + 16)?;
+        sections.push(Section {
+            virtual_address,
+            virtual_size,
+            raw_pointer,
+            raw_size,
+        });
+    }
+    r.set_offset(rva_to_offset(&sections, cli_rva)? + 8)?;
+    let metadata_rva = r.read_u32()?;
+    let metadata_size = r.read_u32()? as usize;
+    let start = rva_to_offset(&sections, metadata_rva)?;
+    data.get(start..start.checked_add(metadata_size).ok_or(UnityError::Eof)?).ok_or(UnityError::Eof)
+}
+
+fn read_metadata(root: &[u8]) -> UnityResult<Metadata<'_>> {
+    let mut r = Reader::new(root, ByteOrder::Little);
+    if r.read_u32()? != 0x424A5342 {
+        return Err(UnityError::UnsupportFileType("invalid CLI metadata signature".into()));
+    }
+    let _major = r.read_u16()?;
+    let _minor = r.read_u16()?;
+    let _reserved = r.read_u32()?;
+    let version_length = r.read_u32()? as usize;
+    r.set_offset(r.get_offset() + version_length)?;
+    let _flags = r.read_u16()?;
+    let stream_count = r.read_u16()?;
+    let mut streams = HashMap::new();
+    for _ in 0..stream_count {
+        let offset = r.read_u32()? as usize;
+        let size = r.read_u32()? as usize;
+        let name = r.read_string_util_null()?;
+        r.align(4)?;
+        streams.insert(name, root.get(offset..offset + size).ok_or(UnityError::Eof)?);
+    }
+    let strings = streams.get("#Strings").copied().unwrap_or_default();
+    let blobs = streams.get("#Blob").copied().unwrap_or_default();
+    let table_stream = streams.get("#~").or_else(|| streams.get("#-")).copied().ok_or(UnityError::InvalidValue)?;
+
+    let mut r = Reader::new(table_stream, ByteOrder::Little);
+    let _reserved = r.read_u32()?;
+    let _major = r.read_u8()?;
+    let _minor = r.read_u8()?;
+    let heap_sizes = r.read_u8()?;
+    let _reserved = r.read_u8()?;
+    let valid = r.read_u64()?;
+    let _sorted = r.read_u64()?;
+    let mut row_counts = [0usize; 64];
+    for (i, count) in row_counts.iter_mut().enumerate() {
+        if valid & (1 << i) != 0 {
+            *count = r.read_u32()? as usize;
+        }
+    }
+    if heap_sizes & 0x40 != 0 {
+        let _extra_data = r.read_u32()?;
+    }
+    if valid >> TABLE_COUNT != 0 {
+        return Err(UnityError::CustomError("unknown CLI metadata table".to_string()));
+    }
+    let string_size = if heap_sizes & 0x01 != 0 { 4 } else { 2 };
+    let guid_size = if heap_sizes & 0x02 != 0 { 4 } else { 2 };
+    let blob_size = if heap_sizes & 0x04 != 0 { 4 } else { 2 };
+    let column_size = |column: &Column| -> usize {
+        match column {
+            Column::U16 => 2,
+            Column::U32 => 4,
+            Column::Str => string_size,
+            Column::Guid => guid_size,
+            Column::Blob => blob_size,
+            Column::Index(table) => {
+                if row_counts[*table] < 1 << 16 {
+                    2
+                } else {
+                    4
+                }
+            }
+            Column::Coded(coded) => {
+                let max = coded.tables().iter().filter(|x| **x != usize::MAX).map(|x| row_counts[*x]).max().unwrap_or(0);
+                if max < 1 << (16 - coded.tag_bits()) {
+                    2
+                } else {
+                    4
+                }
+            }
+        }
+    };
+
+    let mut tables = Vec::with_capacity(TABLE_COUNT);
+    for (table, count) in row_counts.iter().enumerate().take(TABLE_COUNT) {
+        let columns = schema(table);
+        let row_size: usize = columns.iter().map(column_size).sum();
+        r.has_space(row_size * count)?;
+        let mut rows = Vec::with_capacity(*count);
+        for _ in 0..*count {
+            let mut row = Vec::with_capacity(columns.len());
+            for column in columns {
+                let value = match column_size(column) {
+                    2 => r.read_u16()? as u32,
+                    _ => r.read_u32()?,
+                };
+                row.push(value);
+            }
+            rows.push(row)
+        }
+        tables.push(rows);
+    }
+    Ok(Metadata { strings, blobs, tables })
+}
+
+struct Loader<'a> {
+    md: Metadata<'a>,
+    /// full names of every TypeDef, in table order
+    type_def_names: Vec<(String, String)>,
+}
+
+impl<'a> Loader<'a> {
+    fn new(md: Metadata<'a>) -> Self {
+        let mut enclosing = HashMap::new();
+        for row in md.rows(NESTED_CLASS) {
+            enclosing.insert((row[0] as usize).saturating_sub(1), (row[1] as usize).saturating_sub(1));
+        }
+        let type_defs = md.rows(TYPE_DEF);
+        let mut type_def_names = Vec::with_capacity(type_defs.len());
+        for (index, row) in type_defs.iter().enumerate() {
+            let mut name = md.string(row[1]);
+            let mut current = row;
+            let mut at = index;
+            let mut depth = 0;
+            // Nesting rows of malformed images can point past the TypeDef table; the chain stops there.
+            while let Some(parent) = enclosing.get(&at).and_then(|parent| Some((*parent, type_defs.get(*parent)?))) {
+                name = format!("{}/{}", md.string(parent.1[1]), name);
+                (at, current) = parent;
+                depth += 1;
+                if depth > 64 {
+                    break;
+                }
+            }
+            let namespace = md.string(current[2]);
+            type_def_names.push((namespace, name));
+        }
+        Self { md, type_def_names }
+    }
+
+    fn type_def_sig(&self, index: usize) -> TypeSig {
+        match self.type_def_names.get(index) {
+            Some((namespace, name)) => TypeSig::Named(full_name(namespace, name)),
+            None => TypeSig::Unsupported,
+        }
+    }
+
+    fn type_ref_name(&self, index: usize, depth: usize) -> Option<String> {
+        let row = self.md.row(TYPE_REF, index)?;
+        let name = self.md.string(row[1]);
+        let namespace = self.md.string(row[2]);
+        if let Some((TYPE_REF, parent)) = Coded::ResolutionScope.decode(row[0]) {
+            if depth < 64 {
+                return Some(format!("{}/{}", self.type_ref_name(parent, depth + 1)?, name));
+            }
+        }
+        Some(full_name(&namespace, &name))
+    }
+
+    fn type_def_or_ref(&self, table: usize, index: usize, depth: usize) -> TypeSig {
+        match table {
+            TYPE_DEF => self.type_def_sig(index),
+            TYPE_REF => self.type_ref_name(index, 0).map(TypeSig::Named).unwrap_or(TypeSig::Unsupported),
+            TYPE_SPEC => {
+                let Some(row) = self.md.row(TYPE_SPEC, index) else {
+                    return TypeSig::Unsupported;
+                };
+                let Ok(blob) = self.md.blob(row[0]) else {
+                    return TypeSig::Unsupported;
+                };
+                let mut r = Reader::new(blob, ByteOrder::Little);
+                self.read_type(&mut r, depth + 1).unwrap_or(TypeSig::Unsupported)
+            }
+            _ => TypeSig::Unsupported,
+        }
+    }
+
+    fn read_type_def_or_ref_encoded(&self, r: &mut Reader, depth: usize) -> UnityResult<TypeSig> {
+        let value = read_compressed_u32(r)?;
+        let table = match value & 3 {
+            0 => TYPE_DEF,
+            1 => TYPE_REF,
+            2 => TYPE_SPEC,
+            _ => return Ok(TypeSig::Unsupported),
+        };
+        match (value >> 2) as usize {
+            0 => Ok(TypeSig::Unsupported),
+            index => Ok(self.type_def_or_ref(table, index - 1, depth)),
+        }
+    }
+
+    fn read_type(&self, r: &mut Reader, depth: usize) -> UnityResult<TypeSig> {
+        if depth > 32 {
+            return Err(UnityError::InvalidValue);
+        }
+        let element_type = r.read_u8()?;
+        let sig = match element_type {
+            0x02 => TypeSig::Primitive(ElementType::Boolean),
+            0x03 => TypeSig::Primitive(ElementType::Char),
+            0x04 => TypeSig::Primitive(ElementType::I1),
+            0x05 => TypeSig::Primitive(ElementType::U1),
+            0x06 => TypeSig::Primitive(ElementType::I2),
+            0x07 => TypeSig::Primitive(ElementType::U2),
+            0x08 => TypeSig::Primitive(ElementType::I4),
+            0x09 => TypeSig::Primitive(ElementType::U4),
+            0x0A => TypeSig::Primitive(ElementType::I8),
+            0x0B => TypeSig::Primitive(ElementType::U8),
+            0x0C => TypeSig::Primitive(ElementType::R4),
+            0x0D => TypeSig::Primitive(ElementType::R8),
+            0x0E => TypeSig::Primitive(ElementType::String),
+            0x11 | 0x12 => self.read_type_def_or_ref_encoded(r, depth)?,
+            0x13 | 0x1E => TypeSig::GenericParam(read_compressed_u32(r)?),
+            0x15 => {
+                let _kind = r.read_u8()?;
+                let generic = self.read_type_def_or_ref_encoded(r, depth)?;
+                let count = read_compressed_u32(r)?;
+                let mut args = Vec::new();
+                for _ in 0..count {
+                    args.push(self.read_type(r, depth + 1)?);
+                }
+                TypeSig::GenericInst(Box::new(generic), args)
+            }
+            0x1C => TypeSig::Primitive(ElementType::Object),
+            0x1D => TypeSig::SzArray(Box::new(self.read_type(r, depth + 1)?)),
+            0x1F | 0x20 => {
+                self.read_type_def_or_ref_encoded(r, depth)?;
+                return self.read_type(r, depth + 1);
+            }
+            _ => TypeSig::Unsupported,
+        };
+        Ok(sig)
+    }
+
+    fn field_sig(&self, blob: u32) -> TypeSig {
+        let Ok(blob) = self.md.blob(blob) else {
+            return TypeSig::Unsupported;
+        };
+        let mut r = Reader::new(blob, ByteOrder::Little);
+        match r.read_u8() {
+            Ok(0x06) => self.read_type(&mut r, 0).unwrap_or(TypeSig::Unsupported),
+            _ => TypeSig::Unsupported,
+        }
+    }
+
+    /// Resolves the owning TypeDef of a method row through the method lists.
+    fn method_owner(&self, method: usize) -> Option<usize> {
+        let type_defs = self.md.rows(TYPE_DEF);
+        let position = type_defs.partition_point(|row| (row[5] as usize).saturating_sub(1) <= method);
+        position.checked_sub(1)
+    }
+
+    fn attribute_type(&self, value: u32) -> Option<String> {
+        match Coded::CustomAttributeType.decode(value)? {
+            (METHOD_DEF, index) => {
+                let owner = self.method_owner(index)?;
+                let (namespace, name) = self.type_def_names.get(owner)?;
+                Some(full_name(namespace, name))
+            }
+            (MEMBER_REF, index) => {
+                let row = self.md.row(MEMBER_REF, index)?;
+                match Coded::MemberRefParent.decode(row[0])? {
+                    (table @ (TYPE_DEF | TYPE_REF | TYPE_SPEC), index) => match self.type_def_or_ref(table, index, 0) {
+                        TypeSig::Named(name) => Some(name),
+                        TypeSig::GenericInst(generic, _) => match *generic {
+                            TypeSig::Named(name) => Some(name),
+                            _ => None,
+                        },
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn field_index(&self, index: usize) -> usize {
+        match self.md.rows(FIELD_PTR).get(index) {
+            Some(row) => (row[0] as usize).saturating_sub(1),
+            None => index,
+        }
+    }
+
+    fn load(&self) -> UnityResult<ManagedAssembly> {
+        let name = match self.md.rows(ASSEMBLY).first() {
+            Some(row) => self.md.string(row[7]),
+            None => String::new(),
+        };
+        let mut field_attributes: HashMap<usize, Vec<String>> = HashMap::new();
+        let mut type_attributes: HashMap<usize, Vec<String>> = HashMap::new();
+        for row in self.md.rows(CUSTOM_ATTRIBUTE) {
+            let Some(attribute) = self.attribute_type(row[1]) else {
+                continue;
+            };
+            match Coded::HasCustomAttribute.decode(row[0]) {
+                Some((FIELD, index)) => field_attributes.entry(index).or_default().push(attribute),
+                Some((TYPE_DEF, index)) => type_attributes.entry(index).or_default().push(attribute),
+                _ => {}
+            }
+        }
+        let mut generic_param_counts: HashMap<usize, usize> = HashMap::new();
+        for row in self.md.rows(GENERIC_PARAM) {
+            if let Some((TYPE_DEF, index)) = Coded::TypeOrMethodDef.decode(row[2]) {
+                *generic_param_counts.entry(index).or_default() += 1;
+            }
+        }
+        let type_defs = self.md.rows(TYPE_DEF);
+        let field_count = self.md.rows(FIELD_PTR).len().max(self.md.rows(FIELD).len());
+        let mut types = Vec::with_capacity(type_defs.len());
+        for (index, row) in type_defs.iter().enumerate() {
+            let (namespace, type_name) = self.type_def_names[index].clone();
+            let base = Coded::TypeDefOrRef.decode(row[3]).map(|(table, index)| self.type_def_or_ref(table, index, 0));
+            let field_start = (row[4] as usize).saturating_sub(1);
+            let field_end = match type_defs.get(index + 1) {
+                Some(next) => (next[4] as usize).saturating_sub(1),
+                None => field_count,
+            }
+            .min(field_count);
+            let mut fields = Vec::new();
+            for i in field_start..field_end.max(field_start) {
+                let field = self.field_index(i);
+                let Some(field_row) = self.md.row(FIELD, field) else {
+                    continue;
+                };
+                fields.push(ManagedField {
+                    name: self.md.string(field_row[1]),
+                    flags: field_row[0] as u16,
+                    ty: self.field_sig(field_row[2]),
+                    attributes: field_attributes.remove(&field).unwrap_or_default(),
+                });
+            }
+            types.push(ManagedType {
+                namespace,
+                name: type_name,
+                flags: row[0],
+                base,
+                generic_param_count: generic_param_counts.get(&index).copied().unwrap_or(0),
+                fields,
+                attributes: type_attributes.remove(&index).unwrap_or_default(),
+            });
+        }
+        Ok(ManagedAssembly { name, types })
+    }
+}
+
+fn full_name(namespace: &str, name: &str) -> String {
+    if namespace.is_empty() {
+        name.to_string()
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+/// Reads the type definitions of a managed (Mono) assembly image.
+pub fn read_assembly(data: &[u8]) -> UnityResult<ManagedAssembly> {
+    let root = metadata_root(data)?;
+    let md = read_metadata(root)?;
+    Loader::new(md).load()
+}
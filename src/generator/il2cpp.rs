@@ -0,0 +1,856 @@
+use super::{ElementType, ManagedAssembly, ManagedField, ManagedType, TypeSig};
+use crate::error::{UnityError, UnityResult};
+use crate::reader::{ByteOrder, Reader};
+use std::collections::HashMap;
+
+const METADATA_SANITY: u32 = 0xFAB11BAF;
+
+/// Metadata versions scaled by ten so that sub-versions such as 24.1 compare as integers.
+type Version = u32;
+
+fn unsupported(message: &str) -> UnityError {
+    UnityError::UnsupportFileType(message.to_string())
+}
+
+#[derive(Default)]
+struct Header {
+    string_offset: usize,
+    methods_offset: usize,
+    methods_size: usize,
+    fields_offset: usize,
+    fields_size: usize,
+    generic_parameters_offset: usize,
+    generic_containers_offset: usize,
+    type_definitions_offset: usize,
+    type_definitions_size: usize,
+    images_offset: usize,
+    images_size: usize,
+    attributes_info_offset: usize,
+    attributes_info_size: usize,
+    attribute_types_offset: usize,
+    attribute_types_size: usize,
+    attribute_data_offset: usize,
+    attribute_data_size: usize,
+    attribute_data_range_offset: usize,
+    attribute_data_range_size: usize,
+}
+
+struct TypeDefinition {
+    name_index: u32,
+    namespace_index: u32,
+    declaring_type_index: i32,
+    parent_index: i32,
+    generic_container_index: i32,
+    flags: u32,
+    field_start: i32,
+    field_count: u16,
+}
+
+struct FieldDefinition {
+    name_index: u32,
+    type_index: i32,
+    custom_attribute_index: i32,
+    token: u32,
+}
+
+struct ImageDefinition {
+    name_index: u32,
+    type_start: i32,
+    type_count: u32,
+    token: u32,
+    custom_attribute_start: i32,
+    custom_attribute_count: u32,
+}
+
+struct Metadata<'a> {
+    data: &'a [u8],
+    version: Version,
+    header: Header,
+    type_definitions: Vec<TypeDefinition>,
+    fields: Vec<FieldDefinition>,
+    images: Vec<ImageDefinition>,
+}
+
+impl<'a> Metadata<'a> {
+    fn new(data: &'a [u8]) -> UnityResult<Self> {
+        let mut r = Reader::new(data, ByteOrder::Little);
+        if r.read_u32()? != METADATA_SANITY {
+            return Err(unsupported("invalid global-metadata.dat signature"));
+        }
+        let major = r.read_i32()?;
+        if !(21..=31).contains(&major) {
+            return Err(unsupported("unsupported il2cpp metadata version"));
+        }
+        let mut version = major as u32 * 10;
+        let string_literal_offset = r.read_u32()?;
+        if major == 24 && string_literal_offset == 264 {
+            version = 242;
+        }
+        let mut ret = Self {
+            data,
+            version,
+            header: Header::default(),
+            type_definitions: Vec::new(),
+            fields: Vec::new(),
+            images: Vec::new(),
+        };
+        ret.read_header()?;
+        if major == 24 && version == 240 {
+            ret.images = ret.read_images()?;
+            if ret.images.iter().any(|x| x.token != 1) {
+                ret.version = 241;
+            }
+        }
+        ret.images = ret.read_images()?;
+        ret.type_definitions = ret.read_type_definitions()?;
+        ret.fields = ret.read_fields()?;
+        Ok(ret)
+    }
+
+    fn read_header(&mut self) -> UnityResult<()> {
+        let v = self.version;
+        let mut r = Reader::new(self.data, ByteOrder::Little);
+        r.set_offset(8)?;
+        let mut pair = || -> UnityResult<(usize, usize)> { Ok((r.read_u32()? as usize, r.read_i32()?.max(0) as usize)) };
+        let h = &mut self.header;
+        let _string_literal = pair()?;
+        let _string_literal_data = pair()?;
+        h.string_offset = pair()?.0;
+        let _events = pair()?;
+        let _properties = pair()?;
+        (h.methods_offset, h.methods_size) = pair()?;
+        let _parameter_default_values = pair()?;
+        let _field_default_values = pair()?;
+        let _field_and_parameter_default_value_data = pair()?;
+        let _field_marshaled_sizes = pair()?;
+        let _parameters = pair()?;
+        (h.fields_offset, h.fields_size) = pair()?;
+        h.generic_parameters_offset = pair()?.0;
+        let _generic_parameter_constraints = pair()?;
+        h.generic_containers_offset = pair()?.0;
+        let _nested_types = pair()?;
+        let _interfaces = pair()?;
+        let _vtable_methods = pair()?;
+        let _interface_offsets = pair()?;
+        (h.type_definitions_offset, h.type_definitions_size) = pair()?;
+        if v <= 241 {
+            let _rgctx_entries = pair()?;
+        }
+        (h.images_offset, h.images_size) = pair()?;
+        let _assemblies = pair()?;
+        if v <= 245 {
+            let _metadata_usage_lists = pair()?;
+            let _metadata_usage_pairs = pair()?;
+        }
+        let _field_refs = pair()?;
+        if v >= 200 {
+            let _referenced_assemblies = pair()?;
+        }
+        if v <= 272 {
+            (h.attributes_info_offset, h.attributes_info_size) = pair()?;
+            (h.attribute_types_offset, h.attribute_types_size) = pair()?;
+        } else {
+            (h.attribute_data_offset, h.attribute_data_size) = pair()?;
+            (h.attribute_data_range_offset, h.attribute_data_range_size) = pair()?;
+        }
+        Ok(())
+    }
+
+    fn reader_at(&self, offset: usize) -> UnityResult<Reader<'a>> {
+        let mut r = Reader::new(self.data, ByteOrder::Little);
+        r.set_offset(offset)?;
+        Ok(r)
+    }
+
+    fn read_images(&self) -> UnityResult<Vec<ImageDefinition>> {
+        let v = self.version;
+        let size = 4 * (4 + if v >= 240 { 3 } else { 0 } + 1 + if v >= 241 { 2 } else { 0 });
+        let mut r = self.reader_at(self.header.images_offset)?;
+        let count = self.header.images_size / size;
+        r.has_space(count * size)?;
+        let mut result = Vec::with_capacity(count);
+        for _ in 0..count {
+            let name_index = r.read_u32()?;
+            let _assembly_index = r.read_i32()?;
+            let type_start = r.read_i32()?;
+            let type_count = r.read_u32()?;
+            if v >= 240 {
+                let _exported_type_start = r.read_i32()?;
+                let _exported_type_count = r.read_u32()?;
+                let _entry_point_index = r.read_i32()?;
+            }
+            let token = r.read_u32()?;
+            let (custom_attribute_start, custom_attribute_count) = if v >= 241 { (r.read_i32()?, r.read_u32()?) } else { (0, 0) };
+            result.push(ImageDefinition {
+                name_index,
+                type_start,
+                type_count,
+                token,
+                custom_attribute_start,
+                custom_attribute_count,
+            })
+        }
+        Ok(result)
+    }
+
+    fn read_type_definitions(&self) -> UnityResult<Vec<TypeDefinition>> {
+        let v = self.version;
+        let mut ints = 2 + 1 + 3 + 1 + 1 + 8;
+        if v <= 240 {
+            ints += 1;
+        }
+        if v <= 245 {
+            ints += 1;
+        }
+        if v <= 241 {
+            ints += 2;
+        }
+        if v <= 220 {
+            ints += 2;
+        }
+        if (210..=220).contains(&v) {
+            ints += 2;
+        }
+        let size = ints * 4 + 8 * 2 + 4 + 4;
+        let count = self.header.type_definitions_size / size;
+        let mut r = self.reader_at(self.header.type_definitions_offset)?;
+        r.has_space(count * size)?;
+        let mut result = Vec::with_capacity(count);
+        for _ in 0..count {
+            let start = r.get_offset();
+            let name_index = r.read_u32()?;
+            let namespace_index = r.read_u32()?;
+            if v <= 240 {
+                let _custom_attribute_index = r.read_i32()?;
+            }
+            let _byval_type_index = r.read_i32()?;
+            if v <= 245 {
+                let _byref_type_index = r.read_i32()?;
+            }
+            let declaring_type_index = r.read_i32()?;
+            let parent_index = r.read_i32()?;
+            let _element_type_index = r.read_i32()?;
+            if v <= 241 {
+                let _rgctx_start_index = r.read_i32()?;
+                let _rgctx_count = r.read_i32()?;
+            }
+            let generic_container_index = r.read_i32()?;
+            if v <= 220 {
+                let _delegate_wrapper_index = r.read_i32()?;
+                let _marshaling_functions_index = r.read_i32()?;
+            }
+            if (210..=220).contains(&v) {
+                let _ccw_function_index = r.read_i32()?;
+                let _guid_index = r.read_i32()?;
+            }
+            let flags = r.read_u32()?;
+            let field_start = r.read_i32()?;
+            let _method_start = r.read_i32()?;
+            r.set_offset(r.get_offset() + 6 * 4)?;
+            let _method_count = r.read_u16()?;
+            let _property_count = r.read_u16()?;
+            let field_count = r.read_u16()?;
+            r.set_offset(start + size)?;
+            result.push(TypeDefinition {
+                name_index,
+                namespace_index,
+                declaring_type_index,
+                parent_index,
+                generic_container_index,
+                flags,
+                field_start,
+                field_count,
+            })
+        }
+        Ok(result)
+    }
+
+    fn read_fields(&self) -> UnityResult<Vec<FieldDefinition>> {
+        let v = self.version;
+        let size = if v <= 240 { 16 } else { 12 };
+        let count = self.header.fields_size / size;
+        let mut r = self.reader_at(self.header.fields_offset)?;
+        r.has_space(count * size)?;
+        let mut result = Vec::with_capacity(count);
+        for _ in 0..count {
+            let name_index = r.read_u32()?;
+            let type_index = r.read_i32()?;
+            let custom_attribute_index = if v <= 240 { r.read_i32()? } else { -1 };
+            let token = r.read_u32()?;
+            result.push(FieldDefinition {
+                name_index,
+                type_index,
+                custom_attribute_index,
+                token,
+            })
+        }
+        Ok(result)
+    }
+
+    fn string(&self, index: u32) -> String {
+        let start = self.header.string_offset + index as usize;
+        let Some(s) = self.data.get(start..) else {
+            return String::new();
+        };
+        let end = s.iter().position(|x| *x == 0).unwrap_or(s.len());
+        String::from_utf8_lossy(&s[..end]).to_string()
+    }
+
+    fn generic_parameter_num(&self, index: i64) -> Option<u32> {
+        if index < 0 {
+            return None;
+        }
+        let mut r = self.reader_at(self.header.generic_parameters_offset + index as usize * 16 + 12).ok()?;
+        r.read_u16().ok().map(|x| x as u32)
+    }
+
+    fn generic_param_count(&self, container_index: i32) -> usize {
+        if container_index < 0 {
+            return 0;
+        }
+        let Ok(mut r) = self.reader_at(self.header.generic_containers_offset + container_index as usize * 16 + 4) else {
+            return 0;
+        };
+        r.read_i32().map(|x| x.max(0) as usize).unwrap_or(0)
+    }
+
+    fn method_declaring_type(&self, method_index: u32) -> Option<usize> {
+        let v = self.version;
+        let size = match v {
+            ..=240 => 56,
+            241 => 52,
+            310.. => 36,
+            _ => 32,
+        };
+        if method_index as usize >= self.header.methods_size / size {
+            return None;
+        }
+        let mut r = self.reader_at(self.header.methods_offset + method_index as usize * size + 4).ok()?;
+        let declaring_type = r.read_i32().ok()?;
+        (declaring_type >= 0).then_some(declaring_type as usize)
+    }
+
+    /// Attribute ranges before metadata 24.1 are referenced directly from each definition.
+    fn attribute_range(&self, index: usize, with_token: bool) -> Option<(u32, usize, usize)> {
+        let size = if with_token { 12 } else { 8 };
+        if index >= self.header.attributes_info_size / size {
+            return None;
+        }
+        let mut r = self.reader_at(self.header.attributes_info_offset + index * size).ok()?;
+        let token = if with_token { r.read_u32().ok()? } else { 0 };
+        let start = r.read_i32().ok()?.max(0) as usize;
+        let count = r.read_i32().ok()?.max(0) as usize;
+        Some((token, start, count))
+    }
+
+    fn attribute_type_indices(&self, start: usize, count: usize) -> Vec<i32> {
+        let mut result = Vec::new();
+        for i in start..start + count {
+            if i >= self.header.attribute_types_size / 4 {
+                break;
+            }
+            let Ok(mut r) = self.reader_at(self.header.attribute_types_offset + i * 4) else {
+                break;
+            };
+            match r.read_i32() {
+                Ok(x) => result.push(x),
+                Err(_) => break,
+            }
+        }
+        result
+    }
+
+    /// Decodes the attribute constructors stored in the 29+ attribute data blob.
+    fn attribute_ctor_types(&self, start: usize, end: usize) -> Vec<usize> {
+        let mut result = Vec::new();
+        let Some(blob) = self.data.get(self.header.attribute_data_offset + start..self.header.attribute_data_offset + end) else {
+            return result;
+        };
+        let mut r = Reader::new(blob, ByteOrder::Little);
+        let Ok(count) = read_compressed_u32(&mut r) else {
+            return result;
+        };
+        for _ in 0..count.min(blob.len() as u32 / 4) {
+            let Ok(method) = r.read_u32() else {
+                break;
+            };
+            if let Some(ty) = self.method_declaring_type(method) {
+                result.push(ty);
+            }
+        }
+        result
+    }
+}
+
+fn read_compressed_u32(r: &mut Reader) -> UnityResult<u32> {
+    let b = r.read_u8()? as u32;
+    Ok(match b {
+        _ if b & 0x80 == 0 => b,
+        _ if b & 0xC0 == 0x80 => ((b & !0x80) << 8) | r.read_u8()? as u32,
+        _ if b & 0xE0 == 0xC0 => ((b & !0xC0) << 24) | (r.read_u8()? as u32) << 16 | (r.read_u8()? as u32) << 8 | r.read_u8()? as u32,
+        0xF0 => r.read_u32()?,
+        0xFE => u32::MAX - 1,
+        0xFF => u32::MAX,
+        _ => return Err(UnityError::InvalidValue),
+    })
+}
+
+struct Segment {
+    address: u64,
+    size: u64,
+    offset: usize,
+    file_size: usize,
+}
+
+/// A loaded `libil2cpp.so` or `GameAssembly.dll` image with relocations applied.
+struct Binary {
+    data: Vec<u8>,
+    is_64: bool,
+    segments: Vec<Segment>,
+}
+
+impl Binary {
+    fn new(data: &[u8]) -> UnityResult<Self> {
+        match data.get(..4) {
+            Some(b"\x7fELF") => Self::from_elf(data),
+            Some([b'M', b'Z', ..]) => Self::from_pe(data),
+            _ => Err(unsupported("il2cpp binary must be an ELF or PE image")),
+        }
+    }
+
+    fn from_elf(data: &[u8]) -> UnityResult<Self> {
+        let mut r = Reader::new(data, ByteOrder::Little);
+        r.set_offset(4)?;
+        let is_64 = match r.read_u8()? {
+            1 => false,
+            2 => true,
+            _ => return Err(UnityError::InvalidValue),
+        };
+        if r.read_u8()? != 1 {
+            return Err(unsupported("big endian il2cpp binaries are not supported"));
+        }
+        let (ph_offset, ph_size, ph_count) = if is_64 {
+            r.set_offset(0x20)?;
+            let ph_offset = r.read_u64()? as usize;
+            r.set_offset(0x36)?;
+            (ph_offset, r.read_u16()? as usize, r.read_u16()? as usize)
+        } else {
+            r.set_offset(0x1C)?;
+            let ph_offset = r.read_u32()? as usize;
+            r.set_offset(0x2A)?;
+            (ph_offset, r.read_u16()? as usize, r.read_u16()? as usize)
+        };
+        let mut segments = Vec::new();
+        let mut dynamic = None;
+        for i in 0..ph_count {
+            r.set_offset(ph_offset.saturating_add(i * ph_size))?;
+            let p_type = r.read_u32()?;
+            let (offset, address, file_size, size) = if is_64 {
+                let _flags = r.read_u32()?;
+                let offset = r.read_u64()?;
+                let address = r.read_u64()?;
+                let _physical = r.read_u64()?;
+                (offset, address, r.read_u64()?, r.read_u64()?)
+            } else {
+                let offset = r.read_u32()? as u64;
+                let address = r.read_u32()? as u64;
+                let _physical = r.read_u32()?;
+                (offset, address, r.read_u32()? as u64, r.read_u32()? as u64)
+            };
+            match p_type {
+                1 => segments.push(Segment {
+                    address,
+                    size,
+                    offset: offset as usize,
+                    file_size: file_size as usize,
+                }),
+                2 => dynamic = Some((offset as usize, file_size as usize)),
+                _ => {}
+            }
+        }
+        let mut ret = Self { data: data.to_vec(), is_64, segments };
+        if let (Some((offset, size)), true) = (dynamic, is_64) {
+            ret.apply_rela(offset, size)?;
+        }
+        Ok(ret)
+    }
+
+    /// Writes the addends of relative relocations, which is where arm64/x86_64 builds keep their data pointers.
+    fn apply_rela(&mut self, dynamic_offset: usize, dynamic_size: usize) -> UnityResult<()> {
+        let mut r = Reader::new(&self.data, ByteOrder::Little);
+        r.set_offset(dynamic_offset)?;
+        let (mut rela, mut rela_size) = (None, 0);
+        for _ in 0..dynamic_size / 16 {
+            let tag = r.read_i64()?;
+            let value = r.read_u64()?;
+            match tag {
+                0 => break,
+                7 => rela = Some(value),
+                8 => rela_size = value as usize,
+                _ => {}
+            }
+        }
+        let Some(rela) = rela.and_then(|x| self.map(x)) else {
+            return Ok(());
+        };
+        let mut patches = Vec::new();
+        r.set_offset(rela)?;
+        for _ in 0..rela_size / 24 {
+            let offset = r.read_u64()?;
+            let info = r.read_u64()?;
+            let addend = r.read_i64()?;
+            if matches!(info & 0xFFFF_FFFF, 1027 | 8) {
+                if let Some(target) = self.map(offset) {
+                    patches.push((target, addend as u64));
+                }
+            }
+        }
+        drop(r);
+        for (target, value) in patches {
+            if let Some(slot) = self.data.get_mut(target..target + 8) {
+                slot.copy_from_slice(&value.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+
+    fn from_pe(data: &[u8]) -> UnityResult<Self> {
+        let mut r = Reader::new(data, ByteOrder::Little);
+        r.set_offset(0x3C)?;
+        let pe_offset = r.read_u32()? as usize;
+        r.set_offset(pe_offset)?;
+        if r.read_u8_array::<4>()? != *b"PE\0\0" {
+            return Err(unsupported("not a PE image"));
+        }
+        let _machine = r.read_u16()?;
+        let section_count = r.read_u16()?;
+        r.set_offset(r.get_offset() + 12)?;
+        let optional_header_size = r.read_u16()? as usize;
+        let _characteristics = r.read_u16()?;
+        let optional_header = r.get_offset();
+        let is_64 = match r.read_u16()? {
+            0x10B => false,
+            0x20B => true,
+            _ => return Err(UnityError::InvalidValue),
+        };
+        let image_base = if is_64 {
+            r.set_offset(optional_header + 24)?;
+            r.read_u64()?
+        } else {
+            r.set_offset(optional_header + 28)?;
+            r.read_u32()? as u64
+        };
+        r.set_offset(optional_header + optional_header_size)?;
+        let mut segments = Vec::with_capacity(section_count as usize);
+        for _ in 0..section_count {
+            let _name = r.read_u8_array::<8>()?;
+            let virtual_size = r.read_u32()? as u64;
+            let virtual_address = r.read_u32()? as u64;
+            let raw_size = r.read_u32()? as usize;
+            let raw_pointer = r.read_u32()? as usize;
+            r.set_offset(r.get_offset() + 16)?;
+            segments.push(Segment {
+                address: image_base.checked_add(virtual_address).ok_or(UnityError::InvalidValue)?,
+                size: virtual_size.max(raw_size as u64),
+                offset: raw_pointer,
+                file_size: raw_size,
+            })
+        }
+        Ok(Self { data: data.to_vec(), is_64, segments })
+    }
+
+    fn pointer_size(&self) -> usize {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn map(&self, address: u64) -> Option<usize> {
+        self.segments.iter().find_map(|s| {
+            // Segment headers are not trusted to stay within the address space.
+            let delta = address.checked_sub(s.address).filter(|delta| *delta < s.size)? as usize;
+            (delta < s.file_size).then(|| s.offset.checked_add(delta)).flatten()
+        })
+    }
+
+    fn read_ptr_at(&self, offset: usize) -> Option<u64> {
+        if self.is_64 {
+            self.data.get(offset..offset + 8).map(|x| u64::from_le_bytes(x.try_into().unwrap_or_default()))
+        } else {
+            self.data.get(offset..offset + 4).map(|x| u32::from_le_bytes(x.try_into().unwrap_or_default()) as u64)
+        }
+    }
+
+    fn read_ptr(&self, address: u64) -> Option<u64> {
+        self.read_ptr_at(self.map(address)?)
+    }
+
+    /// Finds `Il2CppMetadataRegistration` from its `fieldOffsetsCount` and `typeDefinitionsSizesCount`,
+    /// which both equal the number of type definitions.
+    fn find_metadata_registration(&self, type_definition_count: u64) -> Option<u64> {
+        let ptr = self.pointer_size();
+        for segment in &self.segments {
+            let end = segment.offset.saturating_add(segment.file_size).min(self.data.len());
+            let mut offset = segment.offset;
+            while offset + 3 * ptr <= end {
+                if self.read_ptr_at(offset) == Some(type_definition_count) && self.read_ptr_at(offset + 2 * ptr) == Some(type_definition_count) {
+                    let field_offsets = self.read_ptr_at(offset + ptr)?;
+                    if self.map(field_offsets).is_some() {
+                        let address = segment.address.checked_add((offset - segment.offset) as u64);
+                        if let Some(start) = address.and_then(|x| x.checked_sub(10 * ptr as u64)) {
+                            let types_count = self.read_ptr(start + 6 * ptr as u64).unwrap_or(0);
+                            let types = self.read_ptr(start + 7 * ptr as u64).unwrap_or(0);
+                            if types_count > 0 && types_count < 1 << 24 && self.map(types).is_some() {
+                                return Some(start);
+                            }
+                        }
+                    }
+                }
+                offset += ptr;
+            }
+        }
+        None
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Il2CppType {
+    data: u64,
+    attrs: u16,
+    type_: u8,
+}
+
+struct Loader<'a> {
+    metadata: Metadata<'a>,
+    binary: Binary,
+    types: Vec<u64>,
+    type_names: Vec<String>,
+}
+
+impl<'a> Loader<'a> {
+    fn new(metadata: Metadata<'a>, binary: Binary) -> UnityResult<Self> {
+        let registration = binary
+            .find_metadata_registration(metadata.type_definitions.len() as u64)
+            .ok_or_else(|| unsupported("can not find Il2CppMetadataRegistration in il2cpp binary"))?;
+        let ptr = binary.pointer_size() as u64;
+        let types_count = binary.read_ptr(registration + 6 * ptr).ok_or(UnityError::Eof)? as usize;
+        let types_address = binary.read_ptr(registration + 7 * ptr).ok_or(UnityError::Eof)?;
+        let mut types = Vec::with_capacity(types_count);
+        for i in 0..types_count {
+            let address = types_address.checked_add(i as u64 * ptr).ok_or(UnityError::Eof)?;
+            types.push(binary.read_ptr(address).ok_or(UnityError::Eof)?);
+        }
+        let mut ret = Self {
+            metadata,
+            binary,
+            types,
+            type_names: Vec::new(),
+        };
+        ret.type_names = (0..ret.metadata.type_definitions.len()).map(|i| ret.type_definition_name(i, 0)).collect();
+        Ok(ret)
+    }
+
+    fn read_type(&self, address: u64) -> Option<Il2CppType> {
+        let offset = self.binary.map(address)?;
+        let data = self.binary.read_ptr_at(offset)?;
+        let bits = self.binary.data.get(offset + self.binary.pointer_size()..offset + self.binary.pointer_size() + 4)?;
+        let bits = u32::from_le_bytes(bits.try_into().ok()?);
+        Some(Il2CppType {
+            data,
+            attrs: (bits & 0xFFFF) as u16,
+            type_: ((bits >> 16) & 0xFF) as u8,
+        })
+    }
+
+    fn type_at(&self, index: i32) -> Option<Il2CppType> {
+        let address = *self.types.get(usize::try_from(index).ok()?)?;
+        self.read_type(address)
+    }
+
+    fn type_definition_name(&self, index: usize, depth: usize) -> String {
+        let Some(definition) = self.metadata.type_definitions.get(index) else {
+            return String::new();
+        };
+        let name = self.metadata.string(definition.name_index);
+        if definition.declaring_type_index >= 0 && depth < 32 {
+            if let Some(declaring) = self.type_at(definition.declaring_type_index) {
+                return format!("{}/{}", self.type_definition_name(declaring.data as usize, depth + 1), name);
+            }
+        }
+        let namespace = self.metadata.string(definition.namespace_index);
+        if namespace.is_empty() {
+            name
+        } else {
+            format!("{namespace}.{name}")
+        }
+    }
+
+    fn named(&self, index: u64) -> TypeSig {
+        match self.type_names.get(index as usize) {
+            Some(name) => TypeSig::Named(name.clone()),
+            None => TypeSig::Unsupported,
+        }
+    }
+
+    fn sig(&self, ty: Il2CppType, depth: usize) -> TypeSig {
+        if depth > 32 {
+            return TypeSig::Unsupported;
+        }
+        match ty.type_ {
+            0x02 => TypeSig::Primitive(ElementType::Boolean),
+            0x03 => TypeSig::Primitive(ElementType::Char),
+            0x04 => TypeSig::Primitive(ElementType::I1),
+            0x05 => TypeSig::Primitive(ElementType::U1),
+            0x06 => TypeSig::Primitive(ElementType::I2),
+            0x07 => TypeSig::Primitive(ElementType::U2),
+            0x08 => TypeSig::Primitive(ElementType::I4),
+            0x09 => TypeSig::Primitive(ElementType::U4),
+            0x0A => TypeSig::Primitive(ElementType::I8),
+            0x0B => TypeSig::Primitive(ElementType::U8),
+            0x0C => TypeSig::Primitive(ElementType::R4),
+            0x0D => TypeSig::Primitive(ElementType::R8),
+            0x0E => TypeSig::Primitive(ElementType::String),
+            0x1C => TypeSig::Primitive(ElementType::Object),
+            0x11 | 0x12 => self.named(ty.data & 0xFFFF_FFFF),
+            0x13 | 0x1E => match self.metadata.generic_parameter_num(ty.data as i32 as i64) {
+                Some(num) => TypeSig::GenericParam(num),
+                None => TypeSig::Unsupported,
+            },
+            0x1D => match self.read_type(ty.data) {
+                Some(element) => TypeSig::SzArray(Box::new(self.sig(element, depth + 1))),
+                None => TypeSig::Unsupported,
+            },
+            0x15 => self.generic_class(ty.data, depth).unwrap_or(TypeSig::Unsupported),
+            _ => TypeSig::Unsupported,
+        }
+    }
+
+    fn generic_class(&self, address: u64, depth: usize) -> Option<TypeSig> {
+        let ptr = self.binary.pointer_size() as u64;
+        let first = self.binary.read_ptr(address)?;
+        let generic = if self.metadata.version >= 270 { self.sig(self.read_type(first)?, depth + 1) } else { self.named(first & 0xFFFF_FFFF) };
+        let class_inst = self.binary.read_ptr(address.checked_add(ptr)?)?;
+        let argc = self.binary.read_ptr(class_inst)?;
+        let argv = self.binary.read_ptr(class_inst.checked_add(ptr)?)?;
+        let mut args = Vec::new();
+        for i in 0..argc.min(32) {
+            let arg = self.binary.read_ptr(argv.checked_add(i * ptr)?)?;
+            args.push(self.sig(self.read_type(arg)?, depth + 1));
+        }
+        Some(TypeSig::GenericInst(Box::new(generic), args))
+    }
+
+    fn attribute_names_by_token(&self, image: &ImageDefinition) -> HashMap<u32, Vec<String>> {
+        let mut result: HashMap<u32, Vec<String>> = HashMap::new();
+        let start = image.custom_attribute_start.max(0) as usize;
+        let count = image.custom_attribute_count as usize;
+        if self.metadata.version >= 290 {
+            let ranges = self.metadata.header.attribute_data_range_size / 8;
+            let read_range = |i: usize| -> Option<(u32, usize)> {
+                let mut r = self.metadata.reader_at(self.metadata.header.attribute_data_range_offset + i * 8).ok()?;
+                Some((r.read_u32().ok()?, r.read_u32().ok()? as usize))
+            };
+            for i in start..(start + count).min(ranges) {
+                let Some((token, begin)) = read_range(i) else {
+                    continue;
+                };
+                let end = read_range(i + 1).map(|x| x.1).unwrap_or(self.metadata.header.attribute_data_size);
+                let names = self.metadata.attribute_ctor_types(begin, end).into_iter().filter_map(|x| self.type_names.get(x).cloned());
+                result.entry(token).or_default().extend(names);
+            }
+        } else {
+            for i in start..start + count {
+                let Some((token, type_start, type_count)) = self.metadata.attribute_range(i, true) else {
+                    continue;
+                };
+                result.entry(token).or_default().extend(self.attribute_types(type_start, type_count));
+            }
+        }
+        result
+    }
+
+    fn attribute_types(&self, start: usize, count: usize) -> Vec<String> {
+        self.metadata
+            .attribute_type_indices(start, count)
+            .into_iter()
+            .filter_map(|x| match self.sig(self.type_at(x)?, 0) {
+                TypeSig::Named(name) => Some(name),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn load(&self) -> Vec<ManagedAssembly> {
+        let mut assemblies = Vec::with_capacity(self.metadata.images.len());
+        for image in &self.metadata.images {
+            let name = self.metadata.string(image.name_index);
+            let attributes = self.attribute_names_by_token(image);
+            let mut types = Vec::with_capacity(image.type_count as usize);
+            let type_start = image.type_start.max(0) as usize;
+            for index in type_start..type_start + image.type_count as usize {
+                let Some(definition) = self.metadata.type_definitions.get(index) else {
+                    break;
+                };
+                let full_name = &self.type_names[index];
+                let (namespace, type_name) = match full_name.split_once('/') {
+                    Some((outer, _)) => match outer.rsplit_once('.') {
+                        Some((namespace, _)) => (namespace.to_string(), full_name[namespace.len() + 1..].to_string()),
+                        None => (String::new(), full_name.clone()),
+                    },
+                    None => match full_name.rsplit_once('.') {
+                        Some((namespace, name)) => (namespace.to_string(), name.to_string()),
+                        None => (String::new(), full_name.clone()),
+                    },
+                };
+                let base = if definition.parent_index >= 0 { self.type_at(definition.parent_index).map(|x| self.sig(x, 0)) } else { None };
+                let mut fields = Vec::with_capacity(definition.field_count as usize);
+                let field_start = definition.field_start.max(0) as usize;
+                for field_index in field_start..field_start + definition.field_count as usize {
+                    let Some(field) = self.metadata.fields.get(field_index) else {
+                        break;
+                    };
+                    let Some(ty) = self.type_at(field.type_index) else {
+                        continue;
+                    };
+                    let field_attributes = if self.metadata.version <= 240 {
+                        match self.metadata.attribute_range(field.custom_attribute_index.max(0) as usize, false) {
+                            Some((_, start, count)) if field.custom_attribute_index >= 0 => self.attribute_types(start, count),
+                            _ => Vec::new(),
+                        }
+                    } else {
+                        attributes.get(&field.token).cloned().unwrap_or_default()
+                    };
+                    fields.push(ManagedField {
+                        name: self.metadata.string(field.name_index),
+                        flags: ty.attrs,
+                        ty: self.sig(ty, 0),
+                        attributes: field_attributes,
+                    })
+                }
+                types.push(ManagedType {
+                    namespace,
+                    name: type_name,
+                    flags: definition.flags,
+                    base,
+                    generic_param_count: self.metadata.generic_param_count(definition.generic_container_index),
+                    fields,
+                    attributes: Vec::new(),
+                })
+            }
+            assemblies.push(ManagedAssembly {
+                name: name.strip_suffix(".dll").unwrap_or(&name).to_string(),
+                types,
+            })
+        }
+        assemblies
+    }
+}
+
+/// Recovers the managed type definitions of an IL2CPP build from `global-metadata.dat` and its native binary.
+pub fn read_assemblies(metadata: &[u8], binary: &[u8]) -> UnityResult<Vec<ManagedAssembly>> {
+    let metadata = Metadata::new(metadata)?;
+    let binary = Binary::new(binary)?;
+    Ok(Loader::new(metadata, binary)?.load())
+}
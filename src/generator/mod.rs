@@ -0,0 +1,171 @@
+mod dotnet;
+mod il2cpp;
+mod serialization;
+
+use crate::error::{UnityError, UnityResult};
+use crate::object::ObjectInfo;
+use crate::typetree::TypeTree;
+use dashmap::DashMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementType {
+    Boolean,
+    Char,
+    I1,
+    U1,
+    I2,
+    U2,
+    I4,
+    U4,
+    I8,
+    U8,
+    R4,
+    R8,
+    String,
+    Object,
+}
+
+/// A field or base type reference, with named types identified by their full name (`Namespace.Outer/Inner`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeSig {
+    Primitive(ElementType),
+    Named(String),
+    GenericInst(Box<TypeSig>, Vec<TypeSig>),
+    SzArray(Box<TypeSig>),
+    GenericParam(u32),
+    Unsupported,
+}
+
+#[derive(Debug, Clone)]
+pub struct ManagedField {
+    pub name: String,
+    pub flags: u16,
+    pub ty: TypeSig,
+    pub attributes: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ManagedType {
+    pub namespace: String,
+    pub name: String,
+    pub flags: u32,
+    pub base: Option<TypeSig>,
+    pub generic_param_count: usize,
+    pub fields: Vec<ManagedField>,
+    pub attributes: Vec<String>,
+}
+
+impl ManagedType {
+    pub fn full_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.name)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ManagedAssembly {
+    pub name: String,
+    pub types: Vec<ManagedType>,
+}
+
+/// Synthesizes MonoBehaviour TypeTrees from managed type definitions, for bundles built with stripped type trees.
+#[derive(Default, Clone)]
+pub struct TypeTreeGenerator {
+    assemblies: Vec<ManagedAssembly>,
+    index: HashMap<String, Vec<(usize, usize)>>,
+    cache: DashMap<String, Arc<TypeTree>>,
+}
+
+impl TypeTreeGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assemblies.is_empty()
+    }
+
+    pub fn assemblies(&self) -> &[ManagedAssembly] {
+        &self.assemblies
+    }
+
+    /// Loads a managed assembly image such as `Managed/Assembly-CSharp.dll`.
+    pub fn load_assembly(&mut self, data: &[u8]) -> UnityResult<()> {
+        let assembly = dotnet::read_assembly(data)?;
+        self.add_assembly(assembly);
+        Ok(())
+    }
+
+    /// Loads every `*.dll` of a `Managed` directory, skipping files that are not managed assemblies.
+    pub fn load_managed_dir(&mut self, dir: impl AsRef<Path>) -> UnityResult<()> {
+        let entries = std::fs::read_dir(dir).map_err(|e| UnityError::CustomError(e.to_string()))?;
+        for entry in entries {
+            let path = entry.map_err(|e| UnityError::CustomError(e.to_string()))?.path();
+            if !path.extension().is_some_and(|x| x.eq_ignore_ascii_case("dll")) {
+                continue;
+            }
+            let data = std::fs::read(&path).map_err(|e| UnityError::CustomError(e.to_string()))?;
+            match dotnet::read_assembly(&data) {
+                Ok(assembly) => self.add_assembly(assembly),
+                Err(UnityError::UnsupportFileType(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Loads the type definitions of an IL2CPP build from `global-metadata.dat` and `libil2cpp.so` or `GameAssembly.dll`.
+    pub fn load_il2cpp(&mut self, metadata: &[u8], binary: &[u8]) -> UnityResult<()> {
+        for assembly in il2cpp::read_assemblies(metadata, binary)? {
+            self.add_assembly(assembly);
+        }
+        Ok(())
+    }
+
+    pub fn add_assembly(&mut self, assembly: ManagedAssembly) {
+        self.cache.clear();
+        let assembly_index = self.assemblies.len();
+        for (type_index, ty) in assembly.types.iter().enumerate() {
+            self.index.entry(ty.full_name()).or_default().push((assembly_index, type_index));
+        }
+        self.assemblies.push(assembly);
+    }
+
+    /// Finds a type by full name, preferring the given assembly when several define it.
+    pub fn find_type(&self, assembly: Option<&str>, full_name: &str) -> Option<&ManagedType> {
+        let candidates = self.index.get(full_name)?;
+        let assembly = assembly.map(normalize_assembly_name);
+        let found = candidates.iter().find(|(a, _)| Some(self.assemblies[*a].name.as_str()) == assembly).or_else(|| candidates.first())?;
+        Some(&self.assemblies[found.0].types[found.1])
+    }
+
+    /// Builds the TypeTree of a MonoBehaviour whose script is `namespace.class_name` in `assembly_name`.
+    pub fn generate(&self, assembly_name: &str, namespace: &str, class_name: &str, info: &ObjectInfo) -> UnityResult<TypeTree> {
+        let full_name = if namespace.is_empty() { class_name.to_string() } else { format!("{namespace}.{class_name}") };
+        let ty = self
+            .find_type(Some(assembly_name), &full_name)
+            .ok_or_else(|| UnityError::CustomError(format!("can not find managed type {full_name} in {assembly_name}")))?;
+        serialization::TreeBuilder::new(self, info).build_mono_behaviour(ty)
+    }
+
+    /// Like [`generate`](Self::generate), but reuses trees already built for the same script and serialization version.
+    pub fn generate_cached(&self, assembly_name: &str, namespace: &str, class_name: &str, info: &ObjectInfo) -> UnityResult<Arc<TypeTree>> {
+        let key = format!("{}:{namespace}.{class_name}:{}:{}", normalize_assembly_name(assembly_name), info.version, info.asset_version);
+        if let Some(tree) = self.cache.get(&key) {
+            return Ok(tree.clone());
+        }
+        let tree = Arc::new(self.generate(assembly_name, namespace, class_name, info)?);
+        self.cache.insert(key, tree.clone());
+        Ok(tree)
+    }
+}
+
+fn normalize_assembly_name(name: &str) -> &str {
+    name.strip_suffix(".dll").unwrap_or(name)
+}
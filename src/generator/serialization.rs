@@ -0,0 +1,470 @@
+use super::{ElementType, ManagedType, TypeSig, TypeTreeGenerator};
+use crate::error::{UnityError, UnityResult};
+use crate::object::ObjectInfo;
+use crate::typetree::{TypeTree, TypeTreeNode};
+use crate::version::UnityVersion;
+
+const ALIGN: i32 = 0x4000;
+/// Unity stops serializing nested custom classes after this many levels.
+const MAX_DEPTH: usize = 10;
+
+const FIELD_ACCESS_MASK: u16 = 0x0007;
+const FIELD_PUBLIC: u16 = 0x0006;
+const FIELD_STATIC: u16 = 0x0010;
+const FIELD_INIT_ONLY: u16 = 0x0020;
+const FIELD_LITERAL: u16 = 0x0040;
+const FIELD_NOT_SERIALIZED: u16 = 0x0080;
+
+const TYPE_INTERFACE: u32 = 0x0020;
+const TYPE_ABSTRACT: u32 = 0x0080;
+const TYPE_SERIALIZABLE: u32 = 0x2000;
+
+const SERIALIZE_FIELD: &str = "UnityEngine.SerializeField";
+
+/// Script base classes whose own fields are covered by the MonoBehaviour header.
+const SCRIPT_ROOTS: [&str; 7] = [
+    "UnityEngine.MonoBehaviour",
+    "UnityEngine.ScriptableObject",
+    "UnityEngine.Behaviour",
+    "UnityEngine.Component",
+    "UnityEngine.Object",
+    "System.Object",
+    "System.ValueType",
+];
+
+/// UnityEngine.Object subclasses recognised when UnityEngine.CoreModule is not loaded.
+const KNOWN_OBJECTS: [&str; 40] = [
+    "Object",
+    "GameObject",
+    "Component",
+    "Behaviour",
+    "MonoBehaviour",
+    "ScriptableObject",
+    "Transform",
+    "RectTransform",
+    "Sprite",
+    "Texture",
+    "Texture2D",
+    "Texture3D",
+    "Texture2DArray",
+    "Cubemap",
+    "RenderTexture",
+    "Material",
+    "Shader",
+    "Mesh",
+    "AudioClip",
+    "AnimationClip",
+    "TextAsset",
+    "Font",
+    "Camera",
+    "Animator",
+    "Animation",
+    "RuntimeAnimatorController",
+    "AnimatorOverrideController",
+    "ParticleSystem",
+    "Rigidbody",
+    "Rigidbody2D",
+    "Collider",
+    "Collider2D",
+    "Renderer",
+    "MeshRenderer",
+    "SpriteRenderer",
+    "SkinnedMeshRenderer",
+    "Light",
+    "Canvas",
+    "CanvasGroup",
+    "Avatar",
+];
+
+enum Kind<'a> {
+    Primitive(&'static str, i32),
+    String,
+    PPtr(String),
+    Builtin(&'static str),
+    Class(&'a ManagedType, Vec<TypeSig>),
+    Array(Box<Kind<'a>>),
+}
+
+pub(super) struct TreeBuilder<'a> {
+    generator: &'a TypeTreeGenerator,
+    version: UnityVersion,
+    asset_version: u32,
+    nodes: Vec<TypeTreeNode>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    pub(super) fn new(generator: &'a TypeTreeGenerator, info: &ObjectInfo) -> Self {
+        Self {
+            generator,
+            version: info.version,
+            asset_version: info.asset_version,
+            nodes: Vec::new(),
+        }
+    }
+
+    pub(super) fn build_mono_behaviour(mut self, ty: &'a ManagedType) -> UnityResult<TypeTree> {
+        self.push("MonoBehaviour", "Base", 0, -1, 0);
+        self.add_pptr("GameObject", "m_GameObject", 1);
+        self.push("UInt8", "m_Enabled", 1, 1, ALIGN);
+        self.add_pptr("MonoScript", "m_Script", 1);
+        self.add_string("m_Name", 1);
+        self.add_class_fields(ty, &[], 1, 0)?;
+        Ok(TypeTree { nodes: self.nodes, string_buffer: Vec::new() })
+    }
+
+    fn push(&mut self, type_: &str, name: &str, level: i32, size: i32, meta_flag: i32) {
+        let index = self.nodes.len() as i32;
+        self.nodes.push(TypeTreeNode {
+            type_: type_.to_string(),
+            name: name.to_string(),
+            size,
+            index,
+            type_flag: (type_ == "Array") as i32,
+            version: 1,
+            meta_flag,
+            level,
+            ..TypeTreeNode::default()
+        })
+    }
+
+    fn add_pptr(&mut self, class: &str, name: &str, level: i32) {
+        let path_id_size = if self.asset_version < 14 { 4 } else { 8 };
+        self.push(&format!("PPtr<{class}>"), name, level, 4 + path_id_size, 0);
+        self.push("int", "m_FileID", level + 1, 4, 0);
+        if path_id_size == 4 {
+            self.push("int", "m_PathID", level + 1, 4, 0);
+        } else {
+            self.push("SInt64", "m_PathID", level + 1, 8, 0);
+        }
+    }
+
+    fn add_string(&mut self, name: &str, level: i32) {
+        self.push("string", name, level, -1, 0);
+        self.push("Array", "Array", level + 1, -1, ALIGN);
+        self.push("int", "size", level + 2, 4, 0);
+        self.push("char", "data", level + 2, 1, 0);
+    }
+
+    fn add_floats(&mut self, type_: &str, name: &str, level: i32, fields: &[&str]) {
+        self.push(type_, name, level, 4 * fields.len() as i32, 0);
+        for field in fields {
+            self.push("float", field, level + 1, 4, 0);
+        }
+    }
+
+    fn add_ints(&mut self, type_: &str, name: &str, level: i32, fields: &[&str]) {
+        self.push(type_, name, level, 4 * fields.len() as i32, 0);
+        for field in fields {
+            self.push("int", field, level + 1, 4, 0);
+        }
+    }
+
+    fn add_class_fields(&mut self, ty: &'a ManagedType, args: &[TypeSig], level: i32, depth: usize) -> UnityResult<()> {
+        if let Some(base) = &ty.base {
+            let base = substitute(base, args);
+            let (base_name, base_args) = match &base {
+                TypeSig::Named(name) => (name.as_str(), Vec::new()),
+                TypeSig::GenericInst(generic, base_args) => match generic.as_ref() {
+                    TypeSig::Named(name) => (name.as_str(), base_args.clone()),
+                    _ => ("", Vec::new()),
+                },
+                _ => ("", Vec::new()),
+            };
+            if !base_name.is_empty() && !SCRIPT_ROOTS.contains(&base_name) {
+                if let Some(base_ty) = self.generator.find_type(None, base_name) {
+                    self.add_class_fields(base_ty, &base_args, level, depth)?;
+                }
+            }
+        }
+        for field in &ty.fields {
+            if !is_serialized_field(field.flags, &field.attributes) {
+                continue;
+            }
+            let sig = substitute(&field.ty, args);
+            if let Some(kind) = self.classify(&sig, depth)? {
+                self.emit(&kind, &field.name, level, depth)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn classify(&self, sig: &TypeSig, depth: usize) -> UnityResult<Option<Kind<'a>>> {
+        let kind = match sig {
+            TypeSig::Primitive(element) => match element {
+                ElementType::Boolean => Kind::Primitive("bool", 1),
+                ElementType::Char => Kind::Primitive("UInt16", 2),
+                ElementType::I1 => Kind::Primitive("SInt8", 1),
+                ElementType::U1 => Kind::Primitive("UInt8", 1),
+                ElementType::I2 => Kind::Primitive("SInt16", 2),
+                ElementType::U2 => Kind::Primitive("UInt16", 2),
+                ElementType::I4 => Kind::Primitive("int", 4),
+                ElementType::U4 => Kind::Primitive("unsigned int", 4),
+                ElementType::I8 => Kind::Primitive("SInt64", 8),
+                ElementType::U8 => Kind::Primitive("UInt64", 8),
+                ElementType::R4 => Kind::Primitive("float", 4),
+                ElementType::R8 => Kind::Primitive("double", 8),
+                ElementType::String => Kind::String,
+                ElementType::Object => return Ok(None),
+            },
+            TypeSig::SzArray(element) => match self.classify(element, depth)? {
+                Some(Kind::Array(_)) | None => return Ok(None),
+                Some(element) => Kind::Array(Box::new(element)),
+            },
+            TypeSig::GenericInst(generic, args) => {
+                let TypeSig::Named(name) = generic.as_ref() else {
+                    return Ok(None);
+                };
+                if name == "System.Collections.Generic.List`1" {
+                    let Some(element) = args.first() else {
+                        return Ok(None);
+                    };
+                    return Ok(match self.classify(element, depth)? {
+                        Some(Kind::Array(_)) | None => None,
+                        Some(element) => Some(Kind::Array(Box::new(element))),
+                    });
+                }
+                if self.version.major < 2020 {
+                    return Ok(None);
+                }
+                return self.classify_named(name, args.clone(), depth);
+            }
+            TypeSig::Named(name) => return self.classify_named(name, Vec::new(), depth),
+            TypeSig::GenericParam(_) | TypeSig::Unsupported => return Ok(None),
+        };
+        Ok(Some(kind))
+    }
+
+    fn classify_named(&self, name: &str, args: Vec<TypeSig>, depth: usize) -> UnityResult<Option<Kind<'a>>> {
+        match name {
+            "System.Boolean" => return self.classify(&TypeSig::Primitive(ElementType::Boolean), depth),
+            "System.String" => return Ok(Some(Kind::String)),
+            _ => {}
+        }
+        if let Some(builtin) = builtin_struct(name) {
+            return Ok(Some(Kind::Builtin(builtin)));
+        }
+        if name == "UnityEngine.GUIStyle" {
+            return Err(UnityError::CustomError("UnityEngine.GUIStyle fields are not supported by the type tree generator".to_string()));
+        }
+        let Some(ty) = self.generator.find_type(None, name) else {
+            let (namespace, short) = name.rsplit_once('.').unwrap_or(("", name));
+            if namespace == "UnityEngine" && KNOWN_OBJECTS.contains(&short) {
+                return Ok(Some(Kind::PPtr(short.to_string())));
+            }
+            if namespace.starts_with("System") || namespace.starts_with("UnityEngine") {
+                return Ok(None);
+            }
+            return Err(UnityError::CustomError(format!("can not resolve managed type {name}, load the assembly that defines it")));
+        };
+        if self.is_enum(ty) {
+            let underlying = ty.fields.iter().find(|x| x.flags & FIELD_STATIC == 0).map(|x| x.ty.clone()).unwrap_or(TypeSig::Primitive(ElementType::I4));
+            return self.classify(&underlying, depth);
+        }
+        if self.is_unity_object(ty) {
+            let short = ty.name.rsplit('/').next().unwrap_or(&ty.name);
+            return Ok(Some(Kind::PPtr(short.to_string())));
+        }
+        if ty.flags & (TYPE_INTERFACE | TYPE_ABSTRACT) != 0 || ty.flags & TYPE_SERIALIZABLE == 0 {
+            return Ok(None);
+        }
+        if depth >= MAX_DEPTH || ty.generic_param_count != args.len() {
+            return Ok(None);
+        }
+        Ok(Some(Kind::Class(ty, args)))
+    }
+
+    fn base_name(ty: &ManagedType) -> Option<&str> {
+        match ty.base.as_ref()? {
+            TypeSig::Named(name) => Some(name),
+            TypeSig::GenericInst(generic, _) => match generic.as_ref() {
+                TypeSig::Named(name) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn is_enum(&self, ty: &ManagedType) -> bool {
+        Self::base_name(ty) == Some("System.Enum")
+    }
+
+    fn is_unity_object(&self, ty: &ManagedType) -> bool {
+        let mut current = ty;
+        for _ in 0..64 {
+            if current.full_name() == "UnityEngine.Object" {
+                return true;
+            }
+            let Some(base) = Self::base_name(current) else {
+                return false;
+            };
+            match self.generator.find_type(None, base) {
+                Some(next) => current = next,
+                None => {
+                    let (namespace, short) = base.rsplit_once('.').unwrap_or(("", base));
+                    return namespace == "UnityEngine" && KNOWN_OBJECTS.contains(&short);
+                }
+            }
+        }
+        false
+    }
+
+    fn emit(&mut self, kind: &Kind<'a>, name: &str, level: i32, depth: usize) -> UnityResult<()> {
+        match kind {
+            Kind::Primitive(type_, size) => {
+                let flag = if *size < 4 { ALIGN } else { 0 };
+                self.push(type_, name, level, *size, flag);
+            }
+            Kind::String => self.add_string(name, level),
+            Kind::PPtr(class) => self.add_pptr(class, name, level),
+            Kind::Builtin(builtin) => self.add_builtin(builtin, name, level),
+            Kind::Class(ty, args) => {
+                self.push(ty.name.rsplit('/').next().unwrap_or(&ty.name), name, level, -1, 0);
+                self.add_class_fields(ty, args, level + 1, depth + 1)?;
+            }
+            Kind::Array(element) => {
+                self.push("vector", name, level, -1, ALIGN);
+                self.push("Array", "Array", level + 1, -1, ALIGN);
+                self.push("int", "size", level + 2, 4, 0);
+                match element.as_ref() {
+                    // Only the array is aligned, after all of its elements.
+                    Kind::Primitive(type_, size) => self.push(type_, "data", level + 2, *size, 0),
+                    element => self.emit(element, "data", level + 2, depth)?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn add_builtin(&mut self, builtin: &str, name: &str, level: i32) {
+        match builtin {
+            "Vector2f" => self.add_floats(builtin, name, level, &["x", "y"]),
+            "Vector3f" => self.add_floats(builtin, name, level, &["x", "y", "z"]),
+            "Vector4f" | "Quaternionf" => self.add_floats(builtin, name, level, &["x", "y", "z", "w"]),
+            "ColorRGBA" => self.add_floats(builtin, name, level, &["r", "g", "b", "a"]),
+            "Color32" => {
+                self.push("ColorRGBA", name, level, 4, 0);
+                self.push("unsigned int", "rgba", level + 1, 4, 0);
+            }
+            "Rectf" => self.add_floats(builtin, name, level, &["x", "y", "width", "height"]),
+            "RectInt" => self.add_ints(builtin, name, level, &["x", "y", "width", "height"]),
+            "RectOffset" => self.add_ints(builtin, name, level, &["m_Left", "m_Right", "m_Top", "m_Bottom"]),
+            "int2_storage" => self.add_ints(builtin, name, level, &["x", "y"]),
+            "int3_storage" => self.add_ints(builtin, name, level, &["x", "y", "z"]),
+            "AABB" => {
+                self.push(builtin, name, level, 24, 0);
+                self.add_floats("Vector3f", "m_Center", level + 1, &["x", "y", "z"]);
+                self.add_floats("Vector3f", "m_Extent", level + 1, &["x", "y", "z"]);
+            }
+            "BoundsInt" => {
+                self.push(builtin, name, level, 24, 0);
+                self.add_ints("int3_storage", "m_Position", level + 1, &["x", "y", "z"]);
+                self.add_ints("int3_storage", "m_Size", level + 1, &["x", "y", "z"]);
+            }
+            "Matrix4x4f" => {
+                let names: Vec<String> = (0..16).map(|i| format!("e{}{}", i / 4, i % 4)).collect();
+                let names: Vec<&str> = names.iter().map(|x| x.as_str()).collect();
+                self.add_floats(builtin, name, level, &names)
+            }
+            "BitField" => {
+                self.push(builtin, name, level, 4, 0);
+                self.push("unsigned int", "m_Bits", level + 1, 4, 0);
+            }
+            "Hash128" => {
+                self.push(builtin, name, level, 16, 0);
+                for i in 0..16 {
+                    self.push("UInt8", &format!("bytes[{i}]"), level + 1, 1, 0);
+                }
+            }
+            "AnimationCurve" => self.add_animation_curve(name, level),
+            "Gradient" => self.add_gradient(name, level),
+            _ => {}
+        }
+    }
+
+    fn add_animation_curve(&mut self, name: &str, level: i32) {
+        let version = self.version;
+        self.push("AnimationCurve", name, level, -1, 0);
+        self.push("vector", "m_Curve", level + 1, -1, 0);
+        self.push("Array", "Array", level + 2, -1, ALIGN);
+        self.push("int", "size", level + 3, 4, 0);
+        self.push("Keyframe", "data", level + 3, -1, 0);
+        let key_level = level + 4;
+        self.push("float", "time", key_level, 4, 0);
+        self.push("float", "value", key_level, 4, 0);
+        self.push("float", "inSlope", key_level, 4, 0);
+        self.push("float", "outSlope", key_level, 4, 0);
+        if version.major >= 2018 {
+            self.push("int", "weightedMode", key_level, 4, 0);
+            self.push("float", "inWeight", key_level, 4, 0);
+            self.push("float", "outWeight", key_level, 4, 0);
+        }
+        self.push("int", "m_PreInfinity", level + 1, 4, 0);
+        self.push("int", "m_PostInfinity", level + 1, 4, 0);
+        if version >= (5, 3) {
+            self.push("int", "m_RotationOrder", level + 1, 4, 0);
+        }
+    }
+
+    fn add_gradient(&mut self, name: &str, level: i32) {
+        let version = self.version;
+        self.push("Gradient", name, level, -1, 0);
+        for i in 0..8 {
+            self.add_floats("ColorRGBA", &format!("key{i}"), level + 1, &["r", "g", "b", "a"]);
+        }
+        for i in 0..8 {
+            self.push("UInt16", &format!("ctime{i}"), level + 1, 2, 0);
+        }
+        for i in 0..8 {
+            self.push("UInt16", &format!("atime{i}"), level + 1, 2, 0);
+        }
+        if version >= (5, 5) {
+            self.push("int", "m_Mode", level + 1, 4, 0);
+        }
+        if version >= (2022, 2) {
+            self.push("int", "m_ColorSpace", level + 1, 4, 0);
+        }
+        self.push("UInt8", "m_NumColorKeys", level + 1, 1, 0);
+        self.push("UInt8", "m_NumAlphaKeys", level + 1, 1, ALIGN);
+    }
+}
+
+fn is_serialized_field(flags: u16, attributes: &[String]) -> bool {
+    if flags & (FIELD_STATIC | FIELD_INIT_ONLY | FIELD_LITERAL | FIELD_NOT_SERIALIZED) != 0 {
+        return false;
+    }
+    flags & FIELD_ACCESS_MASK == FIELD_PUBLIC || attributes.iter().any(|x| x == SERIALIZE_FIELD || x == "UnityEngine.SerializeFieldAttribute")
+}
+
+fn builtin_struct(name: &str) -> Option<&'static str> {
+    let builtin = match name {
+        "UnityEngine.Vector2" => "Vector2f",
+        "UnityEngine.Vector3" => "Vector3f",
+        "UnityEngine.Vector4" => "Vector4f",
+        "UnityEngine.Quaternion" => "Quaternionf",
+        "UnityEngine.Color" => "ColorRGBA",
+        "UnityEngine.Color32" => "Color32",
+        "UnityEngine.Rect" => "Rectf",
+        "UnityEngine.RectInt" => "RectInt",
+        "UnityEngine.RectOffset" => "RectOffset",
+        "UnityEngine.Vector2Int" => "int2_storage",
+        "UnityEngine.Vector3Int" => "int3_storage",
+        "UnityEngine.Bounds" => "AABB",
+        "UnityEngine.BoundsInt" => "BoundsInt",
+        "UnityEngine.Matrix4x4" => "Matrix4x4f",
+        "UnityEngine.LayerMask" => "BitField",
+        "UnityEngine.Hash128" => "Hash128",
+        "UnityEngine.AnimationCurve" => "AnimationCurve",
+        "UnityEngine.Gradient" => "Gradient",
+        _ => return None,
+    };
+    Some(builtin)
+}
+
+fn substitute(sig: &TypeSig, args: &[TypeSig]) -> TypeSig {
+    match sig {
+        TypeSig::GenericParam(index) => args.get(*index as usize).cloned().unwrap_or(TypeSig::Unsupported),
+        TypeSig::GenericInst(generic, inner) => TypeSig::GenericInst(generic.clone(), inner.iter().map(|x| substitute(x, args)).collect()),
+        TypeSig::SzArray(element) => TypeSig::SzArray(Box::new(substitute(element, args))),
+        _ => sig.clone(),
+    }
+}
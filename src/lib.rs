@@ -1,15 +1,23 @@
+extern crate self as unity_rs;
+
 pub mod asset;
 pub mod bundle;
 pub mod classes;
 mod common;
 mod env;
 pub mod error;
+pub mod generator;
 mod math;
 mod object;
 pub mod reader;
 pub mod typetree;
+pub mod version;
 
 pub use crate::classes::{ClassID, Sprite};
 pub use crate::env::{Env, Object};
-pub use crate::error::UnityError;
 pub use crate::error::UnityResult;
+pub use crate::error::{ErrorContext, UnityError};
+pub use crate::version::UnityVersion;
+
+#[doc(hidden)]
+pub use crate::classes::derive as __private;
@@ -97,6 +97,7 @@ impl Matrix4x4 {
     }
 }
 
+#[derive(Default, Debug, Copy, Clone)]
 pub struct Color {
     pub r: f32,
     pub g: f32,
@@ -114,6 +115,7 @@ impl Color {
     }
 }
 
+#[derive(Default, Debug, Copy, Clone)]
 pub struct Quaternion {
     pub x: f32,
     pub y: f32,
@@ -1,15 +1,16 @@
-use crate::asset::{BuildType, SerializedType};
+use crate::asset::SerializedType;
 use crate::classes::ClassID;
-use crate::error::UnityResult;
-use crate::reader::{ByteOrder, Reader};
+use crate::error::{ConsumptionError, UnityError, UnityResult};
+use crate::reader::{ByteOrder, ReadLimits, Reader};
 use crate::typetree::TypeTreeNode;
+use crate::version::UnityVersion;
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct ObjectInfo {
-    pub build_type: BuildType,
     pub asset_version: u32,
     pub bytes_start: usize,
     pub bytes_size: usize,
@@ -21,12 +22,67 @@ pub struct ObjectInfo {
     pub stripped: u8,
     pub path_id: i64,
     pub serialized_type: SerializedType,
-    pub version: [i32; 4],
+    pub ref_types: Arc<Vec<SerializedType>>,
+    pub version: UnityVersion,
+    pub(crate) consumed: ReadEnd,
+    pub limits: ReadLimits,
+}
+
+/// The offset the last reader of one object stopped at, starting over for every clone so that each read can work
+/// on a clone of its own.
+#[derive(Default)]
+pub(crate) struct ReadEnd(AtomicUsize);
+
+impl Clone for ReadEnd {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+struct TypeTreeContext<'a> {
+    ref_types: &'a [SerializedType],
+    depth: usize,
 }
 
 impl ObjectInfo {
     pub fn get_reader(&self) -> Reader {
-        Reader::new(&self.data[self.bytes_start..], self.bytes_order)
+        Reader::with_end(&self.data[self.bytes_start..], self.bytes_order, &self.consumed.0).with_limits(self.limits)
+    }
+
+    /// The offset the last reader handed out by this clone stopped at.
+    pub(crate) fn consumed_offset(&self) -> usize {
+        self.consumed.0.load(Ordering::Relaxed)
+    }
+
+    /// Checks that the last reader handed out by this clone stopped exactly at the end of the object.
+    pub fn check_consumption(&self) -> UnityResult<()> {
+        let consumed = self.consumed_offset();
+        if consumed == self.bytes_size {
+            return Ok(());
+        }
+        Err(UnityError::Consumption(Box::new(ConsumptionError {
+            class: self.class(),
+            version: self.version,
+            expected: self.bytes_size,
+            consumed,
+            field: self.type_tree_field_at(consumed),
+        })))
+    }
+
+    /// Finds the top level TypeTree field that spans `offset`.
+    pub(crate) fn type_tree_field_at(&self, offset: usize) -> Option<String> {
+        let nodes = &self.serialized_type.type_tree.nodes;
+        let mut r = Reader::new(&self.data[self.bytes_start..], self.bytes_order).with_limits(self.limits);
+        let mut ctx = TypeTreeContext { ref_types: &self.ref_types, depth: 0 };
+        let mut i = 1;
+        while i < nodes.len() {
+            let name = nodes[i].name.clone();
+            if Self::read_type_tree_value(nodes, &mut r, &mut i, &mut ctx).is_err() || r.get_offset() > offset {
+                return Some(name);
+            }
+            i += 1;
+        }
+        None
     }
 
     pub fn class(&self) -> ClassID {
@@ -34,32 +90,152 @@ impl ObjectInfo {
     }
 
     pub fn read_type_tree(&self) -> UnityResult<HashMap<String, Value>> {
+        self.read_type_tree_nodes(&self.serialized_type.type_tree.nodes)
+    }
+
+    pub fn read_type_tree_nodes(&self, nodes: &[TypeTreeNode]) -> UnityResult<HashMap<String, Value>> {
         let mut r = self.get_reader();
+        let mut ctx = TypeTreeContext { ref_types: &self.ref_types, depth: 0 };
+        let mut result = Self::read_type_tree_fields(nodes, &mut r, &mut ctx)?;
+        if let Some(registry) = nodes.iter().find(|x| x.level == 1 && x.type_ == "ManagedReferencesRegistry") {
+            Self::link_managed_references(&mut result, &registry.name);
+        }
+        Ok(result)
+    }
+
+    fn read_type_tree_fields(nodes: &[TypeTreeNode], r: &mut Reader, ctx: &mut TypeTreeContext) -> UnityResult<HashMap<String, Value>> {
         let mut result = HashMap::new();
-        let nodes = &self.serialized_type.type_tree.nodes;
         let mut i = 1;
         while i < nodes.len() {
             let node = &nodes[i];
-            let value = Self::read_type_tree_value(nodes, &mut r, &mut i)?;
+            let value = Self::read_type_tree_value(nodes, r, &mut i, ctx)?;
             result.insert(node.name.clone(), value);
             i += 1;
         }
         Ok(result)
     }
 
-    fn read_type_tree_value(nodes: &[TypeTreeNode], r: &mut Reader, index: &mut usize) -> UnityResult<Value> {
-        fn get_nodes(nodes: &[TypeTreeNode], index: usize) -> Vec<TypeTreeNode> {
-            let mut result = vec![nodes[index].clone()];
-            let level = nodes[index].level;
-            for node in &nodes[index + 1..nodes.len()] {
-                if node.level <= level {
-                    return result;
+    /// Reads a `ManagedReferencesRegistry`: version 1 lists objects until a terminus entry, version 2 stores them with their rid.
+    fn read_managed_references_registry(nodes: &[TypeTreeNode], r: &mut Reader, ctx: &mut TypeTreeContext) -> UnityResult<Value> {
+        let version = r.read_i32()?;
+        let ref_ids = match version {
+            1 => {
+                let object = nodes.get(2).filter(|x| x.type_ == "ReferencedObject").ok_or(UnityError::InvalidValue)?;
+                let object_nodes: Vec<TypeTreeNode> = nodes[2..].iter().take_while(|x| x.level > object.level || std::ptr::eq(*x, object)).cloned().collect();
+                let mut refs = Vec::new();
+                loop {
+                    let value = Self::read_type_tree_value(&object_nodes, r, &mut 0, ctx)?;
+                    if is_terminus(&value["type"]) {
+                        break;
+                    }
+                    if refs.len() >= r.limits().max_array_len {
+                        return Err(UnityError::LimitExceeded("array length"));
+                    }
+                    refs.push(value);
+                }
+                json!(refs)
+            }
+            2 => {
+                if nodes.len() < 3 {
+                    return Err(UnityError::InvalidValue);
+                }
+                Self::read_type_tree_value(nodes, r, &mut 2, ctx)?
+            }
+            _ => return Err(UnityError::CustomError(format!("unsupported ManagedReferencesRegistry version {version}"))),
+        };
+        Ok(json!({"version": version, "RefIds": ref_ids}))
+    }
+
+    /// Reads a `ReferencedObject`, decoding its data with the type tree of the matching `ref_types` entry.
+    fn read_referenced_object(nodes: &[TypeTreeNode], r: &mut Reader, ctx: &mut TypeTreeContext) -> UnityResult<Value> {
+        let mut v = serde_json::Map::new();
+        let mut j = 1;
+        while j < nodes.len() {
+            let node = &nodes[j];
+            let value = if node.type_ == "ReferencedObjectData" {
+                let class = v.get("type").and_then(|x| x["class"].as_str()).unwrap_or_default();
+                let namespace = v.get("type").and_then(|x| x["ns"].as_str()).unwrap_or_default();
+                let assembly = v.get("type").and_then(|x| x["asm"].as_str()).unwrap_or_default();
+                // The terminus closing a version 1 registry has no data, nor a ref type to read it with.
+                let value = if class.is_empty() || v.get("type").is_some_and(is_terminus) {
+                    json!({})
+                } else {
+                    let ref_type = ctx
+                        .ref_types
+                        .iter()
+                        .find(|x| x.klass_name == class && x.name_space == namespace && x.asm_name == assembly)
+                        .ok_or_else(|| UnityError::CustomError(format!("can not find ref type {namespace}.{class} in {assembly}")))?;
+                    json!(Self::read_type_tree_fields(&ref_type.type_tree.nodes, r, ctx)?)
+                };
+                if node.meta_flag & 0x4000 != 0 {
+                    r.align(4)?;
+                }
+                value
+            } else {
+                Self::read_type_tree_value(nodes, r, &mut j, ctx)?
+            };
+            v.insert(node.name.clone(), value);
+            j += 1;
+        }
+        Ok(json!(v))
+    }
+
+    /// Attaches the `type` and `data` of the registry entry each managed reference (`rid`, or `id` in version 1) points to.
+    fn link_managed_references(result: &mut HashMap<String, Value>, registry: &str) {
+        let Some(registry_value) = result.get(registry) else {
+            return;
+        };
+        let version = registry_value["version"].as_i64().unwrap_or_default();
+        let (key, refs) = match (version, registry_value["RefIds"].as_array()) {
+            (1, Some(refs)) => ("id", refs.iter().enumerate().map(|(i, x)| (i as i64, x.clone())).collect::<HashMap<_, _>>()),
+            (_, Some(refs)) => ("rid", refs.iter().filter_map(|x| Some((x["rid"].as_i64()?, x.clone()))).collect()),
+            _ => return,
+        };
+        fn link(value: &mut Value, key: &str, refs: &HashMap<i64, Value>, stack: &mut Vec<i64>) {
+            match value {
+                Value::Object(map) => {
+                    if let (1, Some(rid)) = (map.len(), map.get(key).and_then(|x| x.as_i64())) {
+                        if let (Some(entry), false) = (refs.get(&rid), stack.contains(&rid)) {
+                            let mut entry = entry.clone();
+                            stack.push(rid);
+                            link(&mut entry["data"], key, refs, stack);
+                            stack.pop();
+                            map.insert("type".to_string(), entry["type"].take());
+                            map.insert("data".to_string(), entry["data"].take());
+                        }
+                        return;
+                    }
+                    map.values_mut().for_each(|x| link(x, key, refs, stack));
                 }
-                result.push(node.clone())
+                Value::Array(values) => values.iter_mut().for_each(|x| link(x, key, refs, stack)),
+                _ => {}
+            }
+        }
+        for (name, value) in result.iter_mut() {
+            if name != registry {
+                link(value, key, &refs, &mut Vec::new());
             }
-            result
         }
-        let node = &nodes[*index];
+    }
+
+    fn read_type_tree_value(nodes: &[TypeTreeNode], r: &mut Reader, index: &mut usize, ctx: &mut TypeTreeContext) -> UnityResult<Value> {
+        r.check_depth(ctx.depth)?;
+        ctx.depth += 1;
+        let value = Self::read_type_tree_node(nodes, r, index, ctx);
+        ctx.depth -= 1;
+        value
+    }
+
+    fn read_type_tree_node(nodes: &[TypeTreeNode], r: &mut Reader, index: &mut usize, ctx: &mut TypeTreeContext) -> UnityResult<Value> {
+        fn node_at(nodes: &[TypeTreeNode], index: usize) -> UnityResult<&TypeTreeNode> {
+            nodes.get(index).ok_or_else(|| UnityError::CustomError(format!("TypeTree node {index} is out of range")))
+        }
+        fn get_nodes(nodes: &[TypeTreeNode], index: usize) -> UnityResult<Vec<TypeTreeNode>> {
+            let node = node_at(nodes, index)?;
+            let children = nodes[index + 1..].iter().take_while(|x| x.level > node.level);
+            Ok(std::iter::once(node).chain(children).cloned().collect())
+        }
+        let node = node_at(nodes, *index)?;
         let mut align = (node.meta_flag & 0x4000) != 0;
         let value = match node.type_.as_str() {
             "SInt8" => json!(r.read_i8()?),
@@ -79,29 +255,39 @@ impl ObjectInfo {
                 v
             }
             "map" => {
-                if nodes[*index + 1].meta_flag & 0x4000 != 0 {
+                if node_at(nodes, *index + 1)?.meta_flag & 0x4000 != 0 {
                     align = true;
                 }
-                let map_ = get_nodes(nodes, *index);
+                let map_ = get_nodes(nodes, *index)?;
                 *index += map_.len() - 1;
-                let first = get_nodes(&map_, 4);
-                let second = get_nodes(&map_, 4 + first.len());
-                let size = r.read_i32()? as usize;
+                let first = get_nodes(&map_, 4)?;
+                let second = get_nodes(&map_, 4 + first.len())?;
+                let size = r.read_array_len()?;
                 let mut v = serde_json::Map::new();
                 for _ in 0..size {
-                    let key = Self::read_type_tree_value(&first, r, &mut 0)?;
+                    let key = Self::read_type_tree_value(&first, r, &mut 0, ctx)?;
                     let key = match key {
-                        Value::String(_) => key.as_str().unwrap().to_string(),
+                        Value::String(s) => s,
                         _ => key.to_string(),
                     };
-                    let value_ = Self::read_type_tree_value(&second, r, &mut 0)?;
-                    v.insert(key.to_string(), value_);
+                    let value_ = Self::read_type_tree_value(&second, r, &mut 0, ctx)?;
+                    v.insert(key, value_);
                 }
                 json!(v)
             }
+            "ManagedReferencesRegistry" => {
+                let registry = get_nodes(nodes, *index)?;
+                *index += registry.len() - 1;
+                Self::read_managed_references_registry(&registry, r, ctx)?
+            }
+            "ReferencedObject" => {
+                let object = get_nodes(nodes, *index)?;
+                *index += object.len() - 1;
+                Self::read_referenced_object(&object, r, ctx)?
+            }
             "TypelessData" => {
-                let size = r.read_i32()?;
-                let v = r.read_u8_list(size as usize)?;
+                let size = r.read_array_len()?;
+                let v = r.read_u8_list(size)?;
                 *index += 2;
                 json!(v.to_vec())
             }
@@ -110,16 +296,16 @@ impl ObjectInfo {
                     if (nodes[*index + 1].meta_flag & 0x4000) != 0 {
                         align = true;
                     }
-                    let vector = get_nodes(nodes, *index);
+                    let vector = get_nodes(nodes, *index)?;
                     *index += vector.len() - 1;
-                    let size = r.read_i32()? as usize;
+                    let size = r.read_array_len()?;
                     let mut v = Vec::new();
                     for _ in 0..size {
-                        v.push(Self::read_type_tree_value(&vector, r, &mut 3)?)
+                        v.push(Self::read_type_tree_value(&vector, r, &mut 3, ctx)?)
                     }
                     json!(v)
                 } else {
-                    let clz = get_nodes(nodes, *index);
+                    let clz = get_nodes(nodes, *index)?;
                     *index += clz.len() - 1;
                     let mut v = serde_json::Map::new();
                     let j = &mut 1;
@@ -128,7 +314,7 @@ impl ObjectInfo {
                             break;
                         }
                         let clz_node = &clz[*j];
-                        v.insert(clz_node.name.clone(), Self::read_type_tree_value(&clz, r, j)?);
+                        v.insert(clz_node.name.clone(), Self::read_type_tree_value(&clz, r, j, ctx)?);
                         *j += 1;
                     }
                     json!(v)
@@ -141,3 +327,8 @@ impl ObjectInfo {
         Ok(value)
     }
 }
+
+/// Whether a `ReferencedManagedType` is the entry that ends a version 1 registry.
+fn is_terminus(ty: &Value) -> bool {
+    ty["class"] == "Terminus" && ty["ns"] == "UnityEngine.DMAT" && ty["asm"] == "FAKE_ASM"
+}
@@ -1,5 +1,6 @@
 use crate::error::{UnityError, UnityResult};
 use crate::math::{Matrix4x4, RectF32, Vector2, Vector3, Vector4};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum ByteOrder {
@@ -7,16 +8,109 @@ pub enum ByteOrder {
     Little,
 }
 
+/// Bounds on what parsing may allocate or follow, so that untrusted files fail with an error instead of exhausting memory or the stack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadLimits {
+    /// The largest buffer, in bytes, a single read may allocate.
+    pub max_alloc: usize,
+    /// The largest element count accepted for a serialized array.
+    pub max_array_len: usize,
+    /// The deepest nesting followed when reading TypeTrees and the values they describe.
+    pub max_depth: usize,
+}
+
+impl Default for ReadLimits {
+    fn default() -> Self {
+        Self {
+            max_alloc: 1 << 30,
+            max_array_len: 1 << 26,
+            max_depth: 128,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Reader<'a> {
     buf: &'a [u8],
     offset: usize,
     order: ByteOrder,
+    end: Option<&'a AtomicUsize>,
+    limits: ReadLimits,
+}
+
+impl Drop for Reader<'_> {
+    fn drop(&mut self) {
+        if let Some(end) = self.end {
+            end.store(self.offset, Ordering::Relaxed);
+        }
+    }
 }
 
 impl<'a> Reader<'a> {
     pub fn new(buf: &'a [u8], order: ByteOrder) -> Self {
-        Self { buf, offset: 0, order }
+        Self {
+            buf,
+            offset: 0,
+            order,
+            end: None,
+            limits: ReadLimits::default(),
+        }
+    }
+
+    /// Creates a reader that stores the offset it stopped at into `end` when dropped.
+    pub fn with_end(buf: &'a [u8], order: ByteOrder, end: &'a AtomicUsize) -> Self {
+        Self {
+            buf,
+            offset: 0,
+            order,
+            end: Some(end),
+            limits: ReadLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> ReadLimits {
+        self.limits
+    }
+
+    /// Fails when allocating `size` bytes would exceed [`ReadLimits::max_alloc`].
+    pub fn check_alloc(&self, size: usize) -> UnityResult<()> {
+        if size > self.limits.max_alloc {
+            return Err(UnityError::LimitExceeded("allocation size"));
+        }
+        Ok(())
+    }
+
+    /// Fails when `depth` exceeds [`ReadLimits::max_depth`].
+    pub fn check_depth(&self, depth: usize) -> UnityResult<()> {
+        if depth > self.limits.max_depth {
+            return Err(UnityError::LimitExceeded("nesting depth"));
+        }
+        Ok(())
+    }
+
+    /// Reads the `i32` element count of a serialized array. Every element takes at least one byte, so counts larger
+    /// than the remaining data are rejected along with negative ones and those over [`ReadLimits::max_array_len`].
+    pub fn read_array_len(&mut self) -> UnityResult<usize> {
+        let len = usize::try_from(self.read_i32()?).map_err(|_| UnityError::InvalidValue)?;
+        if len > self.limits.max_array_len {
+            return Err(UnityError::LimitExceeded("array length"));
+        }
+        if len > self.buf.len() - self.offset {
+            return Err(UnityError::Eof);
+        }
+        Ok(len)
+    }
+
+    /// Allocates room for `len` items that take `item_size` bytes each in the buffer, after checking they fit in it.
+    fn list_with_capacity<T>(&self, len: usize, item_size: usize) -> UnityResult<Vec<T>> {
+        self.has_space(len.checked_mul(item_size).ok_or(UnityError::Eof)?)?;
+        self.check_alloc(len.saturating_mul(std::mem::size_of::<T>()))?;
+        Ok(Vec::with_capacity(len))
     }
 
     pub fn get_offset(&self) -> usize {
@@ -53,11 +147,9 @@ impl<'a> Reader<'a> {
     }
 
     pub fn has_space(&self, length: usize) -> UnityResult<usize> {
-        let end = length + self.get_offset();
-        if self.buf.len() < end {
-            Err(UnityError::Eof)
-        } else {
-            Ok(end)
+        match length.checked_add(self.get_offset()) {
+            Some(end) if end <= self.buf.len() => Ok(end),
+            _ => Err(UnityError::Eof),
         }
     }
 
@@ -112,6 +204,7 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_u8_list(&mut self, length: usize) -> UnityResult<Vec<u8>> {
+        self.check_alloc(length)?;
         Ok(self.read_u8_slice(length)?.to_vec())
     }
 
@@ -199,6 +292,9 @@ impl<'a> Reader<'a> {
         let mut out = 0u32;
         let mut shift = 0u32;
         loop {
+            if shift >= 32 {
+                return Err(UnityError::InvalidValue);
+            }
             let b = self.read_u8()?;
             out |= ((b & 0x7f) as u32) << shift;
             shift += 7;
@@ -258,15 +354,14 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_aligned_string(&mut self) -> UnityResult<String> {
-        let length = self.read_i32()?;
-        let result = self.read_string_with_length(length as usize);
+        let length = usize::try_from(self.read_i32()?).map_err(|_| UnityError::InvalidValue)?;
+        let result = self.read_string_with_length(length);
         self.align(4)?;
         result
     }
 
     pub fn read_i32_list(&mut self, length: usize) -> UnityResult<Vec<i32>> {
-        self.has_space(length)?;
-        let mut ret = Vec::with_capacity(length);
+        let mut ret = self.list_with_capacity(length, 4)?;
         for _ in 0..length {
             ret.push(self.read_i32()?)
         }
@@ -325,8 +420,7 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_u16_list(&mut self, size: usize) -> UnityResult<Vec<u16>> {
-        let _end = self.has_space(size)?;
-        let mut ret = Vec::with_capacity(size);
+        let mut ret = self.list_with_capacity(size, 2)?;
         for _ in 0..size {
             ret.push(self.read_u16()?)
         }
@@ -334,8 +428,7 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_u32_list(&mut self, size: usize) -> UnityResult<Vec<u32>> {
-        let _end = self.has_space(size)?;
-        let mut ret = Vec::with_capacity(size);
+        let mut ret = self.list_with_capacity(size, 4)?;
         for _ in 0..size {
             ret.push(self.read_u32()?)
         }
@@ -343,8 +436,8 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_string_list(&mut self) -> UnityResult<Vec<String>> {
-        let length = self.read_i32()?;
-        let mut result = Vec::with_capacity(length as usize);
+        let length = self.read_array_len()?;
+        let mut result = self.list_with_capacity(length, 4)?;
         for _ in 0..length {
             result.push(self.read_aligned_string()?);
         }
@@ -352,8 +445,7 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_f32_list(&mut self, size: usize) -> UnityResult<Vec<f32>> {
-        let _end = self.has_space(size)?;
-        let mut ret = Vec::with_capacity(size);
+        let mut ret = self.list_with_capacity(size, 4)?;
         for _ in 0..size {
             ret.push(self.read_f32()?)
         }
@@ -361,8 +453,7 @@ impl<'a> Reader<'a> {
     }
 
     pub fn read_matrix4x4_list(&mut self, size: usize) -> UnityResult<Vec<Matrix4x4>> {
-        let _end = self.has_space(size)?;
-        let mut ret = Vec::with_capacity(size);
+        let mut ret = self.list_with_capacity(size, 64)?;
         for _ in 0..size {
             ret.push(self.read_matrix4x4()?)
         }
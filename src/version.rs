@@ -0,0 +1,157 @@
+use crate::error::UnityError;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// The release channel letter in a Unity version such as the `f` in `2021.3.5f1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BuildType {
+    #[default]
+    Unknown,
+    Alpha,
+    Beta,
+    Experimental,
+    Final,
+    Patch,
+    Other(char),
+}
+
+impl BuildType {
+    pub fn new(letter: char) -> Self {
+        match letter {
+            'a' => Self::Alpha,
+            'b' => Self::Beta,
+            'x' => Self::Experimental,
+            'f' => Self::Final,
+            'p' => Self::Patch,
+            c => Self::Other(c),
+        }
+    }
+
+    pub fn is_patch(&self) -> bool {
+        &Self::Patch == self
+    }
+
+    fn letter(&self) -> Option<char> {
+        match self {
+            Self::Unknown => None,
+            Self::Alpha => Some('a'),
+            Self::Beta => Some('b'),
+            Self::Experimental => Some('x'),
+            Self::Final => Some('f'),
+            Self::Patch => Some('p'),
+            Self::Other(c) => Some(*c),
+        }
+    }
+}
+
+/// A Unity engine version such as `2021.3.5f1`, or `2021.3.5f1c1` for the China releases.
+///
+/// Besides full ordering between versions, it compares against `(major, minor)` and
+/// `(major, minor, patch)` tuples on that prefix only, so `version >= (2020, 2)` holds for every 2020.2 release.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnityVersion {
+    pub major: i32,
+    pub minor: i32,
+    pub patch: i32,
+    pub build_type: BuildType,
+    pub build: i32,
+    /// The number after the `c` of China releases.
+    pub china: Option<i32>,
+}
+
+impl UnityVersion {
+    pub const fn new(major: i32, minor: i32, patch: i32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            build_type: BuildType::Unknown,
+            build: 0,
+            china: None,
+        }
+    }
+
+    /// Parses a version string, returning `None` instead of failing on anything it does not recognize.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let mut numbers = s.splitn(3, '.');
+        let major = numbers.next()?.parse().ok()?;
+        let minor = numbers.next()?.parse().ok()?;
+        let rest = numbers.next().unwrap_or("0");
+
+        let (patch, rest) = split_number(rest)?;
+        let mut version = Self::new(major, minor, patch);
+        let mut chars = rest.chars();
+        let Some(letter) = chars.next() else {
+            return Some(version);
+        };
+        version.build_type = BuildType::new(letter);
+        let (build, rest) = split_number(chars.as_str())?;
+        version.build = build;
+        if let Some(china) = rest.strip_prefix('c') {
+            version.china = Some(split_number(china)?.0);
+        }
+        Some(version)
+    }
+
+    /// Whether the version is missing, as in bundles built with the version stripped to `0.0.0`.
+    pub fn is_stripped(&self) -> bool {
+        self.major == 0 && self.minor == 0 && self.patch == 0
+    }
+
+    pub fn is_china(&self) -> bool {
+        self.china.is_some()
+    }
+}
+
+fn split_number(s: &str) -> Option<(i32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let number = if end == 0 { 0 } else { s[..end].parse().ok()? };
+    Some((number, &s[end..]))
+}
+
+impl FromStr for UnityVersion {
+    type Err = UnityError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s).ok_or_else(|| UnityError::CustomError(format!("invalid Unity version {s:?}")))
+    }
+}
+
+impl fmt::Display for UnityVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(letter) = self.build_type.letter() {
+            write!(f, "{letter}{}", self.build)?;
+        }
+        if let Some(china) = self.china {
+            write!(f, "c{china}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq<(i32, i32)> for UnityVersion {
+    fn eq(&self, other: &(i32, i32)) -> bool {
+        (self.major, self.minor) == *other
+    }
+}
+
+impl PartialOrd<(i32, i32)> for UnityVersion {
+    fn partial_cmp(&self, other: &(i32, i32)) -> Option<Ordering> {
+        (self.major, self.minor).partial_cmp(other)
+    }
+}
+
+impl PartialEq<(i32, i32, i32)> for UnityVersion {
+    fn eq(&self, other: &(i32, i32, i32)) -> bool {
+        (self.major, self.minor, self.patch) == *other
+    }
+}
+
+impl PartialOrd<(i32, i32, i32)> for UnityVersion {
+    fn partial_cmp(&self, other: &(i32, i32, i32)) -> Option<Ordering> {
+        (self.major, self.minor, self.patch).partial_cmp(other)
+    }
+}
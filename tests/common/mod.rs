@@ -0,0 +1,85 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use unity_rs::classes::{Texture2D, TextureFormat};
+
+/// An uncompressed UnityFS bundle of `files`, one block each, with `revision` in its header.
+pub fn bundle(revision: &str, files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut info = vec![0; 16];
+    let size = files.iter().map(|(_, x)| x.len() as u32).sum::<u32>();
+    info.extend((files.len() as i32).to_be_bytes());
+    for (_, file) in files {
+        info.extend((file.len() as u32).to_be_bytes());
+        info.extend((file.len() as u32).to_be_bytes());
+        info.extend(0u16.to_be_bytes());
+    }
+    info.extend((files.len() as i32).to_be_bytes());
+    let mut offset = 0i64;
+    for (path, file) in files {
+        info.extend(offset.to_be_bytes());
+        info.extend((file.len() as i64).to_be_bytes());
+        info.extend(4u32.to_be_bytes());
+        info.extend(path.bytes().chain([0]));
+        offset += file.len() as i64;
+    }
+
+    let mut data = b"UnityFS\0".to_vec();
+    data.extend(6u32.to_be_bytes());
+    data.extend(b"5.x.x\0");
+    data.extend(revision.bytes().chain([0]));
+    let total = data.len() + 20 + info.len() + size as usize;
+    data.extend((total as i64).to_be_bytes());
+    data.extend((info.len() as u32).to_be_bytes());
+    data.extend((info.len() as u32).to_be_bytes());
+    data.extend(0u32.to_be_bytes());
+    data.extend(info);
+    files.iter().for_each(|(_, x)| data.extend(x));
+    data
+}
+
+/// A version 17 serialized file of Unity `version` holding one object of `class_id`, without a TypeTree.
+pub fn serialized_file(version: &str, class_id: i32, mut object: Vec<u8>) -> Vec<u8> {
+    let mut metadata = version.bytes().chain([0]).collect::<Vec<_>>();
+    metadata.extend(0i32.to_le_bytes());
+    metadata.push(0);
+    // One type: class, stripped, script index, hash.
+    metadata.extend(1i32.to_le_bytes());
+    metadata.extend(class_id.to_le_bytes());
+    metadata.push(0);
+    metadata.extend((-1i16).to_le_bytes());
+    metadata.extend([0; 16]);
+    // One object: path_id, start, size, type; no scripts, externals or user information.
+    object.resize(object.len().next_multiple_of(4), 0);
+    metadata.extend(1i32.to_le_bytes());
+    metadata.resize((metadata.len() + 20).next_multiple_of(4) - 20, 0);
+    metadata.extend(1i64.to_le_bytes());
+    metadata.extend(0u32.to_le_bytes());
+    metadata.extend((object.len() as u32).to_le_bytes());
+    metadata.extend(0i32.to_le_bytes());
+    metadata.extend([0; 9]);
+
+    let data_offset = (20 + metadata.len()).next_multiple_of(16);
+    let mut file = Vec::new();
+    file.extend((metadata.len() as u32).to_be_bytes());
+    file.extend(((data_offset + object.len()) as u32).to_be_bytes());
+    file.extend(17u32.to_be_bytes());
+    file.extend((data_offset as u32).to_be_bytes());
+    file.extend([0; 4]);
+    file.extend(metadata);
+    file.resize(data_offset, 0);
+    file.extend(object);
+    file
+}
+
+/// A Texture2D of a single image, not read from any object.
+pub fn texture(format: TextureFormat, width: i32, height: i32, mip_count: i32, data: Vec<u8>) -> Texture2D {
+    let mut texture = Texture2D::default();
+    texture.format = format;
+    texture.width = width;
+    texture.height = height;
+    texture.mip_count = mip_count;
+    texture.image_count = 1;
+    texture.data = data;
+    texture
+}
@@ -0,0 +1,275 @@
+use texture_decoder::block::{decode_astc, decode_bc1, decode_bc3, decode_bc4, decode_bc5, decode_bc6h, decode_eac_r, decode_eac_rg, decode_etc1_3ds};
+use texture_decoder::error::DecodeImageError;
+use texture_decoder::ImageSize;
+
+// Reference texels below are worked out by hand from the format specifications.
+
+/// Packs fields least significant bit first, the way every BC format lays out its bits.
+#[derive(Default)]
+struct BitWriter(u128, u32);
+
+impl BitWriter {
+    fn push(mut self, value: u32, bits: u32) -> Self {
+        self.0 |= ((value as u128) & ((1 << bits) - 1)) << self.1;
+        self.1 += bits;
+        self
+    }
+
+    fn bytes(self) -> [u8; 16] {
+        self.0.to_le_bytes()
+    }
+}
+
+/// 2-bit or 3-bit indices where texel `i` uses `index(i)`.
+fn indices(bits: u32, index: impl Fn(usize) -> u32) -> BitWriter {
+    (0..16).fold(BitWriter::default(), |w, i| w.push(index(i), bits))
+}
+
+fn bc4_block(e0: u8, e1: u8, index: impl Fn(usize) -> u32) -> Vec<u8> {
+    let mut block = vec![e0, e1];
+    block.extend_from_slice(&indices(3, index).bytes()[..6]);
+    block
+}
+
+#[test]
+fn test_bc1() {
+    let size = ImageSize::new(4, 4);
+    let red = [255, 0, 0, 255];
+    let blue = [0, 0, 255, 255];
+
+    // c0 > c1 selects four opaque colors.
+    let mut block = vec![0x00, 0xf8, 0x1f, 0x00];
+    block.extend_from_slice(&indices(2, |i| i as u32 % 4).bytes()[..4]);
+    let img = decode_bc1(&size, &block).unwrap();
+    let palette = [red, blue, [170, 0, 85, 255], [85, 0, 170, 255]];
+    for (x, y, pixel) in img.enumerate_pixels() {
+        // The single block row ends up flipped: texel row 0 is the bottom of the image.
+        let i = (3 - y as usize) * 4 + x as usize;
+        assert_eq!(pixel.0, palette[i % 4], "texel {i}");
+    }
+
+    // c0 <= c1 selects three colors and transparent black.
+    let mut block = vec![0x1f, 0x00, 0x00, 0xf8];
+    block.extend_from_slice(&indices(2, |i| i as u32 % 4).bytes()[..4]);
+    let img = decode_bc1(&size, &block).unwrap();
+    assert_eq!(img.get_pixel(0, 3).0, blue);
+    assert_eq!(img.get_pixel(1, 3).0, red);
+    assert_eq!(img.get_pixel(2, 3).0, [127, 0, 127, 255]);
+    assert_eq!(img.get_pixel(3, 3).0, [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_bc3() {
+    let size = ImageSize::new(4, 4);
+    let mut block = bc4_block(255, 0, |i| i as u32 % 8);
+    // Equal endpoints would select transparent black in BC1, but BC3 color blocks are always opaque.
+    block.extend_from_slice(&[0x00, 0xf8, 0x00, 0xf8, 0, 0, 0, 0]);
+    let img = decode_bc3(&size, &block).unwrap();
+    let alpha = [255, 0, 219, 182, 146, 109, 73, 36];
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let i = (3 - y as usize) * 4 + x as usize;
+        assert_eq!(pixel.0, [255, 0, 0, alpha[i % 8]], "texel {i}");
+    }
+}
+
+#[test]
+fn test_bc4_bc5() {
+    let size = ImageSize::new(4, 4);
+    let red = bc4_block(0, 255, |i| i as u32 % 8);
+    let img = decode_bc4(&size, &red).unwrap();
+    let palette = [0, 255, 51, 102, 153, 204, 0, 255];
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let i = (3 - y as usize) * 4 + x as usize;
+        assert_eq!(pixel.0, [palette[i % 8]], "texel {i}");
+    }
+
+    let mut block = red;
+    block.extend(bc4_block(40, 40, |_| 0));
+    let img = decode_bc5(&size, &block).unwrap();
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let i = (3 - y as usize) * 4 + x as usize;
+        assert_eq!(pixel.0, [palette[i % 8], 40], "texel {i}");
+    }
+}
+
+#[test]
+fn test_block_layout() {
+    // Four blocks covering a 6x6 image, stored bottom row first.
+    let data = [1, 2, 3, 4].iter().flat_map(|&v| bc4_block(v, v, |_| 0)).collect::<Vec<_>>();
+    let img = decode_bc4(&ImageSize::new(6, 6), &data).unwrap();
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let expected = match (x < 4, y < 2) {
+            (true, true) => 3,
+            (false, true) => 4,
+            (true, false) => 1,
+            (false, false) => 2,
+        };
+        assert_eq!(pixel.0, [expected], "pixel ({x}, {y})");
+    }
+
+    assert!(matches!(decode_bc4(&ImageSize::new(6, 6), &data[..8]), Err(DecodeImageError::SizeNotMatch(1, 4))));
+    assert!(matches!(decode_bc6h(&ImageSize::new(8, 4), &[0; 16], false), Err(DecodeImageError::SizeNotMatch(1, 2))));
+}
+
+fn bc6h_texel(block: [u8; 16], signed: bool, i: usize) -> [f32; 3] {
+    let img = decode_bc6h(&ImageSize::new(4, 4), &block, signed).unwrap();
+    img.get_pixel(i as u32 % 4, 3 - i as u32 / 4).0
+}
+
+#[test]
+fn test_bc6h_single_region() {
+    // Mode 11: untransformed 10-bit endpoints, 4-bit indices with a 3-bit anchor.
+    let endpoints = BitWriter::default().push(0b00011, 5).push(0, 10).push(0, 10).push(0, 10).push(1023, 10).push(1023, 10).push(1023, 10);
+    let index = |i: usize| [0, 15, 8, 1][i % 4];
+    let block = (0..16).fold(endpoints, |w, i| w.push(index(i), if i == 0 { 3 } else { 4 })).bytes();
+    assert_eq!(bc6h_texel(block, false, 0), [0.0; 3]);
+    assert_eq!(bc6h_texel(block, false, 1), [65504.0; 3]);
+    assert_eq!(bc6h_texel(block, false, 2), [2.935_546_9; 3]);
+
+    // Signed endpoints saturate at the largest magnitude.
+    let endpoints = BitWriter::default().push(0b00011, 5).push(0, 10).push(0, 10).push(0, 10).push(511, 10).push(512, 10).push(0, 10);
+    let block = (0..16).fold(endpoints, |w, i| w.push(15, if i == 0 { 3 } else { 4 })).bytes();
+    assert_eq!(bc6h_texel(block, true, 5), [65504.0, -65504.0, 0.0]);
+}
+
+#[test]
+fn test_bc6h_transformed() {
+    // Mode 14: 16-bit base endpoints whose high bits are stored reversed, and 4-bit signed deltas.
+    // 31711 finishes as exactly 1.0, and a delta of -8 moves it one step below 0.998.
+    let base = 31711u32;
+    let channel = |w: BitWriter| (10..16).rev().fold(w.push(0b1000, 4), |w, bit| w.push(base >> bit, 1));
+    let mut w = BitWriter::default().push(0b01111, 5).push(base, 10).push(base, 10).push(base, 10);
+    for _ in 0..3 {
+        w = channel(w);
+    }
+    let block = (0..16).fold(w, |w, i| w.push(if i % 2 == 0 { 0 } else { 15 }, if i == 0 { 3 } else { 4 })).bytes();
+    assert_eq!(bc6h_texel(block, false, 0), [1.0; 3]);
+    assert_eq!(bc6h_texel(block, false, 1), [0.998_046_9; 3]);
+
+    // Reserved modes decode to black.
+    let block = BitWriter::default().push(0b10011, 5).push(u32::MAX, 32).bytes();
+    assert_eq!(bc6h_texel(block, false, 3), [0.0; 3]);
+}
+
+/// An EAC block: base codeword, multiplier and table, then 3-bit indices stored column by column from the top bits down.
+fn eac_block(base: u8, multiplier: u8, table: u8, index: impl Fn(usize) -> u64) -> Vec<u8> {
+    let indices = (0..16).fold(0u64, |bits, p| bits | index(p) << (45 - p * 3));
+    let mut block = vec![base, multiplier << 4 | table];
+    block.extend_from_slice(&indices.to_be_bytes()[2..]);
+    block
+}
+
+#[test]
+fn test_eac() {
+    let size = ImageSize::new(4, 4);
+    // Base 128 with table 0 gives 1028 + 8 * modifier out of 2047.
+    let red = eac_block(128, 1, 0, |p| p as u64 % 8);
+    let img = decode_eac_r(&size, &red, false).unwrap();
+    let expected = [125, 122, 119, 113, 130, 133, 136, 142];
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let p = x as usize * 4 + (3 - y as usize);
+        assert_eq!(pixel.0, [expected[p % 8]], "pixel ({x}, {y})");
+    }
+
+    // Signed values are remapped from -1023..=1023, saturating on both ends.
+    let signed = [eac_block(0x80, 1, 0, |_| 3), eac_block(0, 0, 0, |_| 0), eac_block(0, 0, 0, |_| 4), eac_block(127, 15, 0, |_| 7)];
+    for (block, expected) in signed.iter().zip([0, 127, 128, 255]) {
+        assert_eq!(decode_eac_r(&size, block, true).unwrap().get_pixel(1, 2).0, [expected]);
+    }
+
+    let mut rg = signed[0].clone();
+    rg.extend(&signed[3]);
+    assert!(decode_eac_rg(&size, &rg, true).unwrap().pixels().all(|p| p.0 == [0, 255]));
+}
+
+/// A solid ETC1 block in individual mode, where index 0 adds 2 to both 4-bit base colors.
+fn etc1_solid(r: u64) -> u64 {
+    r << 60 | r << 56
+}
+
+#[test]
+fn test_etc1_3ds() {
+    // The four blocks of a tile go bottom left, bottom right, top left, top right, each as a little-endian word.
+    let data = [1, 2, 3, 4].iter().flat_map(|&r| etc1_solid(r).to_le_bytes()).collect::<Vec<_>>();
+    let img = decode_etc1_3ds(&ImageSize::new(8, 8), &data, false).unwrap();
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let r = match (x < 4, y < 4) {
+            (true, true) => 3,
+            (false, true) => 4,
+            (true, false) => 1,
+            (false, false) => 2,
+        };
+        assert_eq!(pixel.0, [r * 17 + 2, 2, 2, 255], "pixel ({x}, {y})");
+    }
+
+    // ETC1A4 puts 4-bit alpha, column by column, in front of each block; here alpha follows the column.
+    let alpha = (0..16).fold(0u64, |bits, p| bits | (p as u64 / 4) << (p * 4));
+    let data = (0..4).flat_map(|_| [alpha.to_le_bytes(), etc1_solid(15).to_le_bytes()].concat()).collect::<Vec<_>>();
+    let img = decode_etc1_3ds(&ImageSize::new(8, 8), &data, true).unwrap();
+    for (x, y, pixel) in img.enumerate_pixels() {
+        assert_eq!(pixel.0, [255, 2, 2, (x % 4) as u8 * 17], "pixel ({x}, {y})");
+    }
+
+    // Differential mode with a flipped split: red 16 on top, 16 - 1 below, every texel adding the small modifier 8.
+    let block: u64 = 16 << 59 | 0b111 << 56 | 1 << 33 | 1 << 32 | 0xffff;
+    let img = decode_etc1_3ds(&ImageSize::new(4, 4), &[block.to_le_bytes(), [0; 8], [0; 8], [0; 8]].concat(), false).unwrap();
+    assert_eq!(img.get_pixel(0, 3).0, [140, 8, 8, 255]);
+    assert_eq!(img.get_pixel(3, 0).0, [131, 8, 8, 255]);
+
+    assert!(matches!(decode_etc1_3ds(&ImageSize::new(4, 4), &data[..8], false), Err(DecodeImageError::SizeNotMatch(1, 4))));
+}
+
+/// An ASTC block with a 4x4 grid of 2-bit weights, one partition and two 8-bit endpoint values. Weights are packed
+/// from the top bit of the block down.
+fn astc_block(cem: u128, v0: u128, v1: u128, weight: impl Fn(usize) -> u128) -> [u8; 16] {
+    let weights = (0..16).fold(0u128, |bits, i| bits | weight(i) << (i * 2)).reverse_bits();
+    (0x42 | cem << 13 | v0 << 17 | v1 << 25 | weights).to_le_bytes()
+}
+
+/// A void-extent block of one half float color.
+fn astc_void_extent(color: [f32; 4]) -> [u8; 16] {
+    let color = color.iter().enumerate().fold(0u128, |bits, (c, &v)| bits | (half::f16::from_f32(v).to_bits() as u128) << (64 + c * 16));
+    (0x1fc | 1 << 9 | 3 << 10 | ((1 << 52) - 1) << 12 | color).to_le_bytes()
+}
+
+#[test]
+fn test_astc_ldr() {
+    // Luminance from 0 to 255; 2-bit weights unquantize to 0, 21, 43 and 64 out of 64.
+    let img = decode_astc(&ImageSize::new(4, 4), &astc_block(0, 0, 255, |i| i as u128 % 4), 4, 4).unwrap();
+    let expected = [0.0, 21504.0 / 65535.0, 44031.0 / 65535.0, 1.0];
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let l = expected[x as usize];
+        assert_eq!(pixel.0, [l, l, l, 1.0], "pixel ({x}, {y})");
+    }
+
+    // A reserved block mode is an error, decoded as magenta.
+    let img = decode_astc(&ImageSize::new(4, 4), &[0; 16], 4, 4).unwrap();
+    assert!(img.pixels().all(|p| p.0 == [1.0, 0.0, 1.0, 1.0]));
+}
+
+#[test]
+fn test_astc_hdr() {
+    // HDR luminance endpoints 0x7800 and 0x8000 are 1.0 and 2.0, interpolated in the logarithmic encoding.
+    let img = decode_astc(&ImageSize::new(4, 4), &astc_block(2, 120, 128, |i| i as u128 % 4), 4, 4).unwrap();
+    for (x, l) in [1.0, 1.265625, 1.609375, 2.0].into_iter().enumerate() {
+        assert_eq!(img.get_pixel(x as u32, 1).0, [l, l, l, 1.0], "x {x}");
+    }
+
+    let color = [2.0, 0.5, 16.0, 1.0];
+    let img = decode_astc(&ImageSize::new(4, 4), &astc_void_extent(color), 4, 4).unwrap();
+    assert!(img.pixels().all(|p| p.0 == color));
+}
+
+#[test]
+fn test_astc_block_layout() {
+    // Four 5x5 blocks covering a 6x6 image, stored bottom row first.
+    let colors = [1.0, 2.0, 3.0, 4.0].map(|v| [v, 0.0, 0.0, 1.0]);
+    let data = colors.iter().flat_map(|&c| astc_void_extent(c)).collect::<Vec<_>>();
+    let img = decode_astc(&ImageSize::new(6, 6), &data, 5, 5).unwrap();
+    for (x, y, pixel) in img.enumerate_pixels() {
+        let block = (x / 5 + (5 - y) / 5 * 2) as usize;
+        assert_eq!(pixel.0, colors[block], "pixel ({x}, {y})");
+    }
+
+    assert!(matches!(decode_astc(&ImageSize::new(6, 6), &data[..48], 5, 5), Err(DecodeImageError::SizeNotMatch(3, 4))));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use unity_rs::classes::{PPtr, Texture2D};
+use unity_rs::reader::{ByteOrder, Reader};
+use unity_rs::{ClassID, Env};
+
+const BUNDLE_PATH: &str = "examples/unpack_image/char_1016_agoat2.ab";
+
+#[test]
+fn test_concurrent_load() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    let per_bundle = env.objects().count();
+    let texture = env.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| env.load_from_slice(bundle).expect("Load failure"));
+        }
+        scope.spawn(|| texture.read::<Texture2D>().expect("Read failure"));
+    });
+    assert_eq!(env.bundles().len(), 5);
+    assert_eq!(env.objects().count(), per_bundle * 5);
+    texture.read::<Texture2D>().expect("Read failure");
+}
+
+#[test]
+fn test_load_many() {
+    let env = Env::new();
+    let results = env.load_many(&[BUNDLE_PATH, "examples/unpack_image/missing.ab", BUNDLE_PATH]);
+    assert!(results[0].is_ok() && results[2].is_ok());
+    let error = results[1].as_ref().expect_err("Missing file loaded");
+    assert_eq!(error.contexts()[0].bundle.as_deref(), Some("examples/unpack_image/missing.ab"));
+    let bundles = env.bundles();
+    assert_eq!(bundles.len(), 2);
+    assert!(bundles.iter().all(|x| x.source == BUNDLE_PATH));
+}
+
+#[test]
+fn test_resolve_after_load() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let known = Env::new();
+    known.load_from_slice(bundle).expect("Load failure");
+    let texture = known.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+
+    // A reference read before the bundle holding its target is loaded resolves once it is.
+    let files = [("CAB-shader".to_string(), common::serialized_file("2017.4.39f1", ClassID::Shader as i32, vec![0; 4]))];
+    let env = Env::new();
+    env.load_from_slice(&common::bundle("2017.4.39f1", &files)).expect("Load failure");
+    let object = env.objects().next().expect("No object");
+    let data = [0i32.to_le_bytes().to_vec(), texture.info.path_id.to_le_bytes().to_vec()].concat();
+    let pptr = PPtr::<Texture2D>::load(&object, &mut Reader::new(&data, ByteOrder::Little)).unwrap();
+    assert!(pptr.get_obj().is_none());
+    env.load_from_slice(bundle).expect("Load failure");
+    let target = pptr.get_obj().expect("Unresolved after load");
+    assert_eq!(target.read::<Texture2D>().unwrap().name, texture.read::<Texture2D>().unwrap().name);
+}
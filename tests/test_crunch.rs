@@ -0,0 +1,217 @@
+use texture_decoder::block::decode_bc1;
+use texture_decoder::crunch::{transcode, CrunchFormat};
+use texture_decoder::error::DecodeImageError;
+use texture_decoder::ImageSize;
+use unity_rs::classes::{Cubemap, Texture2D, TextureFormat};
+use unity_rs::UnityError;
+
+/// Writes a crunch bit stream, most significant bit first.
+#[derive(Default)]
+struct Stream {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl Stream {
+    fn bits(&mut self, value: u32, n: usize) {
+        for i in (0..n).rev() {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            *self.bytes.last_mut().unwrap() |= ((value >> i & 1) as u8) << (7 - self.bits % 8);
+            self.bits += 1;
+        }
+    }
+
+    /// Sends a Huffman model where all 512 symbols are 9 bits long, so that every symbol is coded as its value.
+    fn model(&mut self) {
+        self.bits(512, 14);
+        // Only the code length 9, eighth in the order code lengths are sent in, gets a code: a single bit.
+        self.bits(8, 5);
+        for i in 0..8 {
+            self.bits((i == 7) as u32, 3);
+        }
+        for _ in 0..512 {
+            self.bits(0, 1);
+        }
+    }
+
+    fn models(mut self, n: usize) -> Self {
+        for _ in 0..n {
+            self.model();
+        }
+        self
+    }
+
+    fn symbols(mut self, symbols: &[u32]) -> Self {
+        for &symbol in symbols {
+            self.bits(symbol, 9);
+        }
+        self
+    }
+}
+
+/// Assembles a crunch file with one level from its color endpoints, color selectors, alpha endpoints and alpha
+/// selectors palettes given with their lengths, the tables and the level data.
+fn crunch_file(width: u16, height: u16, format: u8, palettes: [(Stream, u16); 4], tables: Stream, level: Stream) -> Vec<u8> {
+    let mut header = vec![0u8; 74];
+    header[..4].copy_from_slice(&[b'H', b'x', 0, 74]);
+    header[12..14].copy_from_slice(&width.to_be_bytes());
+    header[14..16].copy_from_slice(&height.to_be_bytes());
+    header[16] = 1;
+    header[17] = 1;
+    header[18] = format;
+    let mut body = Vec::new();
+    let mut section = |header: &mut [u8], at: usize, size_bytes: usize, stream: Stream| {
+        let offset = (74 + body.len()) as u32;
+        header[at..at + 3].copy_from_slice(&offset.to_be_bytes()[1..]);
+        header[at + 3 + 3 - size_bytes..at + 3 + 3].copy_from_slice(&(stream.bytes.len() as u32).to_be_bytes()[4 - size_bytes..]);
+        body.extend(stream.bytes);
+    };
+    for (i, (stream, len)) in palettes.into_iter().enumerate() {
+        let at = 33 + i * 8;
+        header[at + 6..at + 8].copy_from_slice(&len.to_be_bytes());
+        if len > 0 {
+            section(&mut header, at, 3, stream);
+        }
+    }
+    // The tables have a 2-byte size before a 3-byte offset; the level offset stands alone.
+    let offset = (74 + body.len()) as u32;
+    header[65..67].copy_from_slice(&(tables.bytes.len() as u16).to_be_bytes());
+    header[67..70].copy_from_slice(&offset.to_be_bytes()[1..]);
+    body.extend(tables.bytes);
+    header[70..74].copy_from_slice(&((74 + body.len()) as u32).to_be_bytes());
+    body.extend(level.bytes);
+    header.extend(body);
+    header
+}
+
+fn none() -> (Stream, u16) {
+    (Stream::default(), 0)
+}
+
+const RED_BLUE: [u8; 4] = [0x00, 0xf8, 0x1f, 0x00];
+const GREEN_BLUE: [u8; 4] = [0xe0, 0x07, 0x1f, 0x00];
+
+/// Two DXT endpoints, red to blue and green to blue, sent as channel deltas in the order r0 g0 b0 r1 g1 b1.
+fn dxt_endpoints() -> Stream {
+    Stream::default().models(2).symbols(&[31, 0, 0, 0, 0, 31, 1, 63, 0, 0, 0, 0])
+}
+
+fn block(endpoints: [u8; 4], selectors: u8) -> Vec<u8> {
+    [endpoints, [selectors; 4]].concat()
+}
+
+#[test]
+fn test_legacy_dxt1() {
+    // Color selectors: all texels on the first endpoint, then all moved by 3 to the second.
+    let selectors = Stream::default().models(1).symbols(&[24; 8]).symbols(&[48; 8]);
+    // Chunk encoding 2 gives the left blocks the first endpoint and the right blocks the second.
+    // Selector deltas go top left, top right, bottom left, bottom right.
+    let level = Stream::default().symbols(&[2, 0, 1, 0, 0, 1, 0]);
+    let file = crunch_file(8, 8, 0, [(dxt_endpoints(), 2), (selectors, 2), none(), none()], Stream::default().models(3), level);
+
+    let level = transcode(&file, 0, false).unwrap();
+    assert_eq!(level.format, CrunchFormat::Dxt1);
+    assert_eq!((level.width, level.height), (8, 8));
+    let expected = [block(RED_BLUE, 0), block(GREEN_BLUE, 0), block(RED_BLUE, 0x55), block(GREEN_BLUE, 0x55)].concat();
+    assert_eq!(level.data, expected);
+
+    // The first row of blocks is the bottom of the image.
+    let img = decode_bc1(&ImageSize::new(8, 8), &level.data).unwrap();
+    assert_eq!(img.get_pixel(0, 7).0, [255, 0, 0, 255]);
+    assert_eq!(img.get_pixel(7, 7).0, [0, 255, 0, 255]);
+    assert_eq!(img.get_pixel(0, 0).0, [0, 0, 255, 255]);
+
+    assert!(matches!(transcode(&file, 1, false), Err(DecodeImageError::InvalidCrunch(_))));
+    assert!(matches!(transcode(&file[..60], 0, false), Err(DecodeImageError::InvalidCrunch(_))));
+
+    // Through a texture, the level comes out as plain DXT1.
+    let mut texture = Texture2D::default();
+    texture.width = 8;
+    texture.height = 8;
+    texture.format = TextureFormat::DXT1Crunched;
+    texture.mip_count = 1;
+    texture.data = file;
+    let level = texture.mip_level(0, 0).unwrap();
+    assert_eq!(level.format, TextureFormat::DXT1);
+    assert_eq!(level.data, expected);
+    assert_eq!(texture.decode_image_without_cache().unwrap(), img);
+}
+
+#[test]
+fn test_unity_dxt5() {
+    // Selectors are xored into the previous one: first all on the first endpoint, then all on the second.
+    let color_selectors = Stream::default().models(1).symbols(&[0; 8]).symbols(&[15; 8]);
+    // Alpha endpoints 255 to 0 and 0 to 255; one alpha selector with every texel on the second endpoint.
+    let alpha_endpoints = Stream::default().models(1).symbols(&[255, 0, 1, 255]);
+    let alpha_selectors = Stream::default().models(1).symbols(&[63; 8]);
+    // Per block: a reference group on even rows every other column, endpoint deltas when the reference is 0,
+    // then the color and alpha selectors. References 0, 1 and 2 code new endpoints, copy the left ones, and copy
+    // the above ones.
+    let level = Stream::default().symbols(&[0b01_01_10_00, 0, 0, 0, 0, 1, 0, 0b00_01_10_00, 0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 1, 1, 0]);
+    let palettes = [(dxt_endpoints(), 2), (color_selectors, 2), (alpha_endpoints, 2), (alpha_selectors, 1)];
+    let file = crunch_file(16, 8, 2, palettes, Stream::default().models(5), level);
+
+    let level = transcode(&file, 0, true).unwrap();
+    assert_eq!(level.format, CrunchFormat::Dxt5);
+    let alpha_selector = [0x49, 0x92, 0x24, 0x49, 0x92, 0x24];
+    let alpha = [0, 0, 1, 1, 0, 0, 1, 0];
+    for (i, block) in level.data.chunks_exact(16).enumerate() {
+        let endpoints = if alpha[i] == 0 { [255, 0] } else { [0, 255] };
+        assert_eq!(block[..2], endpoints, "block {i}");
+        assert_eq!(block[2..8], alpha_selector, "block {i}");
+        assert_eq!(block[8..], self::block(RED_BLUE, if i % 2 == 0 { 0 } else { 0x55 }), "block {i}");
+    }
+}
+
+#[test]
+fn test_unity_etc1s() {
+    // Endpoints are sent as bytewise deltas of red, green, blue and the table index.
+    let endpoints = Stream::default().models(1).symbols(&[16, 8, 4, 2]);
+    // The first column at the middle of the range towards the larger modifier, the others at the far negative end.
+    let selectors = Stream::default().models(1).symbols(&[2, 0, 2, 0, 2, 0, 2, 0]);
+    let level = Stream::default().symbols(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let file = crunch_file(4, 4, 13, [(endpoints, 1), (selectors, 1), none(), none()], Stream::default().models(3), level);
+
+    let level = transcode(&file, 0, true).unwrap();
+    assert_eq!(level.format, CrunchFormat::Etc1S);
+    // A differential block with no delta, the same table in both subblocks, and big-endian selectors.
+    assert_eq!(level.data, [128, 64, 32, 0x4a, 0xff, 0xf0, 0xff, 0xf0]);
+
+    assert!(matches!(transcode(&file, 0, false), Err(DecodeImageError::InvalidCrunch(_))));
+}
+
+#[test]
+fn test_legacy_cubemap() {
+    // Every face is a single chunk of one endpoint, the palette index going on from the previous face.
+    let selectors = Stream::default().models(1).symbols(&[24; 8]);
+    let mut level = Stream::default();
+    for face in 0..6 {
+        if face % 3 == 0 {
+            level = level.symbols(&[0]);
+        }
+        level = level.symbols(&[(face > 0) as u32, 0, 0, 0, 0]);
+    }
+    let mut file = crunch_file(4, 4, 0, [(dxt_endpoints(), 2), (selectors, 1), none(), none()], Stream::default().models(3), level);
+    file[17] = 6;
+
+    let level = transcode(&file, 0, false).unwrap();
+    assert_eq!(level.faces, 6);
+    let faces = (0..6).map(|face| block(if face % 2 == 0 { RED_BLUE } else { GREEN_BLUE }, 0)).collect::<Vec<_>>();
+    assert_eq!(level.data, faces.concat());
+
+    let mut texture = Texture2D::default();
+    texture.width = 4;
+    texture.height = 4;
+    texture.format = TextureFormat::DXT1Crunched;
+    texture.mip_count = 1;
+    texture.image_count = 6;
+    texture.data = file;
+    for (face, expected) in faces.iter().enumerate() {
+        assert_eq!(&texture.mip_level(0, face).unwrap().data, expected);
+    }
+    assert!(matches!(texture.mip_level(0, 6), Err(UnityError::InvalidValue)));
+    let dds = Cubemap { texture, source_textures: Vec::new() }.to_dds().unwrap();
+    assert_eq!(dds[148..], faces.concat());
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use serde_json::{json, Map, Value};
+use unity_rs::classes::{Field, FromObject, Texture2D};
+use unity_rs::{ClassID, Env, Object, UnityResult, UnityVersion};
+
+#[derive(FromObject)]
+#[unity(class = "Texture2D", partial)]
+struct TextureHeader {
+    name: String,
+    #[unity(since = "2017.3")]
+    forced_fallback_format: i32,
+    #[unity(since = "2017.3", align)]
+    downscale_fallback: bool,
+    #[unity(since = "2020.2")]
+    is_alpha_channel_optional: Option<bool>,
+    width: i32,
+    height: i32,
+    #[unity(skip)]
+    note: String,
+}
+
+#[derive(FromObject)]
+#[unity(class = "TextAsset", partial = "2018", finish = "Layout::finish")]
+struct Layout {
+    #[unity(offset)]
+    start: Option<usize>,
+    #[unity(align(since = "5", until = "5.4"))]
+    flag: bool,
+    #[unity(when = "version >= (5, 6) || flag", default = "-1")]
+    gated: i32,
+    #[unity(flatten)]
+    inner: Inner,
+    #[unity(skip)]
+    total: i32,
+}
+
+impl Layout {
+    fn finish(&mut self, _object: &Object) -> UnityResult<()> {
+        self.total = self.gated + self.inner.value;
+        Ok(())
+    }
+}
+
+#[derive(FromObject)]
+#[unity(class = "TextAsset", partial)]
+struct Inner {
+    value: i32,
+}
+
+#[test]
+fn test_derive() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+
+    let object = env.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+    assert!(object.version() >= (2017, 3) && object.version() < (2020, 2));
+    let texture: Texture2D = object.read().expect("Read failure");
+    for header in [object.read::<TextureHeader>().expect("Read failure"), object.read_from_type_tree::<TextureHeader>().expect("Read failure")] {
+        assert_eq!(header.name, texture.name);
+        assert_eq!(header.forced_fallback_format, texture.forced_fallback_format);
+        assert_eq!(header.downscale_fallback, texture.downscale_fallback);
+        assert!(header.is_alpha_channel_optional.is_none());
+        assert_eq!((header.width, header.height), (texture.width, texture.height));
+        assert!(header.note.is_empty());
+    }
+}
+
+#[test]
+fn test_derive_layouts() {
+    let read = |version: &str, data: Vec<u8>| {
+        let env = Env::new();
+        env.load_from_slice(&common::bundle(version, &[("CAB-layout".to_string(), common::serialized_file(version, ClassID::TextAsset as i32, data))]))
+            .expect("Load failure");
+        let object = env.objects().next().expect("No object");
+        let layout: Layout = object.read().expect("Read failure");
+        (layout.start, layout.flag, layout.gated, layout.inner.value, layout.total)
+    };
+    let int = |x: i32| x.to_le_bytes().to_vec();
+
+    // aligned after the flag, which lets the gated field be read
+    assert_eq!(read("5.0.1f1", [vec![1, 0, 0, 0], int(7), int(3)].concat()), (Some(0), true, 7, 3, 10));
+    // not aligned, and the gated field takes its default
+    assert_eq!(read("5.4.1f1", [vec![0], int(3)].concat()), (Some(0), false, -1, 3, 2));
+    // the version alone lets the gated field be read
+    assert_eq!(read("5.6.1f1", [vec![0], int(7), int(3)].concat()), (Some(0), false, 7, 3, 10));
+
+    assert!(!Layout::partial(UnityVersion::parse("2017.4.1f1").expect("Parse failure")));
+    assert!(Layout::partial(UnityVersion::parse("2018.1.1f1").expect("Parse failure")));
+}
+
+#[test]
+fn test_byte_array_order() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    let object = env.objects().next().expect("No object");
+
+    let bytes: [u8; 12] = std::array::from_fn(|i| i as u8);
+    // serde_json sorts the keys as text, which puts bytes[10] and bytes[11] before bytes[2]
+    let tree = Value::Object((0..12).map(|i| (format!("bytes[{i}]"), json!(i))).collect::<Map<_, _>>());
+    assert_eq!(<[u8; 12]>::from_value(&object.info, &tree).expect("Read failure"), bytes);
+    assert_eq!(bytes.to_value(), Some(tree));
+}
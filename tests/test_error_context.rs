@@ -0,0 +1,18 @@
+use unity_rs::classes::Mesh;
+use unity_rs::{ClassID, Env};
+
+#[test]
+fn test_error_context() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+
+    let object = env.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+    let error = object.read::<Mesh>().expect_err("Texture2D read as Mesh");
+    let contexts = error.contexts();
+    let context = contexts.first().expect("No context");
+    assert_eq!(context.path_id, Some(object.info.path_id));
+    assert_eq!(context.class, Some(ClassID::Texture2D));
+    assert_eq!(context.asset.as_deref(), Some(object.asset().path.as_str()));
+    assert!(error.to_string().contains(&format!("path_id {}", object.info.path_id)));
+}
@@ -0,0 +1,58 @@
+use half::f16;
+use image::DynamicImage;
+use texture_decoder::implements::{RGB9e5Float, RGBAFloat, RHalf, RGBA64};
+use texture_decoder::{ImageSize, Texture2DDecoder};
+
+#[test]
+fn test_decode_f32() {
+    // Two pixels, the first of which is the bottom row.
+    let size = ImageSize::new(1, 2);
+    let data = [2.5f32, 0.5].iter().flat_map(|&v| f16::from_f32(v).to_le_bytes()).collect::<Vec<_>>();
+    let img = Texture2DDecoder::decode_f32(RHalf, &size, &data, true).unwrap();
+    assert_eq!(img.get_pixel(0, 1).0, [2.5, 0.0, 0.0, 1.0]);
+    assert_eq!(img.get_pixel(0, 0).0, [0.5, 0.0, 0.0, 1.0]);
+    // Squashed to 8 bits, values above 1 saturate.
+    let img = Texture2DDecoder::decode(RHalf, &size, &data, true).unwrap();
+    assert_eq!(img.get_pixel(0, 1).0, [255, 0, 0, 255]);
+    assert_eq!(img.get_pixel(0, 0).0, [128, 0, 0, 255]);
+
+    let pixel = [-1.0f32, 0.25, 1000.0, 0.75];
+    let data = pixel.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+    let img = Texture2DDecoder::decode_f32(RGBAFloat, &ImageSize::new(1, 1), &data, true).unwrap();
+    assert_eq!(img.get_pixel(0, 0).0, pixel);
+
+    // 9-bit mantissas 256, 384 and 1 with an exponent of 16 give 1, 1.5 and 1/256.
+    let shared: u32 = 16 << 27 | 1 << 18 | 384 << 9 | 256;
+    let img = Texture2DDecoder::decode_f32(RGB9e5Float, &ImageSize::new(1, 1), &shared.to_le_bytes(), true).unwrap();
+    assert_eq!(img.get_pixel(0, 0).0, [1.0, 1.5, 1.0 / 256.0, 1.0]);
+}
+
+#[test]
+fn test_decode_u16() {
+    let pixel = [1u16, 65535, 32768, 1000];
+    let data = pixel.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+    let img = Texture2DDecoder::decode_u16(RGBA64, &ImageSize::new(1, 1), &data, true).unwrap();
+    assert_eq!(img.get_pixel(0, 0).0, pixel);
+    let img = Texture2DDecoder::decode(RGBA64, &ImageSize::new(1, 1), &data, true).unwrap();
+    assert_eq!(img.get_pixel(0, 0).0, [0, 255, 128, 4]);
+
+    assert!(Texture2DDecoder::decode_u16(RGBA64, &ImageSize::new(2, 1), &data, true).is_err());
+}
+
+#[test]
+fn test_export() {
+    std::fs::create_dir_all("./target/tests").expect("CreateError");
+    let pixel = [-1.0f32, 0.25, 1000.0, 0.75];
+    let data = pixel.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+    let img = Texture2DDecoder::decode_f32(RGBAFloat, &ImageSize::new(1, 1), &data, true).unwrap();
+    img.save("./target/tests/hdr_output.exr").expect("Save Failure");
+    let DynamicImage::ImageRgba32F(saved) = image::open("./target/tests/hdr_output.exr").unwrap() else { panic!("not float") };
+    assert_eq!(saved.get_pixel(0, 0).0, pixel);
+
+    let pixel = [1u16, 65535, 32768, 1000];
+    let data = pixel.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>();
+    let img = Texture2DDecoder::decode_u16(RGBA64, &ImageSize::new(1, 1), &data, true).unwrap();
+    img.save("./target/tests/hdr_output.png").expect("Save Failure");
+    let DynamicImage::ImageRgba16(saved) = image::open("./target/tests/hdr_output.png").unwrap() else { panic!("not 16-bit") };
+    assert_eq!(saved.get_pixel(0, 0).0, pixel);
+}
@@ -0,0 +1,71 @@
+use unity_rs::generator::TypeTreeGenerator;
+use unity_rs::UnityError;
+
+/// A `global-metadata.dat` of the given version without any definitions.
+fn metadata(version: i32) -> Vec<u8> {
+    let mut data = vec![0; 0x200];
+    data[..4].copy_from_slice(&0xFAB11BAFu32.to_le_bytes());
+    data[4..8].copy_from_slice(&version.to_le_bytes());
+    data
+}
+
+/// A 64-bit ELF image with one loadable segment of `file_size` bytes from 0x100, mapped at `address`.
+fn elf(address: u64, file_size: u64, size: u64) -> Vec<u8> {
+    let mut data = vec![0; 0x100];
+    data[..6].copy_from_slice(b"\x7fELF\x02\x01");
+    data[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes());
+    data[0x36..0x38].copy_from_slice(&56u16.to_le_bytes());
+    data[0x38..0x3A].copy_from_slice(&1u16.to_le_bytes());
+    data[0x40..0x44].copy_from_slice(&1u32.to_le_bytes());
+    for (at, value) in [(0x48, 0x100), (0x50, address), (0x60, file_size), (0x68, size)] {
+        data[at..at + 8].copy_from_slice(&value.to_le_bytes());
+    }
+    data
+}
+
+#[test]
+fn test_unsupported_versions() {
+    let binary = elf(0x1000, 0, 0);
+    for version in [20, 32, -1] {
+        let result = TypeTreeGenerator::new().load_il2cpp(&metadata(version), &binary);
+        assert!(matches!(result, Err(UnityError::UnsupportFileType(_))), "{version}");
+    }
+    let mut data = metadata(29);
+    data[0] = 0;
+    assert!(matches!(TypeTreeGenerator::new().load_il2cpp(&data, &binary), Err(UnityError::UnsupportFileType(_))));
+}
+
+#[test]
+fn test_truncated_input() {
+    let metadata = metadata(29);
+    let binary = elf(0x1000, 0, 0);
+    for len in [0, 4, 8, 64] {
+        assert!(TypeTreeGenerator::new().load_il2cpp(&metadata[..len], &binary).is_err(), "{len}");
+    }
+    for len in [0, 4, 6, 0x30, 0x50] {
+        assert!(TypeTreeGenerator::new().load_il2cpp(&metadata, &binary[..len]).is_err(), "{len}");
+    }
+}
+
+#[test]
+fn test_hostile_segments() {
+    // A segment at the top of the address space holding what looks like the start of a registration, with a pointer
+    // into itself: its end overflows, which must fail the lookup rather than panic.
+    let mut binary = elf(0xFFFF_FFFF_FFFF_F000, 0x18, 0x2000);
+    binary.extend(0u64.to_le_bytes());
+    binary.extend(0xFFFF_FFFF_FFFF_F800u64.to_le_bytes());
+    binary.extend(0u64.to_le_bytes());
+    assert!(matches!(TypeTreeGenerator::new().load_il2cpp(&metadata(29), &binary), Err(UnityError::UnsupportFileType(_))));
+
+    // A PE image whose image base overflows with the address of its section.
+    let mut pe = vec![0; 0x200];
+    pe[..2].copy_from_slice(b"MZ");
+    pe[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    pe[0x40..0x44].copy_from_slice(b"PE\0\0");
+    pe[0x46..0x48].copy_from_slice(&1u16.to_le_bytes());
+    pe[0x54..0x56].copy_from_slice(&240u16.to_le_bytes());
+    pe[0x58..0x5A].copy_from_slice(&0x20Bu16.to_le_bytes());
+    pe[0x70..0x78].copy_from_slice(&0xFFFF_FFFF_FFFF_0000u64.to_le_bytes());
+    pe[0x148 + 12..0x148 + 16].copy_from_slice(&0x10_0000u32.to_le_bytes());
+    assert!(TypeTreeGenerator::new().load_il2cpp(&metadata(29), &pe).is_err());
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use unity_rs::reader::{ByteOrder, ReadLimits, Reader};
+use unity_rs::{Env, UnityError};
+
+#[test]
+fn test_reader_limits() {
+    let data = [0xff, 0xff, 0xff, 0xff, 8, 0, 0, 0, 0, 0, 0, 0];
+    let mut r = Reader::new(&data, ByteOrder::Little);
+    assert!(r.read_array_len().is_err());
+    assert!(matches!(r.read_array_len(), Err(UnityError::Eof)));
+
+    let limits = ReadLimits { max_array_len: 2, ..ReadLimits::default() };
+    let mut r = Reader::new(&data[4..], ByteOrder::Little).with_limits(limits);
+    assert!(matches!(r.read_array_len(), Err(UnityError::LimitExceeded(_))));
+    assert!(r.read_u8_list(usize::MAX).is_err());
+}
+
+#[test]
+fn test_truncated_bundle() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    for len in [0, 8, 64, 256, bundle.len() / 2, bundle.len() - 1] {
+        let env = Env::new();
+        assert!(env.load_from_slice(&bundle[..len]).is_err());
+    }
+}
+
+#[test]
+fn test_bundle_size_limit() {
+    // Each block fits in the limit, but not both of them together.
+    let files = [("CAB-a.resS".to_string(), vec![1; 96]), ("CAB-b.resS".to_string(), vec![2; 96])];
+    let bundle = common::bundle("2017.4.39f1", &files);
+    let mut env = Env::new();
+    env.limits = ReadLimits { max_alloc: 150, ..ReadLimits::default() };
+    assert!(matches!(env.load_from_slice(&bundle), Err(UnityError::LimitExceeded(_))));
+    env.limits = ReadLimits { max_alloc: 200, ..ReadLimits::default() };
+    env.load_from_slice(&bundle).unwrap();
+}
@@ -5,7 +5,7 @@ use unity_rs::Env;
 fn test_load_texture2d() {
     std::fs::create_dir_all("./target/tests").expect("CreateError");
     let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
-    let mut env = Env::new();
+    let env = Env::new();
     env.load_from_slice(bundle).expect("Load failure");
 
     for obj in env.objects() {
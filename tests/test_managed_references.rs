@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+use std::sync::Arc;
+use unity_rs::asset::SerializedType;
+use unity_rs::reader::ByteOrder;
+use unity_rs::typetree::{TypeTree, TypeTreeNode};
+use unity_rs::Env;
+
+const ALIGN: i32 = 0x4000;
+
+fn node(level: i32, type_: &str, name: &str) -> TypeTreeNode {
+    TypeTreeNode {
+        type_: type_.to_string(),
+        name: name.to_string(),
+        level,
+        meta_flag: if type_ == "Array" { ALIGN } else { 0 },
+        ..TypeTreeNode::default()
+    }
+}
+
+fn string(level: i32, name: &str) -> [TypeTreeNode; 4] {
+    [node(level, "string", name), node(level + 1, "Array", "Array"), node(level + 2, "int", "size"), node(level + 2, "char", "data")]
+}
+
+/// A `ReferencedObject` at `level`, with the rid of version 2 registries when `rid` is set.
+fn referenced_object(level: i32, name: &str, rid: bool) -> Vec<TypeTreeNode> {
+    let mut nodes = vec![node(level, "ReferencedObject", name)];
+    if rid {
+        nodes.push(node(level + 1, "SInt64", "rid"));
+    }
+    nodes.push(node(level + 1, "ReferencedManagedType", "type"));
+    for field in ["class", "ns", "asm"] {
+        nodes.extend(string(level + 2, field));
+    }
+    nodes.push(node(level + 1, "ReferencedObjectData", "data"));
+    nodes
+}
+
+/// A managed reference field, which holds an `id` in version 1 registries and a `rid` in version 2.
+fn reference(level: i32, name: &str, version: i32) -> [TypeTreeNode; 2] {
+    match version {
+        1 => [node(level, "managedReference", name), node(level + 1, "int", "id")],
+        _ => [node(level, "managedReference", name), node(level + 1, "SInt64", "rid")],
+    }
+}
+
+/// The tree of a script with a reference field, an array of references and the registry.
+fn nodes(version: i32) -> Vec<TypeTreeNode> {
+    let mut nodes = vec![node(0, "MonoBehaviour", "Base")];
+    nodes.extend(reference(1, "item", version));
+    nodes.extend([node(1, "vector", "items"), node(2, "Array", "Array"), node(3, "int", "size")]);
+    nodes.extend(reference(3, "data", version));
+    nodes.extend([node(1, "ManagedReferencesRegistry", "references"), node(2, "int", "version")]);
+    match version {
+        1 => nodes.extend(referenced_object(2, "00000000", false)),
+        _ => {
+            nodes.extend([node(2, "vector", "RefIds"), node(3, "Array", "Array"), node(4, "int", "size")]);
+            nodes.extend(referenced_object(4, "data", true));
+        }
+    }
+    nodes
+}
+
+/// `Game.Item { int value; [SerializeReference] Item next; }`
+fn item_type(version: i32) -> SerializedType {
+    let mut nodes = vec![node(0, "Item", "Base"), node(1, "int", "value")];
+    nodes.extend(reference(1, "next", version));
+    SerializedType {
+        klass_name: "Item".to_string(),
+        name_space: "Game".to_string(),
+        asm_name: "Assembly-CSharp.dll".to_string(),
+        type_tree: TypeTree { nodes, string_buffer: Vec::new() },
+        ..SerializedType::default()
+    }
+}
+
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Bytes {
+    fn i32(&mut self, value: i32) -> &mut Self {
+        self.0.extend(value.to_le_bytes());
+        self
+    }
+
+    fn i64(&mut self, value: i64) -> &mut Self {
+        self.0.extend(value.to_le_bytes());
+        self
+    }
+
+    fn string(&mut self, value: &str) -> &mut Self {
+        self.i32(value.len() as i32);
+        self.0.extend(value.bytes());
+        self.0.resize(self.0.len().next_multiple_of(4), 0);
+        self
+    }
+
+    fn managed_type(&mut self, class: &str, namespace: &str, assembly: &str) -> &mut Self {
+        self.string(class).string(namespace).string(assembly)
+    }
+}
+
+fn read(version: i32, data: Vec<u8>) -> serde_json::Map<String, Value> {
+    let env = Env::new();
+    env.load_from_slice(include_bytes!("../examples/unpack_image/char_1016_agoat2.ab")).unwrap();
+    let mut info = env.objects().next().unwrap().info;
+    info.bytes_start = 0;
+    info.bytes_size = data.len();
+    info.data = Arc::new(data);
+    info.bytes_order = ByteOrder::Little;
+    info.ref_types = Arc::new(vec![item_type(version)]);
+    let result = info.read_type_tree_nodes(&nodes(version)).unwrap();
+    info.check_consumption().unwrap();
+    result.into_iter().collect()
+}
+
+fn item_of(value: i32, next: Value) -> Value {
+    json!({"type": {"class": "Item", "ns": "Game", "asm": "Assembly-CSharp.dll"}, "data": {"value": value, "next": next}})
+}
+
+#[test]
+fn test_registry_v1() {
+    // Entries are numbered in order and closed by the terminus.
+    let mut data = Bytes::default();
+    data.i32(0).i32(2).i32(1).i32(0).i32(1);
+    data.managed_type("Item", "Game", "Assembly-CSharp.dll").i32(10).i32(1);
+    data.managed_type("Item", "Game", "Assembly-CSharp.dll").i32(20).i32(-1);
+    data.managed_type("Terminus", "UnityEngine.DMAT", "FAKE_ASM");
+    let result = read(1, data.0);
+
+    let mut second = item_of(20, json!({"id": -1}));
+    second["id"] = json!(1);
+    let mut first = item_of(10, second.clone());
+    first["id"] = json!(0);
+    assert_eq!(result["item"], first);
+    assert_eq!(result["items"], json!([second, first]));
+    assert_eq!(result["references"]["version"], 1);
+    assert_eq!(result["references"]["RefIds"].as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_registry_v2() {
+    // Entries carry their rid, in any order; references to unknown rids are left as they are.
+    let mut data = Bytes::default();
+    data.i64(7).i32(2).i64(8).i64(-2).i32(2).i32(2);
+    data.i64(8).managed_type("Item", "Game", "Assembly-CSharp.dll").i32(80).i64(-2);
+    data.i64(7).managed_type("Item", "Game", "Assembly-CSharp.dll").i32(70).i64(8);
+    let result = read(2, data.0);
+
+    let mut second = item_of(80, json!({"rid": -2}));
+    second["rid"] = json!(8);
+    let mut first = item_of(70, second.clone());
+    first["rid"] = json!(7);
+    assert_eq!(result["item"], first);
+    assert_eq!(result["items"], json!([second, {"rid": -2}]));
+    assert_eq!(result["references"]["RefIds"][0]["data"], json!({"value": 80, "next": {"rid": -2}}));
+}
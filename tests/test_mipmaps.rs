@@ -0,0 +1,84 @@
+mod common;
+
+use common::texture;
+use unity_rs::classes::TextureFormat;
+use unity_rs::UnityError;
+
+/// A DXT1 block of a single color, given as RGB565.
+fn solid_block(color: u16) -> Vec<u8> {
+    [color.to_le_bytes(), color.to_le_bytes(), [0; 2], [0; 2]].concat()
+}
+
+#[test]
+fn test_uncompressed_slices() {
+    // Two images of three levels each, every level a single color.
+    let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 0, 255], [0, 255, 255, 255], [255, 0, 255, 255]];
+    let mut data = Vec::new();
+    for (i, color) in colors.iter().enumerate() {
+        let pixels = [8, 2, 1][i % 3];
+        data.extend(color.repeat(pixels));
+    }
+    let mut texture = texture(TextureFormat::RGBA32, 4, 2, 3, data);
+    texture.image_count = 2;
+    texture.complete_image_size = 44;
+
+    let mips = texture.decode_all_mips().unwrap();
+    assert_eq!(mips.iter().map(|img| img.dimensions()).collect::<Vec<_>>(), [(4, 2), (2, 1), (1, 1)]);
+    for (mip, img) in mips.iter().enumerate() {
+        assert!(img.pixels().all(|p| p.0 == colors[mip]), "level {mip}");
+    }
+    let img = texture.decode_mip(1, 1).unwrap();
+    assert_eq!(img.dimensions(), (2, 1));
+    assert!(img.pixels().all(|p| p.0 == colors[4]));
+
+    let level = texture.mip_level(2, 1).unwrap();
+    assert_eq!((level.width, level.height, level.mip_count, level.image_count), (1, 1, 1, 1));
+    assert_eq!(level.data, colors[5]);
+
+    assert!(matches!(texture.mip_level(3, 0), Err(UnityError::InvalidValue)));
+    assert!(matches!(texture.mip_level(0, 2), Err(UnityError::InvalidValue)));
+}
+
+#[test]
+fn test_block_levels() {
+    // Levels smaller than a block still take a whole one.
+    let data = [vec![0; 32], solid_block(0xf800), solid_block(0x07e0), solid_block(0x001f)].concat();
+    let texture = texture(TextureFormat::DXT1, 8, 8, 4, data);
+    let mips = texture.decode_all_mips().unwrap();
+    assert_eq!(mips.iter().map(|img| img.dimensions()).collect::<Vec<_>>(), [(8, 8), (4, 4), (2, 2), (1, 1)]);
+    assert!(mips[1].pixels().all(|p| p.0 == [255, 0, 0, 255]));
+    assert!(mips[2].pixels().all(|p| p.0 == [0, 255, 0, 255]));
+    assert_eq!(mips[3].get_pixel(0, 0).0, [0, 0, 255, 255]);
+}
+
+#[test]
+fn test_minimum_block_dimensions() {
+    // PVRTC 2bpp levels are at least 2x2 blocks of 8x4, so every level of an 8x8 texture takes 32 bytes.
+    let mut texture = texture(TextureFormat::PVRTC_RGBA2, 8, 8, 4, vec![0; 128]);
+    assert_eq!(texture.mip_level(3, 0).unwrap().data.len(), 32);
+    texture.data.pop();
+    assert!(matches!(texture.mip_level(3, 0), Err(UnityError::Eof)));
+
+    // 3DS ETC is stored in 8x8 tiles.
+    texture.format = TextureFormat::ETC_RGB4_3DS;
+    assert_eq!(texture.mip_level(2, 0).unwrap().data.len(), 32);
+
+    // ASTC 6x6 rounds each level up to whole blocks.
+    texture.format = TextureFormat::ASTC_RGBA_6x6;
+    texture.width = 12;
+    texture.height = 12;
+    texture.mip_count = 3;
+    texture.data = vec![0; 64 + 16 + 16];
+    assert_eq!(texture.mip_level(2, 0).unwrap().data.len(), 16);
+}
+
+#[test]
+fn test_mip_levels() {
+    // Before 5.2 only a flag is stored and the chain goes down to 1x1.
+    let mut texture = texture(TextureFormat::RGBA32, 8, 2, 0, Vec::new());
+    assert_eq!(texture.mip_levels(), 1);
+    texture.mip_map = true;
+    assert_eq!(texture.mip_levels(), 4);
+    assert_eq!(texture.mip_size(3), (1, 1));
+    assert_eq!(texture.mip_size(1), (4, 1));
+}
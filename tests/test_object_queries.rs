@@ -0,0 +1,30 @@
+use unity_rs::classes::Texture2D;
+use unity_rs::{ClassID, Env, Sprite};
+
+#[test]
+fn test_object_queries() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+
+    let sprites: Vec<Sprite> = env.objects_of::<Sprite>().collect::<Result<_, _>>().expect("Read failure");
+    assert_eq!(sprites.len(), env.objects().filter(|x| x.class() == ClassID::Sprite).count());
+    let name = &sprites.first().expect("No Sprite").name;
+
+    let found = env.find_by_name::<Sprite>(name).expect("Read failure").expect("Not found");
+    assert_eq!(&found.name, name);
+    assert!(env.find_by_name::<Texture2D>("no such texture").expect("Read failure").is_none());
+
+    for object in env.objects().filter(|x| x.class() == ClassID::Texture2D) {
+        let texture: Texture2D = object.read().expect("Read failure");
+        assert_eq!(object.peek_name().expect("Peek failure"), Some(texture.name));
+    }
+
+    let globbed = env.objects_by_glob::<Sprite>("char_1016_*").expect("Bad glob").count();
+    let matched = env.objects_by_regex::<Sprite>("^char_1016_").expect("Bad regex").count();
+    let expected = sprites.iter().filter(|x| x.name.starts_with("char_1016_")).count();
+    assert!(expected > 0);
+    assert_eq!(globbed, expected);
+    assert_eq!(matched, expected);
+    assert!(env.objects_by_regex::<Sprite>("(").is_err());
+}
@@ -0,0 +1,37 @@
+use unity_rs::classes::{Material, PPtr, Texture2D};
+use unity_rs::{ClassID, Env, Object, Sprite};
+
+fn assert_owned<T: Send + Sync + 'static>() {}
+
+fn load_sprites() -> Vec<Sprite> {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    env.objects().filter(|x| x.class() == ClassID::Sprite).map(|x| x.read::<Sprite>().expect("Read failure")).collect()
+}
+
+#[test]
+fn test_owned_handles() {
+    assert_owned::<Object>();
+    assert_owned::<PPtr<Texture2D>>();
+    assert_owned::<Sprite>();
+    assert_owned::<Material>();
+
+    let sprites = load_sprites();
+    assert!(!sprites.is_empty());
+    let handles: Vec<_> = sprites
+        .into_iter()
+        .map(|sprite| {
+            std::thread::spawn(move || {
+                let texture = sprite.rd.texture.get_obj().expect("No texture");
+                assert_eq!(texture.class(), ClassID::Texture2D);
+                let image = sprite.decode_image().expect("Decode failure");
+                (image.width(), image.height())
+            })
+        })
+        .collect();
+    for handle in handles {
+        let (width, height) = handle.join().expect("Thread panicked");
+        assert!(width > 0 && height > 0);
+    }
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use rayon::prelude::*;
+use std::sync::Arc;
+use unity_rs::bundle::AssetBundle;
+use unity_rs::classes::Texture2D;
+use unity_rs::{ClassID, Env};
+
+#[test]
+fn test_parallel() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let mut env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    env.thread_pool = Some(Arc::new(rayon::ThreadPoolBuilder::new().num_threads(2).build().expect("Pool failure")));
+
+    assert_eq!(env.install(|| env.par_objects().count()), env.objects().count());
+    let sprites = env.objects().filter(|x| x.class() == ClassID::Sprite).count();
+    assert_eq!(env.install(|| env.par_objects_with_class(ClassID::Sprite).count()), sprites);
+
+    let textures = env.decode_all_textures();
+    assert_eq!(textures.len(), env.objects().filter(|x| x.class() == ClassID::Texture2D).count());
+    for texture in textures {
+        let texture = texture.expect("Decode failure");
+        let cached = env.cache.get(&texture.image_key()).expect("Not cached");
+        assert_eq!(cached.width() as i32, texture.width);
+        assert!(std::ptr::eq(&*texture.decode_image().expect("Decode failure"), &*cached));
+    }
+}
+
+#[test]
+fn test_cache_per_asset() {
+    // The same textures under another asset path share their path_ids but not their cache entries.
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let fixture = AssetBundle::from_slice(bundle).expect("Load failure");
+    let mut files = fixture.nodes.iter().zip(&fixture.files).map(|(node, file)| (node.path.clone(), file.to_vec())).collect::<Vec<_>>();
+    files[0].0 = "CAB-copy".to_string();
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    env.load_from_slice(&common::bundle("2017.4.39f1", &files)).expect("Load failure");
+
+    let textures = env.objects().filter(|x| x.class() == ClassID::Texture2D).map(|x| x.read::<Texture2D>().expect("Read failure")).collect::<Vec<_>>();
+    let (first, second) = textures.split_at(textures.len() / 2);
+    for (a, b) in first.iter().zip(second) {
+        assert_eq!(a.path_id, b.path_id);
+        assert_ne!(a.image_key(), b.image_key());
+    }
+    for texture in &textures {
+        texture.decode_image().expect("Decode failure");
+    }
+    assert_eq!(env.cache.len(), textures.len());
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use unity_rs::classes::AnyObject;
+use unity_rs::{ClassID, Env};
+
+#[test]
+fn test_read_any() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+
+    let mut fallbacks = 0;
+    for object in env.objects() {
+        let any = object.read_any().expect("Read failure");
+        assert_eq!(any.class(), object.class());
+        match any {
+            AnyObject::Texture2D(texture) => assert_eq!(object.peek_name().expect("Peek failure").as_deref(), Some(texture.name.as_str())),
+            AnyObject::Sprite(sprite) => assert!(!sprite.name.is_empty()),
+            AnyObject::TypeTree(class, _) | AnyObject::Raw(class, _) => {
+                assert!(!matches!(class, ClassID::Texture2D | ClassID::Sprite));
+                fallbacks += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(fallbacks > 0);
+}
+
+#[test]
+fn test_read_any_shader() {
+    let name = "Custom/Outline";
+    let mut object = (name.len() as i32).to_le_bytes().to_vec();
+    object.extend(name.bytes());
+    let files = [("CAB-shader".to_string(), common::serialized_file("2017.4.39f1", ClassID::Shader as i32, object))];
+    let env = Env::new();
+    env.load_from_slice(&common::bundle("2017.4.39f1", &files)).expect("Load failure");
+
+    let object = env.objects().next().expect("No object");
+    let any = object.read_any().expect("Read failure");
+    assert_eq!(any.class(), ClassID::Shader);
+    assert_eq!(any.name(), Some(name));
+    assert!(matches!(any, AnyObject::Shader(shader) if shader.name == name));
+}
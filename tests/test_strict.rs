@@ -0,0 +1,114 @@
+use serde_json::Value;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Barrier, LazyLock};
+use unity_rs::classes::{FromObject, GameObject, Sprite, Texture2D};
+use unity_rs::{ClassID, Env, Object, UnityError, UnityResult};
+
+#[test]
+fn test_strict_read() {
+    let mut env = Env::new();
+    env.strict = true;
+    env.load_from_slice(include_bytes!("../examples/unpack_image/char_1016_agoat2.ab")).expect("Load failure");
+
+    let mut counts = [0; 3];
+    for object in env.objects() {
+        match object.class() {
+            ClassID::GameObject => {
+                let game_object = object.read::<GameObject>().unwrap();
+                let tree = object.info.read_type_tree().unwrap();
+                assert_eq!(game_object.is_active, tree["m_IsActive"].as_bool().unwrap());
+                counts[0] += 1;
+            }
+            ClassID::Sprite => {
+                object.read::<Sprite>().unwrap();
+                counts[1] += 1;
+            }
+            ClassID::Texture2D => {
+                object.read::<Texture2D>().unwrap();
+                counts[2] += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(counts.iter().all(|x| *x > 0), "{counts:?}");
+}
+
+/// The name of a Texture2D, which the loader reads without the rest of the object.
+struct TextureName {
+    name: String,
+    from_type_tree: bool,
+}
+
+impl FromObject for TextureName {
+    fn load(object: &Object) -> UnityResult<Self> {
+        let name = object.info.get_reader().read_aligned_string()?;
+        Ok(Self { name, from_type_tree: false })
+    }
+
+    fn load_type_tree(_object: &Object, tree: &Value) -> UnityResult<Self> {
+        let name = tree["m_Name"].as_str().unwrap().to_string();
+        Ok(Self { name, from_type_tree: true })
+    }
+
+    fn class() -> ClassID {
+        ClassID::Texture2D
+    }
+}
+
+#[test]
+fn test_strict_fallback() {
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let mut env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    let object = env.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+    let texture = object.read::<Texture2D>().unwrap();
+    assert!(!object.read::<TextureName>().unwrap().from_type_tree);
+
+    // A loader that stops short of the end of a known version is replaced by the TypeTree.
+    env.strict = true;
+    let object = env.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+    let read = object.read::<TextureName>().unwrap();
+    assert!(read.from_type_tree);
+    assert_eq!(read.name, texture.name);
+
+    // Without a TypeTree the strict error stands.
+    let mut stripped = object.clone();
+    stripped.info.serialized_type.type_tree.nodes.clear();
+    assert!(matches!(stripped.read::<TextureName>().err().as_ref().map(UnityError::root), Some(UnityError::Consumption(_))));
+}
+
+/// Reads the whole object in the first call, then holds it until a second call has started before either returns.
+struct Racing;
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+static BARRIER: LazyLock<Barrier> = LazyLock::new(|| Barrier::new(2));
+
+impl FromObject for Racing {
+    fn load(object: &Object) -> UnityResult<Self> {
+        if CALLS.fetch_add(1, Ordering::SeqCst) == 0 {
+            object.info.get_reader().read_u8_slice(object.info.bytes_size)?;
+        }
+        BARRIER.wait();
+        Ok(Racing)
+    }
+
+    fn class() -> ClassID {
+        ClassID::Texture2D
+    }
+}
+
+#[test]
+fn test_strict_shared_handle() {
+    let mut env = Env::new();
+    env.strict = true;
+    env.load_from_slice(include_bytes!("../examples/unpack_image/char_1016_agoat2.ab")).expect("Load failure");
+
+    // Each read of one handle checks where its own loader stopped, whatever other threads read meanwhile.
+    let texture = env.objects().find(|x| x.class() == ClassID::Texture2D).expect("No Texture2D");
+    let results = std::thread::scope(|s| {
+        let threads = [s.spawn(|| texture.read::<Racing>()), s.spawn(|| texture.read::<Racing>())];
+        threads.map(|x| x.join().unwrap())
+    });
+    assert_eq!(results.iter().filter(|x| x.is_ok()).count(), 1);
+    assert!(results.iter().any(|x| matches!(x.as_ref().map_err(UnityError::root), Err(UnityError::Consumption(_)))));
+}
@@ -0,0 +1,132 @@
+mod common;
+
+use common::texture;
+use texture_decoder::swizzle::{deswizzle_switch, untile_ps4};
+use unity_rs::classes::{Texture2DArray, Texture3D, TextureFormat};
+
+// Where each of the 32 units of a GOB goes, in units across and rows down.
+const GOB_X: [usize; 32] = [0, 1, 0, 1, 2, 3, 2, 3, 0, 1, 0, 1, 2, 3, 2, 3, 0, 1, 0, 1, 2, 3, 2, 3, 0, 1, 0, 1, 2, 3, 2, 3];
+const GOB_Y: [usize; 32] = [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 3, 3, 2, 2, 3, 3, 4, 4, 5, 5, 4, 4, 5, 5, 6, 6, 7, 7, 6, 6, 7, 7];
+
+/// Lays out `linear`, `units_x` by `units_y` 16-byte units padded to whole GOBs and blocks, as the Switch stores it.
+fn swizzle_switch(linear: &[u8], units_x: usize, units_y: usize, gobs_per_block: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    for row in 0..units_y.div_ceil(8 * gobs_per_block) {
+        for gob_x in 0..units_x.div_ceil(4) {
+            for gob_y in 0..gobs_per_block {
+                for i in 0..32 {
+                    let (x, y) = (gob_x * 4 + GOB_X[i], (row * gobs_per_block + gob_y) * 8 + GOB_Y[i]);
+                    if x < units_x && y < units_y {
+                        out.extend(&linear[(y * units_x + x) * 16..][..16]);
+                    } else {
+                        out.extend([0xee; 16]);
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Lays out `linear` in the 8x8 Morton tiles of the PS4.
+fn tile_ps4(linear: &[u8], blocks_x: usize, blocks_y: usize, block_bytes: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    for tile_y in 0..blocks_y.div_ceil(8) {
+        for tile_x in 0..blocks_x.div_ceil(8) {
+            for i in 0..64 {
+                let x = tile_x * 8 + (i & 1 | i >> 1 & 2 | i >> 2 & 4);
+                let y = tile_y * 8 + (i >> 1 & 1 | i >> 2 & 2 | i >> 3 & 4);
+                if x < blocks_x && y < blocks_y {
+                    out.extend(&linear[(y * blocks_x + x) * block_bytes..][..block_bytes]);
+                } else {
+                    out.extend(vec![0xee; block_bytes]);
+                }
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn test_deswizzle_switch() {
+    // 10x20 blocks of 8 bytes, two to a unit, in blocks two GOBs high.
+    let linear = (0..10 * 20 * 8).map(|i| (i / 8) as u8).collect::<Vec<_>>();
+    let swizzled = swizzle_switch(&linear, 5, 20, 2);
+    assert_eq!(swizzled.len(), 2 * 2 * 2 * 512);
+    assert_eq!(deswizzle_switch(&swizzled, 10, 20, 8, 2).unwrap(), linear);
+    assert!(deswizzle_switch(&swizzled[..512], 10, 20, 8, 2).is_err());
+    assert!(deswizzle_switch(&swizzled, 10, 20, 3, 2).is_err());
+}
+
+#[test]
+fn test_untile_ps4() {
+    // 10x9 blocks of 2 bytes, over 2x2 tiles.
+    let linear = (0..10 * 9 * 2).map(|i| (i / 2) as u8).collect::<Vec<_>>();
+    let tiled = tile_ps4(&linear, 10, 9, 2);
+    assert_eq!(tiled.len(), 4 * 64 * 2);
+    assert_eq!(untile_ps4(&tiled, 10, 9, 2).unwrap(), linear);
+    assert!(untile_ps4(&tiled[..255], 10, 9, 2).is_err());
+}
+
+#[test]
+fn test_console_textures() {
+    let linear = (0..8 * 4 * 4).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+    let expected = texture(TextureFormat::RGBA32, 8, 4, 1, linear.clone()).decode_image_without_cache().unwrap();
+
+    // Four pixels to a unit, so two units across.
+    let mut switch = texture(TextureFormat::RGBA32, 8, 4, 1, swizzle_switch(&linear, 2, 4, 1));
+    switch.target_platform = 38;
+    switch.platform_blob = vec![0; 12];
+    assert_eq!(switch.decode_image_without_cache().unwrap(), expected);
+    assert_eq!(switch.mip_level(0, 0).unwrap().data, linear);
+    // Without the blob the data is taken as linear.
+    switch.platform_blob.clear();
+    assert_ne!(switch.decode_image_without_cache().unwrap(), expected);
+
+    let mut ps4 = texture(TextureFormat::RGBA32, 8, 4, 1, tile_ps4(&linear, 8, 4, 4));
+    ps4.target_platform = 31;
+    assert_eq!(ps4.decode_image_without_cache().unwrap(), expected);
+    assert_eq!(ps4.decode_image_f32().unwrap().get_pixel(1, 0).0, expected.get_pixel(1, 0).0.map(|v| v as f32 / 255.0));
+}
+
+#[test]
+fn test_console_mip_levels() {
+    // Two images of three levels each, numbered apart.
+    let levels = [(16, 32), (8, 16), (4, 8)];
+    let linear = (0..2 * levels.len()).map(|i| (0..levels[i % 3].0 * levels[i % 3].1 * 4).map(|j| (j * 7 + i) as u8).collect::<Vec<_>>()).collect::<Vec<_>>();
+    // Blocks of two GOBs, which the last level is too short for.
+    let switch_data = linear
+        .iter()
+        .enumerate()
+        .flat_map(|(i, level)| swizzle_switch(level, levels[i % 3].0 / 4, levels[i % 3].1, if i % 3 == 2 { 1 } else { 2 }))
+        .collect::<Vec<_>>();
+    let ps4_data = linear.iter().enumerate().flat_map(|(i, level)| tile_ps4(level, levels[i % 3].0, levels[i % 3].1, 4)).collect::<Vec<_>>();
+
+    let mut switch = texture(TextureFormat::RGBA32, 16, 32, 1, switch_data);
+    switch.target_platform = 38;
+    switch.platform_blob = [0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0].to_vec();
+    let mut ps4 = texture(TextureFormat::RGBA32, 16, 32, 1, ps4_data.clone());
+    ps4.target_platform = 31;
+    for texture in [&mut switch, &mut ps4] {
+        texture.mip_count = 3;
+        texture.image_count = 2;
+        for (i, level) in linear.iter().enumerate() {
+            assert_eq!(&texture.mip_level(i % 3, i / 3).unwrap().data, level);
+        }
+    }
+
+    let mut array = Texture2DArray::default();
+    array.format = TextureFormat::RGBA32;
+    (array.width, array.height, array.depth, array.mip_count) = (16, 32, 2, 3);
+    array.target_platform = 31;
+    array.data = ps4_data;
+    assert_eq!(array.slice(1).unwrap().mip_level(2, 0).unwrap().data, linear[5]);
+
+    // Each depth slice is padded to whole tiles.
+    let mut volume = Texture3D::default();
+    volume.format = TextureFormat::RGBA32;
+    (volume.width, volume.height, volume.depth) = (8, 4, 2);
+    volume.target_platform = 31;
+    volume.data = [tile_ps4(&linear[0][..128], 8, 4, 4), tile_ps4(&linear[1][..128], 8, 4, 4)].concat();
+    assert_eq!(volume.slice(1).unwrap().mip_level(0, 0).unwrap().data, linear[1][..128]);
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use common::texture;
+use unity_rs::classes::{Container, Cubemap, TextureFormat};
+use unity_rs::UnityError;
+
+fn u32_at(data: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(data[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(data: &[u8], at: usize) -> usize {
+    u64::from_le_bytes(data[at..at + 8].try_into().unwrap()) as usize
+}
+
+#[test]
+fn test_dds() {
+    // 4x2, 2x1 and 1x1 levels of RGBA32.
+    let data = (0..(8 + 2 + 1) * 4).map(|i| i as u8).collect::<Vec<_>>();
+    let dds = texture(TextureFormat::RGBA32, 4, 2, 3, data.clone()).to_dds().unwrap();
+    assert_eq!(&dds[..4], b"DDS ");
+    assert_eq!((u32_at(&dds, 4), u32_at(&dds, 12), u32_at(&dds, 16)), (124, 2, 4));
+    // The pitch of a row and the level count.
+    assert_eq!((u32_at(&dds, 20), u32_at(&dds, 28)), (16, 3));
+    assert_eq!(&dds[84..88], b"DX10");
+    // R8G8B8A8_UNORM, a single 2D texture.
+    assert_eq!([u32_at(&dds, 128), u32_at(&dds, 132), u32_at(&dds, 136), u32_at(&dds, 140)], [28, 3, 0, 1]);
+    assert_eq!(dds[148..], data);
+
+    // sRGB BC1, with the size of the top level.
+    let mut bc1 = texture(TextureFormat::DXT1, 8, 8, 1, vec![7; 32]);
+    bc1.color_space = 1;
+    let (container, dds) = bc1.export_container().unwrap();
+    assert_eq!(container, Container::Dds);
+    assert_eq!((u32_at(&dds, 20), u32_at(&dds, 128)), (32, 72));
+    assert_eq!(dds.len(), 148 + 32);
+
+    // RGB24 has no DXGI format and takes the legacy masks.
+    let dds = texture(TextureFormat::RGB24, 1, 1, 1, vec![1, 2, 3]).to_dds().unwrap();
+    assert_eq!(&dds[84..88], &[0; 4]);
+    assert_eq!([u32_at(&dds, 80), u32_at(&dds, 88), u32_at(&dds, 92)], [0x40, 24, 0xff]);
+    assert_eq!(dds[128..], [1, 2, 3]);
+
+    // 16-bit pixels are turned little-endian for their masks.
+    let dds = texture(TextureFormat::RGBA4444, 2, 1, 1, vec![0x12, 0x34, 0x56, 0x78]).to_dds().unwrap();
+    assert_eq!([u32_at(&dds, 80), u32_at(&dds, 88), u32_at(&dds, 92), u32_at(&dds, 104)], [0x41, 16, 0xf000, 0x000f]);
+    assert_eq!(u32_at(&dds, 20), 4);
+    assert_eq!(dds[128..], [0x34, 0x12, 0x78, 0x56]);
+    let dds = texture(TextureFormat::RGB565, 1, 1, 1, vec![0xf8, 0x00]).to_dds().unwrap();
+    assert_eq!([u32_at(&dds, 80), u32_at(&dds, 92), u32_at(&dds, 96), u32_at(&dds, 100)], [0x40, 0xf800, 0x07e0, 0x001f]);
+    assert_eq!(dds[128..], [0x00, 0xf8]);
+    assert_eq!(u32_at(&texture(TextureFormat::ARGB4444, 1, 1, 1, vec![0; 2]).to_dds().unwrap(), 104), 0xf000);
+
+    assert!(matches!(texture(TextureFormat::ETC2_RGB, 4, 4, 1, vec![0; 8]).to_dds(), Err(UnityError::Unimplemented)));
+}
+
+#[test]
+fn test_dds_cubemap() {
+    let data = (0..6).flat_map(|face| [face as u8; 16]).collect::<Vec<_>>();
+    let mut texture = texture(TextureFormat::DXT5, 4, 4, 1, data.clone());
+    texture.image_count = 6;
+    texture.complete_image_size = 16;
+    let dds = Cubemap { texture, source_textures: Vec::new() }.to_dds().unwrap();
+    // Complex texture, all six faces, and a single cube.
+    assert_eq!((u32_at(&dds, 108) & 0x8, u32_at(&dds, 112)), (0x8, 0xfe00));
+    assert_eq!([u32_at(&dds, 128), u32_at(&dds, 136), u32_at(&dds, 140)], [77, 0x4, 1]);
+    assert_eq!(dds[148..], data);
+}
+
+#[test]
+fn test_ktx2() {
+    // 8x8 and 4x4 levels of ETC2 RGBA8.
+    let (top, small) = (vec![1; 64], vec![2; 16]);
+    let (container, ktx) = texture(TextureFormat::ETC2_RGBA8, 8, 8, 2, [top.clone(), small.clone()].concat()).export_container().unwrap();
+    assert_eq!(container, Container::Ktx2);
+    assert_eq!(ktx[..12], [0xab, b'K', b'T', b'X', b' ', b'2', b'0', 0xbb, b'\r', b'\n', 0x1a, b'\n']);
+    // VK_FORMAT_ETC2_R8G8B8A8_UNORM_BLOCK, 8x8, not an array, one face, two levels.
+    let header = (0..9).map(|i| u32_at(&ktx, 12 + i * 4)).collect::<Vec<_>>();
+    assert_eq!(header, [151, 1, 8, 8, 0, 0, 1, 2, 0]);
+
+    // The descriptor: the ETC2 model and an alpha then a color sample of 64 bits each.
+    let (dfd_offset, dfd_length) = (u32_at(&ktx, 48) as usize, u32_at(&ktx, 52) as usize);
+    assert_eq!(dfd_offset, 80 + 2 * 24);
+    assert_eq!((dfd_length, u32_at(&ktx, dfd_offset)), (60, 60));
+    let dfd = &ktx[dfd_offset..dfd_offset + dfd_length];
+    assert_eq!(dfd[12..16], [161, 1, 1, 0]);
+    assert_eq!(dfd[16..18], [3, 3]);
+    assert_eq!(dfd[20], 16);
+    assert_eq!(dfd[28..32], [0, 0, 63, 15]);
+    assert_eq!(dfd[44..48], [64, 0, 63, 2]);
+
+    let (kvd_offset, kvd_length) = (u32_at(&ktx, 56) as usize, u32_at(&ktx, 60) as usize);
+    assert_eq!(&ktx[kvd_offset + 4..kvd_offset + kvd_length], b"KTXorientation\0ru\0\0\0");
+
+    // The smallest level comes first, aligned to the block size.
+    let (level0, level1) = ((u64_at(&ktx, 80), u64_at(&ktx, 88)), (u64_at(&ktx, 104), u64_at(&ktx, 112)));
+    assert!(level1.0 < level0.0);
+    assert_eq!((level0.0 % 16, level1.0 % 16), (0, 0));
+    assert_eq!(ktx[level0.0..level0.0 + level0.1], top);
+    assert_eq!(ktx[level1.0..level1.0 + level1.1], small);
+    assert_eq!(ktx.len(), level0.0 + level0.1);
+}
+
+#[test]
+fn test_ktx2_formats() {
+    let mut astc = texture(TextureFormat::ASTC_RGBA_6x6, 12, 12, 1, vec![0; 64]);
+    astc.color_space = 1;
+    let ktx = astc.to_ktx2().unwrap();
+    // VK_FORMAT_ASTC_6x6_SRGB_BLOCK with the sRGB transfer function.
+    assert_eq!(u32_at(&ktx, 12), 166);
+    let dfd = u32_at(&ktx, 48) as usize;
+    assert_eq!(ktx[dfd + 12..dfd + 18], [162, 1, 2, 0, 5, 5]);
+
+    // EAC has no sRGB variant.
+    let mut eac = texture(TextureFormat::EAC_RG_SIGNED, 4, 4, 1, vec![0; 16]);
+    eac.color_space = 1;
+    assert_eq!(u32_at(&eac.to_ktx2().unwrap(), 12), 156);
+
+    assert_eq!(Container::for_format(TextureFormat::PVRTC_RGBA4), Some(Container::Ktx2));
+    assert_eq!(Container::for_format(TextureFormat::DXT5Crunched), Some(Container::Dds));
+    assert_eq!(Container::for_format(TextureFormat::ATC_RGB4), None);
+    assert!(matches!(texture(TextureFormat::DXT1, 4, 4, 1, vec![0; 8]).to_ktx2(), Err(UnityError::Unimplemented)));
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use common::texture;
+use unity_rs::classes::{ExportOptions, LightmapEncoding, TextureFormat};
+
+fn assert_near(actual: [f32; 4], expected: [f32; 4]) {
+    assert!(actual.iter().zip(&expected).all(|(a, e)| (a - e).abs() < 1e-3), "{actual:?} for {expected:?}");
+}
+
+#[test]
+fn test_default_options() {
+    // Without options, textures of every kind come out as stored.
+    for (color_space, light_map_format) in [(0, 0), (1, 0), (0, 2), (0, 3)] {
+        let mut texture = texture(TextureFormat::RGBA32, 1, 1, 1, vec![10, 128, 200, 51]);
+        texture.color_space = color_space;
+        texture.light_map_format = light_map_format;
+        let options = ExportOptions::default();
+        assert_eq!(texture.export_image(&options).unwrap(), texture.decode_image_without_cache().unwrap());
+        assert_eq!(texture.export_image_f32(&options).unwrap(), texture.decode_image_f32().unwrap());
+    }
+}
+
+#[test]
+fn test_color_space() {
+    let options = ExportOptions { color_space: true, ..Default::default() };
+
+    // Linear textures are encoded to sRGB for 8-bit output and kept for floats.
+    let mut linear = texture(TextureFormat::RGBA32, 1, 1, 1, vec![0, 55, 255, 55]);
+    linear.color_space = 0;
+    assert_eq!(linear.export_image(&options).unwrap().get_pixel(0, 0).0, [0, 128, 255, 55]);
+    assert_eq!(linear.export_image_f32(&options).unwrap(), linear.decode_image_f32().unwrap());
+
+    // sRGB textures are decoded to linear for floats and kept for 8-bit output.
+    let mut srgb = texture(TextureFormat::RGBA32, 1, 1, 1, vec![0, 130, 255, 130]);
+    srgb.color_space = 1;
+    assert_near(srgb.export_image_f32(&options).unwrap().get_pixel(0, 0).0, [0.0, 0.2232, 1.0, 130.0 / 255.0]);
+    assert_eq!(srgb.export_image(&options).unwrap().get_pixel(0, 0).0, [0, 130, 255, 130]);
+}
+
+#[test]
+fn test_lightmaps() {
+    let options = ExportOptions { lightmaps: true, ..Default::default() };
+    let lightmap = |format, data, light_map_format| {
+        let mut texture = texture(format, 1, 1, 1, data);
+        texture.light_map_format = light_map_format;
+        texture
+    };
+
+    // RGBM: the color times 5 times alpha, then the gamma of 2.2.
+    let rgbm = lightmap(TextureFormat::RGBA32, vec![255, 51, 0, 102], 2);
+    assert_eq!(rgbm.lightmap_encoding(), Some(LightmapEncoding::Rgbm));
+    assert_near(rgbm.export_image_f32(&options).unwrap().get_pixel(0, 0).0, [2f32.powf(2.2), 0.4f32.powf(2.2), 0.0, 1.0]);
+    // Light past 1 is clamped in 8 bits, the rest encoded to sRGB.
+    let pixel = rgbm.export_image(&options).unwrap().get_pixel(0, 0).0;
+    assert_eq!([pixel[0], pixel[2], pixel[3]], [255, 0, 255]);
+    assert!(pixel[1].abs_diff(102) <= 1, "{pixel:?}");
+
+    // Double LDR: twice the color, then the gamma of 2.2.
+    let double_ldr = lightmap(TextureFormat::RGBA32, vec![51, 102, 255, 0], 8);
+    assert_eq!(double_ldr.lightmap_encoding(), Some(LightmapEncoding::DoubleLdr));
+    assert_near(double_ldr.export_image_f32(&options).unwrap().get_pixel(0, 0).0, [0.4f32.powf(2.2), 0.8f32.powf(2.2), 2f32.powf(2.2), 1.0]);
+
+    // Full HDR lightmaps already hold linear light.
+    let values = [2.5f32, 0.5, 0.0, 1.0];
+    let full_hdr = lightmap(TextureFormat::RGBAFloat, values.iter().flat_map(|v| v.to_le_bytes()).collect(), 10);
+    assert_eq!(full_hdr.lightmap_encoding(), Some(LightmapEncoding::FullHdr));
+    assert_near(full_hdr.export_image_f32(&options).unwrap().get_pixel(0, 0).0, values);
+
+    assert_eq!(lightmap(TextureFormat::RGBA32, vec![0; 4], 0).lightmap_encoding(), None);
+}
+
+#[test]
+fn test_normal_maps() {
+    let options = ExportOptions { normal_maps: true, ..Default::default() };
+
+    // BC5 holds X in red and Y in green: two BC4 blocks of a single value each.
+    let mut data = vec![255, 0, 0, 0, 0, 0, 0, 0];
+    data.extend([128, 0, 0, 0, 0, 0, 0, 0]);
+    let bc5 = texture(TextureFormat::BC5, 1, 1, 1, data);
+    assert!(bc5.is_normal_map());
+    assert_eq!(bc5.export_image(&options).unwrap().get_pixel(0, 0).0, [255, 128, 128, 255]);
+
+    // DXT5nm holds X in alpha and Y in green, with red left at 1.
+    let mut dxt5nm = texture(TextureFormat::RGBA32, 1, 1, 1, vec![255, 128, 0, 128]);
+    dxt5nm.light_map_format = 3;
+    assert!(dxt5nm.is_normal_map());
+    assert_eq!(dxt5nm.export_image(&options).unwrap().get_pixel(0, 0).0, [128, 128, 255, 255]);
+    let normal = dxt5nm.export_image_f32(&options).unwrap().get_pixel(0, 0).0;
+    assert!((normal[2] - 1.0).abs() < 1e-3, "{normal:?}");
+
+    // Normal maps are left out of the color space conversion.
+    dxt5nm.color_space = 0;
+    assert_eq!(dxt5nm.export_image(&ExportOptions::all()).unwrap().get_pixel(0, 0).0, [128, 128, 255, 255]);
+
+    assert!(!texture(TextureFormat::RGBA32, 1, 1, 1, vec![0; 4]).is_normal_map());
+}
@@ -0,0 +1,218 @@
+mod common;
+
+use image::{Rgba, RgbaImage};
+use std::sync::Arc;
+use texture_decoder::block;
+use texture_decoder::ImageSize;
+use unity_rs::classes::{FromObject, Texture2D, TextureFormat};
+use unity_rs::{ClassID, Env, UnityError};
+
+/// An image that runs from `from` at the top left to `to` at the bottom right.
+fn gradient(width: u32, height: u32, from: [u8; 4], to: [u8; 4]) -> RgbaImage {
+    let span = (width + height - 2).max(1) as f32;
+    RgbaImage::from_fn(width, height, |x, y| {
+        let t = (x + y) as f32 / span;
+        Rgba([0, 1, 2, 3].map(|c| (from[c] as f32 + (to[c] as f32 - from[c] as f32) * t).round() as u8))
+    })
+}
+
+fn assert_close(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) {
+    assert_eq!(actual.dimensions(), expected.dimensions());
+    for (x, y, pixel) in expected.enumerate_pixels() {
+        let decoded = actual.get_pixel(x, y);
+        assert!((0..4).all(|c| decoded[c].abs_diff(pixel[c]) <= tolerance), "({x}, {y}): {:?} for {:?}", decoded.0, pixel.0);
+    }
+}
+
+#[test]
+fn test_exact_formats() {
+    // Formats of fewer bits give back the nearest color they hold.
+    let expand = |value: u8, bits: u32| {
+        let level = (value as u32 * ((1 << bits) - 1) + 127) / 255;
+        (level << (8 - bits) | level >> (2 * bits - 8)) as u8
+    };
+    let colors = [[0, 0, 0, 0], [255, 255, 255, 255], [136, 17, 238, 68], [132, 130, 66, 255], [255, 0, 132, 17]];
+    let img = RgbaImage::from_fn(5, 3, |x, y| Rgba(colors[((x + y * 2) % 5) as usize]));
+    let formats = [TextureFormat::RGBA32, TextureFormat::ARGB32, TextureFormat::RGB24, TextureFormat::RGBA4444, TextureFormat::RGB565, TextureFormat::Alpha8];
+    for format in formats {
+        let expected = |[r, g, b, a]: [u8; 4]| match format {
+            TextureFormat::RGB24 => [r, g, b, 255],
+            TextureFormat::RGBA4444 => [r, g, b, a].map(|c| expand(c, 4)),
+            TextureFormat::RGB565 => [expand(r, 5), expand(g, 6), expand(b, 5), 255],
+            TextureFormat::Alpha8 => [255, 255, 255, a],
+            _ => [r, g, b, a],
+        };
+        let mut texture = common::texture(format, 0, 0, 1, Vec::new());
+        texture.set_image(&img).unwrap();
+        assert_eq!((texture.width, texture.height), (5, 3));
+        assert_eq!(texture.complete_image_size as usize, texture.data.len());
+        let decoded = texture.decode_image_without_cache().unwrap();
+        for (x, y, pixel) in img.enumerate_pixels() {
+            assert_eq!(decoded.get_pixel(x, y).0, expected(pixel.0), "{format:?} at ({x}, {y})");
+        }
+    }
+
+    // The bottom row comes first.
+    let mut texture = common::texture(TextureFormat::RGBA32, 0, 0, 1, Vec::new());
+    texture.set_image(&img).unwrap();
+    assert_eq!(texture.data[..4], img.get_pixel(0, 2).0);
+}
+
+#[test]
+fn test_regenerated_mipmaps() {
+    let img = RgbaImage::from_pixel(8, 4, Rgba([10, 20, 30, 255]));
+    let mut texture = common::texture(TextureFormat::RGBA32, 0, 0, 3, Vec::new());
+    texture.set_image(&img).unwrap();
+    assert_eq!(texture.mip_count, 3);
+    assert_eq!(texture.complete_image_size, (32 + 8 + 2) * 4);
+    let mips = texture.decode_all_mips().unwrap();
+    assert_eq!(mips.iter().map(|img| img.dimensions()).collect::<Vec<_>>(), [(8, 4), (4, 2), (2, 1)]);
+    assert!(mips.iter().all(|mip| mip.pixels().all(|p| p.0 == [10, 20, 30, 255])));
+
+    // A smaller image keeps the chain down to 1x1.
+    let mut texture = common::texture(TextureFormat::RGBA32, 0, 0, 10, Vec::new());
+    texture.set_image(&RgbaImage::new(4, 2)).unwrap();
+    assert_eq!(texture.mip_count, 3);
+    assert_eq!(texture.data.len(), (8 + 2 + 1) * 4);
+
+    // Before 5.2 there is only the flag, and the chain follows the new size.
+    let mut texture = common::texture(TextureFormat::RGBA32, 0, 0, 0, Vec::new());
+    texture.mip_map = true;
+    texture.set_image(&RgbaImage::new(2, 2)).unwrap();
+    assert_eq!(texture.mip_levels(), 2);
+    assert_eq!(texture.data.len(), (4 + 1) * 4);
+}
+
+#[test]
+fn test_unsupported() {
+    for format in [TextureFormat::DXT1, TextureFormat::ASTC_HDR_4x4, TextureFormat::DXT5Crunched] {
+        assert!(matches!(common::texture(format, 0, 0, 1, Vec::new()).set_image(&RgbaImage::new(4, 4)), Err(UnityError::Unimplemented)), "{format:?}");
+    }
+    assert!(matches!(common::texture(TextureFormat::RGBA32, 0, 0, 1, Vec::new()).set_image(&RgbaImage::new(0, 4)), Err(UnityError::ZeroSizeImage)));
+}
+
+#[test]
+fn test_astc() {
+    for (format, block_width, block_height) in [(TextureFormat::ASTC_RGBA_4x4, 4, 4), (TextureFormat::ASTC_RGB_6x6, 6, 6), (TextureFormat::ASTC_RGBA_8x8, 8, 8)] {
+        let img = gradient(13, 9, [250, 10, 40, 255], [20, 200, 120, 100]);
+        let mut texture = common::texture(format, 0, 0, 1, Vec::new());
+        texture.set_image(&img).unwrap();
+        assert_eq!(texture.data.len(), 13usize.div_ceil(block_width) * 9usize.div_ceil(block_height) * 16);
+        let decoded = block::decode_astc(&ImageSize::new(13, 9), &texture.data, block_width, block_height).unwrap();
+        let decoded = image::DynamicImage::ImageRgba32F(decoded).into_rgba8();
+        // Larger blocks stretch the same 16 weights over more texels.
+        assert_close(&decoded, &img, 16);
+
+        // A single color is only off by the rounding of the endpoints to 192 values.
+        let img = RgbaImage::from_pixel(7, 5, Rgba([12, 34, 56, 78]));
+        texture.set_image(&img).unwrap();
+        let decoded = block::decode_astc(&ImageSize::new(7, 5), &texture.data, block_width, block_height).unwrap();
+        assert_close(&image::DynamicImage::ImageRgba32F(decoded).into_rgba8(), &img, 1);
+    }
+}
+
+/// Rearranges ETC blocks into the little-endian 8x8 tiles of the 3DS, so that the ETC1 decoder can read them.
+fn to_3ds(data: &[u8], blocks_x: usize, blocks_y: usize) -> Vec<u8> {
+    let mut tiled = Vec::new();
+    for ty in (0..blocks_y).step_by(2) {
+        for tx in (0..blocks_x).step_by(2) {
+            for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                let offset = ((ty + dy) * blocks_x + tx + dx) * 8;
+                tiled.extend(data[offset..offset + 8].iter().rev());
+            }
+        }
+    }
+    tiled
+}
+
+#[test]
+fn test_etc2() {
+    // ETC moves all channels of a half block by the same amount, which suits changes in brightness.
+    let img = gradient(8, 8, [40, 60, 80, 255], [200, 220, 240, 255]);
+    let mut texture = common::texture(TextureFormat::ETC2_RGB, 0, 0, 1, Vec::new());
+    texture.set_image(&img).unwrap();
+    assert_eq!(texture.data.len(), 4 * 8);
+    // Only the ETC1 modes are used, so every block decodes as ETC1.
+    let decoded = block::decode_etc1_3ds(&ImageSize::new(8, 8), &to_3ds(&texture.data, 2, 2), false).unwrap();
+    assert_close(&decoded, &img, 16);
+
+    let img = gradient(8, 8, [40, 60, 80, 0], [200, 220, 240, 255]);
+    let mut texture = common::texture(TextureFormat::ETC2_RGBA8, 0, 0, 1, Vec::new());
+    texture.set_image(&img).unwrap();
+    assert_eq!(texture.data.len(), 4 * 16);
+    let (alpha, color): (Vec<_>, Vec<_>) = texture.data.chunks_exact(16).map(|block| (&block[..8], &block[8..])).unzip();
+    let decoded = block::decode_etc1_3ds(&ImageSize::new(8, 8), &to_3ds(&color.concat(), 2, 2), false).unwrap();
+    let opaque = RgbaImage::from_fn(8, 8, |x, y| {
+        let mut pixel = *img.get_pixel(x, y);
+        pixel[3] = 255;
+        pixel
+    });
+    assert_close(&decoded, &opaque, 16);
+    // The 8-bit alpha blocks share their layout with 11-bit EAC R, which decodes them to nearly the same values.
+    let decoded = block::decode_eac_r(&ImageSize::new(8, 8), &alpha.concat(), false).unwrap();
+    for (x, y, pixel) in img.enumerate_pixels() {
+        assert!(decoded.get_pixel(x, y)[0].abs_diff(pixel[3]) <= 12, "({x}, {y})");
+    }
+}
+
+/// Decodes a BC7 block of mode 6, the only one the encoder writes.
+fn decode_bc7_mode6(block: &[u8]) -> [[u8; 4]; 16] {
+    const WEIGHTS: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+    let bits = u128::from_le_bytes(block.try_into().unwrap());
+    assert_eq!(bits & 0x7f, 0x40);
+    let field = |start: usize, count: usize| (bits >> start) as u32 & ((1 << count) - 1);
+    let ends = [0, 1].map(|e| [0, 1, 2, 3].map(|c| field(7 + c * 14 + e * 7, 7) << 1 | field(63 + e, 1)));
+    let mut texels = [[0; 4]; 16];
+    let mut position = 65;
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let count = if i == 0 { 3 } else { 4 };
+        let w = WEIGHTS[field(position, count) as usize];
+        position += count;
+        *texel = [0, 1, 2, 3].map(|c| (((64 - w) * ends[0][c] + w * ends[1][c] + 32) >> 6) as u8);
+    }
+    texels
+}
+
+#[test]
+fn test_bc7() {
+    let img = gradient(8, 4, [255, 0, 64, 255], [0, 128, 255, 32]);
+    let mut texture = common::texture(TextureFormat::BC7, 0, 0, 1, Vec::new());
+    texture.set_image(&img).unwrap();
+    assert_eq!(texture.data.len(), 2 * 16);
+    for (bx, block) in texture.data.chunks_exact(16).enumerate() {
+        for (i, texel) in decode_bc7_mode6(block).iter().enumerate() {
+            // Rows start from the bottom of the image.
+            let (x, y) = (bx * 4 + i % 4, 3 - i / 4);
+            let expected = img.get_pixel(x as u32, y as u32).0;
+            assert!((0..4).all(|c| texel[c].abs_diff(expected[c]) <= 8), "({x}, {y}): {texel:?} for {expected:?}");
+        }
+    }
+}
+
+#[test]
+fn test_write_object() {
+    let env = Env::new();
+    env.load_from_slice(include_bytes!("../examples/unpack_image/char_1016_agoat2.ab")).unwrap();
+    let object = env.objects().find(|obj| obj.class() == ClassID::Texture2D).unwrap();
+    let mut texture: Texture2D = object.read().unwrap();
+    // A streamed ASTC texture, whose data moves into the object.
+    assert_eq!(texture.format, TextureFormat::ASTC_RGBA_6x6);
+    assert!(!texture.stream_info.path.is_empty());
+    let img = gradient(12, 7, [255, 0, 0, 255], [0, 0, 255, 128]);
+    texture.set_image(&img).unwrap();
+    let bytes = texture.write_object(&object).unwrap();
+
+    let mut patched = object.clone();
+    patched.info.data = Arc::new(bytes);
+    patched.info.bytes_start = 0;
+    patched.info.bytes_size = patched.info.data.len();
+    // The loader's reader is the last one the handle gave out, so it has to stop at the end of the new bytes.
+    let read = Texture2D::load(&patched).unwrap();
+    patched.info.check_consumption().unwrap();
+    assert_eq!(read.name, texture.name);
+    assert_eq!(read.format, TextureFormat::ASTC_RGBA_6x6);
+    assert_eq!((read.width, read.height, read.complete_image_size), (12, 7, 2 * 2 * 16));
+    assert_eq!(read.mip_count, 1);
+    assert_eq!(read.data, texture.data);
+    assert!(read.stream_info.path.is_empty());
+}
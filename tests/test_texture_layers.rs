@@ -0,0 +1,93 @@
+use unity_rs::classes::{Cubemap, Texture2D, Texture2DArray, Texture3D, TextureFormat};
+use unity_rs::UnityError;
+
+const COLORS: [[u8; 4]; 6] = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255], [255, 255, 0, 255], [0, 255, 255, 255], [255, 0, 255, 255]];
+
+#[test]
+fn test_cubemap() {
+    // 2x2 faces, each with a black bottom row.
+    let mut data = Vec::new();
+    for color in COLORS {
+        data.extend(color.repeat(2));
+        data.extend([0, 0, 0, 255].repeat(2));
+    }
+    let mut texture = Texture2D::default();
+    texture.format = TextureFormat::RGBA32;
+    texture.width = 2;
+    texture.height = 2;
+    texture.mip_count = 1;
+    texture.image_count = 6;
+    texture.complete_image_size = 16;
+    texture.data = data;
+    let cubemap = Cubemap { texture, source_textures: Vec::new() };
+
+    // Faces are stored top row first.
+    let faces = cubemap.decode_faces().unwrap();
+    for (face, color) in faces.iter().zip(COLORS) {
+        assert_eq!(face.get_pixel(1, 0).0, color);
+        assert_eq!(face.get_pixel(1, 1).0, [0, 0, 0, 255]);
+    }
+
+    let cross = cubemap.decode_cross().unwrap();
+    assert_eq!(cross.dimensions(), (8, 6));
+    // +X right of +Z, -X at the left end, -Z at the right end, +Y above and -Y below +Z.
+    for (face, (x, y)) in [(4, 2), (0, 2), (2, 0), (2, 4), (2, 2), (6, 2)].into_iter().enumerate() {
+        assert_eq!(cross.get_pixel(x, y).0, COLORS[face], "face {face}");
+    }
+    assert_eq!(cross.get_pixel(0, 0).0, [0, 0, 0, 0]);
+    assert_eq!(cross.get_pixel(7, 5).0, [0, 0, 0, 0]);
+}
+
+#[test]
+fn test_texture2d_array() {
+    // Three 2x1 slices with a 1x1 mip level each.
+    let mut array = Texture2DArray::default();
+    array.format = TextureFormat::RGBA32;
+    array.width = 2;
+    array.height = 1;
+    array.depth = 3;
+    array.mip_count = 2;
+    array.data = COLORS[..3].iter().flat_map(|color| color.repeat(3)).collect();
+
+    let slices = array.decode_slices().unwrap();
+    assert_eq!(slices.len(), 3);
+    for (slice, color) in slices.iter().zip(COLORS) {
+        assert_eq!(slice.dimensions(), (2, 1));
+        assert!(slice.pixels().all(|p| p.0 == color));
+    }
+    let slice = array.slice(1).unwrap();
+    assert_eq!(slice.decode_mip(1, 0).unwrap().get_pixel(0, 0).0, COLORS[1]);
+    assert!(matches!(array.slice(3), Err(UnityError::InvalidValue)));
+}
+
+#[test]
+fn test_texture3d_strip() {
+    // A 2x1x3 volume of R8 followed by a smaller mip level that is ignored.
+    let mut volume = Texture3D::default();
+    volume.format = TextureFormat::R8;
+    volume.width = 2;
+    volume.height = 1;
+    volume.depth = 3;
+    volume.mip_count = 2;
+    volume.data = vec![10, 11, 20, 21, 30, 31, 99];
+
+    let strip = volume.decode_strip().unwrap();
+    assert_eq!(strip.dimensions(), (2, 3));
+    for (i, value) in [10, 11, 20, 21, 30, 31].into_iter().enumerate() {
+        assert_eq!(strip.get_pixel(i as u32 % 2, i as u32 / 2).0, [value, 0, 0, 255]);
+    }
+    assert!(matches!(volume.slice(3), Err(UnityError::InvalidValue)));
+    volume.data.truncate(5);
+    assert!(matches!(volume.slice(2), Err(UnityError::Eof)));
+}
+
+#[test]
+fn test_graphics_format() {
+    assert_eq!(TextureFormat::from_graphics_format(4), TextureFormat::RGBA32);
+    assert_eq!(TextureFormat::from_graphics_format(8), TextureFormat::RGBA32);
+    assert_eq!(TextureFormat::from_graphics_format(48), TextureFormat::RGBAHalf);
+    assert_eq!(TextureFormat::from_graphics_format(101), TextureFormat::DXT5);
+    assert_eq!(TextureFormat::from_graphics_format(130), TextureFormat::ASTC_RGBA_4x4);
+    assert_eq!(TextureFormat::from_graphics_format(150), TextureFormat::ASTC_HDR_12x12);
+    assert_eq!(TextureFormat::from_graphics_format(0), TextureFormat::UnknownType);
+}
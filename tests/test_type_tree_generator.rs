@@ -0,0 +1,206 @@
+use std::sync::Arc;
+use unity_rs::generator::{ElementType, ManagedAssembly, ManagedField, ManagedType, TypeSig, TypeTreeGenerator};
+use unity_rs::Env;
+
+const STRINGS: &[&str] = &[
+    "",
+    "<Module>",
+    "Player",
+    "Game",
+    "Stats",
+    "MonoBehaviour",
+    "UnityEngine",
+    "Object",
+    "System",
+    "hp",
+    "title",
+    "stats",
+    "speed",
+    "cache",
+    "Assembly-CSharp",
+];
+/// Field signatures: int, string, the TypeDef `Stats` and float.
+const BLOBS: &[&[u8]] = &[&[], &[0x06, 0x08], &[0x06, 0x0E], &[0x06, 0x12, 3 << 2], &[0x06, 0x0C]];
+
+fn string(s: &str) -> u16 {
+    let index = STRINGS.iter().position(|x| *x == s).unwrap();
+    STRINGS[..index].iter().map(|x| x.len() as u16 + 1).sum()
+}
+
+fn blob(index: usize) -> u16 {
+    BLOBS[..index].iter().map(|x| x.len() as u16 + 1).sum()
+}
+
+fn u16s(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// A PE image holding the CLI metadata of
+/// `namespace Game { class Player : MonoBehaviour { public int hp; public string title; public Stats stats; int cache;
+/// [Serializable] public class Stats { public float speed; } } }`, with `nesting` as its NestedClass row. A
+/// `hostile` image starts its section table with a section whose end overflows.
+fn assembly(nesting: [u16; 2], hostile: bool) -> Vec<u8> {
+    let mut tables = Vec::new();
+    // TypeRef: resolution scope, name, namespace.
+    tables.extend(u16s(&[0, string("MonoBehaviour"), string("UnityEngine")]));
+    tables.extend(u16s(&[0, string("Object"), string("System")]));
+    // TypeDef: flags, name, namespace, extends, field list, method list.
+    for (flags, name, namespace, extends, fields) in [(0u32, "<Module>", "", 0, 1), (0x1, "Player", "Game", 1 << 2 | 1, 1), (0x2002, "Stats", "", 2 << 2 | 1, 5)] {
+        tables.extend(flags.to_le_bytes());
+        tables.extend(u16s(&[string(name), string(namespace), extends, fields, 1]));
+    }
+    // Field: flags, name, signature.
+    for (flags, name, signature) in [(0x6, "hp", 1), (0x6, "title", 2), (0x6, "stats", 3), (0x1, "cache", 1), (0x6, "speed", 4)] {
+        tables.extend(u16s(&[flags, string(name), blob(signature)]));
+    }
+    // Assembly: hash algorithm, version, flags, public key, name, culture.
+    tables.extend(0u32.to_le_bytes());
+    tables.extend(u16s(&[0; 4]));
+    tables.extend(0u32.to_le_bytes());
+    tables.extend(u16s(&[0, string("Assembly-CSharp"), 0]));
+    // NestedClass: nested, enclosing.
+    tables.extend(u16s(&nesting));
+
+    let valid: u64 = 1 << 0x01 | 1 << 0x02 | 1 << 0x04 | 1 << 0x20 | 1 << 0x29;
+    let mut table_stream = vec![0, 0, 0, 0, 2, 0, 0, 1];
+    table_stream.extend(valid.to_le_bytes());
+    table_stream.extend(0u64.to_le_bytes());
+    for count in [2u32, 3, 5, 1, 1] {
+        table_stream.extend(count.to_le_bytes());
+    }
+    table_stream.extend(tables);
+    table_stream.resize(table_stream.len().next_multiple_of(4), 0);
+    let mut strings = STRINGS.iter().flat_map(|x| x.bytes().chain([0])).collect::<Vec<_>>();
+    strings.resize(strings.len().next_multiple_of(4), 0);
+    let mut blobs = BLOBS.iter().flat_map(|x| [x.len() as u8].into_iter().chain(x.iter().copied())).collect::<Vec<_>>();
+    blobs.resize(blobs.len().next_multiple_of(4), 0);
+
+    let mut root = Vec::new();
+    root.extend(0x424A5342u32.to_le_bytes());
+    root.extend(u16s(&[1, 1]));
+    root.extend(0u32.to_le_bytes());
+    root.extend(12u32.to_le_bytes());
+    root.extend(b"v4.0.30319\0\0");
+    root.extend(u16s(&[0, 3]));
+    let headers_size = 3 * 8 + 4 + 12 + 8;
+    let mut offset = root.len() + headers_size;
+    for (name, stream) in [(&b"#~\0\0"[..], &table_stream), (b"#Strings\0\0\0\0", &strings), (b"#Blob\0\0\0", &blobs)] {
+        root.extend((offset as u32).to_le_bytes());
+        root.extend((stream.len() as u32).to_le_bytes());
+        root.extend(name);
+        offset += stream.len();
+    }
+    root.extend(table_stream);
+    root.extend(strings);
+    root.extend(blobs);
+
+    // The section is mapped at 0x2000 from 0x200 in the file, the CLI header first and the metadata root after it.
+    let mut section = vec![0; 0x48];
+    section[..4].copy_from_slice(&0x48u32.to_le_bytes());
+    section[8..12].copy_from_slice(&0x2048u32.to_le_bytes());
+    section[12..16].copy_from_slice(&(root.len() as u32).to_le_bytes());
+    section.extend(root);
+
+    let mut data = vec![0; 0x200];
+    data[..2].copy_from_slice(b"MZ");
+    data[0x3C..0x40].copy_from_slice(&0x40u32.to_le_bytes());
+    data[0x40..0x44].copy_from_slice(b"PE\0\0");
+    let section_count: u16 = if hostile { 2 } else { 1 };
+    data[0x46..0x48].copy_from_slice(&section_count.to_le_bytes());
+    data[0x54..0x56].copy_from_slice(&224u16.to_le_bytes());
+    data[0x58..0x5A].copy_from_slice(&0x10Bu16.to_le_bytes());
+    // The CLI header is the 15th data directory.
+    data[0x58 + 96 + 14 * 8..0x58 + 96 + 14 * 8 + 4].copy_from_slice(&0x2000u32.to_le_bytes());
+    let mut sections = Vec::new();
+    if hostile {
+        sections.push([0x1000, 0xFFFF_F000, 0x1000, 0]);
+    }
+    sections.push([section.len() as u32, 0x2000, section.len() as u32, 0x200]);
+    for (i, [virtual_size, virtual_address, raw_size, raw_pointer]) in sections.into_iter().enumerate() {
+        let at = 0x58 + 224 + i * 40 + 8;
+        for (j, value) in [virtual_size, virtual_address, raw_size, raw_pointer].into_iter().enumerate() {
+            data[at + j * 4..at + j * 4 + 4].copy_from_slice(&value.to_le_bytes());
+        }
+    }
+    data.extend(section);
+    data
+}
+
+#[test]
+fn test_generate_from_assembly() {
+    let mut generator = TypeTreeGenerator::new();
+    generator.load_assembly(&assembly([3, 2], false)).unwrap();
+    let assembly = &generator.assemblies()[0];
+    assert_eq!(assembly.name, "Assembly-CSharp");
+    let names = assembly.types.iter().map(|x| x.full_name()).collect::<Vec<_>>();
+    assert_eq!(names, ["<Module>", "Game.Player", "Game.Player/Stats"]);
+    let player = generator.find_type(None, "Game.Player").unwrap();
+    assert_eq!(player.base, Some(TypeSig::Named("UnityEngine.MonoBehaviour".to_string())));
+    assert_eq!(player.fields[2].ty, TypeSig::Named("Game.Player/Stats".to_string()));
+
+    let env = Env::new();
+    env.load_from_slice(include_bytes!("../examples/unpack_image/char_1016_agoat2.ab")).unwrap();
+    let tree = generator.generate("Assembly-CSharp.dll", "Game", "Player", &env.objects().next().unwrap().info).unwrap();
+    let fields = tree.nodes.iter().skip_while(|x| x.name != "hp").map(|x| (x.level, x.type_.as_str(), x.name.as_str())).collect::<Vec<_>>();
+    assert_eq!(
+        fields,
+        [(1, "int", "hp"), (1, "string", "title"), (2, "Array", "Array"), (3, "int", "size"), (3, "char", "data"), (1, "Stats", "stats"), (2, "float", "speed")]
+    );
+    assert_eq!(tree.nodes[0].type_, "MonoBehaviour");
+}
+
+#[test]
+fn test_malformed_assembly() {
+    // A nesting row past the TypeDef table leaves the type unnested, and a section whose end overflows is skipped.
+    let mut generator = TypeTreeGenerator::new();
+    generator.load_assembly(&assembly([3, 40], true)).unwrap();
+    let names = generator.assemblies()[0].types.iter().map(|x| x.full_name()).collect::<Vec<_>>();
+    assert_eq!(names, ["<Module>", "Game.Player", "Stats"]);
+
+    let data = assembly([3, 2], false);
+    for len in [0, 0x40, 0x180, 0x220, data.len() - 1] {
+        assert!(TypeTreeGenerator::new().load_assembly(&data[..len]).is_err());
+    }
+}
+
+#[test]
+fn test_byte_array_field() {
+    let field = |name: &str, ty| ManagedField {
+        name: name.to_string(),
+        flags: 0x6,
+        ty,
+        attributes: Vec::new(),
+    };
+    let mut generator = TypeTreeGenerator::new();
+    generator.add_assembly(ManagedAssembly {
+        name: "Assembly-CSharp".to_string(),
+        types: vec![ManagedType {
+            namespace: "Game".to_string(),
+            name: "Save".to_string(),
+            flags: 0x1,
+            base: Some(TypeSig::Named("UnityEngine.MonoBehaviour".to_string())),
+            generic_param_count: 0,
+            fields: vec![field("bytes", TypeSig::SzArray(Box::new(TypeSig::Primitive(ElementType::U1)))), field("after", TypeSig::Primitive(ElementType::I4))],
+            attributes: Vec::new(),
+        }],
+    });
+    let env = Env::new();
+    env.load_from_slice(include_bytes!("../examples/unpack_image/char_1016_agoat2.ab")).unwrap();
+    let mut info = env.objects().next().unwrap().info.clone();
+    let tree = generator.generate("Assembly-CSharp.dll", "Game", "Save", &info).unwrap();
+    let fields = tree.nodes.iter().skip_while(|x| x.name != "bytes").map(|x| (x.type_.as_str(), x.name.as_str(), x.meta_flag)).collect::<Vec<_>>();
+    assert_eq!(fields, [("vector", "bytes", 0x4000), ("Array", "Array", 0x4000), ("int", "size", 0), ("UInt8", "data", 0), ("int", "after", 0)]);
+
+    // The MonoBehaviour header, then three bytes padded to 4 and the int after them.
+    let mut data = vec![0; 12];
+    data.extend([1, 0, 0, 0]);
+    data.extend([0; 12]);
+    data.extend(0i32.to_le_bytes());
+    data.extend(3i32.to_le_bytes());
+    data.extend([7, 8, 9, 0]);
+    data.extend(42i32.to_le_bytes());
+    (info.data, info.bytes_start, info.bytes_size) = (Arc::new(data.clone()), 0, data.len());
+    let values = info.read_type_tree_nodes(&tree.nodes).unwrap();
+    assert_eq!(values["bytes"], serde_json::json!([7, 8, 9]));
+    assert_eq!(values["after"], 42);
+}
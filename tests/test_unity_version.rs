@@ -0,0 +1,26 @@
+use unity_rs::version::BuildType;
+use unity_rs::UnityVersion;
+
+#[test]
+fn test_parse_unity_version() {
+    let version = UnityVersion::parse("2021.3.5f1c1").expect("Parse failure");
+    assert_eq!((version.major, version.minor, version.patch, version.build), (2021, 3, 5, 1));
+    assert_eq!(version.build_type, BuildType::Final);
+    assert_eq!(version.china, Some(1));
+    assert_eq!(version.to_string(), "2021.3.5f1c1");
+
+    let stripped = UnityVersion::parse("0.0.0").expect("Parse failure");
+    assert!(stripped.is_stripped());
+    assert!(UnityVersion::parse("").is_none());
+    assert!(UnityVersion::parse("not.a.version").is_none());
+}
+
+#[test]
+fn test_compare_unity_version() {
+    let version: UnityVersion = "2020.2.0b3".parse().expect("Parse failure");
+    assert!(version >= (2020, 2));
+    assert!(version < (2020, 3));
+    assert!(version == (2020, 2, 0));
+    assert!(version < "2020.2.0f1".parse::<UnityVersion>().unwrap());
+    assert!(UnityVersion::parse("5.4.1p3").unwrap().build_type.is_patch());
+}
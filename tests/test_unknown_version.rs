@@ -0,0 +1,34 @@
+use unity_rs::classes::{Sprite, Texture2D};
+use unity_rs::{ClassID, Env, UnityVersion};
+
+#[test]
+fn test_read_unknown_version() {
+    // A version the loaders were not written against is read through the TypeTree.
+    let bundle = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+    let known = Env::new();
+    known.load_from_slice(bundle).expect("Load failure");
+    let mut env = Env::new();
+    env.load_from_slice(bundle).expect("Load failure");
+    env.set_unity_version(UnityVersion::parse("6000.0.0f1").unwrap());
+
+    let mut counts = [0; 2];
+    for (object, expected) in env.objects().zip(known.objects()) {
+        assert_eq!(object.info.version.major, 6000);
+        match object.class() {
+            ClassID::Texture2D => {
+                let (texture, expected) = (object.read::<Texture2D>().unwrap(), expected.read::<Texture2D>().unwrap());
+                assert_eq!((texture.name, texture.width, texture.height, texture.format), (expected.name, expected.width, expected.height, expected.format));
+                assert_eq!(texture.data, expected.data);
+                counts[0] += 1;
+            }
+            ClassID::Sprite => {
+                let (sprite, expected) = (object.read::<Sprite>().unwrap(), expected.read::<Sprite>().unwrap());
+                assert_eq!((sprite.name, sprite.rect.w, sprite.rect.h), (expected.name, expected.rect.w, expected.rect.h));
+                assert_eq!(sprite.physics_shape.len(), expected.physics_shape.len());
+                counts[1] += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(counts.iter().all(|x| *x > 0), "{counts:?}");
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{bundle, serialized_file};
+use unity_rs::bundle::AssetBundle;
+use unity_rs::classes::{Sprite, Texture2D};
+use unity_rs::{ClassID, Env, UnityVersion};
+
+const FIXTURE: &[u8] = include_bytes!("../examples/unpack_image/char_1016_agoat2.ab");
+const FIXTURE_VERSION: &str = "2017.4.39f1";
+
+/// The files of the fixture, with the version of its serialized file stripped.
+fn stripped_fixture() -> Vec<(String, Vec<u8>)> {
+    let fixture = AssetBundle::from_slice(FIXTURE).unwrap();
+    let mut files = fixture.nodes.iter().zip(&fixture.files).map(|(node, file)| (node.path.clone(), file.to_vec())).collect::<Vec<_>>();
+    let file = &mut files[0].1;
+    let data_offset = u32::from_be_bytes(file[12..16].try_into().unwrap()) as usize;
+    let version = format!("{FIXTURE_VERSION}\0");
+    let at = file.windows(version.len()).position(|x| x == version.as_bytes()).unwrap();
+    // A version 4 bytes shorter, made up for by padding before the object data, keeps the metadata aligned.
+    let mut stripped = file[..at].to_vec();
+    stripped.extend(b"0.0.0f1\0");
+    stripped.extend(&file[at + version.len()..data_offset]);
+    stripped.extend([0; 4]);
+    stripped.extend(&file[data_offset..]);
+    *file = stripped;
+    files
+}
+
+/// A stripped serialized file holding a BuildSettings object with `version` in it.
+fn build_settings(version: &str) -> (String, Vec<u8>) {
+    let mut object = (version.len() as i32).to_le_bytes().to_vec();
+    object.extend(version.bytes());
+    ("globalgamemanagers".to_string(), serialized_file("0.0.0", 141, object))
+}
+
+fn versions(env: &Env) -> Vec<UnityVersion> {
+    env.bundles().iter().flat_map(|x| x.assets.iter().map(|x| x.version)).collect()
+}
+
+fn read_all(env: &Env) {
+    let mut counts = [0; 2];
+    for object in env.objects() {
+        match object.class() {
+            ClassID::Texture2D => {
+                object.read::<Texture2D>().unwrap();
+                counts[0] += 1;
+            }
+            ClassID::Sprite => {
+                object.read::<Sprite>().unwrap();
+                counts[1] += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(counts.iter().all(|x| *x > 0), "{counts:?}");
+}
+
+#[test]
+fn test_set_unity_version() {
+    let version = UnityVersion::parse("2017.3.0f1").unwrap();
+    let mut env = Env::new();
+    env.load_from_slice(FIXTURE).unwrap();
+    env.set_unity_version(version);
+    // Bundles loaded afterwards get the version too, stripped or not.
+    env.load_from_slice(&bundle("0.0.0", &stripped_fixture())).unwrap();
+    assert_eq!(versions(&env), [version, version]);
+    assert!(env.objects().all(|x| x.info.version == version));
+    env.strict = true;
+    read_all(&env);
+}
+
+#[test]
+fn test_revision_from_header() {
+    let env = Env::new();
+    env.load_from_slice(&bundle(FIXTURE_VERSION, &stripped_fixture())).unwrap();
+    assert_eq!(versions(&env), [UnityVersion::parse(FIXTURE_VERSION).unwrap()]);
+
+    let env = Env::new();
+    env.load_from_slice(&bundle("0.0.0", &stripped_fixture())).unwrap();
+    assert!(versions(&env)[0].is_stripped());
+}
+
+#[test]
+fn test_version_from_build_settings() {
+    let version = UnityVersion::parse(FIXTURE_VERSION).unwrap();
+    let mut files = stripped_fixture();
+    files.push(build_settings(FIXTURE_VERSION));
+    let env = Env::new();
+    env.load_from_slice(&bundle("0.0.0", &files)).unwrap();
+    assert_eq!(env.version_from_build_settings(), Some(version));
+    // Loading applies it to the stripped assets.
+    assert_eq!(versions(&env), [version, version]);
+
+    let env = Env::new();
+    env.load_from_slice(&bundle("0.0.0", &stripped_fixture())).unwrap();
+    assert_eq!(env.version_from_build_settings(), None);
+}
+
+#[test]
+fn test_probe_unity_version() {
+    let mut env = Env::new();
+    env.load_from_slice(&bundle("0.0.0", &stripped_fixture())).unwrap();
+    // 2017.3 is the newest candidate with the layout of 2017.4 for both Texture2D and Sprite.
+    let expected = UnityVersion::new(2017, 3, 0);
+    assert_eq!(env.probe_unity_version(), Some(expected));
+    assert_eq!(env.infer_unity_version(), Some(expected));
+    assert_eq!(versions(&env), [expected]);
+    env.strict = true;
+    read_all(&env);
+    assert_eq!(env.infer_unity_version(), None);
+}
@@ -0,0 +1,647 @@
+use half::f16;
+
+/// Blocks that break the rules of the format decode to magenta.
+const ERROR_COLOR: [f32; 4] = [1.0, 0.0, 1.0, 1.0];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Packing {
+    Bits,
+    Trits,
+    Quints,
+}
+
+/// The ranges of the integer sequence encoding, from 2 to 256 values: each value is a trit, a quint or nothing,
+/// followed by plain bits.
+const RANGES: [(Packing, u32); 21] = [
+    (Packing::Bits, 1),
+    (Packing::Trits, 0),
+    (Packing::Bits, 2),
+    (Packing::Quints, 0),
+    (Packing::Trits, 1),
+    (Packing::Bits, 3),
+    (Packing::Quints, 1),
+    (Packing::Trits, 2),
+    (Packing::Bits, 4),
+    (Packing::Quints, 2),
+    (Packing::Trits, 3),
+    (Packing::Bits, 5),
+    (Packing::Quints, 3),
+    (Packing::Trits, 4),
+    (Packing::Bits, 6),
+    (Packing::Quints, 4),
+    (Packing::Trits, 5),
+    (Packing::Bits, 7),
+    (Packing::Quints, 5),
+    (Packing::Trits, 6),
+    (Packing::Bits, 8),
+];
+
+/// Endpoints need at least six values per channel.
+const MIN_COLOR_RANGE: usize = 4;
+
+/// Decodes one block of `width` by `height` texels to row-major RGBA. LDR endpoints give values in 0..=1, HDR ones
+/// the half floats they encode.
+pub(super) fn decode_block(block: &[u8], width: usize, height: usize, out: &mut [[f32; 4]]) {
+    let bits = u128::from_le_bytes(block.try_into().unwrap());
+    if decode(bits, width, height, out).is_none() {
+        out.fill(ERROR_COLOR);
+    }
+}
+
+fn decode(bits: u128, width: usize, height: usize, out: &mut [[f32; 4]]) -> Option<()> {
+    let field = |start: usize, count: usize| (bits >> start) as u32 & ((1 << count) - 1);
+    if field(0, 9) == 0x1fc {
+        // A void-extent block: one color for the whole block, as UNORM16 or, with bit 9 set, as half floats.
+        let hdr = field(9, 1) == 1;
+        let color = [0, 1, 2, 3].map(|c| {
+            let value = field(64 + c * 16, 16) as u16;
+            if hdr {
+                f16::from_bits(value).to_f32()
+            } else {
+                value as f32 / 65535.0
+            }
+        });
+        out.fill(color);
+        return Some(());
+    }
+
+    let mode = BlockMode::read(field(0, 11))?;
+    let partitions = field(11, 2) as usize + 1;
+    let planes = 1 + mode.dual_plane as usize;
+    let weight_count = mode.width * mode.height * planes;
+    let weight_bits = ise_bits(weight_count, mode.range);
+    if mode.width > width || mode.height > height || weight_count > 64 || !(24..=96).contains(&weight_bits) || (partitions == 4 && mode.dual_plane) {
+        return None;
+    }
+
+    // Whatever does not fit in the fixed fields is stored just below the weights, which fill the block from the top.
+    let mut below_weights = 128 - weight_bits;
+    let mut cems = [0; 4];
+    let color_start = if partitions == 1 {
+        cems[0] = field(13, 4);
+        17
+    } else {
+        let encoded = field(23, 6);
+        if encoded & 3 == 0 {
+            cems = [encoded >> 2; 4];
+        } else {
+            // Each partition adds 0 or 1 to the class of the endpoint modes, then picks one of its four modes.
+            let extra = 3 * partitions - 4;
+            below_weights -= extra;
+            let selectors = encoded >> 2 | field(below_weights, extra) << 4;
+            let class = (encoded & 3) - 1;
+            for (i, cem) in cems[..partitions].iter_mut().enumerate() {
+                *cem = (class + (selectors >> i & 1)) << 2 | selectors >> (partitions + i * 2) & 3;
+            }
+        }
+        29
+    };
+    let plane2_channel = if mode.dual_plane {
+        below_weights -= 2;
+        Some(field(below_weights, 2) as usize)
+    } else {
+        None
+    };
+
+    let value_count: usize = cems[..partitions].iter().map(|&cem| (cem as usize / 4 + 1) * 2).sum();
+    if value_count > 18 || below_weights < color_start {
+        return None;
+    }
+    let color_bits = below_weights - color_start;
+    let color_range = (MIN_COLOR_RANGE..RANGES.len()).rev().find(|&range| ise_bits(value_count, range) <= color_bits)?;
+    let values = read_ise(bits, color_start, value_count, color_range).into_iter().map(|value| unquantize_color(value, color_range)).collect::<Vec<_>>();
+    let mut endpoints = [Endpoints::default(); 4];
+    let mut rest = values.as_slice();
+    for (endpoints, &cem) in endpoints.iter_mut().zip(&cems[..partitions]) {
+        let (values, next) = rest.split_at((cem as usize / 4 + 1) * 2);
+        *endpoints = Endpoints::decode(cem, values);
+        rest = next;
+    }
+
+    // Weights are stored bit-reversed from the top of the block, the planes interleaved.
+    let weights = read_ise(bits.reverse_bits(), 0, weight_count, mode.range).into_iter().map(|value| unquantize_weight(value, mode.range)).collect::<Vec<_>>();
+    let seed = field(13, 10);
+    let small_block = width * height < 31;
+    for y in 0..height {
+        for x in 0..width {
+            let partition = if partitions > 1 { select_partition(seed, x as u32, y as u32, partitions as u32, small_block) } else { 0 };
+            let plane_weights = [0, 1].map(|plane| if plane < planes { infill(&weights, &mode, planes, plane, x, y, width, height) } else { 0 });
+            let endpoints = &endpoints[partition];
+            out[y * width + x] = [0, 1, 2, 3].map(|c| {
+                let weight = plane_weights[(plane2_channel == Some(c)) as usize];
+                let value = (endpoints.colors[0][c] * (64 - weight) + endpoints.colors[1][c] * weight + 32) >> 6;
+                if endpoints.hdr[c] {
+                    f16::from_bits(lns_to_half(value)).to_f32()
+                } else {
+                    value as f32 / 65535.0
+                }
+            });
+        }
+    }
+    Some(())
+}
+
+struct BlockMode {
+    /// Size of the weight grid, which is stretched over the block.
+    width: usize,
+    height: usize,
+    dual_plane: bool,
+    /// Index in [`RANGES`] of the weights.
+    range: usize,
+}
+
+impl BlockMode {
+    fn read(mode: u32) -> Option<Self> {
+        let bit = |i: u32| mode >> i & 1;
+        let mut range = bit(4);
+        let mut high_precision = bit(9);
+        let mut dual_plane = bit(10);
+        let a = mode >> 5 & 3;
+        let (width, height) = if mode & 3 != 0 {
+            range |= (mode & 3) << 1;
+            let b = mode >> 7 & 3;
+            match mode >> 2 & 3 {
+                0 => (b + 4, a + 2),
+                1 => (b + 8, a + 2),
+                2 => (a + 2, b + 8),
+                _ if bit(8) == 1 => ((b & 1) + 2, a + 2),
+                _ => (a + 2, (b & 1) + 6),
+            }
+        } else {
+            range |= (mode >> 2 & 3) << 1;
+            if mode >> 2 & 3 == 0 {
+                return None;
+            }
+            match mode >> 7 & 3 {
+                0 => (12, a + 2),
+                1 => (a + 2, 12),
+                2 => {
+                    // Bits 9 and 10 size the grid instead.
+                    let b = mode >> 9 & 3;
+                    high_precision = 0;
+                    dual_plane = 0;
+                    (a + 6, b + 6)
+                }
+                _ => match a {
+                    0 => (6, 10),
+                    1 => (10, 6),
+                    _ => return None,
+                },
+            }
+        };
+        Some(Self {
+            width: width as usize,
+            height: height as usize,
+            dual_plane: dual_plane == 1,
+            range: (range - 2 + high_precision * 6) as usize,
+        })
+    }
+}
+
+/// Bits taken by `count` values of a range, trits and quints being packed five to eight bits and three to seven bits.
+fn ise_bits(count: usize, range: usize) -> usize {
+    let (packing, bits) = RANGES[range];
+    count * bits as usize
+        + match packing {
+            Packing::Bits => 0,
+            Packing::Trits => (count * 8).div_ceil(5),
+            Packing::Quints => (count * 7).div_ceil(3),
+        }
+}
+
+/// A value of the integer sequence encoding: its trit or quint, and its plain bits.
+#[derive(Clone, Copy)]
+pub(crate) struct IseValue {
+    pub(crate) digit: u32,
+    pub(crate) bits: u32,
+}
+
+/// Reads `count` values starting at bit `start`. The last group of trits or quints may be cut short, in which case
+/// its missing bits are zeros.
+fn read_ise(stream: u128, start: usize, count: usize, range: usize) -> Vec<IseValue> {
+    let end = start + ise_bits(count, range);
+    let mut position = start;
+    let mut read = |n: u32| {
+        let mut value = 0;
+        for i in 0..n {
+            if position < end {
+                value |= ((stream >> position) as u32 & 1) << i;
+            }
+            position += 1;
+        }
+        value
+    };
+
+    let (packing, bits) = RANGES[range];
+    let mut values = Vec::with_capacity(count);
+    while values.len() < count {
+        // The bits of the packed digits are spread between the values of a group.
+        let group: &[(u32, u32)] = match packing {
+            Packing::Bits => &[(0, 0)],
+            Packing::Trits => &[(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)],
+            Packing::Quints => &[(0, 3), (3, 2), (5, 2)],
+        };
+        let mut packed = 0;
+        let mut group_bits = [0; 5];
+        for (value_bits, &(shift, n)) in group_bits.iter_mut().zip(group) {
+            *value_bits = read(bits);
+            packed |= read(n) << shift;
+        }
+        let digits = match packing {
+            Packing::Trits => trits(packed),
+            Packing::Quints => {
+                let [q0, q1, q2] = quints(packed);
+                [q0, q1, q2, 0, 0]
+            }
+            Packing::Bits => [0; 5],
+        };
+        for (&digit, &bits) in digits.iter().zip(&group_bits).take(group.len().min(count - values.len())) {
+            values.push(IseValue { digit, bits });
+        }
+    }
+    values
+}
+
+pub(crate) fn trits(t: u32) -> [u32; 5] {
+    let bit = |value: u32, i: u32| value >> i & 1;
+    let (c, t3, t4) = if t >> 2 & 7 == 7 {
+        ((t >> 5 & 7) << 2 | (t & 3), 2, 2)
+    } else if t >> 5 & 3 == 3 {
+        (t & 0x1f, bit(t, 7), 2)
+    } else {
+        (t & 0x1f, t >> 5 & 3, bit(t, 7))
+    };
+    let (t0, t1, t2) = if c & 3 == 3 {
+        (bit(c, 3) << 1 | (bit(c, 2) & !bit(c, 3) & 1), bit(c, 4), 2)
+    } else if c >> 2 & 3 == 3 {
+        (c & 3, 2, 2)
+    } else {
+        (bit(c, 1) << 1 | (bit(c, 0) & !bit(c, 1) & 1), c >> 2 & 3, bit(c, 4))
+    };
+    [t0, t1, t2, t3, t4]
+}
+
+fn quints(q: u32) -> [u32; 3] {
+    let bit = |value: u32, i: u32| value >> i & 1;
+    if q >> 1 & 3 == 3 && q >> 5 & 3 == 0 {
+        let not_q0 = !bit(q, 0) & 1;
+        return [4, 4, bit(q, 0) << 2 | (bit(q, 4) & not_q0) << 1 | (bit(q, 3) & not_q0)];
+    }
+    let (q2, c) = if q >> 1 & 3 == 3 { (4, (q >> 3 & 3) << 3 | (!(q >> 5) & 3) << 1 | bit(q, 0)) } else { (q >> 5 & 3, q & 0x1f) };
+    let (q0, q1) = if c & 7 == 5 { (c >> 3 & 3, 4) } else { (c & 7, c >> 3 & 3) };
+    [q0, q1, q2]
+}
+
+/// Repeats the low `bits` bits of `value` to fill `to` bits.
+fn replicate(value: u32, bits: u32, to: u32) -> u32 {
+    let (mut result, mut filled) = (0, 0);
+    while filled < to {
+        result = result << bits | value;
+        filled += bits;
+    }
+    result >> (filled - to)
+}
+
+/// Maps an endpoint value to 0..=255. With trits and quints, the lowest bit mirrors the value, the other plain bits
+/// are the most significant and the digit sits between them.
+pub(crate) fn unquantize_color(value: IseValue, range: usize) -> i32 {
+    let (packing, bits) = RANGES[range];
+    let m = value.bits;
+    if packing == Packing::Bits {
+        return replicate(m, bits, 8) as i32;
+    }
+    let x = m >> 1;
+    let (b, c) = match (packing, bits) {
+        (Packing::Trits, 1) => (0, 204),
+        (Packing::Trits, 2) => (x << 8 | x << 4 | x << 2 | x << 1, 93),
+        (Packing::Trits, 3) => (x << 7 | x << 2 | x, 44),
+        (Packing::Trits, 4) => (x << 6 | x, 22),
+        (Packing::Trits, 5) => (x << 5 | x >> 2, 11),
+        (Packing::Trits, _) => (x << 4 | x >> 4, 5),
+        (_, 1) => (0, 113),
+        (_, 2) => (x << 8 | x << 3 | x << 2, 54),
+        (_, 3) => (x << 7 | x << 1 | x >> 1, 26),
+        (_, 4) => (x << 6 | x >> 1, 13),
+        (_, _) => (x << 5 | x >> 3, 6),
+    };
+    let a = if m & 1 == 1 { 0x1ff } else { 0 };
+    let t = (value.digit * c + b) ^ a;
+    ((a & 0x80) | t >> 2) as i32
+}
+
+/// Maps a weight to 0..=64.
+fn unquantize_weight(value: IseValue, range: usize) -> i32 {
+    let (packing, bits) = RANGES[range];
+    let m = value.bits;
+    let weight = match (packing, bits) {
+        (Packing::Bits, _) => replicate(m, bits, 6),
+        (Packing::Trits, 0) => [0, 32, 63][value.digit as usize],
+        (Packing::Quints, 0) => [0, 16, 32, 47, 63][value.digit as usize],
+        _ => {
+            let x = m >> 1;
+            let (b, c) = match (packing, bits) {
+                (Packing::Trits, 1) => (0, 50),
+                (Packing::Trits, 2) => (x << 6 | x << 2 | x, 23),
+                (Packing::Trits, _) => (x << 5 | x, 11),
+                (_, 1) => (0, 28),
+                (_, _) => (x << 6 | x << 1, 13),
+            };
+            let a = if m & 1 == 1 { 0x7f } else { 0 };
+            let t = (value.digit * c + b) ^ a;
+            (a & 0x20) | t >> 2
+        }
+    };
+    (weight + (weight > 32) as u32) as i32
+}
+
+/// The weight of plane `plane` at texel (`x`, `y`), interpolated bilinearly from the grid.
+#[allow(clippy::too_many_arguments)]
+fn infill(weights: &[i32], mode: &BlockMode, planes: usize, plane: usize, x: usize, y: usize, width: usize, height: usize) -> i32 {
+    let grid_coordinate = |texel: usize, size: usize, grid: usize| {
+        let scaled = (1024 + size / 2) / (size - 1) * texel;
+        let position = (scaled * (grid - 1) + 32) >> 6;
+        (position >> 4, (position & 0xf) as i32)
+    };
+    let (gx, fx) = grid_coordinate(x, width, mode.width);
+    let (gy, fy) = grid_coordinate(y, height, mode.height);
+    let weight = |gx: usize, gy: usize| weights.get((gy.min(mode.height - 1) * mode.width + gx.min(mode.width - 1)) * planes + plane).copied().unwrap_or(0);
+    let w11 = (fx * fy + 8) >> 4;
+    let (w01, w10) = (fx - w11, fy - w11);
+    let w00 = 16 - fx - fy + w11;
+    (weight(gx, gy) * w00 + weight(gx + 1, gy) * w01 + weight(gx, gy + 1) * w10 + weight(gx + 1, gy + 1) * w11 + 8) >> 4
+}
+
+/// The partition of a texel, from a hash of the partition seed and its coordinates.
+fn select_partition(seed: u32, x: u32, y: u32, partitions: u32, small_block: bool) -> usize {
+    let (x, y) = if small_block { (x << 1, y << 1) } else { (x, y) };
+    let seed = seed + (partitions - 1) * 1024;
+    let mut r = seed;
+    r ^= r >> 15;
+    r = r.wrapping_sub(r << 17);
+    r = r.wrapping_add(r << 7);
+    r = r.wrapping_add(r << 4);
+    r ^= r >> 5;
+    r = r.wrapping_add(r << 16);
+    r ^= r >> 7;
+    r ^= r >> 3;
+    r ^= r << 6;
+    r ^= r >> 17;
+
+    // 3D blocks would take four more seeds for their z coordinate.
+    let three = partitions == 3;
+    let (sh1, sh2) = if seed & 1 == 1 {
+        (if seed & 2 != 0 { 4 } else { 5 }, if three { 6 } else { 5 })
+    } else {
+        (if three { 6 } else { 5 }, if seed & 2 != 0 { 4 } else { 5 })
+    };
+    let [s1, s2, s3, s4, s5, s6, s7, s8] = [0, 4, 8, 12, 16, 20, 24, 28].map(|shift| {
+        let s = r >> shift & 0xf;
+        (s * s) >> if shift % 8 == 0 { sh1 } else { sh2 }
+    });
+    let a = (s1 * x + s2 * y + (r >> 14)) & 0x3f;
+    let b = (s3 * x + s4 * y + (r >> 10)) & 0x3f;
+    let c = if partitions >= 3 { (s5 * x + s6 * y + (r >> 6)) & 0x3f } else { 0 };
+    let d = if partitions >= 4 { (s7 * x + s8 * y + (r >> 2)) & 0x3f } else { 0 };
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+/// Converts an interpolated value of the logarithmic HDR encoding to half float bits.
+fn lns_to_half(value: i32) -> u16 {
+    let (exponent, mantissa) = (value >> 11, value & 0x7ff);
+    let mantissa = if mantissa < 512 {
+        mantissa * 3
+    } else if mantissa < 1536 {
+        mantissa * 4 - 512
+    } else {
+        mantissa * 5 - 2048
+    };
+    ((exponent << 10 | mantissa >> 3) as u16).min(0x7bff)
+}
+
+/// The two endpoints of a partition as 16-bit values: UNORM16 for LDR channels and the logarithmic encoding for HDR
+/// ones.
+#[derive(Default, Clone, Copy)]
+struct Endpoints {
+    colors: [[i32; 4]; 2],
+    hdr: [bool; 4],
+}
+
+impl Endpoints {
+    fn decode(cem: u32, v: &[i32]) -> Self {
+        let (colors, rgb_hdr, alpha_hdr) = match cem {
+            0 => ([[v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]], false, false),
+            1 => {
+                let l0 = v[0] >> 2 | (v[1] & 0xc0);
+                let l1 = (l0 + (v[1] & 0x3f)).min(255);
+                ([[l0, l0, l0, 255], [l1, l1, l1, 255]], false, false)
+            }
+            2 => {
+                let (y0, y1) = if v[1] >= v[0] { (v[0] << 4, v[1] << 4) } else { ((v[1] << 4) + 8, (v[0] << 4) - 8) };
+                (hdr_luminance(y0, y1), true, true)
+            }
+            3 => {
+                let (y0, d) = if v[0] & 0x80 != 0 {
+                    ((v[1] & 0xe0) << 4 | (v[0] & 0x7f) << 2, (v[1] & 0x1f) << 2)
+                } else {
+                    ((v[1] & 0xf0) << 4 | (v[0] & 0x7f) << 1, (v[1] & 0x0f) << 1)
+                };
+                (hdr_luminance(y0, (y0 + d).min(0xfff)), true, true)
+            }
+            4 => ([[v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]], false, false),
+            5 => {
+                let (l, dl) = bit_transfer_signed(v[1], v[0]);
+                let (a, da) = bit_transfer_signed(v[3], v[2]);
+                let l1 = (l + dl).clamp(0, 255);
+                ([[l, l, l, a], [l1, l1, l1, (a + da).clamp(0, 255)]], false, false)
+            }
+            6 => ([[(v[0] * v[3]) >> 8, (v[1] * v[3]) >> 8, (v[2] * v[3]) >> 8, 255], [v[0], v[1], v[2], 255]], false, false),
+            7 => (hdr_rgb_scale(v), true, true),
+            8 => (ldr_direct([v[0], v[2], v[4], 255], [v[1], v[3], v[5], 255]), false, false),
+            9 => (ldr_offset(v, 255), false, false),
+            10 => ([[(v[0] * v[3]) >> 8, (v[1] * v[3]) >> 8, (v[2] * v[3]) >> 8, v[4]], [v[0], v[1], v[2], v[5]]], false, false),
+            11 => (hdr_rgb(v, [0x7800, 0x7800]), true, true),
+            12 => (ldr_direct([v[0], v[2], v[4], v[6]], [v[1], v[3], v[5], v[7]]), false, false),
+            13 => (ldr_offset(v, -1), false, false),
+            14 => (hdr_rgb(v, [v[6], v[7]]), true, false),
+            _ => (hdr_rgb(v, hdr_alpha(v[6], v[7])), true, true),
+        };
+        let hdr = [rgb_hdr, rgb_hdr, rgb_hdr, alpha_hdr];
+        // LDR values expand to 16 bits by repeating the byte.
+        let colors = colors.map(|color| [0, 1, 2, 3].map(|c| if hdr[c] { color[c] } else { color[c] * 257 }));
+        Self { colors, hdr }
+    }
+}
+
+/// Moves the top bit of `a` into `b`, leaving `a` as a signed 6-bit offset from `b`.
+fn bit_transfer_signed(a: i32, b: i32) -> (i32, i32) {
+    let b = b >> 1 | (a & 0x80);
+    let a = (a >> 1) & 0x3f;
+    (b, if a & 0x20 != 0 { a - 0x40 } else { a })
+}
+
+fn blue_contract([r, g, b, a]: [i32; 4]) -> [i32; 4] {
+    [(r + b) >> 1, (g + b) >> 1, b, a]
+}
+
+/// Endpoints sent as is; when the second is the darker one, they are swapped and blue contracted.
+fn ldr_direct(e0: [i32; 4], e1: [i32; 4]) -> [[i32; 4]; 2] {
+    if e1[0] + e1[1] + e1[2] >= e0[0] + e0[1] + e0[2] {
+        [e0, e1]
+    } else {
+        [blue_contract(e1), blue_contract(e0)]
+    }
+}
+
+/// A base endpoint and an offset, alpha being 255 or, when `alpha` is negative, sent as well.
+fn ldr_offset(v: &[i32], alpha: i32) -> [[i32; 4]; 2] {
+    let mut base = [0; 4];
+    let mut offset = [0; 4];
+    for c in 0..4 {
+        (base[c], offset[c]) = if c < 3 || alpha < 0 { bit_transfer_signed(v[c * 2 + 1], v[c * 2]) } else { (alpha, 0) };
+    }
+    let sum = [0, 1, 2, 3].map(|c| base[c] + offset[c]);
+    let [e0, e1] = if offset[0] + offset[1] + offset[2] >= 0 { [base, sum] } else { [blue_contract(sum), blue_contract(base)] };
+    [e0, e1].map(|e| e.map(|c| c.clamp(0, 255)))
+}
+
+/// 12-bit luminance endpoints, scaled to 16 bits with an alpha of 1.0.
+fn hdr_luminance(y0: i32, y1: i32) -> [[i32; 4]; 2] {
+    [[y0 << 4, y0 << 4, y0 << 4, 0x7800], [y1 << 4, y1 << 4, y1 << 4, 0x7800]]
+}
+
+/// A base color and a scale subtracted from it for the first endpoint, with a varying precision for each.
+fn hdr_rgb_scale(v: &[i32]) -> [[i32; 4]; 2] {
+    let bit = |value: i32, i: u32| value >> i & 1;
+    let mode_value = (v[0] & 0xc0) >> 6 | bit(v[1], 7) << 2 | bit(v[2], 7) << 3;
+    let (major, mode) = if mode_value & 0xc != 0xc {
+        (mode_value >> 2, mode_value & 3)
+    } else if mode_value != 0xf {
+        (mode_value & 3, 4)
+    } else {
+        (0, 5)
+    };
+    let (mut red, mut green, mut blue, mut scale) = (v[0] & 0x3f, v[1] & 0x1f, v[2] & 0x1f, v[3] & 0x1f);
+    let bits = [bit(v[1], 6), bit(v[1], 5), bit(v[2], 6), bit(v[2], 5), bit(v[3], 7), bit(v[3], 6), bit(v[3], 5)];
+    let mode_mask = 1 << mode;
+    // (modes using it, target, bit index, shift)
+    let placements: [(i32, u8, usize, i32); 17] = [
+        (0x30, 1, 0, 6),
+        (0x3a, 1, 1, 5),
+        (0x30, 2, 2, 6),
+        (0x3a, 2, 3, 5),
+        (0x3d, 3, 6, 5),
+        (0x2d, 3, 5, 6),
+        (0x04, 3, 4, 7),
+        (0x3b, 0, 4, 6),
+        (0x04, 0, 3, 6),
+        (0x10, 0, 5, 7),
+        (0x0f, 0, 2, 7),
+        (0x05, 0, 1, 8),
+        (0x0a, 0, 0, 8),
+        (0x05, 0, 0, 9),
+        (0x02, 0, 6, 9),
+        (0x01, 0, 3, 10),
+        (0x02, 0, 5, 10),
+    ];
+    for (modes, target, index, shift) in placements {
+        if modes & mode_mask != 0 {
+            let value = bits[index] << shift;
+            match target {
+                0 => red |= value,
+                1 => green |= value,
+                2 => blue |= value,
+                _ => scale |= value,
+            }
+        }
+    }
+    let shift = [1, 1, 2, 3, 4, 5][mode as usize];
+    let (red, mut green, mut blue, scale) = (red << shift, green << shift, blue << shift, scale << shift);
+    if mode != 5 {
+        green = red - green;
+        blue = red - blue;
+    }
+    let mut rgb = [red, green, blue];
+    if major != 0 && major < 3 {
+        rgb.swap(0, major as usize);
+    }
+    let e1 = rgb.map(|c| c.max(0));
+    let e0 = rgb.map(|c| (c - scale).max(0));
+    [[e0[0] << 4, e0[1] << 4, e0[2] << 4, 0x7800], [e1[0] << 4, e1[1] << 4, e1[2] << 4, 0x7800]]
+}
+
+/// Two colors coded as a base and differences of varying precision, with the given alpha.
+fn hdr_rgb(v: &[i32], alpha: [i32; 2]) -> [[i32; 4]; 2] {
+    let bit = |value: i32, i: u32| value >> i & 1;
+    let mode = bit(v[1], 7) | bit(v[2], 7) << 1 | bit(v[3], 7) << 2;
+    let major = bit(v[4], 7) | bit(v[5], 7) << 1;
+    if major == 3 {
+        return [[v[0] << 8, v[2] << 8, (v[4] & 0x7f) << 9, alpha[0]], [v[1] << 8, v[3] << 8, (v[5] & 0x7f) << 9, alpha[1]]];
+    }
+
+    let (mut a, mut c) = (v[0] | (v[1] & 0x40) << 2, v[1] & 0x3f);
+    let (mut b0, mut b1) = (v[2] & 0x3f, v[3] & 0x3f);
+    let (mut d0, mut d1) = (v[4] & 0x7f, v[5] & 0x7f);
+    let bits = [bit(v[2], 6), bit(v[3], 6), bit(v[4], 6), bit(v[5], 6), bit(v[4], 5), bit(v[5], 5)];
+    let mode_mask = 1 << mode;
+    let place = |modes: i32, target: &mut i32, index: usize, shift: i32| {
+        if modes & mode_mask != 0 {
+            *target |= bits[index] << shift;
+        }
+    };
+    place(0xa4, &mut a, 0, 9);
+    place(0x08, &mut a, 2, 9);
+    place(0x50, &mut a, 4, 9);
+    place(0x50, &mut a, 5, 10);
+    place(0xa0, &mut a, 1, 10);
+    place(0xc0, &mut a, 2, 11);
+    place(0x04, &mut c, 1, 6);
+    place(0xe8, &mut c, 3, 6);
+    place(0x20, &mut c, 2, 7);
+    place(0x5b, &mut b0, 0, 6);
+    place(0x5b, &mut b1, 1, 6);
+    place(0x12, &mut b0, 2, 7);
+    place(0x12, &mut b1, 3, 7);
+    place(0xaf, &mut d0, 4, 5);
+    place(0xaf, &mut d1, 5, 5);
+    place(0x05, &mut d0, 2, 6);
+    place(0x05, &mut d1, 3, 6);
+
+    // The differences are signed, with a width depending on the mode.
+    let sign_shift = 32 - [7, 6, 7, 6, 5, 6, 5, 6][mode as usize];
+    let (d0, d1) = ((d0 << sign_shift) >> sign_shift, (d1 << sign_shift) >> sign_shift);
+    let shift = (mode >> 1) ^ 3;
+    let [a, b0, b1, c, d0, d1] = [a, b0, b1, c, d0, d1].map(|value| value << shift);
+    let mut e1 = [a, a - b0, a - b1];
+    let mut e0 = [a - c, a - b0 - c - d0, a - b1 - c - d1];
+    if major != 0 {
+        e0.swap(0, major as usize);
+        e1.swap(0, major as usize);
+    }
+    let [e0, e1] = [e0, e1].map(|e| e.map(|c| c.clamp(0, 0xfff) << 4));
+    [[e0[0], e0[1], e0[2], alpha[0]], [e1[0], e1[1], e1[2], alpha[1]]]
+}
+
+/// HDR alpha endpoints, already scaled to 16 bits.
+fn hdr_alpha(v6: i32, v7: i32) -> [i32; 2] {
+    let mode = (v6 >> 7 & 1) | (v7 >> 6 & 2);
+    let (mut a0, mut a1) = (v6 & 0x7f, v7 & 0x7f);
+    if mode == 3 {
+        return [a0 << 9, a1 << 9];
+    }
+    a0 |= (a1 << (mode + 1)) & 0x780;
+    a1 &= 0x3f >> mode;
+    a1 ^= 0x20 >> mode;
+    a1 -= 0x20 >> mode;
+    a0 <<= 4 - mode;
+    a1 <<= 4 - mode;
+    a1 = (a0 + a1).clamp(0, 0xfff);
+    [a0 << 4, a1 << 4]
+}
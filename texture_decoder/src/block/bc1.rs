@@ -0,0 +1,26 @@
+/// Decodes a BC1 color block. With `alpha_mode`, a block whose first endpoint is not greater than the second
+/// uses three colors and transparent black; BC2 and BC3 always use four colors.
+pub(super) fn decode_color(block: &[u8], alpha_mode: bool, out: &mut [[u8; 4]; 16]) {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+
+    let (a, b) = (rgb565(c0), rgb565(c1));
+    let mix = |wa: u16, wb: u16, d: u16| {
+        let mut c = [0, 0, 0, 255];
+        for i in 0..3 {
+            c[i] = ((a[i] as u16 * wa + b[i] as u16 * wb) / d) as u8;
+        }
+        c
+    };
+    let palette = if c0 > c1 || !alpha_mode { [a, b, mix(2, 1, 3), mix(1, 2, 3)] } else { [a, b, mix(1, 1, 2), [0, 0, 0, 0]] };
+
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = palette[(indices >> (i * 2)) as usize & 3];
+    }
+}
+
+fn rgb565(c: u16) -> [u8; 4] {
+    let (r, g, b) = ((c >> 11) as u8 & 0x1f, (c >> 5) as u8 & 0x3f, c as u8 & 0x1f);
+    [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2, 255]
+}
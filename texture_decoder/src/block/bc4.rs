@@ -0,0 +1,19 @@
+/// Decodes a BC4 block, which is also the alpha block of BC3 and each channel of BC5.
+pub(super) fn decode_channel(block: &[u8], out: &mut [u8; 16]) {
+    let (e0, e1) = (block[0] as u16, block[1] as u16);
+    let mut palette = [e0 as u8, e1 as u8, 0, 0, 0, 0, 0, 255];
+    if e0 > e1 {
+        for k in 1..7 {
+            palette[k + 1] = (((7 - k as u16) * e0 + k as u16 * e1 + 3) / 7) as u8;
+        }
+    } else {
+        for k in 1..5 {
+            palette[k + 1] = (((5 - k as u16) * e0 + k as u16 * e1 + 2) / 5) as u8;
+        }
+    }
+
+    let indices = block[2..8].iter().rev().fold(0u64, |bits, &byte| bits << 8 | byte as u64);
+    for (i, texel) in out.iter_mut().enumerate() {
+        *texel = palette[(indices >> (i * 3)) as usize & 7];
+    }
+}
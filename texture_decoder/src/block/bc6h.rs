@@ -0,0 +1,197 @@
+use half::f16;
+
+// Endpoint fields in the layout tables: channel * 4 + endpoint, endpoints being w and x for the first region
+// and y and z for the second.
+const RW: u8 = 0;
+const RX: u8 = 1;
+const RY: u8 = 2;
+const RZ: u8 = 3;
+const GW: u8 = 4;
+const GX: u8 = 5;
+const GY: u8 = 6;
+const GZ: u8 = 7;
+const BW: u8 = 8;
+const BX: u8 = 9;
+const BY: u8 = 10;
+const BZ: u8 = 11;
+
+struct Mode {
+    /// Mode bits, 2 for the first two modes and 5 for the rest.
+    value: u32,
+    two_regions: bool,
+    transformed: bool,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    /// `(field, first bit, bit count)` in stream order.
+    layout: &'static [(u8, u8, u8)],
+}
+
+#[rustfmt::skip]
+const MODES: [Mode; 14] = [
+    Mode { value: 0b00, two_regions: true, transformed: true, endpoint_bits: 10, delta_bits: [5, 5, 5], layout: &[
+        (GY, 4, 1), (BY, 4, 1), (BZ, 4, 1), (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 5), (GZ, 4, 1), (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1),
+        (GZ, 0, 4), (BX, 0, 5), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b01, two_regions: true, transformed: true, endpoint_bits: 7, delta_bits: [6, 6, 6], layout: &[
+        (GY, 5, 1), (GZ, 4, 1), (GZ, 5, 1), (RW, 0, 7), (BZ, 0, 1), (BZ, 1, 1), (BY, 4, 1), (GW, 0, 7), (BY, 5, 1), (BZ, 2, 1), (GY, 4, 1),
+        (BW, 0, 7), (BZ, 3, 1), (BZ, 5, 1), (BZ, 4, 1), (RX, 0, 6), (GY, 0, 4), (GX, 0, 6), (GZ, 0, 4), (BX, 0, 6), (BY, 0, 4), (RY, 0, 6),
+        (RZ, 0, 6),
+    ] },
+    Mode { value: 0b00010, two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [5, 4, 4], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 5), (RW, 10, 1), (GY, 0, 4), (GX, 0, 4), (GW, 10, 1), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 4),
+        (BW, 10, 1), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b00110, two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [4, 5, 4], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 4), (RW, 10, 1), (GZ, 4, 1), (GY, 0, 4), (GX, 0, 5), (GW, 10, 1), (GZ, 0, 4), (BX, 0, 4),
+        (BW, 10, 1), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 4), (BZ, 0, 1), (BZ, 2, 1), (RZ, 0, 4), (GY, 4, 1), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b01010, two_regions: true, transformed: true, endpoint_bits: 11, delta_bits: [4, 4, 5], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 4), (RW, 10, 1), (BY, 4, 1), (GY, 0, 4), (GX, 0, 4), (GW, 10, 1), (BZ, 0, 1), (GZ, 0, 4),
+        (BX, 0, 5), (BW, 10, 1), (BY, 0, 4), (RY, 0, 4), (BZ, 1, 1), (BZ, 2, 1), (RZ, 0, 4), (BZ, 4, 1), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b01110, two_regions: true, transformed: true, endpoint_bits: 9, delta_bits: [5, 5, 5], layout: &[
+        (RW, 0, 9), (BY, 4, 1), (GW, 0, 9), (GY, 4, 1), (BW, 0, 9), (BZ, 4, 1), (RX, 0, 5), (GZ, 4, 1), (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1),
+        (GZ, 0, 4), (BX, 0, 5), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b10010, two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [6, 5, 5], layout: &[
+        (RW, 0, 8), (GZ, 4, 1), (BY, 4, 1), (GW, 0, 8), (BZ, 2, 1), (GY, 4, 1), (BW, 0, 8), (BZ, 3, 1), (BZ, 4, 1), (RX, 0, 6), (GY, 0, 4),
+        (GX, 0, 5), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 5), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 6), (RZ, 0, 6),
+    ] },
+    Mode { value: 0b10110, two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [5, 6, 5], layout: &[
+        (RW, 0, 8), (BZ, 0, 1), (BY, 4, 1), (GW, 0, 8), (GY, 5, 1), (GY, 4, 1), (BW, 0, 8), (GZ, 5, 1), (BZ, 4, 1), (RX, 0, 5), (GZ, 4, 1),
+        (GY, 0, 4), (GX, 0, 6), (GZ, 0, 4), (BX, 0, 5), (BZ, 1, 1), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b11010, two_regions: true, transformed: true, endpoint_bits: 8, delta_bits: [5, 5, 6], layout: &[
+        (RW, 0, 8), (BZ, 1, 1), (BY, 4, 1), (GW, 0, 8), (BY, 5, 1), (GY, 4, 1), (BW, 0, 8), (BZ, 5, 1), (BZ, 4, 1), (RX, 0, 5), (GZ, 4, 1),
+        (GY, 0, 4), (GX, 0, 5), (BZ, 0, 1), (GZ, 0, 4), (BX, 0, 6), (BY, 0, 4), (RY, 0, 5), (BZ, 2, 1), (RZ, 0, 5), (BZ, 3, 1),
+    ] },
+    Mode { value: 0b11110, two_regions: true, transformed: false, endpoint_bits: 6, delta_bits: [6, 6, 6], layout: &[
+        (RW, 0, 6), (GZ, 4, 1), (BZ, 0, 1), (BZ, 1, 1), (BY, 4, 1), (GW, 0, 6), (GY, 5, 1), (BY, 5, 1), (BZ, 2, 1), (GY, 4, 1), (BW, 0, 6),
+        (GZ, 5, 1), (BZ, 3, 1), (BZ, 5, 1), (BZ, 4, 1), (RX, 0, 6), (GY, 0, 4), (GX, 0, 6), (GZ, 0, 4), (BX, 0, 6), (BY, 0, 4), (RY, 0, 6),
+        (RZ, 0, 6),
+    ] },
+    Mode { value: 0b00011, two_regions: false, transformed: false, endpoint_bits: 10, delta_bits: [10, 10, 10], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 10), (GX, 0, 10), (BX, 0, 10),
+    ] },
+    Mode { value: 0b00111, two_regions: false, transformed: true, endpoint_bits: 11, delta_bits: [9, 9, 9], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 9), (RW, 10, 1), (GX, 0, 9), (GW, 10, 1), (BX, 0, 9), (BW, 10, 1),
+    ] },
+    Mode { value: 0b01011, two_regions: false, transformed: true, endpoint_bits: 12, delta_bits: [8, 8, 8], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 8), (RW, 11, 1), (RW, 10, 1), (GX, 0, 8), (GW, 11, 1), (GW, 10, 1), (BX, 0, 8),
+        (BW, 11, 1), (BW, 10, 1),
+    ] },
+    Mode { value: 0b01111, two_regions: false, transformed: true, endpoint_bits: 16, delta_bits: [4, 4, 4], layout: &[
+        (RW, 0, 10), (GW, 0, 10), (BW, 0, 10), (RX, 0, 4), (RW, 15, 1), (RW, 14, 1), (RW, 13, 1), (RW, 12, 1), (RW, 11, 1), (RW, 10, 1),
+        (GX, 0, 4), (GW, 15, 1), (GW, 14, 1), (GW, 13, 1), (GW, 12, 1), (GW, 11, 1), (GW, 10, 1), (BX, 0, 4), (BW, 15, 1), (BW, 14, 1),
+        (BW, 13, 1), (BW, 12, 1), (BW, 11, 1), (BW, 10, 1),
+    ] },
+];
+
+/// The first 32 two-subset partitions shared with BC7, one bit per texel.
+const PARTITIONS: [u16; 32] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80, 0xc800, 0xffec, 0xfe80, 0xe800, 0xffe8, 0xff00, 0xfff0, 0xf000, 0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce, 0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0,
+    0x718e, 0x399c,
+];
+
+/// Texel index of the second subset's anchor, whose index is stored with one bit less.
+const ANCHORS: [usize; 32] = [15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2];
+
+const WEIGHTS_3: [i32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [i32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+struct Bits(u128, u32);
+
+impl Bits {
+    fn read(&mut self, n: u32) -> u32 {
+        let value = (self.0 >> self.1) as u32 & ((1 << n) - 1);
+        self.1 += n;
+        value
+    }
+}
+
+pub(super) fn decode_block(block: &[u8], signed: bool, out: &mut [[f32; 3]; 16]) {
+    let mut bits = Bits(u128::from_le_bytes(block.try_into().unwrap()), 0);
+    let mut value = bits.read(2);
+    if value > 1 {
+        value |= bits.read(3) << 2;
+    }
+    let Some(mode) = MODES.iter().find(|mode| mode.value == value) else {
+        // Reserved modes decode to black.
+        *out = [[0.0; 3]; 16];
+        return;
+    };
+
+    let mut fields = [0i32; 12];
+    for &(field, first, count) in mode.layout {
+        fields[field as usize] |= (bits.read(count as u32) as i32) << first;
+    }
+    let partition = if mode.two_regions { bits.read(5) as usize } else { 0 };
+    let endpoints = if mode.two_regions { 4 } else { 2 };
+
+    for channel in 0..3 {
+        let endpoint = &mut fields[channel * 4..channel * 4 + endpoints];
+        if signed {
+            endpoint[0] = extend_sign(endpoint[0], mode.endpoint_bits);
+        }
+        let base = endpoint[0];
+        for e in endpoint[1..].iter_mut() {
+            if mode.transformed || signed {
+                *e = extend_sign(*e, mode.delta_bits[channel]);
+            }
+            if mode.transformed {
+                *e = (*e + base) & ((1 << mode.endpoint_bits) - 1);
+                if signed {
+                    *e = extend_sign(*e, mode.endpoint_bits);
+                }
+            }
+        }
+        for e in endpoint.iter_mut() {
+            *e = unquantize(*e, mode.endpoint_bits, signed);
+        }
+    }
+
+    let (weights, index_bits) = if mode.two_regions { (&WEIGHTS_3[..], 3) } else { (&WEIGHTS_4[..], 4) };
+    for (i, texel) in out.iter_mut().enumerate() {
+        let subset = if mode.two_regions { (PARTITIONS[partition] >> i) as usize & 1 } else { 0 };
+        let anchor = i == 0 || (mode.two_regions && i == ANCHORS[partition]);
+        let weight = weights[bits.read(if anchor { index_bits - 1 } else { index_bits }) as usize];
+        for (channel, value) in texel.iter_mut().enumerate() {
+            let (a, b) = (fields[channel * 4 + subset * 2], fields[channel * 4 + subset * 2 + 1]);
+            let interpolated = (a * (64 - weight) + b * weight + 32) >> 6;
+            *value = finish_unquantize(interpolated, signed).to_f32();
+        }
+    }
+}
+
+fn extend_sign(value: i32, bits: u32) -> i32 {
+    (value << (32 - bits)) >> (32 - bits)
+}
+
+fn unquantize(value: i32, bits: u32, signed: bool) -> i32 {
+    if !signed {
+        match value {
+            _ if bits >= 15 => value,
+            0 => 0,
+            _ if value == (1 << bits) - 1 => 0xffff,
+            _ => ((value << 16) + 0x8000) >> bits,
+        }
+    } else if bits >= 16 {
+        value
+    } else {
+        let magnitude = match value.abs() {
+            0 => 0,
+            v if v >= (1 << (bits - 1)) - 1 => 0x7fff,
+            v => ((v << 15) + 0x4000) >> (bits - 1),
+        };
+        magnitude * value.signum()
+    }
+}
+
+fn finish_unquantize(value: i32, signed: bool) -> f16 {
+    if !signed {
+        f16::from_bits(((value * 31) >> 6) as u16)
+    } else {
+        let magnitude = (value.abs() * 31) >> 5;
+        f16::from_bits(if value < 0 { 0x8000 | magnitude as u16 } else { magnitude as u16 })
+    }
+}
@@ -0,0 +1,39 @@
+pub(crate) const MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+/// Decodes an 11-bit EAC block into 8 bits. Unsigned values span 0..=2047; signed ones span -1023..=1023
+/// and are remapped so that -1.0 becomes 0, 0.0 becomes 128 and 1.0 becomes 255.
+pub(super) fn decode_channel(block: &[u8], signed: bool, out: &mut [u8; 16]) {
+    let bits = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let multiplier = (bits >> 52) as i32 & 0xf;
+    let modifiers = MODIFIERS[(bits >> 48) as usize & 0xf];
+    let base = if signed { ((bits >> 56) as i8).max(-127) as i32 * 8 } else { (bits >> 56) as i32 * 8 + 4 };
+
+    for (i, texel) in out.iter_mut().enumerate() {
+        // Indices are stored column by column.
+        let pixel = (i % 4) * 4 + i / 4;
+        let modifier = modifiers[(bits >> (45 - pixel * 3)) as usize & 7];
+        let value = base + if multiplier == 0 { modifier } else { modifier * multiplier * 8 };
+        *texel = if signed {
+            (((value.clamp(-1023, 1023) + 1023) * 255 + 1023) / 2046) as u8
+        } else {
+            ((value.clamp(0, 2047) * 255 + 1023) / 2047) as u8
+        };
+    }
+}
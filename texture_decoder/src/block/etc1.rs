@@ -0,0 +1,26 @@
+pub(crate) const MODIFIERS: [[i16; 2]; 8] = [[2, 8], [5, 17], [9, 29], [13, 42], [18, 60], [24, 80], [33, 106], [47, 183]];
+
+/// Decodes an ETC1 block given as its big-endian 64-bit value.
+pub(super) fn decode_block(block: u64, out: &mut [[u8; 4]; 16]) {
+    let bits = |shift: u32, n: u32| (block >> shift) as u8 & ((1 << n) - 1);
+    let flip = bits(32, 1) == 1;
+    let bases = if bits(33, 1) == 0 {
+        [60, 56].map(|shift| [0, 1, 2].map(|c| bits(shift - c * 8, 4) * 17))
+    } else {
+        let extend = |v: u8| v << 3 | v >> 2;
+        let base = [0, 1, 2].map(|c| bits(59 - c * 8, 5));
+        let delta = [0, 1, 2].map(|c| ((bits(56 - c * 8, 3) << 5) as i8 >> 5) as i16);
+        [base.map(extend), [0, 1, 2].map(|c| extend((base[c] as i16 + delta[c]) as u8 & 0x1f))]
+    };
+    let tables = [bits(37, 3) as usize, bits(34, 3) as usize];
+
+    for (i, texel) in out.iter_mut().enumerate() {
+        let (x, y) = (i % 4, i / 4);
+        let subblock = if flip { y / 2 } else { x / 2 };
+        let pixel = (x * 4 + y) as u32;
+        let index = ((block >> (16 + pixel)) as usize & 1) << 1 | (block >> pixel) as usize & 1;
+        let modifier = MODIFIERS[tables[subblock]][index & 1] * if index & 2 == 0 { 1 } else { -1 };
+        let base = bases[subblock];
+        *texel = [0, 1, 2, 3].map(|c| if c == 3 { 255 } else { (base[c] as i16 + modifier).clamp(0, 255) as u8 });
+    }
+}
@@ -0,0 +1,168 @@
+//! Decoders for block compressed formats: BC1 to BC6H, EAC, ETC1 as tiled on the 3DS, and ASTC with HDR endpoints.
+//!
+//! Unity stores the bottom row first, so every decoder returns the image flipped to top-down order,
+//! the same as [`Texture2DDecoder::decode`](crate::Texture2DDecoder::decode) with `flip` set.
+
+pub(crate) mod astc;
+mod bc1;
+mod bc4;
+mod bc6h;
+pub(crate) mod eac;
+pub(crate) mod etc1;
+
+use crate::error::DecodeImageError;
+use crate::ImageSize;
+use image::{GrayImage, ImageBuffer, LumaA, Rgb32FImage, Rgba32FImage, RgbaImage};
+use rayon::prelude::*;
+
+pub type GrayAlphaImage = ImageBuffer<LumaA<u8>, Vec<u8>>;
+
+/// DXT1: RGB with an optional 1-bit alpha.
+pub fn decode_bc1(size: &ImageSize, data: &[u8]) -> Result<RgbaImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 8, |block, out: &mut [[u8; 4]; 16]| bc1::decode_color(block, true, out))?;
+    RgbaImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// DXT5: a BC1 color block without the alpha mode, preceded by a BC4 alpha block.
+pub fn decode_bc3(size: &ImageSize, data: &[u8]) -> Result<RgbaImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 16, |block, out: &mut [[u8; 4]; 16]| {
+        bc1::decode_color(&block[8..], false, out);
+        let mut alpha = [0; 16];
+        bc4::decode_channel(&block[..8], &mut alpha);
+        for (texel, a) in out.iter_mut().zip(alpha) {
+            texel[3] = a;
+        }
+    })?;
+    RgbaImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// A single red channel, returned as a grayscale image.
+pub fn decode_bc4(size: &ImageSize, data: &[u8]) -> Result<GrayImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 8, bc4::decode_channel)?;
+    GrayImage::from_raw(size.width as _, size.height as _, texels).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// Red and green channels, returned with red in the luma channel and green in the alpha channel.
+pub fn decode_bc5(size: &ImageSize, data: &[u8]) -> Result<GrayAlphaImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 16, |block, out: &mut [[u8; 2]; 16]| {
+        let (mut r, mut g) = ([0; 16], [0; 16]);
+        bc4::decode_channel(&block[..8], &mut r);
+        bc4::decode_channel(&block[8..], &mut g);
+        for (i, texel) in out.iter_mut().enumerate() {
+            *texel = [r[i], g[i]];
+        }
+    })?;
+    GrayAlphaImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// HDR RGB stored as half floats, `signed` selecting BC6H_SF16 over BC6H_UF16.
+pub fn decode_bc6h(size: &ImageSize, data: &[u8], signed: bool) -> Result<Rgb32FImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 16, |block, out| bc6h::decode_block(block, signed, out))?;
+    Rgb32FImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// EAC R11, returned as a grayscale image of the red channel.
+pub fn decode_eac_r(size: &ImageSize, data: &[u8], signed: bool) -> Result<GrayImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 8, |block, out| eac::decode_channel(block, signed, out))?;
+    GrayImage::from_raw(size.width as _, size.height as _, texels).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// EAC RG11, returned with red in the luma channel and green in the alpha channel.
+pub fn decode_eac_rg(size: &ImageSize, data: &[u8], signed: bool) -> Result<GrayAlphaImage, DecodeImageError> {
+    let texels = decode_blocks(size, data, 16, |block, out: &mut [[u8; 2]; 16]| {
+        let (mut r, mut g) = ([0; 16], [0; 16]);
+        eac::decode_channel(&block[..8], signed, &mut r);
+        eac::decode_channel(&block[8..], signed, &mut g);
+        for (i, texel) in out.iter_mut().enumerate() {
+            *texel = [r[i], g[i]];
+        }
+    })?;
+    GrayAlphaImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// ETC1 as laid out on the 3DS: 8x8 tiles of four blocks stored as little-endian words. With `alpha` (ETC1A4),
+/// every color block is preceded by 4-bit alpha values stored column by column.
+pub fn decode_etc1_3ds(size: &ImageSize, data: &[u8], alpha: bool) -> Result<RgbaImage, DecodeImageError> {
+    let block_bytes = if alpha { 16 } else { 8 };
+    let data = untile_3ds(size, data, block_bytes)?;
+    let texels = decode_blocks(size, &data, block_bytes, |block, out: &mut [[u8; 4]; 16]| {
+        let word = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+        etc1::decode_block(word(&block[block_bytes - 8..]), out);
+        if alpha {
+            let alpha = word(&block[..8]);
+            for (i, texel) in out.iter_mut().enumerate() {
+                texel[3] = ((alpha >> ((i % 4 * 4 + i / 4) * 4)) as u8 & 0xf) * 17;
+            }
+        }
+    })?;
+    RgbaImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// ASTC with blocks of `block_width` by `block_height` texels. LDR endpoints decode to 0..=1 and HDR endpoints to the
+/// half floats they encode, so this covers both the LDR and HDR formats.
+pub fn decode_astc(size: &ImageSize, data: &[u8], block_width: usize, block_height: usize) -> Result<Rgba32FImage, DecodeImageError> {
+    let texels = decode_sized_blocks(size, data, 16, (block_width, block_height), |block, out| astc::decode_block(block, block_width, block_height, out))?;
+    Rgba32FImage::from_raw(size.width as _, size.height as _, texels.concat()).ok_or(DecodeImageError::ImageDecode)
+}
+
+/// Reorders 3DS tiles, each holding two by two blocks in row-major order, into plain rows of blocks.
+fn untile_3ds(size: &ImageSize, data: &[u8], block_bytes: usize) -> Result<Vec<u8>, DecodeImageError> {
+    let (blocks_x, blocks_y) = (size.width.div_ceil(4), size.height.div_ceil(4));
+    let tiles_x = size.width.div_ceil(8);
+    let blocks = tiles_x * size.height.div_ceil(8) * 4;
+    if data.len() / block_bytes < blocks {
+        return Err(DecodeImageError::SizeNotMatch(data.len() / block_bytes, blocks));
+    }
+
+    let mut untiled = vec![0; blocks_x * blocks_y * block_bytes];
+    for (i, block) in data.chunks_exact(block_bytes).take(blocks).enumerate() {
+        let (tile, j) = (i / 4, i % 4);
+        let (bx, by) = (tile % tiles_x * 2 + j % 2, tile / tiles_x * 2 + j / 2);
+        if bx < blocks_x && by < blocks_y {
+            let offset = (by * blocks_x + bx) * block_bytes;
+            untiled[offset..offset + block_bytes].copy_from_slice(block);
+        }
+    }
+    Ok(untiled)
+}
+
+/// Splits `data` into blocks of `block_bytes`, lets `decode_block` fill in the 16 texels of each one in row-major order
+/// and returns the texels of the image in top-down order.
+fn decode_blocks<T, F>(size: &ImageSize, data: &[u8], block_bytes: usize, decode_block: F) -> Result<Vec<T>, DecodeImageError>
+where
+    T: Copy + Default + Send + Sync,
+    F: Fn(&[u8], &mut [T; 16]) + Sync,
+{
+    decode_sized_blocks(size, data, block_bytes, (4, 4), |block, out| decode_block(block, out.try_into().unwrap()))
+}
+
+/// Like [`decode_blocks`], for blocks of `block_width` by `block_height` texels.
+fn decode_sized_blocks<T, F>(size: &ImageSize, data: &[u8], block_bytes: usize, (block_width, block_height): (usize, usize), decode_block: F) -> Result<Vec<T>, DecodeImageError>
+where
+    T: Copy + Default + Send + Sync,
+    F: Fn(&[u8], &mut [T]) + Sync,
+{
+    let (width, height) = (size.width, size.height);
+    let blocks_x = width.div_ceil(block_width);
+    let blocks = blocks_x * height.div_ceil(block_height);
+    if data.len() / block_bytes < blocks {
+        return Err(DecodeImageError::SizeNotMatch(data.len() / block_bytes, blocks));
+    }
+    if width == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut texels = vec![T::default(); size.size()];
+    texels.par_chunks_mut(width * block_height).zip(data.par_chunks(blocks_x * block_bytes)).for_each(|(rows, blocks)| {
+        let mut block = vec![T::default(); block_width * block_height];
+        for (bx, data) in blocks.chunks_exact(block_bytes).enumerate() {
+            decode_block(data, &mut block);
+            for (row, line) in rows.chunks_mut(width).enumerate() {
+                let x = bx * block_width;
+                let n = (width - x).min(block_width);
+                line[x..x + n].copy_from_slice(&block[row * block_width..row * block_width + n]);
+            }
+        }
+    });
+    Ok(texels.chunks_exact(width).rev().flatten().copied().collect())
+}
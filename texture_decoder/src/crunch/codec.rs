@@ -0,0 +1,125 @@
+use super::invalid;
+use crate::error::DecodeImageError;
+
+const MAX_CODE_SIZE: usize = 16;
+const MAX_CODELENGTH_CODES: usize = 21;
+/// Order in which the code sizes of the code length alphabet are sent.
+const CODELENGTH_ORDER: [usize; MAX_CODELENGTH_CODES] = [17, 18, 19, 20, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15, 16];
+
+/// Reads a crunch bit stream, most significant bit first, as zeros past the end.
+pub(super) struct Codec<'a> {
+    data: &'a [u8],
+    bit: usize,
+}
+
+impl<'a> Codec<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self { data, bit: 0 }
+    }
+
+    pub(super) fn bits(&mut self, n: u32) -> u32 {
+        let mut value = 0;
+        for _ in 0..n {
+            let byte = self.data.get(self.bit / 8).copied().unwrap_or(0);
+            value = value << 1 | (byte >> (7 - self.bit % 8)) as u32 & 1;
+            self.bit += 1;
+        }
+        value
+    }
+
+    pub(super) fn decode(&mut self, model: &Model) -> Result<u32, DecodeImageError> {
+        if model.symbols.is_empty() {
+            return Ok(0);
+        }
+        let mut code = 0;
+        for len in 1..=MAX_CODE_SIZE {
+            code = code << 1 | self.bits(1);
+            let index = code.wrapping_sub(model.first[len]);
+            if index < model.count[len] {
+                return Ok(model.symbols[(model.offset[len] + index) as usize] as u32);
+            }
+        }
+        Err(invalid("bad huffman code"))
+    }
+
+    /// Reads a static Huffman model, sent as run-length coded code sizes which are themselves Huffman coded.
+    pub(super) fn receive_model(&mut self) -> Result<Model, DecodeImageError> {
+        let total = self.bits(14) as usize;
+        if total == 0 {
+            return Ok(Model::default());
+        }
+        let sent = self.bits(5) as usize;
+        if !(1..=MAX_CODELENGTH_CODES).contains(&sent) {
+            return Err(invalid("bad code length count"));
+        }
+        let mut codelength_sizes = [0; MAX_CODELENGTH_CODES];
+        for &code in &CODELENGTH_ORDER[..sent] {
+            codelength_sizes[code] = self.bits(3) as u8;
+        }
+        let codelengths = Model::new(&codelength_sizes)?;
+
+        let mut sizes = vec![0u8; total];
+        let mut i = 0;
+        while i < total {
+            let code = self.decode(&codelengths)?;
+            let run = match code {
+                0..=16 => {
+                    sizes[i] = code as u8;
+                    i += 1;
+                    continue;
+                }
+                17 => self.bits(3) + 3,
+                18 => self.bits(7) + 11,
+                19 => self.bits(2) + 3,
+                20 => self.bits(6) + 7,
+                _ => return Err(invalid("bad code length code")),
+            } as usize;
+            if run > total - i {
+                return Err(invalid("code length run too long"));
+            }
+            if code >= 19 {
+                let previous = if i > 0 { sizes[i - 1] } else { 0 };
+                if previous == 0 {
+                    return Err(invalid("repeated code length without a previous one"));
+                }
+                sizes[i..i + run].fill(previous);
+            }
+            i += run;
+        }
+        Model::new(&sizes)
+    }
+}
+
+/// A canonical Huffman code: codes of each length are consecutive, assigned in symbol order.
+#[derive(Default)]
+pub(super) struct Model {
+    first: [u32; MAX_CODE_SIZE + 1],
+    count: [u32; MAX_CODE_SIZE + 1],
+    offset: [u32; MAX_CODE_SIZE + 1],
+    symbols: Vec<u16>,
+}
+
+impl Model {
+    fn new(sizes: &[u8]) -> Result<Self, DecodeImageError> {
+        let mut model = Self::default();
+        for &size in sizes {
+            if size as usize > MAX_CODE_SIZE {
+                return Err(invalid("code size too large"));
+            }
+            model.count[size as usize] += 1;
+        }
+        model.count[0] = 0;
+        let mut code = 0;
+        let mut offset = 0;
+        for len in 1..=MAX_CODE_SIZE {
+            model.first[len] = code;
+            model.offset[len] = offset;
+            code = (code + model.count[len]) << 1;
+            offset += model.count[len];
+        }
+        for len in 1..=MAX_CODE_SIZE {
+            model.symbols.extend((0..sizes.len()).filter(|&s| sizes[s] as usize == len).map(|s| s as u16));
+        }
+        Ok(model)
+    }
+}
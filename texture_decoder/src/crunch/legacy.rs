@@ -0,0 +1,62 @@
+//! The original crunch layout: the level is cut into chunks of two by two blocks, visited in a serpentine order,
+//! and each chunk picks one of eight patterns for sharing up to four endpoints between its blocks.
+
+use super::{advance, entry, Blocks, CrunchFormat, Palettes, Tables};
+use crate::crunch::codec::Codec;
+use crate::error::DecodeImageError;
+
+/// Endpoints used by the top left, top right, bottom left and bottom right blocks of a chunk.
+const CHUNK_TILES: [[usize; 4]; 8] = [[0, 0, 0, 0], [0, 0, 1, 1], [0, 1, 0, 1], [0, 0, 1, 2], [1, 2, 0, 0], [0, 1, 0, 2], [1, 0, 2, 0], [0, 1, 2, 3]];
+const CHUNK_ENDPOINTS: [usize; 8] = [1, 2, 2, 3, 3, 3, 3, 4];
+
+pub(super) fn unpack(codec: &mut Codec, tables: &Tables, palettes: &Palettes, format: CrunchFormat, blocks: &mut Blocks) -> Result<(), DecodeImageError> {
+    let alpha = format.is_dxt5();
+    let chunks_x = blocks.blocks_x.div_ceil(2);
+    let chunks_y = blocks.blocks_y.div_ceil(2);
+    let (mut color_endpoint, mut color_selector, mut alpha_endpoint, mut alpha_selector) = (0, 0, 0, 0);
+    // Three chunk encodings per symbol, with a marker bit above them.
+    let mut encodings = 1;
+
+    for face in 0..blocks.faces {
+        for y in 0..chunks_y {
+            let columns: Box<dyn Iterator<Item = usize>> = if y % 2 == 0 { Box::new(0..chunks_x) } else { Box::new((0..chunks_x).rev()) };
+            for x in columns {
+                if encodings == 1 {
+                    encodings = codec.decode(&tables.encoding)? | 512;
+                }
+                let encoding = (encodings & 7) as usize;
+                encodings >>= 3;
+
+                let mut color_endpoints = [0u32; 4];
+                for endpoint in &mut color_endpoints[..CHUNK_ENDPOINTS[encoding]] {
+                    color_endpoint = advance(color_endpoint, codec.decode(&tables.endpoint_delta[0])?, palettes.color_endpoints.len())?;
+                    *endpoint = palettes.color_endpoints[color_endpoint];
+                }
+                let mut alpha_endpoints = [[0u8; 2]; 4];
+                if alpha {
+                    for endpoint in &mut alpha_endpoints[..CHUNK_ENDPOINTS[encoding]] {
+                        alpha_endpoint = advance(alpha_endpoint, codec.decode(&tables.endpoint_delta[1])?, palettes.alpha_endpoints.len())?;
+                        *endpoint = palettes.alpha_endpoints[alpha_endpoint];
+                    }
+                }
+
+                for (i, &tile) in CHUNK_TILES[encoding].iter().enumerate() {
+                    color_selector = advance(color_selector, codec.decode(&tables.selector_delta[0])?, palettes.color_selectors.len())?;
+                    let mut block = [0u8; 16];
+                    let color = if alpha {
+                        alpha_selector = advance(alpha_selector, codec.decode(&tables.selector_delta[1])?, palettes.alpha_selectors.len())?;
+                        block[..2].copy_from_slice(&alpha_endpoints[tile]);
+                        block[2..8].copy_from_slice(&entry(&palettes.alpha_selectors, alpha_selector)?);
+                        &mut block[8..]
+                    } else {
+                        &mut block[..8]
+                    };
+                    color[..4].copy_from_slice(&color_endpoints[tile].to_le_bytes());
+                    color[4..8].copy_from_slice(&palettes.color_selectors[color_selector].to_le_bytes());
+                    blocks.put(face, x * 2 + i % 2, y * 2 + i / 2, &block[..format.block_size()]);
+                }
+            }
+        }
+    }
+    Ok(())
+}
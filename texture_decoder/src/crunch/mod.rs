@@ -0,0 +1,443 @@
+//! Transcoding of crunch (`.crn`) textures back to the DXT or ETC blocks they were compressed from.
+//!
+//! Unity 2017.3 switched to its own revision of crunch. It keeps the container, but codes blocks and selectors
+//! differently and adds the ETC formats; the `unity` flag of [`transcode`] selects that revision.
+
+mod codec;
+mod legacy;
+mod unity;
+
+use crate::error::DecodeImageError;
+use codec::{Codec, Model};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrunchFormat {
+    Dxt1,
+    Dxt3,
+    Dxt5,
+    Dxt5CCxY,
+    Dxt5xGxR,
+    Dxt5xGBR,
+    Dxt5AGBR,
+    DxnXY,
+    DxnYX,
+    Dxt5A,
+    Etc1,
+    Etc2,
+    Etc2A,
+    Etc1S,
+    Etc2AS,
+}
+
+impl CrunchFormat {
+    const ALL: [Self; 15] = [
+        Self::Dxt1,
+        Self::Dxt3,
+        Self::Dxt5,
+        Self::Dxt5CCxY,
+        Self::Dxt5xGxR,
+        Self::Dxt5xGBR,
+        Self::Dxt5AGBR,
+        Self::DxnXY,
+        Self::DxnYX,
+        Self::Dxt5A,
+        Self::Etc1,
+        Self::Etc2,
+        Self::Etc2A,
+        Self::Etc1S,
+        Self::Etc2AS,
+    ];
+
+    /// Bytes per 4x4 block.
+    pub fn block_size(self) -> usize {
+        match self {
+            Self::Dxt1 | Self::Dxt5A | Self::Etc1 | Self::Etc2 | Self::Etc1S => 8,
+            _ => 16,
+        }
+    }
+
+    fn is_dxt5(self) -> bool {
+        matches!(self, Self::Dxt5 | Self::Dxt5CCxY | Self::Dxt5xGxR | Self::Dxt5xGBR | Self::Dxt5AGBR)
+    }
+
+    /// Whether ETC color blocks keep both subblocks, rather than one endpoint for the whole block.
+    fn has_subblocks(self) -> bool {
+        matches!(self, Self::Etc1 | Self::Etc2 | Self::Etc2A)
+    }
+
+    fn is_etc(self) -> bool {
+        matches!(self, Self::Etc1 | Self::Etc2 | Self::Etc2A | Self::Etc1S | Self::Etc2AS)
+    }
+}
+
+/// One mip level of a crunch texture, transcoded to rows of plain blocks with its faces one after another.
+pub struct CrunchLevel {
+    pub format: CrunchFormat,
+    pub width: usize,
+    pub height: usize,
+    /// 6 for cubemaps, 1 otherwise.
+    pub faces: usize,
+    pub data: Vec<u8>,
+}
+
+/// Transcodes mip `level` of the crunch file in `data`. The faces of a cubemap are coded one after another, each
+/// going on from the palette indices the previous one ended with.
+pub fn transcode(data: &[u8], level: usize, unity: bool) -> Result<CrunchLevel, DecodeImageError> {
+    let header = Header::read(data)?;
+    if level >= header.level_offsets.len() {
+        return Err(invalid("missing mip level"));
+    }
+    let format = header.format;
+    let supported = if unity {
+        format == CrunchFormat::Dxt1 || format.is_dxt5() || format.is_etc()
+    } else {
+        format == CrunchFormat::Dxt1 || format.is_dxt5()
+    };
+    if !supported {
+        return Err(invalid("unsupported format"));
+    }
+
+    let palettes = Palettes::read(data, &header, unity)?;
+    let tables = Tables::read(section(data, header.tables_offset, header.tables_size)?, &header)?;
+    let start = header.level_offsets[level];
+    let end = header.level_offsets.get(level + 1).copied().unwrap_or(data.len());
+    let mut codec = Codec::new(section(data, start, end.saturating_sub(start))?);
+
+    let width = (header.width >> level).max(1);
+    let height = (header.height >> level).max(1);
+    let mut blocks = Blocks::new(width.div_ceil(4), height.div_ceil(4), header.faces, format.block_size());
+    if unity {
+        unity::unpack(&mut codec, &tables, &palettes, format, &mut blocks)?;
+    } else {
+        legacy::unpack(&mut codec, &tables, &palettes, format, &mut blocks)?;
+    }
+    Ok(CrunchLevel {
+        format,
+        width,
+        height,
+        faces: header.faces,
+        data: blocks.data,
+    })
+}
+
+pub(crate) fn invalid(reason: &'static str) -> DecodeImageError {
+    DecodeImageError::InvalidCrunch(reason)
+}
+
+fn section(data: &[u8], offset: usize, size: usize) -> Result<&[u8], DecodeImageError> {
+    data.get(offset..offset + size).ok_or_else(|| invalid("section out of bounds"))
+}
+
+/// A big-endian field of `n` bytes.
+fn field(data: &[u8], offset: usize, n: usize) -> usize {
+    data[offset..offset + n].iter().fold(0, |value, &b| value << 8 | b as usize)
+}
+
+struct Palette {
+    offset: usize,
+    size: usize,
+    len: usize,
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    faces: usize,
+    format: CrunchFormat,
+    color_endpoints: Palette,
+    color_selectors: Palette,
+    alpha_endpoints: Palette,
+    alpha_selectors: Palette,
+    tables_size: usize,
+    tables_offset: usize,
+    level_offsets: Vec<usize>,
+}
+
+impl Header {
+    const SIGNATURE: usize = 0x4878;
+    /// The offsets of the mip levels end the header.
+    const LEVEL_OFFSETS: usize = 70;
+
+    fn read(data: &[u8]) -> Result<Self, DecodeImageError> {
+        if data.len() < Self::LEVEL_OFFSETS || field(data, 0, 2) != Self::SIGNATURE {
+            return Err(invalid("not a crunch file"));
+        }
+        let levels = field(data, 16, 1);
+        let size = Self::LEVEL_OFFSETS + levels * 4;
+        if levels == 0 || field(data, 2, 2) < size || data.len() < size {
+            return Err(invalid("truncated header"));
+        }
+        let faces = field(data, 17, 1);
+        if faces == 0 || faces > 6 {
+            return Err(invalid("bad face count"));
+        }
+        let palette = |offset| Palette {
+            offset: field(data, offset, 3),
+            size: field(data, offset + 3, 3),
+            len: field(data, offset + 6, 2),
+        };
+        Ok(Self {
+            width: field(data, 12, 2),
+            height: field(data, 14, 2),
+            faces,
+            format: *CrunchFormat::ALL.get(field(data, 18, 1)).ok_or_else(|| invalid("unknown format"))?,
+            color_endpoints: palette(33),
+            color_selectors: palette(41),
+            alpha_endpoints: palette(49),
+            alpha_selectors: palette(57),
+            tables_size: field(data, 65, 2),
+            tables_offset: field(data, 67, 3),
+            level_offsets: (0..levels).map(|i| field(data, Self::LEVEL_OFFSETS + i * 4, 4)).collect(),
+        })
+    }
+}
+
+/// Huffman models for the block data: how blocks share endpoints (chunk encodings in the legacy format, endpoint
+/// references in Unity's), then endpoint deltas and selectors, color first and alpha second.
+pub(super) struct Tables {
+    encoding: Model,
+    endpoint_delta: [Model; 2],
+    selector_delta: [Model; 2],
+}
+
+impl Tables {
+    fn read(data: &[u8], header: &Header) -> Result<Self, DecodeImageError> {
+        let mut codec = Codec::new(data);
+        let encoding = codec.receive_model()?;
+        if header.color_endpoints.len == 0 && header.alpha_endpoints.len == 0 {
+            return Err(invalid("no endpoints"));
+        }
+        let mut tables = Self {
+            encoding,
+            endpoint_delta: Default::default(),
+            selector_delta: Default::default(),
+        };
+        for (i, palette) in [&header.color_endpoints, &header.alpha_endpoints].into_iter().enumerate() {
+            if palette.len > 0 {
+                tables.endpoint_delta[i] = codec.receive_model()?;
+                tables.selector_delta[i] = codec.receive_model()?;
+            }
+        }
+        Ok(tables)
+    }
+}
+
+/// Maps the selectors crunch stores in endpoint order to DXT1 and DXT5 indices.
+const DXT1_FROM_LINEAR: [u32; 4] = [0, 2, 3, 1];
+const DXT5_FROM_LINEAR: [u64; 8] = [0, 2, 3, 4, 5, 6, 7, 1];
+
+/// Endpoints and selectors in the layout of the output blocks. Color endpoints and selectors are the two little-endian
+/// words of a DXT1 or ETC block; alpha endpoints and selectors are the two bytes and six bytes of a DXT5 or EAC alpha
+/// block. Unity's ETC formats store each selector both transposed and as is, picked by the flip bit of the block.
+pub(super) struct Palettes {
+    color_endpoints: Vec<u32>,
+    color_selectors: Vec<u32>,
+    alpha_endpoints: Vec<[u8; 2]>,
+    alpha_selectors: Vec<[u8; 6]>,
+}
+
+impl Palettes {
+    fn read(data: &[u8], header: &Header, unity: bool) -> Result<Self, DecodeImageError> {
+        let codec = |palette: &Palette| section(data, palette.offset, palette.size).map(Codec::new);
+        let mut palettes = Self {
+            color_endpoints: Vec::new(),
+            color_selectors: Vec::new(),
+            alpha_endpoints: Vec::new(),
+            alpha_selectors: Vec::new(),
+        };
+        if header.color_endpoints.len > 0 {
+            palettes.read_color_endpoints(&mut codec(&header.color_endpoints)?, header.color_endpoints.len, header.format)?;
+        }
+        if header.color_selectors.len > 0 {
+            palettes.read_color_selectors(&mut codec(&header.color_selectors)?, header.color_selectors.len, header.format, unity)?;
+        }
+        if header.alpha_endpoints.len > 0 {
+            palettes.read_alpha_endpoints(&mut codec(&header.alpha_endpoints)?, header.alpha_endpoints.len)?;
+        }
+        if header.alpha_selectors.len > 0 {
+            palettes.read_alpha_selectors(&mut codec(&header.alpha_selectors)?, header.alpha_selectors.len, header.format, unity)?;
+        }
+        Ok(palettes)
+    }
+
+    fn read_color_endpoints(&mut self, codec: &mut Codec, len: usize, format: CrunchFormat) -> Result<(), DecodeImageError> {
+        if format.is_etc() {
+            // Three 5-bit colors and a 3-bit table index, sent as bytewise deltas.
+            let model = codec.receive_model()?;
+            let mut a = 0u32;
+            for _ in 0..len {
+                for shift in (0..32).step_by(8) {
+                    a = a.wrapping_add(codec.decode(&model)? << shift);
+                }
+                a &= 0x1f1f1f1f;
+                self.color_endpoints.push(if format.has_subblocks() {
+                    a
+                } else {
+                    // A differential block whose two subblocks share the color and table.
+                    (a & 0x07000000) << 5 | (a & 0x07000000) << 2 | 0x02000000 | (a & 0x001f1f1f) << 3
+                });
+            }
+        } else {
+            // Two RGB565 colors, each channel sent as a delta from the previous endpoint.
+            let models = [codec.receive_model()?, codec.receive_model()?];
+            let mut channels = [0u32; 6];
+            for _ in 0..len {
+                for (i, (channel, bits)) in channels.iter_mut().zip([5, 6, 5, 5, 6, 5]).enumerate() {
+                    let model = &models[(i % 3 == 1) as usize];
+                    *channel = (*channel + codec.decode(model)?) & ((1 << bits) - 1);
+                }
+                let [r0, g0, b0, r1, g1, b1] = channels;
+                self.color_endpoints.push(b0 | g0 << 5 | r0 << 11 | b1 << 16 | g1 << 21 | r1 << 27);
+            }
+        }
+        Ok(())
+    }
+
+    fn read_color_selectors(&mut self, codec: &mut Codec, len: usize, format: CrunchFormat, unity: bool) -> Result<(), DecodeImageError> {
+        let model = codec.receive_model()?;
+        if !unity {
+            // Each symbol moves the 2-bit selectors of two texels by -3..=3.
+            let mut current = [0u32; 16];
+            for _ in 0..len {
+                for pair in current.chunks_exact_mut(2) {
+                    let symbol = codec.decode(&model)? as i32;
+                    pair[0] = (pair[0] as i32 + symbol % 7 - 3) as u32 & 3;
+                    pair[1] = (pair[1] as i32 + symbol / 7 - 3) as u32 & 3;
+                }
+                self.color_selectors.push(current.iter().enumerate().fold(0, |s, (i, &c)| s | DXT1_FROM_LINEAR[c as usize] << (i * 2)));
+            }
+            return Ok(());
+        }
+
+        // Each symbol is xored into four bits, two texels, of the previous selector.
+        let mut s = 0u32;
+        for _ in 0..len {
+            for shift in (0..32).step_by(4) {
+                s ^= codec.decode(&model)? << shift;
+            }
+            if !format.is_etc() {
+                self.color_selectors.push(((s ^ s << 1) & 0xaaaaaaaa) | (s >> 1 & 0x55555555));
+                continue;
+            }
+            // ETC selectors go column by column, with the most significant bit of every texel in the first half
+            // of the big-endian word.
+            let indices = (!s & 0xaaaaaaaa) | (!(s ^ s >> 1) & 0x55555555);
+            let etc = |texel: u32, x: u32, y: u32| {
+                let t = (x * 4 + y + 8) & 15;
+                ((texel >> 1 & 1) | (texel & 1) << 16) << t
+            };
+            let (mut transposed, mut plain) = (0, 0);
+            for y in 0..4 {
+                for x in 0..4 {
+                    transposed |= etc(indices >> (x * 8 + y * 2), x, y);
+                    plain |= etc(indices >> (y * 8 + x * 2), x, y);
+                }
+            }
+            if format.has_subblocks() {
+                self.color_selectors.push(transposed);
+            }
+            self.color_selectors.push(plain);
+        }
+        Ok(())
+    }
+
+    fn read_alpha_endpoints(&mut self, codec: &mut Codec, len: usize) -> Result<(), DecodeImageError> {
+        let model = codec.receive_model()?;
+        let (mut a, mut b) = (0u32, 0u32);
+        for _ in 0..len {
+            a = (a + codec.decode(&model)?) & 0xff;
+            b = (b + codec.decode(&model)?) & 0xff;
+            self.alpha_endpoints.push([a as u8, b as u8]);
+        }
+        Ok(())
+    }
+
+    fn read_alpha_selectors(&mut self, codec: &mut Codec, len: usize, format: CrunchFormat, unity: bool) -> Result<(), DecodeImageError> {
+        let model = codec.receive_model()?;
+        let dxt = |texels: &[u64; 16]| {
+            let bits = texels.iter().enumerate().fold(0u64, |bits, (i, &s)| bits | DXT5_FROM_LINEAR[s as usize] << (i * 3));
+            bits.to_le_bytes()[..6].try_into().unwrap()
+        };
+        let mut current = [0u64; 16];
+        if !unity {
+            // Each symbol moves the 3-bit selectors of two texels by -7..=7.
+            for _ in 0..len {
+                for pair in current.chunks_exact_mut(2) {
+                    let symbol = codec.decode(&model)? as i64;
+                    pair[0] = (pair[0] as i64 + symbol % 15 - 7) as u64 & 7;
+                    pair[1] = (pair[1] as i64 + symbol / 15 - 7) as u64 & 7;
+                }
+                self.alpha_selectors.push(dxt(&current));
+            }
+            return Ok(());
+        }
+
+        // Each symbol is xored into six bits, two texels, of the previous selector.
+        let mut groups = [0u32; 8];
+        for _ in 0..len {
+            for (pair, group) in groups.iter_mut().enumerate() {
+                *group ^= codec.decode(&model)?;
+                current[pair * 2] = (*group & 7) as u64;
+                current[pair * 2 + 1] = (*group >> 3 & 7) as u64;
+            }
+            if !format.is_etc() {
+                self.alpha_selectors.push(dxt(&current));
+                continue;
+            }
+            // EAC modifiers run from the smallest negative one at index 0 to the largest at 3, then the positive ones.
+            let eac = |order: &dyn Fn(u64, u64) -> u64| {
+                let bits = (0..16).fold(0u64, |bits, i| {
+                    let s = current[i];
+                    let s = if s <= 3 { 3 - s } else { s };
+                    bits | s << (45 - 3 * order(i as u64 % 4, i as u64 / 4))
+                });
+                bits.to_be_bytes()[2..].try_into().unwrap()
+            };
+            self.alpha_selectors.push(eac(&|x, y| y * 4 + x));
+            self.alpha_selectors.push(eac(&|x, y| x * 4 + y));
+        }
+        Ok(())
+    }
+}
+
+/// The output rows of blocks of every face, ignoring blocks past the edges of the level.
+pub(super) struct Blocks {
+    blocks_x: usize,
+    blocks_y: usize,
+    faces: usize,
+    block_size: usize,
+    data: Vec<u8>,
+}
+
+impl Blocks {
+    fn new(blocks_x: usize, blocks_y: usize, faces: usize, block_size: usize) -> Self {
+        Self {
+            blocks_x,
+            blocks_y,
+            faces,
+            block_size,
+            data: vec![0; blocks_x * blocks_y * faces * block_size],
+        }
+    }
+
+    pub(super) fn put(&mut self, face: usize, x: usize, y: usize, block: &[u8]) {
+        if x < self.blocks_x && y < self.blocks_y {
+            let offset = ((face * self.blocks_y + y) * self.blocks_x + x) * self.block_size;
+            self.data[offset..offset + self.block_size].copy_from_slice(block);
+        }
+    }
+}
+
+/// Advances a palette index by a delta, wrapping around the palette.
+pub(super) fn advance(index: usize, delta: u32, len: usize) -> Result<usize, DecodeImageError> {
+    let index = index + delta as usize;
+    let index = if index >= len { index - len } else { index };
+    if index >= len {
+        return Err(invalid("palette index out of range"));
+    }
+    Ok(index)
+}
+
+pub(super) fn entry<T: Copy>(palette: &[T], index: usize) -> Result<T, DecodeImageError> {
+    palette.get(index).copied().ok_or_else(|| invalid("palette index out of range"))
+}
@@ -0,0 +1,162 @@
+//! Unity's crunch layout: blocks are visited row by row and each one either codes new endpoints, reuses those of
+//! the block to its left, or those of the block above, as told by references sent for every two by two blocks.
+//! Selectors are indices into their palette rather than deltas.
+
+use super::{advance, entry, Blocks, CrunchFormat, Palettes, Tables};
+use crate::crunch::codec::Codec;
+use crate::error::DecodeImageError;
+
+#[derive(Default, Clone, Copy)]
+struct Above {
+    reference: u32,
+    color: usize,
+    alpha: usize,
+}
+
+pub(super) fn unpack(codec: &mut Codec, tables: &Tables, palettes: &Palettes, format: CrunchFormat, blocks: &mut Blocks) -> Result<(), DecodeImageError> {
+    if format.has_subblocks() {
+        unpack_subblocks(codec, tables, palettes, format == CrunchFormat::Etc2A, blocks)
+    } else {
+        unpack_blocks(codec, tables, palettes, format, blocks)
+    }
+}
+
+/// Formats with one color endpoint per block: DXT1, DXT5, ETC1S and ETC2AS.
+fn unpack_blocks(codec: &mut Codec, tables: &Tables, palettes: &Palettes, format: CrunchFormat, blocks: &mut Blocks) -> Result<(), DecodeImageError> {
+    let alpha = format.block_size() == 16;
+    let width = blocks.blocks_x.next_multiple_of(2);
+    let height = blocks.blocks_y.next_multiple_of(2);
+    let mut row = vec![Above::default(); width];
+    let (mut color, mut alpha_endpoint, mut references) = (0, 0, 0);
+
+    for face in 0..blocks.faces {
+        for y in 0..height {
+            for (x, above) in row.iter_mut().enumerate() {
+                if y % 2 == 0 && x % 2 == 0 {
+                    references = codec.decode(&tables.encoding)?;
+                }
+                // Even rows take the reference of this block and of the one below from the group.
+                let reference = if y % 2 == 1 {
+                    above.reference
+                } else {
+                    let reference = references & 3;
+                    above.reference = references >> 2 & 3;
+                    references >>= 4;
+                    reference
+                };
+                match reference {
+                    0 => {
+                        color = advance(color, codec.decode(&tables.endpoint_delta[0])?, palettes.color_endpoints.len())?;
+                        if alpha {
+                            alpha_endpoint = advance(alpha_endpoint, codec.decode(&tables.endpoint_delta[1])?, palettes.alpha_endpoints.len())?;
+                        }
+                        above.color = color;
+                        above.alpha = alpha_endpoint;
+                    }
+                    1 => {
+                        above.color = color;
+                        above.alpha = alpha_endpoint;
+                    }
+                    _ => {
+                        color = above.color;
+                        alpha_endpoint = above.alpha;
+                    }
+                }
+
+                let color_selector = codec.decode(&tables.selector_delta[0])? as usize;
+                let mut block = [0u8; 16];
+                let color_block = if alpha {
+                    let alpha_selector = codec.decode(&tables.selector_delta[1])? as usize;
+                    // ETC2AS blocks are never flipped, so they take the selectors as is.
+                    let alpha_selector = if format.is_etc() { alpha_selector * 2 + 1 } else { alpha_selector };
+                    block[..2].copy_from_slice(&entry(&palettes.alpha_endpoints, alpha_endpoint)?);
+                    block[2..8].copy_from_slice(&entry(&palettes.alpha_selectors, alpha_selector)?);
+                    &mut block[8..]
+                } else {
+                    &mut block[..8]
+                };
+                color_block[..4].copy_from_slice(&entry(&palettes.color_endpoints, color)?.to_le_bytes());
+                color_block[4..8].copy_from_slice(&entry(&palettes.color_selectors, color_selector)?.to_le_bytes());
+                blocks.put(face, x, y, &block[..format.block_size()]);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// ETC1, ETC2 and ETC2A, whose blocks have an endpoint for each subblock and may be flipped. Besides the left and
+/// above blocks, the first endpoint may come from the second endpoint of the block up and to the left.
+fn unpack_subblocks(codec: &mut Codec, tables: &Tables, palettes: &Palettes, alpha: bool, blocks: &mut Blocks) -> Result<(), DecodeImageError> {
+    let width = blocks.blocks_x.next_multiple_of(2);
+    let height = blocks.blocks_y.next_multiple_of(2);
+    // The first and second endpoints of the row above, interleaved.
+    let mut above = vec![Above::default(); width * 2];
+    let (mut color, mut diagonal, mut alpha_endpoint) = (0, 0, 0);
+
+    for face in 0..blocks.faces {
+        for y in 0..height {
+            for x in 0..width {
+                let mut reference = if y % 2 == 1 {
+                    above[x * 2].reference
+                } else {
+                    let references = codec.decode(&tables.encoding)?;
+                    above[x * 2].reference = (references >> 2 & 3) | (references >> 4 & 12);
+                    (references & 3) | (references >> 2 & 12)
+                };
+                let current = &mut above[x * 2];
+                match reference & 3 {
+                    0 => {
+                        color = advance(color, codec.decode(&tables.endpoint_delta[0])?, palettes.color_endpoints.len())?;
+                        if alpha {
+                            alpha_endpoint = advance(alpha_endpoint, codec.decode(&tables.endpoint_delta[1])?, palettes.alpha_endpoints.len())?;
+                        }
+                        current.color = color;
+                        current.alpha = alpha_endpoint;
+                    }
+                    1 => {
+                        current.color = color;
+                        current.alpha = alpha_endpoint;
+                    }
+                    3 => {
+                        color = diagonal;
+                        current.color = color;
+                        current.alpha = alpha_endpoint;
+                    }
+                    _ => {
+                        color = current.color;
+                        alpha_endpoint = current.alpha;
+                    }
+                }
+                // The upper two bits tell whether the second subblock has its own endpoint and, inverted, the flip bit.
+                reference >>= 2;
+                let e0 = entry(&palettes.color_endpoints, color)?.to_le_bytes();
+                let selector = codec.decode(&tables.selector_delta[0])? as usize;
+                let alpha_selector = if alpha { codec.decode(&tables.selector_delta[1])? as usize } else { 0 };
+                if reference != 0 {
+                    color = advance(color, codec.decode(&tables.endpoint_delta[0])?, palettes.color_endpoints.len())?;
+                }
+                diagonal = above[x * 2 + 1].color;
+                above[x * 2 + 1].color = color;
+                let e1 = entry(&palettes.color_endpoints, color)?.to_le_bytes();
+
+                let flip = (reference >> 1 ^ 1) as usize;
+                let diff = (0..3).all(|c| e0[c] + 3 >= e1[c] && e1[c] + 4 >= e0[c]);
+                let mut block = [0u8; 16];
+                let color_block = if alpha {
+                    block[..2].copy_from_slice(&entry(&palettes.alpha_endpoints, alpha_endpoint)?);
+                    block[2..8].copy_from_slice(&entry(&palettes.alpha_selectors, alpha_selector * 2 + flip)?);
+                    &mut block[8..]
+                } else {
+                    &mut block[..8]
+                };
+                for c in 0..3 {
+                    color_block[c] = if diff { e0[c] << 3 | (e1[c].wrapping_sub(e0[c]) & 7) } else { (e0[c] << 3 & 0xf0) | e1[c] >> 1 };
+                }
+                color_block[3] = e0[3] << 5 | e1[3] << 2 | (diff as u8) << 1 | flip as u8;
+                color_block[4..8].copy_from_slice(&entry(&palettes.color_selectors, selector * 2 + flip)?.to_le_bytes());
+                blocks.put(face, x, y, &block[..if alpha { 16 } else { 8 }]);
+            }
+        }
+    }
+    Ok(())
+}
@@ -45,3 +45,13 @@ pub trait ImageDecoder<const PIXEL_NUM: usize = 1> {
 
     fn decode_pixel(data: &mut &[u8]) -> io::Result<[Pixel; PIXEL_NUM]>;
 }
+
+/// Decodes a pixel to float RGBA, keeping the range and precision that 8 bits per channel would lose.
+pub trait FloatImageDecoder: ImageDecoder {
+    fn decode_pixel_f32(data: &mut &[u8]) -> io::Result<[f32; 4]>;
+}
+
+/// Decodes a pixel to 16-bit RGBA, for formats with 16 bits per channel.
+pub trait U16ImageDecoder: ImageDecoder {
+    fn decode_pixel_u16(data: &mut &[u8]) -> io::Result<[u16; 4]>;
+}
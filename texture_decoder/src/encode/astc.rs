@@ -0,0 +1,123 @@
+use crate::block::astc::{trits, unquantize_color, IseValue};
+use std::sync::OnceLock;
+
+/// The block mode of a 4x4 grid of weights in 0..=7. The 48 bits of weights leave 63 for the endpoints, which the
+/// decoder fills with the largest range that fits: a trit and 6 bits per value.
+const BLOCK_MODE: u128 = 0x53;
+const GRID: usize = 4;
+/// Index of the range of 192 values among the ranges of the integer sequence encoding.
+const COLOR_RANGE: usize = 19;
+/// Color endpoint mode 12: LDR RGBA with direct endpoints.
+const LDR_RGBA_DIRECT: u128 = 12;
+/// The weights 0..=7 stand for, out of 64.
+const WEIGHTS: [f32; 8] = [0.0, 9.0, 18.0, 27.0, 37.0, 46.0, 55.0, 64.0];
+
+/// Encodes a block of `width` by `height` texels with a single partition and one plane of weights.
+pub(super) fn encode_block(texels: &[[u8; 4]], width: usize, height: usize) -> [u8; 16] {
+    let endpoints = endpoint_values();
+    let mut ends = super::fit_line(texels).map(|color| color.map(|v| endpoints[v.round() as usize]));
+    // Endpoints whose second color is darker get swapped and blue contracted by the decoder.
+    let brightness = |color: [(IseValue, u8); 4]| color[..3].iter().map(|&(_, v)| v as u32).sum::<u32>();
+    if brightness(ends[1]) < brightness(ends[0]) {
+        ends.swap(0, 1);
+    }
+
+    // Where every texel falls between the endpoints, from 0 to 1.
+    let axis = [0, 1, 2, 3].map(|c| ends[1][c].1 as f32 - ends[0][c].1 as f32);
+    let length = axis.iter().map(|v| v * v).sum::<f32>();
+    let positions = texels
+        .iter()
+        .map(|texel| {
+            if length == 0.0 {
+                0.0
+            } else {
+                ((0..4).map(|c| (texel[c] as f32 - ends[0][c].1 as f32) * axis[c]).sum::<f32>() / length).clamp(0.0, 1.0)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // The grid is fitted to the positions by least squares, one point at a time, in a few passes.
+    let shares = (0..width * height).map(|i| contributions(i % width, i / width, width, height)).collect::<Vec<_>>();
+    let mut grid = [0f32; GRID * GRID];
+    let mut residuals = positions;
+    for _ in 0..8 {
+        for (point, value) in grid.iter_mut().enumerate() {
+            let share_of = |shares: &[(usize, f32); 4]| shares.iter().filter(|&&(p, _)| p == point).map(|&(_, share)| share).sum::<f32>();
+            let (numerator, denominator) = shares.iter().zip(&residuals).fold((0.0, 0.0), |(numerator, denominator), (shares, residual)| {
+                let share = share_of(shares);
+                (numerator + share * residual, denominator + share * share)
+            });
+            if denominator == 0.0 {
+                continue;
+            }
+            let next = (*value + numerator / denominator).clamp(0.0, 1.0);
+            for (shares, residual) in shares.iter().zip(&mut residuals) {
+                *residual -= share_of(shares) * (next - *value);
+            }
+            *value = next;
+        }
+    }
+    let weights = grid.map(|value| (0..8).min_by(|&a, &b| (WEIGHTS[a] - value * 64.0).abs().total_cmp(&(WEIGHTS[b] - value * 64.0).abs())).unwrap() as u128);
+
+    let mut bits = BLOCK_MODE | LDR_RGBA_DIRECT << 13;
+    // The values go red, green, blue and alpha, each first for one endpoint and then for the other. They are packed
+    // in groups of five, the plain bits of each followed by some bits of the trits of the group.
+    let values = [0, 1, 2, 3].map(|c| [ends[0][c].0, ends[1][c].0]).concat();
+    let mut position = 17;
+    for group in values.chunks(5) {
+        let mut digits = [0; 5];
+        digits.iter_mut().zip(group).for_each(|(digit, value)| *digit = value.digit as usize);
+        let packed = trit_packing()[digits.iter().rev().fold(0, |index, &digit| index * 3 + digit)] as u128;
+        for (value, (shift, count)) in group.iter().zip([(0, 2), (2, 2), (4, 1), (5, 2), (7, 1)]) {
+            bits |= (value.bits as u128) << position | (packed >> shift & ((1 << count) - 1)) << (position + 6);
+            position += 6 + count;
+        }
+    }
+    // Weights are stored bit-reversed from the top of the block.
+    for (i, weight) in weights.iter().enumerate() {
+        for b in 0..3 {
+            bits |= (weight >> b & 1) << (127 - i * 3 - b);
+        }
+    }
+    bits.to_le_bytes()
+}
+
+/// For every 8-bit value, the nearest endpoint value of [`COLOR_RANGE`] and what it decodes to.
+fn endpoint_values() -> &'static [(IseValue, u8); 256] {
+    static VALUES: OnceLock<[(IseValue, u8); 256]> = OnceLock::new();
+    VALUES.get_or_init(|| {
+        let decoded = (0..3)
+            .flat_map(|digit| (0..64).map(move |bits| IseValue { digit, bits }))
+            .map(|value| (value, unquantize_color(value, COLOR_RANGE) as u8))
+            .collect::<Vec<_>>();
+        std::array::from_fn(|v| *decoded.iter().min_by_key(|(_, decoded)| decoded.abs_diff(v as u8)).unwrap())
+    })
+}
+
+/// The 8 bits five trits are packed into, by the trits as a number in base 3 with the first trit lowest. Of the
+/// packings of the same trits the smallest is kept, whose high bits are zero when the last trits are, as they have to
+/// be for a group cut short.
+fn trit_packing() -> &'static [u8; 243] {
+    static PACKING: OnceLock<[u8; 243]> = OnceLock::new();
+    PACKING.get_or_init(|| {
+        let mut packing = [0; 243];
+        for t in (0..=255u8).rev() {
+            packing[trits(t as u32).iter().rev().fold(0, |index, &digit| index * 3 + digit as usize)] = t;
+        }
+        packing
+    })
+}
+
+/// The grid points the weight of texel (`x`, `y`) is interpolated from, with their shares.
+fn contributions(x: usize, y: usize, width: usize, height: usize) -> [(usize, f32); 4] {
+    let grid_coordinate = |texel: usize, size: usize| {
+        let scaled = (1024 + size / 2) / (size - 1) * texel;
+        let position = (scaled * (GRID - 1) + 32) >> 6;
+        (position >> 4, (position & 0xf) as u32)
+    };
+    let (gx, fx) = grid_coordinate(x, width);
+    let (gy, fy) = grid_coordinate(y, height);
+    let point = |gx: usize, gy: usize| gy.min(GRID - 1) * GRID + gx.min(GRID - 1);
+    let w11 = (fx * fy + 8) >> 4;
+    [(point(gx, gy), 16 + w11 - fx - fy), (point(gx + 1, gy), fx - w11), (point(gx, gy + 1), fy - w11), (point(gx + 1, gy + 1), w11)].map(|(point, share)| (point, share as f32 / 16.0))
+}
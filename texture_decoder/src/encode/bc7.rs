@@ -0,0 +1,46 @@
+/// The weights of the 16 levels between the endpoints of mode 6, out of 64.
+const WEIGHTS: [i32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+/// Encodes 16 texels as a mode 6 block: two RGBA endpoints of 7 bits with a p-bit each and a 4-bit index per texel.
+pub(super) fn encode_block(texels: &[[u8; 4]]) -> [u8; 16] {
+    let mut ends = super::fit_line(texels).map(quantize);
+    let colors = ends.map(|(color, p)| color.map(|c| (c << 1 | p) as i32));
+    let palette = WEIGHTS.map(|w| [0, 1, 2, 3].map(|c| ((64 - w) * colors[0][c] + w * colors[1][c] + 32) >> 6));
+    let mut indices = texels.iter().map(|texel| (0..16).min_by_key(|&i| super::distance(palette[i], texel.map(|c| c as i32))).unwrap()).collect::<Vec<_>>();
+    // The top bit of the first index is left out, so it has to be zero.
+    if indices[0] >= 8 {
+        ends.swap(0, 1);
+        indices.iter_mut().for_each(|i| *i = 15 - *i);
+    }
+
+    let mut bits = 1u128 << 6;
+    let mut position = 7;
+    let mut put = |value: u128, count: usize| {
+        bits |= value << position;
+        position += count;
+    };
+    for c in 0..4 {
+        put(ends[0].0[c] as u128, 7);
+        put(ends[1].0[c] as u128, 7);
+    }
+    put(ends[0].1 as u128, 1);
+    put(ends[1].1 as u128, 1);
+    for (i, &index) in indices.iter().enumerate() {
+        put(index as u128, if i == 0 { 3 } else { 4 });
+    }
+    bits.to_le_bytes()
+}
+
+/// The 7-bit channels and the p-bit shared by them that come closest to `color`.
+fn quantize(color: [f32; 4]) -> ([u32; 4], u32) {
+    [0, 1]
+        .map(|p| {
+            let channels = color.map(|v| ((v - p as f32) / 2.0).round().clamp(0.0, 127.0) as u32);
+            let error = (0..4).map(|c| ((channels[c] << 1 | p) as f32 - color[c]).powi(2)).sum::<f32>();
+            (channels, p, error)
+        })
+        .into_iter()
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(channels, p, _)| (channels, p))
+        .unwrap()
+}
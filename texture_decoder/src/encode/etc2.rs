@@ -0,0 +1,98 @@
+use crate::block::eac::MODIFIERS as ALPHA_MODIFIERS;
+use crate::block::etc1::MODIFIERS;
+
+/// Encodes the colors of 16 texels as an ETC1 block, which ETC2 decodes the same way, given as its big-endian 64-bit
+/// value. Both ways of splitting the block in halves are tried, each with 4-bit bases or with 5-bit bases that differ
+/// by less than the 3 bits the second is stored in; the differential mode never overflows into the modes ETC2 adds.
+pub(super) fn encode_color(texels: &[[u8; 4]]) -> u64 {
+    let mut best = (u32::MAX, 0);
+    for flip in [false, true] {
+        let half = |i: usize| if flip { i / 8 } else { i % 4 / 2 };
+        let mean = [0, 1].map(|h| {
+            let texels = texels.iter().enumerate().filter(|&(i, _)| half(i) == h);
+            let mut sum = [0u32; 3];
+            for (_, texel) in texels {
+                (0..3).for_each(|c| sum[c] += texel[c] as u32);
+            }
+            sum.map(|v| v as f32 / 8.0)
+        });
+
+        let individual = mean.map(|m| m.map(|v| (v * 15.0 / 255.0).round() as u8));
+        let differential = mean.map(|m| m.map(|v| (v * 31.0 / 255.0).round() as u8));
+        let mut candidates = vec![(individual.map(|base| base.map(|v| v * 17)), individual.map(|b| b.map(|v| v as u64)), false)];
+        if (0..3).all(|c| (-4..=3).contains(&(differential[1][c] as i32 - differential[0][c] as i32))) {
+            let delta = [0, 1, 2].map(|c| (differential[1][c] as i32 - differential[0][c] as i32) as u64 & 7);
+            candidates.push((differential.map(|base| base.map(|v| v << 3 | v >> 2)), [differential[0].map(|v| v as u64), delta], true));
+        }
+
+        for (bases, stored, diff) in candidates {
+            let mut error = 0;
+            let mut block = (diff as u64) << 33 | (flip as u64) << 32;
+            for (c, (first, second)) in stored[0].iter().zip(&stored[1]).enumerate() {
+                if diff {
+                    block |= first << (59 - c * 8) | second << (56 - c * 8);
+                } else {
+                    block |= first << (60 - c * 8) | second << (56 - c * 8);
+                }
+            }
+            for (h, base) in bases.iter().enumerate() {
+                let (table_error, table, indices) = (0..8)
+                    .map(|table| {
+                        let mut error = 0;
+                        let mut indices = 0u64;
+                        for (i, texel) in texels.iter().enumerate().filter(|&(i, _)| half(i) == h) {
+                            let (index_error, index) = (0..4)
+                                .map(|index| {
+                                    let modifier = MODIFIERS[table][index & 1] as i32 * if index & 2 == 0 { 1 } else { -1 };
+                                    let color = base.map(|b| (b as i32 + modifier).clamp(0, 255));
+                                    (super::distance(color, [texel[0], texel[1], texel[2]].map(|c| c as i32)), index as u64)
+                                })
+                                .min()
+                                .unwrap();
+                            error += index_error;
+                            // Indices are stored column by column, the high bits above the low ones.
+                            let pixel = i % 4 * 4 + i / 4;
+                            indices |= (index >> 1) << (16 + pixel) | (index & 1) << pixel;
+                        }
+                        (error, table as u64, indices)
+                    })
+                    .min()
+                    .unwrap();
+                error += table_error;
+                block |= table << (37 - h * 3) | indices;
+            }
+            if error < best.0 {
+                best = (error, block);
+            }
+        }
+    }
+    best.1
+}
+
+/// Encodes the alpha of 16 texels as an 8-bit EAC block, given as its big-endian 64-bit value. For every table, the
+/// multipliers around the one that spans the range of the alpha are tried with the bases around its middle.
+pub(super) fn encode_alpha(texels: &[[u8; 4]]) -> u64 {
+    let (min, max) = texels.iter().fold((255, 0), |(min, max), t| (t[3].min(min), t[3].max(max)));
+    let mut best = (u32::MAX, 0);
+    for (table, modifiers) in ALPHA_MODIFIERS.iter().enumerate() {
+        let (low, high) = (modifiers[3], modifiers[7]);
+        let fit = (max as i32 - min as i32 + high - low - 1) / (high - low);
+        for multiplier in (fit - 1..=fit + 1).filter(|m| (1..=15).contains(m)) {
+            let middle = ((min as i32 + max as i32) as f32 / 2.0 - (low + high) as f32 * multiplier as f32 / 2.0).round() as i32;
+            for base in (middle - 1..=middle + 1).map(|b| b.clamp(0, 255)) {
+                let mut error = 0;
+                let mut block = (base as u64) << 56 | (multiplier as u64) << 52 | (table as u64) << 48;
+                for (i, texel) in texels.iter().enumerate() {
+                    let (index_error, index) = modifiers.iter().enumerate().map(|(index, m)| (((base + m * multiplier).clamp(0, 255) - texel[3] as i32).pow(2) as u32, index as u64)).min().unwrap();
+                    error += index_error;
+                    let pixel = i % 4 * 4 + i / 4;
+                    block |= index << (45 - pixel * 3);
+                }
+                if error < best.0 {
+                    best = (error, block);
+                }
+            }
+        }
+    }
+    best.1
+}
@@ -0,0 +1,110 @@
+//! Encoders for the block compressed formats a texture can be imported to: BC7, ETC2 and LDR ASTC.
+//!
+//! Every block is fitted with a single line through its colors, which is quick and keeps flat areas and gradients,
+//! but leaves blocks of unrelated colors coarser than a full search would. Images are taken top-down and encoded
+//! bottom row first, as Unity stores them, so they decode back with the [`block`](crate::block) decoders. Blocks past
+//! the edge of the image repeat its last row and column.
+
+mod astc;
+mod bc7;
+mod etc2;
+
+use image::RgbaImage;
+use rayon::prelude::*;
+
+/// BC7 in mode 6: RGBA endpoints with 16 levels between them.
+pub fn encode_bc7(img: &RgbaImage) -> Vec<u8> {
+    encode_blocks(img, (4, 4), bc7::encode_block)
+}
+
+/// ETC2 RGB, in the ETC1 compatible modes.
+pub fn encode_etc2_rgb(img: &RgbaImage) -> Vec<u8> {
+    encode_blocks(img, (4, 4), |texels| etc2::encode_color(texels).to_be_bytes())
+}
+
+/// ETC2 RGBA8: an EAC alpha block followed by an ETC2 RGB block.
+pub fn encode_etc2_rgba8(img: &RgbaImage) -> Vec<u8> {
+    encode_blocks(img, (4, 4), |texels| {
+        let mut block = [0; 16];
+        block[..8].copy_from_slice(&etc2::encode_alpha(texels).to_be_bytes());
+        block[8..].copy_from_slice(&etc2::encode_color(texels).to_be_bytes());
+        block
+    })
+}
+
+/// LDR ASTC with blocks of `block_width` by `block_height` texels, each given a 4x4 grid of weights.
+pub fn encode_astc(img: &RgbaImage, block_width: usize, block_height: usize) -> Vec<u8> {
+    encode_blocks(img, (block_width, block_height), |texels| astc::encode_block(texels, block_width, block_height))
+}
+
+/// Hands the texels of every block to `encode_block` in row-major order, the block rows from the bottom of the image.
+fn encode_blocks<const N: usize, F>(img: &RgbaImage, (block_width, block_height): (usize, usize), encode_block: F) -> Vec<u8>
+where
+    F: Fn(&[[u8; 4]]) -> [u8; N] + Sync,
+{
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let blocks_x = width.div_ceil(block_width);
+    let blocks = blocks_x * height.div_ceil(block_height);
+    (0..blocks)
+        .into_par_iter()
+        .flat_map_iter(|i| {
+            let (x0, y0) = (i % blocks_x * block_width, i / blocks_x * block_height);
+            let texels = (0..block_width * block_height)
+                .map(|j| {
+                    let x = (x0 + j % block_width).min(width - 1);
+                    let y = height - 1 - (y0 + j / block_width).min(height - 1);
+                    img.get_pixel(x as u32, y as u32).0
+                })
+                .collect::<Vec<_>>();
+            encode_block(&texels)
+        })
+        .collect()
+}
+
+/// The ends of the line through `texels` along the direction they vary the most, clamped to 0..=255.
+fn fit_line(texels: &[[u8; 4]]) -> [[f32; 4]; 2] {
+    let n = texels.len() as f32;
+    let mean = [0, 1, 2, 3].map(|c| texels.iter().map(|t| t[c] as f32).sum::<f32>() / n);
+    let mut covariance = [[0f32; 4]; 4];
+    for texel in texels {
+        let d = [0, 1, 2, 3].map(|c| texel[c] as f32 - mean[c]);
+        for (row, &a) in covariance.iter_mut().zip(&d) {
+            for (value, &b) in row.iter_mut().zip(&d) {
+                *value += a * b;
+            }
+        }
+    }
+
+    // Power iteration, starting from the diagonal of the bounding box.
+    let mut axis = [0, 1, 2, 3].map(|c| {
+        let (min, max) = texels.iter().fold((255, 0), |(min, max), t| (t[c].min(min), t[c].max(max)));
+        (max - min) as f32
+    });
+    for _ in 0..8 {
+        let next = [0, 1, 2, 3].map(|c| (0..4).map(|k| covariance[c][k] * axis[k]).sum::<f32>());
+        let length = next.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if length < 1e-6 {
+            break;
+        }
+        axis = next.map(|v| v / length);
+    }
+    let length = axis.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if length < 1e-6 {
+        return [mean, mean];
+    }
+    let axis = axis.map(|v| v / length);
+
+    let (low, high) = texels.iter().fold((f32::MAX, f32::MIN), |(low, high), texel| {
+        let t = (0..4).map(|c| (texel[c] as f32 - mean[c]) * axis[c]).sum::<f32>();
+        (low.min(t), high.max(t))
+    });
+    [low, high].map(|t| [0, 1, 2, 3].map(|c| (mean[c] + axis[c] * t).clamp(0.0, 255.0)))
+}
+
+/// The squared distance between two colors.
+fn distance<const N: usize>(a: [i32; N], b: [i32; N]) -> u32 {
+    a.iter().zip(&b).map(|(a, b)| (a - b).pow(2) as u32).sum()
+}
@@ -0,0 +1,13 @@
+use crate::ImageDecoder;
+
+/// Encodes a pixel back into the format of its [`ImageDecoder`], so that decoding gives back every pixel the format
+/// can represent.
+pub trait ImageEncoder: ImageDecoder {
+    fn encode_pixel(pixel: [u8; 4], out: &mut Vec<u8>);
+}
+
+/// The nearest value with `bits` bits to the 8-bit `value`.
+pub(crate) fn quantize(value: u8, bits: u32) -> u16 {
+    let max = (1u32 << bits) - 1;
+    ((value as u32 * max + 127) / 255) as u16
+}
@@ -10,4 +10,6 @@ pub enum DecodeImageError {
     ImageDecode,
     #[error("expect {0} times pixel decode, but need {1} times")]
     SizeNotMatch(usize, usize),
+    #[error("invalid crunch data: {0}")]
+    InvalidCrunch(&'static str),
 }
@@ -1,5 +1,5 @@
 use crate::pixel_info::{Pixel, SinglePixel};
-use crate::ImageDecoder;
+use crate::{ImageDecoder, ImageEncoder};
 use byteorder::ReadBytesExt;
 use std::io;
 
@@ -12,3 +12,9 @@ impl ImageDecoder<1> for Alpha8 {
         Ok([Pixel::new_rgba(255, 255, 255, data.read_u8()?)])
     }
 }
+
+impl ImageEncoder for Alpha8 {
+    fn encode_pixel(pixel: [u8; 4], out: &mut Vec<u8>) {
+        out.push(pixel[3]);
+    }
+}
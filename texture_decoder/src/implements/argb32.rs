@@ -1,5 +1,5 @@
 use crate::pixel_info::{Pixel, SinglePixel};
-use crate::ImageDecoder;
+use crate::{ImageDecoder, ImageEncoder};
 use byteorder::ReadBytesExt;
 
 pub struct ARGB32;
@@ -11,3 +11,9 @@ impl ImageDecoder for ARGB32 {
         Ok([Pixel::builder().alpha(data.read_u8()?).rad(data.read_u8()?).green(data.read_u8()?).blue(data.read_u8()?).build()])
     }
 }
+
+impl ImageEncoder for ARGB32 {
+    fn encode_pixel([r, g, b, a]: [u8; 4], out: &mut Vec<u8>) {
+        out.extend([a, r, g, b]);
+    }
+}
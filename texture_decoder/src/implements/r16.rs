@@ -1,7 +1,7 @@
 use crate::pixel_info::{Pixel, SinglePixel};
 use crate::utils::DownScaleToU8;
-use crate::ImageDecoder;
-use byteorder::{BigEndian, ReadBytesExt};
+use crate::{ImageDecoder, U16ImageDecoder};
+use byteorder::{LittleEndian, ReadBytesExt};
 
 pub struct R16;
 
@@ -9,6 +9,12 @@ impl ImageDecoder for R16 {
     const DECODE_PIXEL_BYTE: usize = 2;
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
-        Ok([Pixel::builder().rad(data.read_u16::<BigEndian>()?.down_scale()).build()])
+        Ok([Pixel::builder().rad(data.read_u16::<LittleEndian>()?.down_scale()).build()])
+    }
+}
+
+impl U16ImageDecoder for R16 {
+    fn decode_pixel_u16(data: &mut &[u8]) -> std::io::Result<[u16; 4]> {
+        Ok([data.read_u16::<LittleEndian>()?, 0, 0, u16::MAX])
     }
 }
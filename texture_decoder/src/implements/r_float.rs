@@ -1,8 +1,8 @@
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt};
 
 use crate::pixel_info::{Pixel, SinglePixel};
 use crate::utils::FloatConvU8;
-use crate::ImageDecoder;
+use crate::{FloatImageDecoder, ImageDecoder};
 
 pub struct RFloat;
 
@@ -10,6 +10,12 @@ impl ImageDecoder for RFloat {
     const DECODE_PIXEL_BYTE: usize = 4;
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
-        Ok(Pixel::builder().rad(data.read_f32::<BigEndian>()?.to_u8()).build().into())
+        Ok(Pixel::builder().rad(data.read_f32::<LittleEndian>()?.to_u8()).build().into())
+    }
+}
+
+impl FloatImageDecoder for RFloat {
+    fn decode_pixel_f32(data: &mut &[u8]) -> std::io::Result<[f32; 4]> {
+        Ok([data.read_f32::<LittleEndian>()?, 0.0, 0.0, 1.0])
     }
 }
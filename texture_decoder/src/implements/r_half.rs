@@ -1,7 +1,7 @@
 use crate::pixel_info::{Pixel, SinglePixel};
 use crate::utils::{FloatConvU8, ReadHalfFloat};
-use crate::ImageDecoder;
-use byteorder::BigEndian;
+use crate::{FloatImageDecoder, ImageDecoder};
+use byteorder::LittleEndian;
 
 pub struct RHalf;
 
@@ -9,6 +9,12 @@ impl ImageDecoder for RHalf {
     const DECODE_PIXEL_BYTE: usize = 2;
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
-        Ok(Pixel::builder().rad(data.read_f16::<BigEndian>()?.to_u8()).build().into())
+        Ok(Pixel::builder().rad(data.read_f16::<LittleEndian>()?.to_u8()).build().into())
+    }
+}
+
+impl FloatImageDecoder for RHalf {
+    fn decode_pixel_f32(data: &mut &[u8]) -> std::io::Result<[f32; 4]> {
+        Ok([data.read_f16::<LittleEndian>()?.to_f32(), 0.0, 0.0, 1.0])
     }
 }
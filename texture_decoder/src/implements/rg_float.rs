@@ -1,7 +1,7 @@
 use crate::pixel_info::Pixel;
 use crate::utils::FloatConvU8;
-use crate::ImageDecoder;
-use byteorder::{BigEndian, ReadBytesExt};
+use crate::{FloatImageDecoder, ImageDecoder};
+use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::Error;
 
 pub struct RGFloat;
@@ -10,6 +10,12 @@ impl ImageDecoder for RGFloat {
     const DECODE_PIXEL_BYTE: usize = 8;
 
     fn decode_pixel(data: &mut &[u8]) -> Result<[Pixel; 1], Error> {
-        Ok(Pixel::builder().rad(data.read_f32::<BigEndian>()?.to_u8()).green(data.read_f32::<BigEndian>()?.to_u8()).build().into())
+        Ok(Pixel::builder().rad(data.read_f32::<LittleEndian>()?.to_u8()).green(data.read_f32::<LittleEndian>()?.to_u8()).build().into())
+    }
+}
+
+impl FloatImageDecoder for RGFloat {
+    fn decode_pixel_f32(data: &mut &[u8]) -> std::io::Result<[f32; 4]> {
+        Ok([data.read_f32::<LittleEndian>()?, data.read_f32::<LittleEndian>()?, 0.0, 1.0])
     }
 }
@@ -1,7 +1,7 @@
 use crate::pixel_info::{Pixel, SinglePixel};
 use crate::utils::{FloatConvU8, ReadHalfFloat};
-use crate::ImageDecoder;
-use byteorder::BigEndian;
+use crate::{FloatImageDecoder, ImageDecoder};
+use byteorder::LittleEndian;
 
 pub struct RGHalf;
 
@@ -9,8 +9,14 @@ impl ImageDecoder for RGHalf {
     const DECODE_PIXEL_BYTE: usize = 4;
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
-        let (r, g) = (data.read_f16::<BigEndian>()?.to_u8(), data.read_f16::<BigEndian>()?.to_u8());
+        let (r, g) = (data.read_f16::<LittleEndian>()?.to_u8(), data.read_f16::<LittleEndian>()?.to_u8());
 
         Ok(Pixel::builder().rad(r).green(g).build().into())
     }
 }
+
+impl FloatImageDecoder for RGHalf {
+    fn decode_pixel_f32(data: &mut &[u8]) -> std::io::Result<[f32; 4]> {
+        Ok([data.read_f16::<LittleEndian>()?.to_f32(), data.read_f16::<LittleEndian>()?.to_f32(), 0.0, 1.0])
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ImageDecoder;
+use crate::{ImageDecoder, ImageEncoder};
 use byteorder::ReadBytesExt;
 
 use crate::pixel_info::{Pixel, SinglePixel};
@@ -14,3 +14,9 @@ impl ImageDecoder for RGB24 {
         Ok(pixel.into())
     }
 }
+
+impl ImageEncoder for RGB24 {
+    fn encode_pixel([r, g, b, _]: [u8; 4], out: &mut Vec<u8>) {
+        out.extend([r, g, b]);
+    }
+}
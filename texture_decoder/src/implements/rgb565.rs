@@ -1,5 +1,6 @@
+use crate::encoder::quantize;
 use crate::pixel_info::{Pixel, SinglePixel};
-use crate::ImageDecoder;
+use crate::{ImageDecoder, ImageEncoder};
 use byteorder::{BigEndian, ReadBytesExt};
 
 pub struct RGB565;
@@ -9,7 +10,13 @@ impl ImageDecoder for RGB565 {
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
         let p = data.read_u16::<BigEndian>()?;
-        let pixel = Pixel::builder().blue(((p << 2) | (p >> 3 & 7)) as _).green(((p >> 3 & 0xfc) | p >> 9 & 3) as _).rad(((p >> 8 & 0xf8) | (p >> 13)) as _).build();
+        let pixel = Pixel::builder().blue(((p << 3) | (p >> 2 & 7)) as _).green(((p >> 3 & 0xfc) | p >> 9 & 3) as _).rad(((p >> 8 & 0xf8) | (p >> 13)) as _).build();
         Ok(pixel.into())
     }
 }
+
+impl ImageEncoder for RGB565 {
+    fn encode_pixel([r, g, b, _]: [u8; 4], out: &mut Vec<u8>) {
+        out.extend((quantize(r, 5) << 11 | quantize(g, 6) << 5 | quantize(b, 5)).to_be_bytes());
+    }
+}
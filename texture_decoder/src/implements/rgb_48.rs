@@ -1,7 +1,7 @@
 use crate::pixel_info::Pixel;
 use crate::utils::DownScaleToU8;
-use crate::ImageDecoder;
-use byteorder::{BigEndian, ReadBytesExt};
+use crate::{ImageDecoder, U16ImageDecoder};
+use byteorder::{LittleEndian, ReadBytesExt};
 
 pub struct RGB48;
 
@@ -10,10 +10,16 @@ impl ImageDecoder for RGB48 {
 
     fn decode_pixel(data: &mut &[u8]) -> Result<[Pixel; 1], std::io::Error> {
         Ok(Pixel::builder()
-            .rad(data.read_u16::<BigEndian>()?.down_scale())
-            .green(data.read_u16::<BigEndian>()?.down_scale())
-            .blue(data.read_u16::<BigEndian>()?.down_scale())
+            .rad(data.read_u16::<LittleEndian>()?.down_scale())
+            .green(data.read_u16::<LittleEndian>()?.down_scale())
+            .blue(data.read_u16::<LittleEndian>()?.down_scale())
             .build()
             .into())
     }
 }
+
+impl U16ImageDecoder for RGB48 {
+    fn decode_pixel_u16(data: &mut &[u8]) -> std::io::Result<[u16; 4]> {
+        Ok([data.read_u16::<LittleEndian>()?, data.read_u16::<LittleEndian>()?, data.read_u16::<LittleEndian>()?, u16::MAX])
+    }
+}
@@ -1,23 +1,37 @@
 use crate::pixel_info::Pixel;
 use crate::utils::FloatConvU8;
-use crate::ImageDecoder;
-use byteorder::{BigEndian, ReadBytesExt};
+use crate::{FloatImageDecoder, ImageDecoder};
+use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::Error;
 
 pub struct RGB9e5Float;
 
-impl ImageDecoder for RGB9e5Float {
-    const DECODE_PIXEL_BYTE: usize = 4;
-
-    fn decode_pixel(data: &mut &[u8]) -> Result<[Pixel; 1], Error> {
-        let val = data.read_i32::<BigEndian>()?;
+impl RGB9e5Float {
+    /// Three 9-bit mantissas sharing a 5-bit exponent.
+    fn decode_rgb(data: &mut &[u8]) -> Result<[f64; 3], Error> {
+        let val = data.read_i32::<LittleEndian>()?;
         let scale = val >> 27 & 0x1f;
         let scale = 2f64.powf((scale - 24) as _);
 
         let b = (val >> 18 & 0x1ff) as f64;
         let g = (val >> 9 & 0x1ff) as f64;
         let r = (val & 0x1ff) as f64;
+        Ok([r * scale, g * scale, b * scale])
+    }
+}
+
+impl ImageDecoder for RGB9e5Float {
+    const DECODE_PIXEL_BYTE: usize = 4;
+
+    fn decode_pixel(data: &mut &[u8]) -> Result<[Pixel; 1], Error> {
+        let [r, g, b] = Self::decode_rgb(data)?;
+        Ok(Pixel::builder().rad(r.to_u8()).green(g.to_u8()).blue(b.to_u8()).build().into())
+    }
+}
 
-        Ok(Pixel::builder().rad((r * scale).to_u8()).green((g * scale).to_u8()).blue((b * scale).to_u8()).build().into())
+impl FloatImageDecoder for RGB9e5Float {
+    fn decode_pixel_f32(data: &mut &[u8]) -> Result<[f32; 4], Error> {
+        let [r, g, b] = Self::decode_rgb(data)?;
+        Ok([r as f32, g as f32, b as f32, 1.0])
     }
 }
@@ -1,5 +1,5 @@
 use crate::pixel_info::{Pixel, SinglePixel};
-use crate::ImageDecoder;
+use crate::{ImageDecoder, ImageEncoder};
 use byteorder::ReadBytesExt;
 
 pub struct RGBA32;
@@ -13,3 +13,9 @@ impl ImageDecoder for RGBA32 {
         Ok(pixel.into())
     }
 }
+
+impl ImageEncoder for RGBA32 {
+    fn encode_pixel(pixel: [u8; 4], out: &mut Vec<u8>) {
+        out.extend(pixel);
+    }
+}
@@ -1,5 +1,6 @@
+use crate::encoder::quantize;
 use crate::pixel_info::{Pixel, SinglePixel};
-use crate::ImageDecoder;
+use crate::{ImageDecoder, ImageEncoder};
 use byteorder::{BigEndian, ReadBytesExt};
 
 pub struct RGBA4444;
@@ -23,3 +24,10 @@ impl ImageDecoder for RGBA4444 {
         Ok(Pixel::new_rgba(r, g, b, a).into())
     }
 }
+
+impl ImageEncoder for RGBA4444 {
+    fn encode_pixel(pixel: [u8; 4], out: &mut Vec<u8>) {
+        let [r, g, b, a] = pixel.map(|c| quantize(c, 4));
+        out.extend((r << 12 | g << 8 | b << 4 | a).to_be_bytes());
+    }
+}
@@ -1,7 +1,7 @@
 use crate::pixel_info::{Pixel, SinglePixel};
 use crate::utils::DownScaleToU8;
-use crate::ImageDecoder;
-use byteorder::{BigEndian, ReadBytesExt};
+use crate::{ImageDecoder, U16ImageDecoder};
+use byteorder::{LittleEndian, ReadBytesExt};
 
 pub struct RGBA64;
 
@@ -10,11 +10,17 @@ impl ImageDecoder for RGBA64 {
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
         Ok(Pixel::builder()
-            .rad(data.read_u16::<BigEndian>()?.down_scale())
-            .green(data.read_u16::<BigEndian>()?.down_scale())
-            .blue(data.read_u16::<BigEndian>()?.down_scale())
-            .alpha(data.read_u16::<BigEndian>()?.down_scale())
+            .rad(data.read_u16::<LittleEndian>()?.down_scale())
+            .green(data.read_u16::<LittleEndian>()?.down_scale())
+            .blue(data.read_u16::<LittleEndian>()?.down_scale())
+            .alpha(data.read_u16::<LittleEndian>()?.down_scale())
             .build()
             .into())
     }
 }
+
+impl U16ImageDecoder for RGBA64 {
+    fn decode_pixel_u16(data: &mut &[u8]) -> std::io::Result<[u16; 4]> {
+        Ok([data.read_u16::<LittleEndian>()?, data.read_u16::<LittleEndian>()?, data.read_u16::<LittleEndian>()?, data.read_u16::<LittleEndian>()?])
+    }
+}
@@ -1,7 +1,7 @@
 use crate::pixel_info::{Pixel, SinglePixel};
 use crate::utils::FloatConvU8;
-use crate::ImageDecoder;
-use byteorder::{BigEndian, ReadBytesExt};
+use crate::{FloatImageDecoder, ImageDecoder};
+use byteorder::{LittleEndian, ReadBytesExt};
 
 pub struct RGBAFloat;
 
@@ -10,11 +10,17 @@ impl ImageDecoder for RGBAFloat {
 
     fn decode_pixel(data: &mut &[u8]) -> std::io::Result<SinglePixel> {
         Ok(Pixel::builder()
-            .rad(data.read_f32::<BigEndian>()?.to_u8())
-            .green(data.read_f32::<BigEndian>()?.to_u8())
-            .blue(data.read_f32::<BigEndian>()?.to_u8())
-            .alpha(data.read_f32::<BigEndian>()?.to_u8())
+            .rad(data.read_f32::<LittleEndian>()?.to_u8())
+            .green(data.read_f32::<LittleEndian>()?.to_u8())
+            .blue(data.read_f32::<LittleEndian>()?.to_u8())
+            .alpha(data.read_f32::<LittleEndian>()?.to_u8())
             .build()
             .into())
     }
 }
+
+impl FloatImageDecoder for RGBAFloat {
+    fn decode_pixel_f32(data: &mut &[u8]) -> std::io::Result<[f32; 4]> {
+        Ok([data.read_f32::<LittleEndian>()?, data.read_f32::<LittleEndian>()?, data.read_f32::<LittleEndian>()?, data.read_f32::<LittleEndian>()?])
+    }
+}
@@ -1,7 +1,7 @@
 use crate::pixel_info::Pixel;
 use crate::utils::{FloatConvU8, ReadHalfFloat};
-use crate::ImageDecoder;
-use byteorder::BigEndian;
+use crate::{FloatImageDecoder, ImageDecoder};
+use byteorder::LittleEndian;
 
 pub struct RGBAHalf;
 
@@ -9,9 +9,25 @@ impl ImageDecoder for RGBAHalf {
     const DECODE_PIXEL_BYTE: usize = 8;
 
     fn decode_pixel(img: &mut &[u8]) -> std::io::Result<[Pixel; 1]> {
-        let (r, g, b, a) = (img.read_f16::<BigEndian>()?.to_u8(), img.read_f16::<BigEndian>()?.to_u8(), img.read_f16::<BigEndian>()?.to_u8(), img.read_f16::<BigEndian>()?.to_u8());
+        let (r, g, b, a) = (
+            img.read_f16::<LittleEndian>()?.to_u8(),
+            img.read_f16::<LittleEndian>()?.to_u8(),
+            img.read_f16::<LittleEndian>()?.to_u8(),
+            img.read_f16::<LittleEndian>()?.to_u8(),
+        );
 
         let pixel = Pixel::new_rgba(r, g, b, a);
         Ok(pixel.into())
     }
 }
+
+impl FloatImageDecoder for RGBAHalf {
+    fn decode_pixel_f32(img: &mut &[u8]) -> std::io::Result<[f32; 4]> {
+        Ok([
+            img.read_f16::<LittleEndian>()?.to_f32(),
+            img.read_f16::<LittleEndian>()?.to_f32(),
+            img.read_f16::<LittleEndian>()?.to_f32(),
+            img.read_f16::<LittleEndian>()?.to_f32(),
+        ])
+    }
+}
@@ -1,7 +1,12 @@
+pub mod block;
+pub mod crunch;
 mod decoder;
+pub mod encode;
+mod encoder;
 pub mod error;
 pub mod implements;
 mod pixel_info;
+pub mod swizzle;
 mod utils;
 mod write_buffer;
 
@@ -19,6 +24,44 @@ impl Texture2DDecoder {
             Ok(img)
         }
     }
+
+    /// Like [`Self::decode`], to float RGBA without clamping.
+    pub fn decode_f32<D: FloatImageDecoder>(_: D, size: &ImageSize, data: &[u8], flip: bool) -> Result<Rgba32FImage, DecodeImageError> {
+        D::check_decodiblity(size, data.len())?;
+        let buffer = decode_pixels(size, data, D::DECODE_PIXEL_BYTE, D::decode_pixel_f32)?;
+        let img = Rgba32FImage::from_raw(size.width as _, size.height as _, buffer).ok_or(DecodeImageError::ImageDecode)?;
+        Ok(if flip { flip_vertical(&img) } else { img })
+    }
+
+    /// Like [`Self::decode`], to 16 bits per channel.
+    pub fn decode_u16<D: U16ImageDecoder>(_: D, size: &ImageSize, data: &[u8], flip: bool) -> Result<Rgba16Image, DecodeImageError> {
+        D::check_decodiblity(size, data.len())?;
+        let buffer = decode_pixels(size, data, D::DECODE_PIXEL_BYTE, D::decode_pixel_u16)?;
+        let img = Rgba16Image::from_raw(size.width as _, size.height as _, buffer).ok_or(DecodeImageError::ImageDecode)?;
+        Ok(if flip { flip_vertical(&img) } else { img })
+    }
+}
+
+pub struct Texture2DEncoder;
+
+impl Texture2DEncoder {
+    /// Encodes a top-down `img` in the format of `D`, bottom row first as Unity stores it.
+    pub fn encode<D: ImageEncoder>(_: D, img: &RgbaImage) -> Vec<u8> {
+        let mut data = Vec::with_capacity(img.width() as usize * img.height() as usize * D::DECODE_PIXEL_BYTE);
+        for row in img.rows().rev() {
+            for pixel in row {
+                D::encode_pixel(pixel.0, &mut data);
+            }
+        }
+        data
+    }
+}
+
+pub type Rgba16Image = ImageBuffer<Rgba<u16>, Vec<u16>>;
+
+fn decode_pixels<T: Send>(size: &ImageSize, data: &[u8], pixel_bytes: usize, decode_pixel: fn(&mut &[u8]) -> io::Result<[T; 4]>) -> Result<Vec<T>, DecodeImageError> {
+    let pixels = data.par_chunks_exact(pixel_bytes).take(size.size()).map(|mut pixel| decode_pixel(&mut pixel)).collect::<io::Result<Vec<_>>>()?;
+    Ok(pixels.into_iter().flatten().collect())
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -42,6 +85,9 @@ impl ImageSize {
 
 use crate::error::DecodeImageError;
 use crate::pixel_info::Pixel;
-pub use decoder::ImageDecoder;
+pub use decoder::{FloatImageDecoder, ImageDecoder, U16ImageDecoder};
+pub use encoder::ImageEncoder;
 use image::imageops::flip_vertical;
-use image::RgbaImage;
+use image::{ImageBuffer, Rgba, Rgba32FImage, RgbaImage};
+use rayon::prelude::*;
+use std::io;
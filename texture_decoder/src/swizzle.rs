@@ -0,0 +1,97 @@
+//! Undoing the tiled layouts consoles store textures in, back to the rows of blocks the decoders read.
+//!
+//! Both work on blocks: the blocks of compressed formats, or single pixels of uncompressed ones.
+
+use crate::error::DecodeImageError;
+
+/// A Tegra X1 GOB is 512 bytes: 8 rows of four 16-byte units.
+const GOB_WIDTH: usize = 4;
+const GOB_HEIGHT: usize = 8;
+const UNIT_BYTES: usize = 16;
+
+/// Undoes the block-linear layout of Switch textures, `blocks_x` by `blocks_y` blocks of `block_bytes` each.
+///
+/// The data is read in 16-byte units of one or more blocks side by side, grouped in GOBs that are stacked
+/// `gobs_per_block` high into the blocks stored row by row. The stored image is padded to whole blocks.
+pub fn deswizzle_switch(data: &[u8], blocks_x: usize, blocks_y: usize, block_bytes: usize, gobs_per_block: usize) -> Result<Vec<u8>, DecodeImageError> {
+    if block_bytes == 0 || !UNIT_BYTES.is_multiple_of(block_bytes) || gobs_per_block == 0 {
+        return Err(DecodeImageError::ImageDecode);
+    }
+    let unit_blocks = UNIT_BYTES / block_bytes;
+    let gobs_x = blocks_x.div_ceil(unit_blocks * GOB_WIDTH);
+    let units = switch_size(blocks_x, blocks_y, block_bytes, gobs_per_block) / UNIT_BYTES;
+    if data.len() / UNIT_BYTES < units {
+        return Err(DecodeImageError::SizeNotMatch(data.len() / UNIT_BYTES, units));
+    }
+
+    let mut out = vec![0; blocks_x * blocks_y * block_bytes];
+    for (i, unit) in data.chunks_exact(UNIT_BYTES).take(units).enumerate() {
+        let (gob, j) = (i / (GOB_WIDTH * GOB_HEIGHT), i % (GOB_WIDTH * GOB_HEIGHT));
+        let (row, gob_x, gob_y) = (gob / (gobs_x * gobs_per_block), gob / gobs_per_block % gobs_x, gob % gobs_per_block);
+        // Units go in Z order within each 2x2 square, and the squares two across and four down.
+        let x = gob_x * GOB_WIDTH + (j >> 1 & 2 | j & 1);
+        let y = (row * gobs_per_block + gob_y) * GOB_HEIGHT + (j >> 2 & 6 | j >> 1 & 1);
+        if y >= blocks_y {
+            continue;
+        }
+        for (k, block) in unit.chunks_exact(block_bytes).enumerate() {
+            let bx = x * unit_blocks + k;
+            if bx < blocks_x {
+                let at = (y * blocks_x + bx) * block_bytes;
+                out[at..at + block_bytes].copy_from_slice(block);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// The bytes a Switch texture of `blocks_x` by `blocks_y` blocks takes, padded to whole blocks of GOBs.
+pub fn switch_size(blocks_x: usize, blocks_y: usize, block_bytes: usize, gobs_per_block: usize) -> usize {
+    let gobs_x = (blocks_x * block_bytes).div_ceil(UNIT_BYTES * GOB_WIDTH);
+    gobs_x * blocks_y.div_ceil(GOB_HEIGHT * gobs_per_block) * gobs_per_block * GOB_WIDTH * GOB_HEIGHT * UNIT_BYTES
+}
+
+/// The blocks of GOBs a Switch texture `blocks_y` blocks high uses, from the `gobs_per_block` of its largest level:
+/// levels at most half as high as a block use blocks half as high, down to a single GOB.
+pub fn switch_gobs_per_block(blocks_y: usize, gobs_per_block: usize) -> usize {
+    let mut gobs = gobs_per_block;
+    while gobs > 1 && blocks_y.div_ceil(GOB_HEIGHT) <= gobs / 2 {
+        gobs /= 2;
+    }
+    gobs
+}
+
+/// Undoes the tiling of PS4 and PS5 textures, `blocks_x` by `blocks_y` blocks of `block_bytes` each: tiles of 8x8
+/// blocks stored row by row, each in Morton order with the lowest bit going across.
+pub fn untile_ps4(data: &[u8], blocks_x: usize, blocks_y: usize, block_bytes: usize) -> Result<Vec<u8>, DecodeImageError> {
+    if block_bytes == 0 {
+        return Err(DecodeImageError::ImageDecode);
+    }
+    let tiles_x = blocks_x.div_ceil(8);
+    let blocks = ps4_size(blocks_x, blocks_y, block_bytes) / block_bytes;
+    if data.len() / block_bytes < blocks {
+        return Err(DecodeImageError::SizeNotMatch(data.len() / block_bytes, blocks));
+    }
+
+    let mut out = vec![0; blocks_x * blocks_y * block_bytes];
+    for (i, block) in data.chunks_exact(block_bytes).take(blocks).enumerate() {
+        let (tile, j) = (i / 64, i % 64);
+        let x = tile % tiles_x * 8 + even_bits(j);
+        let y = tile / tiles_x * 8 + even_bits(j >> 1);
+        if x < blocks_x && y < blocks_y {
+            let at = (y * blocks_x + x) * block_bytes;
+            out[at..at + block_bytes].copy_from_slice(block);
+        }
+    }
+    Ok(out)
+}
+
+/// The bytes a PS4 or PS5 texture of `blocks_x` by `blocks_y` blocks takes, padded to whole tiles.
+pub fn ps4_size(blocks_x: usize, blocks_y: usize, block_bytes: usize) -> usize {
+    blocks_x.div_ceil(8) * blocks_y.div_ceil(8) * 64 * block_bytes
+}
+
+/// Bits 0, 2 and 4 of `i`, packed.
+fn even_bits(i: usize) -> usize {
+    i & 1 | i >> 1 & 2 | i >> 2 & 4
+}
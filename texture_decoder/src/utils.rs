@@ -1,7 +1,6 @@
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
 use half::f16;
 use std::io;
-use std::num::Wrapping;
 
 pub(crate) trait DownScaleToU8 {
     fn down_scale(self) -> u8;
@@ -9,8 +8,7 @@ pub(crate) trait DownScaleToU8 {
 
 impl DownScaleToU8 for u16 {
     fn down_scale(self) -> u8 {
-        let warp = Wrapping(self);
-        (((warp * Wrapping(255)) + Wrapping(32895)) >> 16).0 as _
+        ((self as u32 * 255 + 32895) >> 16) as _
     }
 }
 
@@ -0,0 +1,371 @@
+//! `#[derive(FromObject)]` for the classes of `unity_rs`.
+//!
+//! Fields are read in declaration order. Their TypeTree name is `m_` followed by the field name in
+//! UpperCamelCase unless renamed. Container attributes:
+//! - `#[unity(class = "RectTransform")]`: the `ClassID` variant, the struct name by default;
+//! - `#[unity(partial)]`: the struct reads only the leading fields of the object, in every version;
+//!   `#[unity(partial = "2018")]` from this version on;
+//! - `#[unity(finish = "Mesh::process_data")]`: `process_data(&mut value, object)` completes the value once its fields
+//!   are read, from the object data or from the TypeTree.
+//!
+//! Field attributes:
+//! - `#[unity(since = "2017.3")]`: the field exists from this version on, and is `Default` before it;
+//! - `#[unity(until = "5.4")]`: the field exists before this version, and is `Default` from it on;
+//! - `#[unity(when = "version.build >= 3")]`: the field exists when the expression holds, which can use `version`,
+//!   `object` and the fields before it;
+//! - `#[unity(default = "1")]`: the value of the field where it doesn't exist or is missing from the TypeTree, which
+//!   can use the same names as `when`;
+//! - `#[unity(align)]`: the data is aligned to 4 bytes after the field; `#[unity(align(since = "5", until = "5.4"))]`
+//!   aligns it in these versions whether the field exists or not, `#[unity(align(always))]` in every version;
+//! - `#[unity(pptr)]`: the field holds PPtrs, read through `PPtrField` with the object they belong to;
+//! - `#[unity(rename = "m_Enabled")]`: the TypeTree name of the field;
+//! - `#[unity(with = "components")]`: the field is read by the functions `read(object, r)` and
+//!   `from_value(object, value)` of the module or type `components`, for layouts the other attributes can't express;
+//! - `#[unity(flatten)]`: the fields of another derived struct, read in place, such as those of a base class;
+//!   with `with`, `from_value` gets the whole TypeTree of the object, for fields stored under different names;
+//! - `#[unity(offset)]`: an `Option<usize>` holding where the data of the next field starts, `None` when read from the
+//!   TypeTree;
+//! - `#[unity(object = "path_id")]`: the field is not serialized and is computed by `path_id(object)`;
+//! - `#[unity(skip)]`: the field is not serialized and is always `Default`.
+//!
+//! Strict mode compares the fields read through `Field` with the object's TypeTree.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, Ident, LitStr, Path, Result};
+
+#[proc_macro_derive(FromObject, attributes(unity))]
+pub fn derive_from_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct ContainerAttrs {
+    class: Option<Ident>,
+    partial: Option<TokenStream2>,
+    finish: Option<Path>,
+}
+
+#[derive(Default)]
+struct FieldAttrs {
+    gates: Vec<TokenStream2>,
+    default: Option<TokenStream2>,
+    align: Align,
+    pptr: bool,
+    rename: Option<String>,
+    with: Option<Path>,
+    flatten: bool,
+    offset: bool,
+    object: Option<Path>,
+    skip: bool,
+}
+
+#[derive(Default)]
+enum Align {
+    #[default]
+    None,
+    /// After the field, when it exists.
+    Field,
+    /// After the field, when all of the gates hold.
+    Gated(Vec<TokenStream2>),
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input.ident, "FromObject can only be derived for structs with named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input.ident, "FromObject can only be derived for structs")),
+    };
+    let container = container_attrs(&input)?;
+    let class = container.class.unwrap_or_else(|| name.clone());
+    let partial = container.partial.unwrap_or_else(|| quote! { false });
+    let finish = container.finish.map(|finish| quote! { #finish(&mut value, object)?; });
+
+    let mut reads = Vec::new();
+    let mut tree_reads = Vec::new();
+    let mut idents = Vec::new();
+    let mut checks = Vec::new();
+    for field in fields {
+        let ident = field.ident.clone().expect("named field");
+        let attrs = field_attrs(field)?;
+        let ty = &field.ty;
+        idents.push(ident.clone());
+        if attrs.skip {
+            reads.push(quote! { let #ident: #ty = ::core::default::Default::default(); });
+            tree_reads.push(quote! { let #ident: #ty = ::core::default::Default::default(); });
+            continue;
+        }
+        if let Some(object) = &attrs.object {
+            reads.push(quote! { let #ident: #ty = #object(object); });
+            tree_reads.push(quote! { let #ident: #ty = #object(object); });
+            continue;
+        }
+        let gates = &attrs.gates;
+        let gate = if gates.is_empty() {
+            quote! { true }
+        } else {
+            quote! { #(#gates)&&* }
+        };
+        let default = attrs.default.clone().unwrap_or_else(|| quote! { ::core::default::Default::default() });
+        if attrs.offset {
+            reads.push(quote! { let #ident: #ty = if #gate { ::core::option::Option::Some(r.get_offset()) } else { ::core::option::Option::None }; });
+            tree_reads.push(quote! { let #ident: #ty = ::core::option::Option::None; });
+            continue;
+        }
+
+        let tree_name = attrs.rename.clone().unwrap_or_else(|| tree_name(&ident.to_string()));
+        // How the field is read from the data, and how its TypeTree `value` is turned into it.
+        let (read, convert) = if let Some(with) = &attrs.with {
+            (quote! { #with::read(object, r)? }, quote! { #with::from_value(object, value)? })
+        } else if attrs.flatten {
+            checks.push(quote! { ::unity_rs::__private::Fields::push_checked_fields(&self.#ident, fields); });
+            (
+                quote! { <#ty as ::unity_rs::__private::Fields>::read_fields(object, r)? },
+                quote! { <#ty as ::unity_rs::__private::Fields>::fields_from_tree(object, value)? },
+            )
+        } else if attrs.pptr {
+            (quote! { <#ty as ::unity_rs::classes::PPtrField>::read(object, r)? }, quote! { ::unity_rs::__private::pptr_value::<#ty>(object, value, #tree_name)? })
+        } else {
+            checks.push(quote! {
+                if let Some(value) = ::unity_rs::classes::Field::to_value(&self.#ident) {
+                    fields.push((#tree_name, value));
+                }
+            });
+            (
+                quote! { <#ty as ::unity_rs::classes::Field>::read(&object.info, r)? },
+                quote! { ::unity_rs::__private::field_value::<#ty>(&object.info, value, #tree_name)? },
+            )
+        };
+        let (from_tree, from_tree_or_default) = if attrs.flatten {
+            // a flattened field has no TypeTree value of its own
+            let from_tree = quote! {{
+                let value = tree;
+                #convert
+            }};
+            (from_tree.clone(), from_tree)
+        } else {
+            (
+                quote! {{
+                    let value = ::unity_rs::__private::tree_field(tree, #tree_name)?;
+                    #convert
+                }},
+                quote! {
+                    match tree.get(#tree_name) {
+                        Some(value) => #convert,
+                        None => #default,
+                    }
+                },
+            )
+        };
+
+        let field_align = matches!(attrs.align, Align::Field).then(|| quote! { r.align(4)?; });
+        let gated_align = match &attrs.align {
+            Align::Gated(gates) if gates.is_empty() => Some(quote! { r.align(4)?; }),
+            Align::Gated(gates) => Some(quote! {
+                if #(#gates)&&* {
+                    r.align(4)?;
+                }
+            }),
+            _ => None,
+        };
+        if gates.is_empty() {
+            reads.push(quote! {
+                let #ident: #ty = #read;
+                #field_align
+                #gated_align
+            });
+            let from_tree = if attrs.default.is_some() { from_tree_or_default } else { from_tree };
+            tree_reads.push(quote! { let #ident: #ty = #from_tree; });
+        } else {
+            reads.push(quote! {
+                let #ident: #ty = if #gate {
+                    let value = #read;
+                    #field_align
+                    value
+                } else {
+                    #default
+                };
+                #gated_align
+            });
+            tree_reads.push(quote! {
+                let #ident: #ty = if #gate {
+                    #from_tree_or_default
+                } else {
+                    #default
+                };
+            });
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::unity_rs::__private::Fields for #name #ty_generics #where_clause {
+            #[allow(unused_variables, unused_mut, clippy::manual_range_contains, clippy::nonminimal_bool)]
+            fn read_fields(object: &::unity_rs::Object, r: &mut ::unity_rs::__private::Reader) -> ::unity_rs::UnityResult<Self> {
+                let version = object.version();
+                #(#reads)*
+                let mut value = Self { #(#idents),* };
+                #finish
+                Ok(value)
+            }
+
+            #[allow(unused_variables, unused_mut, clippy::manual_range_contains, clippy::nonminimal_bool)]
+            fn fields_from_tree(object: &::unity_rs::Object, tree: &::unity_rs::__private::Value) -> ::unity_rs::UnityResult<Self> {
+                let version = object.version();
+                #(#tree_reads)*
+                let mut value = Self { #(#idents),* };
+                #finish
+                Ok(value)
+            }
+
+            #[allow(unused_variables)]
+            fn push_checked_fields(&self, fields: &mut ::std::vec::Vec<(&'static str, ::unity_rs::__private::Value)>) {
+                #(#checks)*
+            }
+        }
+
+        impl #impl_generics ::unity_rs::classes::FromObject for #name #ty_generics #where_clause {
+            fn load(object: &::unity_rs::Object) -> ::unity_rs::UnityResult<Self> {
+                <Self as ::unity_rs::__private::Fields>::read_fields(object, &mut object.info.get_reader())
+            }
+
+            fn load_type_tree(object: &::unity_rs::Object, tree: &::unity_rs::__private::Value) -> ::unity_rs::UnityResult<Self> {
+                <Self as ::unity_rs::__private::Fields>::fields_from_tree(object, tree)
+            }
+
+            fn class() -> ::unity_rs::ClassID {
+                ::unity_rs::ClassID::#class
+            }
+
+            #[allow(unused_variables)]
+            fn partial(version: ::unity_rs::UnityVersion) -> bool {
+                #partial
+            }
+
+            fn checked_fields(&self) -> ::std::vec::Vec<(&'static str, ::unity_rs::__private::Value)> {
+                let mut fields = ::std::vec::Vec::new();
+                ::unity_rs::__private::Fields::push_checked_fields(self, &mut fields);
+                fields
+            }
+        }
+    })
+}
+
+/// `m_` followed by the field name in UpperCamelCase, such as `m_LocalRotation` for `local_rotation`.
+fn tree_name(field: &str) -> String {
+    let mut name = String::from("m_");
+    for part in field.split('_').filter(|x| !x.is_empty()) {
+        let mut chars = part.chars();
+        name.extend(chars.next().map(|x| x.to_ascii_uppercase()));
+        name.push_str(chars.as_str());
+    }
+    name
+}
+
+fn container_attrs(input: &DeriveInput) -> Result<ContainerAttrs> {
+    let mut attrs = ContainerAttrs::default();
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("unity")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("class") {
+                let value: LitStr = meta.value()?.parse()?;
+                attrs.class = Some(Ident::new(&value.value(), value.span()));
+            } else if meta.path.is_ident("partial") {
+                attrs.partial = Some(if meta.input.peek(syn::Token![=]) {
+                    let since = version(&meta.value()?.parse()?)?;
+                    quote! { version >= #since }
+                } else {
+                    quote! { true }
+                });
+            } else if meta.path.is_ident("finish") {
+                attrs.finish = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error("expected `class`, `partial` or `finish`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn field_attrs(field: &syn::Field) -> Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field.attrs.iter().filter(|x| x.path().is_ident("unity")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("since") {
+                let since = version(&meta.value()?.parse()?)?;
+                attrs.gates.push(quote! { version >= #since });
+            } else if meta.path.is_ident("until") {
+                let until = version(&meta.value()?.parse()?)?;
+                attrs.gates.push(quote! { version < #until });
+            } else if meta.path.is_ident("when") {
+                let when = expression(&meta.value()?.parse()?)?;
+                attrs.gates.push(quote! { (#when) });
+            } else if meta.path.is_ident("default") {
+                attrs.default = Some(expression(&meta.value()?.parse()?)?);
+            } else if meta.path.is_ident("align") {
+                attrs.align = if meta.input.peek(syn::token::Paren) {
+                    let mut gates = Vec::new();
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("since") {
+                            let since = version(&meta.value()?.parse()?)?;
+                            gates.push(quote! { version >= #since });
+                        } else if meta.path.is_ident("until") {
+                            let until = version(&meta.value()?.parse()?)?;
+                            gates.push(quote! { version < #until });
+                        } else if !meta.path.is_ident("always") {
+                            return Err(meta.error("expected `since`, `until` or `always`"));
+                        }
+                        Ok(())
+                    })?;
+                    Align::Gated(gates)
+                } else {
+                    Align::Field
+                };
+            } else if meta.path.is_ident("pptr") {
+                attrs.pptr = true;
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("with") {
+                attrs.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("flatten") {
+                attrs.flatten = true;
+            } else if meta.path.is_ident("offset") {
+                attrs.offset = true;
+            } else if meta.path.is_ident("object") {
+                attrs.object = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else {
+                return Err(meta.error("expected `since`, `until`, `when`, `default`, `align`, `pptr`, `rename`, `with`, `flatten`, `offset`, `object` or `skip`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// Turns `"2017.3"` or `"5.4.2"` into a tuple that `UnityVersion` compares against.
+fn version(lit: &LitStr) -> Result<TokenStream2> {
+    let parts = lit
+        .value()
+        .split('.')
+        .map(|x| x.parse::<i32>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| Error::new(lit.span(), "expected a version such as \"2017.3\""))?;
+    match parts.as_slice() {
+        [major] => Ok(quote! { (#major, 0) }),
+        [major, minor] => Ok(quote! { (#major, #minor) }),
+        [major, minor, patch] => Ok(quote! { (#major, #minor, #patch) }),
+        _ => Err(Error::new(lit.span(), "expected a version such as \"2017.3\"")),
+    }
+}
+
+/// The Rust expression in the string, kept as tokens since parsing any expression needs the `full` feature of syn.
+fn expression(lit: &LitStr) -> Result<TokenStream2> {
+    lit.parse::<TokenStream2>()
+}